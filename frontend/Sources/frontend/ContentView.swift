@@ -3,7 +3,6 @@ import SwiftCrossUI
 
 /*
 TODO:
-- [ ] search bar
 - [ ] plus button instead of add button
 - [ ] filtering and sorting
 - [ ] hovering effects
@@ -18,425 +17,2619 @@ TODO:
 
 struct ContentView: View {
     @Environment(PasswordStoreViewModel.self) var vm
-    @State var showAddSheet = false
-    @State var showShareSheet = false
     @State var receiveTicket = ""
+    @State var receiveCode = ""
 
     var body: some View {
-        NavigationSplitView(
-            sidebar: {
-                VStack {
-                    if vm.entries.isEmpty {
-                        VStack {
-                            Spacer()
-                            Text("No entries")
-                                .foregroundColor(.gray)
+        VStack {
+            if vm.isLocked {
+                OnboardingView()
+            } else {
+                mainView
+            }
+        }
+        .alert(vm.$errorMessage) {
+            Button("OK") { vm.errorMessage = nil }
+        }
+    }
+
+    var mainView: some View {
+        VStack {
+            NavigationSplitView(
+                sidebar: {
+                    VStack {
+                        HStack {
+                            Text("Entries").emphasized()
+                            Button("by \(vm.sortMode.title)") { vm.perform(.cycleSort) }
+                                .foregroundColor(Display.secondary)
                             Spacer()
                         }
-                    } else {
-                        List(vm.entries, id: \.self, selection: vm.$selectedEntry) { name in
-                            Text(name)
-                        }
-                        .onChange(of: vm.selectedEntry) {
-                            if let name = vm.selectedEntry { vm.select(name) }
-                        }
-                    }
 
-                    Divider()
+                        CategoryTabs()
+                            .padding(.bottom, 4)
 
-                    HStack {
-                        Button("Add") {
-                            showAddSheet = true
+                        HStack {
+                            TextField(vm.folderScope.map { "Search \($0)" } ?? "Search", text: vm.$searchQuery)
+                            Toggle("Contents", isOn: vm.$deepSearch)
+                            if vm.folderScope != nil {
+                                Button("All folders") { vm.folderScope = nil }
+                            } else if let folder = vm.currentFolder {
+                                Button("In \(folder)") { vm.folderScope = folder }
+                            }
                         }
-                        .foregroundColor(.blue)
-                        Button("Delete") {
-                            if let name = vm.selectedEntry { vm.remove(name: name) }
+                        .padding(.bottom, 4)
+
+                        if !vm.tags.isEmpty {
+                            HStack {
+                                Button("All") { vm.tagFilter = nil }
+                                    .foregroundColor(vm.tagFilter == nil ? ink(.blue) : Display.secondary)
+                                ForEach(vm.tags, id: \.self) { tag in
+                                    Button(tag) { vm.tagFilter = vm.tagFilter == tag ? nil : tag }
+                                        .foregroundColor(vm.tagFilter == tag ? tagColor(tag) : Display.secondary)
+                                }
+                                Spacer()
+                            }
+                            .font(.caption)
+                            .padding(.bottom, 4)
                         }
-                        .foregroundColor(.red)
-                        .disabled(vm.selectedEntry == nil)
-                        Spacer()
-                        Button("Share") {
-                            vm.share()
-                            showShareSheet = true
+
+                        if vm.entries.isEmpty {
+                            VStack {
+                                Spacer()
+                                Text("No entries yet")
+                                    .foregroundColor(Display.secondary)
+                                Button("Add your first entry") { vm.perform(.newEntry) }
+                                    .foregroundColor(ink(.blue))
+                                Spacer()
+                            }
+                        } else if vm.listRows.isEmpty {
+                            VStack {
+                                Spacer()
+                                Text("No matching entries")
+                                    .foregroundColor(Display.secondary)
+                                Spacer()
+                            }
+                        } else {
+                            let window = vm.visibleRows
+                            if window.above > 0 {
+                                Button("\(Glyph.up) \(window.above) more") { vm.perform(.pageUp) }
+                                    .foregroundColor(Display.secondary)
+                                    .font(.caption)
+                            }
+                            List(Array(window.rows), id: \.id, selection: vm.$listSelection) { row in
+                                if let folder = row.folder {
+                                    FolderRow(folder: folder, depth: row.depth)
+                                } else if let hit = row.hit {
+                                    SearchResultRow(hit: hit, leafOnly: row.depth > 0)
+                                        .padding(.leading, row.depth * 12)
+                                }
+                            }
+                            .onChange(of: vm.listSelection) {
+                                if let id = vm.listSelection { vm.selectRow(id) }
+                            }
+                            if window.below > 0 {
+                                Button("\(Glyph.down) \(window.below) more") { vm.perform(.pageDown) }
+                                    .foregroundColor(Display.secondary)
+                                    .font(.caption)
+                            }
+                        }
+
+                        Divider()
+
+                        HStack {
+                            Button("Add") { vm.perform(.newEntry) }
+                                .foregroundColor(ink(.blue))
+                            Button("Delete") { vm.perform(.deleteEntry) }
+                                .foregroundColor(ink(.red))
+                                .disabled(vm.selectedEntry == nil)
+                            Button(Glyph.star) { vm.perform(.toggleFavorite) }
+                                .disabled(vm.selectedEntry == nil)
+                            Spacer()
+                            Button("Trash") { vm.perform(.showTrash) }
+                            Button("Share") { vm.perform(.openSync) }
+                            Button("?") { vm.perform(.help) }
                         }
+                        .padding(8)
                     }
                     .padding(8)
+                },
+                detail: {
+                    if let item = vm.selectedItem, let name = vm.selectedEntry {
+                        DetailView(name: name, item: item)
+                    } else if vm.offerFirstItem && vm.entries.isEmpty {
+                        FirstItemPrompt()
+                    } else {
+                        VStack {
+                            Spacer()
+                            Text("Select an entry")
+                                .foregroundColor(Display.secondary)
+                            Spacer()
+                        }
+                    }
                 }
-                .padding(8)
-            },
-            detail: {
-                if let item = vm.selectedItem, let name = vm.selectedEntry {
-                    DetailView(name: name, item: item)
+            )
+            StatusBar()
+        }
+        .sheet(isPresented: vm.$showAddSheet) {
+            AddEntrySheet(isPresented: vm.$showAddSheet)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showDeleteConfirm) {
+            if let name = vm.selectedEntry {
+                DeleteConfirmSheet(name: name, isPresented: vm.$showDeleteConfirm)
+                    .environment(vm)
+            }
+        }
+        .sheet(isPresented: vm.$showTrash) {
+            TrashSheet(isPresented: vm.$showTrash)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showGraveyard) {
+            GraveyardSheet(isPresented: vm.$showGraveyard)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showGenerator) {
+            GeneratorSheet(isPresented: vm.$showGenerator)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showUrlPicker) {
+            UrlPickerSheet(isPresented: vm.$showUrlPicker)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showUnsavedPrompt) {
+            UnsavedChangesSheet()
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showRecent) {
+            RecentSheet(isPresented: vm.$showRecent)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showRenameSheet) {
+            RenameSheet(isPresented: vm.$showRenameSheet)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showMergeSheet) {
+            MergeSheet(isPresented: vm.$showMergeSheet)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showStats) {
+            StatsSheet(isPresented: vm.$showStats)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showLargeField) {
+            LargeFieldSheet(isPresented: vm.$showLargeField)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showShareSheet) {
+            ShareSheet(receiveTicket: $receiveTicket, receiveCode: $receiveCode, isPresented: vm.$showShareSheet)
+                .environment(vm)
+        }
+        .sheet(isPresented: vm.$showHelp) {
+            HelpSheet(isPresented: vm.$showHelp)
+                .environment(vm)
+        }
+    }
+}
+
+// MARK: - Onboarding
+
+/// Shown until a vault is open: creates one on first run, otherwise asks
+/// for the passphrase of the existing one.
+struct OnboardingView: View {
+    @Environment(PasswordStoreViewModel.self) var vm
+    @State var path = ""
+    @State var passphrase = ""
+    @State var confirmation = ""
+
+    var body: some View {
+        VStack {
+            Spacer()
+            if vm.vaultExists {
+                Text("Unlock your vault").font(.title)
+                Text(vm.vaultPath).foregroundColor(Display.secondary).padding(.bottom)
+                TextField("Passphrase", text: $passphrase)
+                    .frame(maxWidth: 360)
+                Button("Unlock") {
+                    vm.unlock(passphrase: passphrase)
+                    passphrase = ""
+                }
+                .disabled(passphrase.isEmpty)
+                .padding(.top)
+            } else {
+                Text("Welcome").font(.title)
+                Text("Create a vault to start storing your passwords.")
+                    .foregroundColor(Display.secondary)
+                    .padding(.bottom)
+                VStack(alignment: .leading) {
+                    Text("Location").font(.caption)
+                    TextField(vm.vaultPath, text: $path)
+                    Text("Passphrase").font(.caption).padding(.top, 8)
+                    TextField("Choose a passphrase", text: $passphrase)
+                    TextField("Repeat it", text: $confirmation)
+                    if let problem {
+                        Text(problem).font(.caption).foregroundColor(ink(.red))
+                    }
+                    Text("The passphrase cannot be recovered; without it the vault stays locked.")
+                        .font(.caption)
+                        .foregroundColor(Display.secondary)
+                }
+                .frame(maxWidth: 360)
+                Button("Create vault") {
+                    vm.createVault(path: path.isEmpty ? vm.vaultPath : path, passphrase: passphrase)
+                }
+                .disabled(passphrase.isEmpty || confirmation != passphrase)
+                .padding(.top)
+            }
+            Spacer()
+        }
+        .padding()
+    }
+
+    private var problem: String? {
+        !confirmation.isEmpty && confirmation != passphrase ? "The passphrases do not match" : nil
+    }
+}
+
+/// The last onboarding step, in the detail pane of a freshly created vault.
+struct FirstItemPrompt: View {
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            Spacer()
+            Text("Your vault is ready").font(.title)
+            Text("Add a first item, such as the login you use most.")
+                .foregroundColor(Display.secondary)
+                .padding(.bottom)
+            HStack {
+                Button("Add first item") { vm.perform(.newEntry) }
+                    .foregroundColor(ink(.blue))
+                Button("Skip") { vm.offerFirstItem = false }
+            }
+            Spacer()
+        }
+    }
+}
+
+// MARK: - Status bar
+
+/// Mode, selection, clipboard countdown and the most relevant keys.
+struct StatusBar: View {
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        HStack {
+            Text(vm.mode.uppercased()).emphasized()
+            if let name = vm.selectedEntry {
+                Text(name)
+            }
+            if let prefix = vm.jumpPrefix {
+                Text("jump: \(prefix)_").foregroundColor(ink(.blue))
+            } else if let status = vm.statusMessage {
+                Text(status).foregroundColor(Display.secondary)
+            }
+            if let source = vm.mergeSource {
+                Text("merging \(source)").foregroundColor(ink(.blue))
+            }
+            if let seconds = vm.clipboardCountdown {
+                Text("clipboard clears in \(seconds)s").foregroundColor(ink(.orange))
+            }
+            Spacer()
+            ForEach(vm.keymap.hints(contexts: vm.activeContexts, limit: 3), id: \.description) { hint in
+                Text("\(hint.keys.first ?? "") \(hint.description)").foregroundColor(Display.secondary)
+            }
+            Text("? help").foregroundColor(Display.secondary)
+        }
+        .font(.caption)
+        .padding(.horizontal, 8)
+        .padding(.vertical, 4)
+    }
+}
+
+// MARK: - Category tabs
+
+/// All / Logins / Identity / Cards / Notes, above the entry list.
+struct CategoryTabs: View {
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        HStack {
+            ForEach(PasswordStoreViewModel.categoryTabs, id: \.self) { category in
+                Button(category.map(categoryTitle) ?? "All") { vm.categoryFilter = category }
+                    .foregroundColor(vm.categoryFilter == category ? ink(.blue) : Display.secondary)
+            }
+            Spacer()
+        }
+    }
+}
+
+private func categoryTitle(_ category: ItemCategory) -> String {
+    switch category {
+    case .logins: return "Logins"
+    case .identity: return "Identity"
+    case .cards: return "Cards"
+    case .notes: return "Notes"
+    }
+}
+
+// MARK: - Search
+
+struct SearchResultRow: View {
+    let hit: FfiSearchHit
+    /// Show only the last name component, for entries listed under their
+    /// folder header.
+    var leafOnly = false
+
+    var body: some View {
+        VStack(alignment: .leading) {
+            HStack {
+                Text(hit.icon ?? Glyph.icon(for: hit.kind))
+                    .foregroundColor(accentColor(hit.accent) ?? .black)
+                    .frame(width: 20)
+                if hit.favorite {
+                    Text(Glyph.star).foregroundColor(ink(.yellow))
+                }
+                if leafOnly {
+                    Text(String(hit.name.split(separator: "/").last ?? ""))
                 } else {
-                    VStack {
-                        Spacer()
-                        Text("Select an entry")
-                            .foregroundColor(.gray)
-                        Spacer()
+                    HighlightedText(text: hit.name, start: hit.nameStart, end: hit.nameEnd)
+                }
+                // The star stands in for the favorite tag.
+                TagChips(tags: hit.favorite ? hit.tags.filter { $0.lowercased() != "favorite" } : hit.tags)
+                Spacer()
+                if let score = hit.passwordScore, score < 3 {
+                    Text("weak").foregroundColor(ink(.orange)).font(.caption)
+                }
+                if let modified = hit.modified {
+                    Text(modified).foregroundColor(Display.secondary).font(.caption)
+                }
+            }
+            if let subtitle = hit.subtitle {
+                HStack {
+                    Text(subtitle).foregroundColor(Display.secondary).font(.caption)
+                    Spacer()
+                }
+                .padding(.leading, 24)
+            }
+            ForEach(Array(hit.matches.enumerated()), id: \.offset) { _, match in
+                HStack {
+                    Text(match.field).foregroundColor(Display.secondary).font(.caption)
+                    HighlightedText(text: match.value, start: match.start, end: match.end)
+                        .font(.caption)
+                }
+            }
+        }
+    }
+}
+
+/// A collapsible folder header in the entry list.
+struct FolderRow: View {
+    @Environment(PasswordStoreViewModel.self) var vm
+    let folder: String
+    let depth: Int
+
+    var body: some View {
+        HStack {
+            Button(vm.collapsedFolders.contains(folder) ? Glyph.collapsed : Glyph.expanded) { vm.toggleFolder(folder) }
+            Text(String(folder.split(separator: "/").last ?? ""))
+                .foregroundColor(Display.secondary)
+            Spacer()
+        }
+        .padding(.leading, depth * 12)
+    }
+}
+
+/// Text with the byte range `start..<end` emphasized.
+struct HighlightedText: View {
+    let text: String
+    let start: UInt32?
+    let end: UInt32?
+
+    var body: some View {
+        if let start, let end {
+            let (before, match, after) = text.splitForHighlight(start: start, end: end)
+            HStack(spacing: 0) {
+                Text(before)
+                Text(match).emphasized().foregroundColor(ink(.orange))
+                Text(after)
+            }
+        } else {
+            Text(text)
+        }
+    }
+}
+
+// MARK: - Detail
+
+struct DetailView: View {
+    let name: String
+    let item: FfiItem
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            FocusedFieldBar(name: name, item: item)
+            ScrollView {
+                detail
+            }
+        }
+        .frame(minWidth: 420)
+    }
+
+    var detail: some View {
+        VStack {
+            if let previous = vm.backStack.last {
+                HStack {
+                    Button("\(Glyph.back) \(previous)") { vm.goBack() }
+                        .font(.caption)
+                    Spacer()
+                }
+            }
+            HStack {
+                if let icon = item.appearance?.icon {
+                    Text(icon).font(.title)
+                }
+                Text(name)
+                    .font(.title)
+                    .emphasized()
+                    .foregroundColor(accentColor(item.appearance?.accent) ?? .black)
+                Spacer()
+                Text(item.displayName)
+                    .foregroundColor(Display.secondary)
+                    .font(.caption)
+                if let urls = try? itemUrls(item: item), !urls.isEmpty {
+                    Button("Open") { vm.perform(.openUrl) }
+                }
+                if !item.isTemplate {
+                    Button(vm.editingDetail ? "Done" : "Edit") {
+                        if vm.editingDetail { vm.finishEditing() } else { vm.editingDetail = true }
                     }
                 }
+                Button(vm.showHistory ? "Hide History" : "History") {
+                    vm.perform(.toggleHistory)
+                }
             }
-        )
-        .sheet(isPresented: $showAddSheet) {
-            AddEntrySheet(isPresented: $showAddSheet)
+            .padding(.bottom, 8)
+
+            if !Display.config.accessible {
+                Divider()
+            }
+
+            if vm.editingDetail {
+                InlineEditForm(name: name, item: item)
+                    .environment(vm)
+            } else {
+                switch item {
+                case .onlineAccount(let a):
+                    OnlineAccountDetail(account: a)
+                case .socialSecurity(let s):
+                    SsnDetail(ssn: s)
+                case .apiCredential(let c):
+                    ApiCredentialDetail(credential: c)
+                case .identityDocument(let d):
+                    IdentityDocumentDetail(document: d)
+                case .wifiNetwork(let w):
+                    WifiNetworkDetail(network: w)
+                case .cryptoWallet(let w):
+                    CryptoWalletDetail(wallet: w)
+                case .softwareLicense(let l):
+                    SoftwareLicenseDetail(license: l)
+                case .secureNote(let n):
+                    SecureNoteDetail(note: n)
+                case .serverCredential(let s):
+                    ServerCredentialDetail(server: s)
+                case .emailAccount(let e):
+                    EmailAccountDetail(mailbox: e)
+                case .membership(let m):
+                    MembershipDetail(membership: m)
+                case .insurancePolicy(let p):
+                    InsurancePolicyDetail(policy: p)
+                case .passkey(let p):
+                    PasskeyDetail(passkey: p)
+                case .pgpKey(let k):
+                    PgpKeyDetail(key: k)
+                case .driversLicense(let l):
+                    DriversLicenseDetail(license: l)
+                case .template(let t):
+                    TemplateItemDetail(item: t, template: vm.template(named: t.template))
+                }
+            }
+
+            TagsSection(name: name, item: item)
                 .environment(vm)
-        }
-        .sheet(isPresented: $showShareSheet) {
-            ShareSheet(receiveTicket: $receiveTicket, isPresented: $showShareSheet)
+
+            CustomFieldsSection(name: name, item: item)
+                .environment(vm)
+
+            if case .onlineAccount(let account) = item {
+                SecurityQuestionsEditor(name: name, item: item, questions: account.securityQuestions ?? [])
+                    .environment(vm)
+                TotpEditor(name: name, item: item, totp: account.totp)
+                    .environment(vm)
+                RecoveryCodesEditor(name: name, item: item, codes: account.recoveryCodes)
+                    .environment(vm)
+            }
+
+            AttachmentsSection(name: name)
                 .environment(vm)
+
+            if vm.showHistory {
+                Divider().padding(.top, 8)
+                HistoryPanel(name: name)
+                    .environment(vm)
+            }
+
+            Spacer()
         }
-        .alert(vm.$errorMessage) {
-            Button("OK") { vm.errorMessage = nil }
+        .padding()
+    }
+}
+
+/// Pinned above the scrolling detail so the field picked with the keyboard
+/// stays in sight however long the entry is.
+struct FocusedFieldBar: View {
+    let name: String
+    let item: FfiItem
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        if Display.config.accessible {
+            HStack {
+                Text(vm.announcement(name: name, item: item))
+                Spacer()
+            }
+            .padding(.horizontal)
+            .padding(.vertical, 4)
+        } else if let label = vm.focusedField,
+           let index = vm.detailFields.firstIndex(where: { $0.label == label }) {
+            let field = vm.detailFields[index]
+            HStack {
+                Text(label).font(.caption).foregroundColor(ink(.blue))
+                Text(field.secret && !vm.isRevealed(label) ? Glyph.mask(16) : field.value)
+                    .font(.caption)
+                Spacer()
+                Text("\(index + 1) of \(vm.detailFields.count)")
+                    .font(.caption)
+                    .foregroundColor(Display.secondary)
+            }
+            .padding(.horizontal)
+            .padding(.vertical, 4)
+        }
+    }
+}
+
+struct OnlineAccountDetail: View {
+    let account: FfiOnlineAccount
+
+    var body: some View {
+        VStack {
+            if let v = account.username { FieldRow(label: "Username", value: v) }
+            if let v = account.email { FieldRow(label: "Email", value: v) }
+            if let v = account.phone { FieldRow(label: "Phone", value: v) }
+            if let v = account.hostWebsite { FieldRow(label: "Website", value: v) }
+            if let v = account.password {
+                PasswordRow(password: v)
+            }
+            if let policy = account.passwordPolicy {
+                FieldRow(label: "Policy", value: policy.summary)
+            }
+            if let totp = account.totp {
+                TotpRow(totp: totp, account: account.username ?? account.email)
+            }
+            if let v = account.status { FieldRow(label: "Status", value: v) }
+            if let tfa = account.twoFactorEnabled {
+                FieldRow(label: "2FA", value: tfa ? "Enabled" : "Disabled")
+            }
+            if let v = account.dateCreated { FieldRow(label: "Created", value: v) }
+            if let questions = account.securityQuestions, !questions.isEmpty {
+                SecurityQuestionsRow(questions: questions)
+            }
+            if !account.recoveryCodes.isEmpty {
+                RecoveryCodesRow(codes: account.recoveryCodes)
+            }
+            if let names = account.associatedItems, !names.isEmpty {
+                LinkedItemsRows(names: names)
+            }
+            if let v = account.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+/// The entries this one links to. Clicking a name, or Return while its
+/// row is focused, opens it; the back key returns here.
+struct LinkedItemsRows: View {
+    let names: [String]
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        ForEach(Array(names.enumerated()), id: \.offset) { index, name in
+            let label = names.count == 1 ? "Linked" : "Linked \(index + 1)"
+            HStack {
+                Button(label) { vm.focus(label: label, value: name) }
+                    .foregroundColor(vm.focusedField == label ? ink(.blue) : Display.secondary)
+                    .font(.caption)
+                    .frame(width: 80)
+                if vm.entries.contains(name) {
+                    Button(name) {
+                        vm.focus(label: label, value: name)
+                        vm.followLink(name)
+                    }
+                    .foregroundColor(ink(.blue))
+                } else {
+                    Text("\(name) (missing)").foregroundColor(Display.secondary)
+                }
+                Spacer()
+            }
+            .padding(.vertical, 4)
+            .onAppear { vm.showLink(label: label, name: name) }
+        }
+    }
+}
+
+/// A count of the entry's security questions that expands (Return while
+/// focused) into each question with its answer masked until revealed.
+struct SecurityQuestionsRow: View {
+    let questions: [FfiSecurityQuestion]
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        let label = PasswordStoreViewModel.securityQuestionsLabel
+        let summary = questions.count == 1 ? "1 question" : "\(questions.count) questions"
+        VStack {
+            HStack {
+                Button(label) { vm.focus(label: label, value: summary) }
+                    .foregroundColor(vm.focusedField == label ? ink(.blue) : Display.secondary)
+                    .font(.caption)
+                    .frame(width: 80)
+                Text(summary)
+                Spacer()
+                Button(vm.showSecurityQuestions ? "Collapse" : "Expand") {
+                    vm.focus(label: label, value: summary)
+                    vm.showSecurityQuestions.toggle()
+                }
+            }
+            .padding(.vertical, 4)
+            .onAppear { vm.showField(label: label, value: summary) }
+
+            if vm.showSecurityQuestions {
+                ForEach(Array(questions.enumerated()), id: \.offset) { index, question in
+                    VStack {
+                        HStack {
+                            Text(question.question).font(.caption)
+                            Spacer()
+                        }
+                        .padding(.leading, 88)
+                        SecretRow(label: "Answer \(index + 1)", secret: question.answer)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How many recovery codes are left, expanding into each code with a button
+/// to mark it used once it has been spent.
+struct RecoveryCodesRow: View {
+    let codes: [FfiRecoveryCode]
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        let label = "Recovery"
+        let left = codes.filter { !$0.used }.count
+        let summary = "\(left) of \(codes.count) left"
+        VStack {
+            HStack {
+                Button(label) { vm.focus(label: label, value: summary) }
+                    .foregroundColor(vm.focusedField == label ? ink(.blue) : Display.secondary)
+                    .font(.caption)
+                    .frame(width: 80)
+                Text(summary).foregroundColor(left == 0 ? ink(.red) : .black)
+                Spacer()
+                Button(vm.showRecoveryCodes ? "Collapse" : "Expand") {
+                    vm.focus(label: label, value: summary)
+                    vm.showRecoveryCodes.toggle()
+                }
+            }
+            .padding(.vertical, 4)
+            .onAppear { vm.showField(label: label, value: summary) }
+
+            if vm.showRecoveryCodes {
+                ForEach(Array(codes.enumerated()), id: \.offset) { index, code in
+                    if code.used {
+                        HStack {
+                            Text("Code \(index + 1)")
+                                .foregroundColor(Display.secondary)
+                                .font(.caption)
+                                .frame(width: 80)
+                            Text("used").foregroundColor(Display.secondary)
+                            Spacer()
+                        }
+                        .padding(.vertical, 4)
+                    } else {
+                        HStack {
+                            SecretRow(label: "Code \(index + 1)", secret: code.code)
+                            Button("Mark used") { vm.markRecoveryCodeUsed(index) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Every form field of an item, each editable in place. A field is saved on
+/// its own as soon as it's committed, after validation in the core.
+struct InlineEditForm: View {
+    let name: String
+    let item: FfiItem
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        let fields = (try? itemEditableFields(item: item)) ?? []
+        VStack {
+            ForEach(fields, id: \.field.name) { entry in
+                if vm.editingField == entry.field.name {
+                    FormFieldRow(field: entry.field, value: vm.$editDraft, policy: item.passwordPolicy)
+                    if let error = vm.editError {
+                        Text(error).foregroundColor(ink(.red)).font(.caption)
+                    }
+                    HStack {
+                        Spacer()
+                        Button("Cancel") { vm.perform(.cancel) }
+                        Button("Save") { vm.perform(.save) }
+                    }
+                } else {
+                    HStack {
+                        Text(entry.field.name).frame(width: 120)
+                        Text(entry.field.kind == .secret && !entry.value.isEmpty ? Glyph.mask(8) : entry.value)
+                            .foregroundColor(entry.value.isEmpty ? .gray : .black)
+                        Spacer()
+                        Button("Edit") { vm.beginEdit(field: entry.field.name, value: entry.value) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct SsnDetail: View {
+    let ssn: FfiSocialSecurity
+
+    var body: some View {
+        VStack {
+            SensitiveHeader()
+            SecretRow(label: "SSN", secret: ssn.accountNumber)
+            if let v = ssn.legalName { FieldRow(label: "Name", value: v) }
+            if let v = ssn.countryOfIssue { FieldRow(label: "Country", value: v) }
+            if let v = ssn.issuanceDate { FieldRow(label: "Issued", value: v) }
+            if let v = ssn.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct IdentityDocumentDetail: View {
+    let document: FfiIdentityDocument
+
+    var body: some View {
+        VStack {
+            SensitiveHeader()
+            FieldRow(label: "Type", value: document.documentType)
+            FieldRow(label: "Number", value: document.number)
+            if let v = document.legalName { FieldRow(label: "Name", value: v) }
+            if let v = document.issuingCountry { FieldRow(label: "Country", value: v) }
+            if let v = document.issueDate { FieldRow(label: "Issued", value: v) }
+            if let v = document.expiryDate { FieldRow(label: "Expires", value: v) }
+            if let v = document.mrz, !v.isEmpty {
+                FieldRow(label: "MRZ", value: v.joined(separator: "\n"))
+            }
+            if let v = document.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct DriversLicenseDetail: View {
+    let license: FfiDriversLicense
+
+    var body: some View {
+        VStack {
+            SensitiveHeader()
+            FieldRow(label: "Number", value: license.number)
+            if let v = license.legalName { FieldRow(label: "Name", value: v) }
+            if let v = license.licenseClass { FieldRow(label: "Class", value: v) }
+            let issuer = [license.issuingRegion, license.issuingCountry].compactMap { $0 }
+            if !issuer.isEmpty { FieldRow(label: "Issued by", value: issuer.joined(separator: ", ")) }
+            if let v = license.issueDate { FieldRow(label: "Issued", value: v) }
+            if let v = license.expiryDate { FieldRow(label: "Expires", value: v) }
+            if let v = license.restrictions, !v.isEmpty {
+                FieldRow(label: "Restrictions", value: v.joined(separator: ", "))
+            }
+            if let v = license.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct WifiNetworkDetail: View {
+    let network: FfiWifiNetwork
+
+    var body: some View {
+        VStack {
+            FieldRow(label: "SSID", value: network.ssid)
+            FieldRow(label: "Security", value: network.security ?? "WPA")
+            if let v = network.passphrase {
+                SecretRow(label: "Passphrase", secret: v)
+            }
+            if network.hidden == true { FieldRow(label: "Hidden", value: "Yes") }
+            if let qr = try? wifiJoinQr(network: network, ascii: Display.config.ascii) {
+                QrCodeView(rendered: qr, caption: "Scan to join")
+            }
+            if let v = network.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+/// A QR code drawn with half-block characters, as rendered by the core.
+struct QrCodeView: View {
+    let rendered: String
+    let caption: String
+
+    var body: some View {
+        VStack {
+            HStack {
+                Text(caption).foregroundColor(Display.secondary).font(.caption)
+                Spacer()
+            }
+            Text(rendered).font(.system(size: 8, design: .monospaced))
+        }
+        .padding(.vertical, 4)
+    }
+}
+
+struct CryptoWalletDetail: View {
+    let wallet: FfiCryptoWallet
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            if let v = wallet.chain { FieldRow(label: "Chain", value: v) }
+            if let phrase = wallet.seedPhrase {
+                HStack {
+                    Button("Seed phrase") { vm.focus(label: "Seed phrase", value: phrase, secret: true) }
+                        .foregroundColor(vm.focusedField == "Seed phrase" ? ink(.blue) : Display.secondary)
+                        .font(.caption)
+                        .frame(width: 80)
+                    Spacer()
+                    RevealButton(label: "Seed phrase")
+                    Button("Copy") { vm.copy(label: "seed phrase", value: phrase) }
+                }
+                .padding(.vertical, 4)
+                if vm.isRevealed("Seed phrase") {
+                    // Numbered words make transcription onto paper less error-prone.
+                    let words = phrase.split(separator: " ").map(String.init)
+                    ForEach(Array(words.enumerated()), id: \.offset) { index, word in
+                        HStack {
+                            Text("\(index + 1).").foregroundColor(Display.secondary).frame(width: 32)
+                            Text(word)
+                            Spacer()
+                        }
+                    }
+                } else {
+                    HStack {
+                        Text(Glyph.mask(16))
+                        Spacer()
+                    }
+                }
+            }
+            if let v = wallet.passphrase {
+                SecretRow(label: "Passphrase", secret: v)
+            }
+            if let v = wallet.derivationPaths, !v.isEmpty {
+                FieldRow(label: "Paths", value: v.joined(separator: "\n"))
+            }
+            if let v = wallet.addresses {
+                ForEach(v, id: \.self) { address in
+                    FieldRow(label: "Address", value: address)
+                }
+            }
+            if let v = wallet.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct SoftwareLicenseDetail: View {
+    let license: FfiSoftwareLicense
+
+    var body: some View {
+        VStack {
+            FieldRow(label: "Product", value: license.product)
+            if let v = license.licenseKey { FieldRow(label: "Key", value: v) }
+            if let v = license.licensee { FieldRow(label: "Licensee", value: v) }
+            if let v = license.seats { FieldRow(label: "Seats", value: String(v)) }
+            if let v = license.purchaseDate { FieldRow(label: "Purchased", value: v) }
+            if let v = license.orderNumber { FieldRow(label: "Order", value: v) }
+            if let v = license.downloadUrl { FieldRow(label: "Download", value: v) }
+            if let v = license.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct SecureNoteDetail: View {
+    let note: FfiSecureNote
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            HStack {
+                Text(note.title).font(.headline)
+                Spacer()
+                Button("Copy") { vm.copy(label: "note", value: note.body) }
+            }
+            .padding(.bottom, 4)
+            HStack {
+                Spacer()
+                Button(vm.showRawNotes ? "Formatted" : "Raw") { vm.perform(.toggleRawNotes) }
+                    .font(.caption)
+            }
+            MarkdownText(text: note.body)
+                .frame(maxWidth: .infinity, alignment: .leading)
+        }
+    }
+}
+
+struct ServerCredentialDetail: View {
+    let server: FfiServerCredential
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            FieldRow(label: "Host", value: server.host)
+            if let v = server.port { FieldRow(label: "Port", value: String(v)) }
+            if let v = server.protocol { FieldRow(label: "Protocol", value: v) }
+            if let v = server.username { FieldRow(label: "Username", value: v) }
+            if let v = server.password { PasswordRow(password: v) }
+            if let v = server.connectionString {
+                HStack {
+                    Text("Connection")
+                        .foregroundColor(Display.secondary)
+                        .font(.caption)
+                        .frame(width: 80)
+                    // Show the template; the secret is only substituted on copy.
+                    Text(v)
+                    Spacer()
+                    Button("Copy") {
+                        if let expanded = try? serverConnectionString(server: server) {
+                            vm.copy(label: "connection string", value: expanded)
+                        }
+                    }
+                }
+                .padding(.vertical, 4)
+            }
+            if let v = server.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct EmailAccountDetail: View {
+    let mailbox: FfiEmailAccount
+
+    var body: some View {
+        VStack {
+            FieldRow(label: "Address", value: mailbox.address)
+            if let v = mailbox.username { FieldRow(label: "Username", value: v) }
+            if let v = mailbox.password { PasswordRow(password: v) }
+            if let v = mailbox.appPassword {
+                SecretRow(label: "App password", secret: v)
+            }
+            if let v = mailbox.imap { FieldRow(label: "IMAP", value: v.summary) }
+            if let v = mailbox.smtp { FieldRow(label: "SMTP", value: v.summary) }
+            if let v = mailbox.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct MembershipDetail: View {
+    let membership: FfiMembership
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            // Shown large: this is what gets read out or scanned at a counter.
+            if let number = membership.barcode ?? membership.memberId {
+                VStack {
+                    Text(number).font(.largeTitle)
+                    Button("Copy") { vm.copy(label: "member number", value: number) }
+                }
+                .padding(.vertical, 8)
+            }
+            FieldRow(label: "Program", value: membership.program)
+            if let v = membership.memberId { FieldRow(label: "Member ID", value: v) }
+            if let v = membership.tier { FieldRow(label: "Tier", value: v) }
+            if let v = membership.memberName { FieldRow(label: "Name", value: v) }
+            if let v = membership.expiryDate { FieldRow(label: "Expires", value: v) }
+            if let v = membership.pin { SecretRow(label: "PIN", secret: v) }
+            if let v = membership.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct InsurancePolicyDetail: View {
+    let policy: FfiInsurancePolicy
+
+    var body: some View {
+        VStack {
+            SensitiveHeader()
+            FieldRow(label: "Provider", value: policy.provider)
+            if let v = policy.policyNumber { FieldRow(label: "Policy", value: v) }
+            if let v = policy.groupNumber { FieldRow(label: "Group", value: v) }
+            if let v = policy.memberId { FieldRow(label: "Member ID", value: v) }
+            if let v = policy.coverageStart { FieldRow(label: "From", value: v) }
+            if let v = policy.coverageEnd { FieldRow(label: "Until", value: v) }
+            if let v = policy.emergencyPhone { FieldRow(label: "Emergency", value: v) }
+            if let v = policy.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct PasskeyDetail: View {
+    let passkey: FfiPasskey
+
+    var body: some View {
+        VStack {
+            FieldRow(label: "Site", value: passkey.rpName ?? passkey.rpId)
+            if passkey.rpName != nil { FieldRow(label: "RP ID", value: passkey.rpId) }
+            if let v = passkey.userName { FieldRow(label: "User", value: v) }
+            if let v = passkey.userDisplayName { FieldRow(label: "Display name", value: v) }
+            if let v = passkey.creationDate { FieldRow(label: "Created", value: v) }
+            FieldRow(label: "Credential", value: passkey.credentialId)
+            SecretRow(label: "Private key", secret: passkey.privateKey)
+            if let v = passkey.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+struct PgpKeyDetail: View {
+    @Environment(PasswordStoreViewModel.self) var vm
+    let key: FfiPgpKey
+
+    var body: some View {
+        VStack {
+            if let uids = key.uids {
+                ForEach(uids, id: \.self) { uid in FieldRow(label: "UID", value: uid) }
+            }
+            if let v = key.keyId { FieldRow(label: "Key ID", value: v) }
+            // Grouped for reading aloud when verifying out of band.
+            if let v = key.formattedFingerprint { FieldRow(label: "Fingerprint", value: v) }
+            if let v = key.expiryDate { FieldRow(label: "Expires", value: v) }
+            SecretRow(label: "Secret key", secret: key.privateKey)
+            if let v = key.passphrase {
+                SecretRow(label: "Passphrase", secret: v)
+            }
+            if let v = key.notes { NotesRow(notes: v) }
+            if let publicKey = key.publicKey {
+                HStack {
+                    Button("Export public key\(Glyph.ellipsis)") {
+                        let panel = NSSavePanel()
+                        panel.nameFieldStringValue = "\(key.keyId ?? "public").asc"
+                        guard panel.runModal() == .OK, let url = panel.url else { return }
+                        do {
+                            try publicKey.write(to: url, atomically: true, encoding: .utf8)
+                        } catch {
+                            vm.errorMessage = error.localizedDescription
+                        }
+                    }
+                    Spacer()
+                }
+                .padding(.top, 4)
+            }
+        }
+    }
+}
+
+/// Renders a user-defined item from its template's field list, so new item
+/// types need no code here. Values with no matching template field (or no
+/// template at all) are shown as plain text after the known fields.
+struct TemplateItemDetail: View {
+    let item: FfiTemplateItem
+    let template: ItemTemplate?
+
+    var body: some View {
+        let values = Dictionary(item.values.map { ($0.field, $0.value) }, uniquingKeysWith: { a, _ in a })
+        let known = template?.fields ?? []
+        let extra = item.values.filter { v in !known.contains { $0.name == v.field } }
+        VStack {
+            ForEach(known, id: \.name) { field in
+                if let value = values[field.name] {
+                    if field.kind == .secret {
+                        SecretRow(label: field.name, secret: value)
+                    } else {
+                        FieldRow(label: field.name, value: value)
+                    }
+                }
+            }
+            ForEach(extra, id: \.field) { v in
+                FieldRow(label: v.field, value: v.value)
+            }
+        }
+    }
+}
+
+struct ApiCredentialDetail: View {
+    let credential: FfiApiCredential
+
+    var body: some View {
+        VStack {
+            if credential.isExpired, let v = credential.expiryDate {
+                HStack {
+                    Text("Expired on \(v)").foregroundColor(ink(.red)).emphasized()
+                    Spacer()
+                }
+                .padding(.vertical, 4)
+            }
+            SecretRow(label: "Token", secret: credential.token)
+            if let v = credential.secret {
+                SecretRow(label: "Secret", secret: v)
+            }
+            if let v = credential.scopes, !v.isEmpty {
+                FieldRow(label: "Scopes", value: v.joined(separator: ", "))
+            }
+            if let v = credential.serviceUrl { FieldRow(label: "Service", value: v) }
+            if let v = credential.environment { FieldRow(label: "Environment", value: v) }
+            if let v = credential.expiryDate { FieldRow(label: "Expires", value: v) }
+            if let v = credential.notes { NotesRow(notes: v) }
+        }
+    }
+}
+
+// MARK: - Tags
+
+struct TagChips: View {
+    let tags: [String]
+
+    var body: some View {
+        HStack(spacing: 4) {
+            ForEach(tags, id: \.self) { tag in
+                Text(tag)
+                    .font(.caption)
+                    .foregroundColor(.white)
+                    .padding(.horizontal, 6)
+                    .background(tagColor(tag))
+                    .cornerRadius(8)
+            }
+        }
+    }
+}
+
+struct TagsSection: View {
+    let name: String
+    let item: FfiItem
+
+    @Environment(PasswordStoreViewModel.self) var vm
+    @State var newTag = ""
+
+    var body: some View {
+        let tags = item.tags
+        HStack {
+            ForEach(tags, id: \.self) { tag in
+                HStack(spacing: 2) {
+                    TagChips(tags: [tag])
+                    Button(Glyph.remove) {
+                        vm.update(name: name, item: item.withTags(tags.filter { $0 != tag }))
+                    }
+                }
+            }
+            TextField("add tag", text: $newTag)
+                .frame(width: 80)
+                .onSubmit {
+                    let tag = newTag.trimmingCharacters(in: .whitespaces)
+                    newTag = ""
+                    guard !tag.isEmpty, !tags.contains(where: { $0.lowercased() == tag.lowercased() }) else { return }
+                    vm.update(name: name, item: item.withTags(tags + [tag]))
+                }
+            Spacer()
+        }
+        .padding(.vertical, 4)
+    }
+}
+
+// MARK: - Custom fields
+
+struct CustomFieldsSection: View {
+    let name: String
+    let item: FfiItem
+
+    @Environment(PasswordStoreViewModel.self) var vm
+    @State var adding = false
+    @State var label = ""
+    @State var value = ""
+    @State var secret = false
+
+    var body: some View {
+        let fields = item.customFields
+        VStack {
+            if !fields.isEmpty {
+                Divider().padding(.vertical, 4)
+            }
+            ForEach(Array(fields.enumerated()), id: \.offset) { index, field in
+                HStack {
+                    if field.secret {
+                        SecretRow(label: field.label, secret: field.value)
+                    } else {
+                        FieldRow(label: field.label, value: field.value)
+                    }
+                    Button("Remove") {
+                        var updated = fields
+                        updated.remove(at: index)
+                        vm.update(name: name, item: item.withCustomFields(updated))
+                    }
+                }
+            }
+            if adding {
+                HStack {
+                    TextField("label", text: $label).frame(width: 80)
+                    TextField("value", text: $value)
+                    Toggle("Secret", isOn: $secret)
+                }
+                HStack {
+                    Button("Cancel") { adding = false }
+                    Spacer()
+                    Button("Save") {
+                        let field = FfiCustomField(label: label, value: value, secret: secret)
+                        vm.update(name: name, item: item.withCustomFields(fields + [field]))
+                        label = ""
+                        value = ""
+                        secret = false
+                        adding = false
+                    }
+                    .disabled(label.isEmpty)
+                }
+            } else {
+                HStack {
+                    Button("Add field") { adding = true }
+                    Spacer()
+                }
+                .padding(.top, 4)
+            }
+        }
+    }
+}
+
+/// Adds, re-answers and removes an account's security questions. A random
+/// answer is a short passphrase stored with its question, so nothing true
+/// about you is on record with the site.
+struct SecurityQuestionsEditor: View {
+    let name: String
+    let item: FfiItem
+    let questions: [FfiSecurityQuestion]
+
+    @Environment(PasswordStoreViewModel.self) var vm
+    @State var editing = false
+    @State var adding = false
+    @State var question = ""
+    @State var answer = ""
+
+    var body: some View {
+        VStack {
+            if editing {
+                ForEach(Array(questions.enumerated()), id: \.offset) { index, entry in
+                    HStack {
+                        Text(entry.question).font(.caption)
+                        Spacer()
+                        Button("Random answer") {
+                            var updated = questions
+                            updated[index].answer = generateSecurityAnswer()
+                            vm.update(name: name, item: item.withSecurityQuestions(updated))
+                        }
+                        Button("Remove") {
+                            var updated = questions
+                            updated.remove(at: index)
+                            vm.update(name: name, item: item.withSecurityQuestions(updated))
+                        }
+                    }
+                }
+            }
+            if adding {
+                HStack {
+                    TextField("question", text: $question)
+                }
+                HStack {
+                    TextField("answer", text: $answer)
+                    Button("Random answer") { answer = generateSecurityAnswer() }
+                }
+                HStack {
+                    Button("Cancel") { adding = false }
+                    Spacer()
+                    Button("Save") {
+                        let entry = FfiSecurityQuestion(question: question, answer: answer)
+                        vm.update(name: name, item: item.withSecurityQuestions(questions + [entry]))
+                        question = ""
+                        answer = ""
+                        adding = false
+                    }
+                    .disabled(question.isEmpty || answer.isEmpty)
+                }
+            } else {
+                HStack {
+                    Button("Add security question") { adding = true }
+                    if !questions.isEmpty {
+                        Button(editing ? "Done" : "Edit questions") { editing.toggle() }
+                    }
+                    Spacer()
+                }
+                .padding(.top, 4)
+            }
+        }
+    }
+}
+
+/// Adds or removes an account's TOTP secret, given as an otpauth:// URI,
+/// the path of a screenshot of its QR code, or in base32, or points the
+/// account at an OATH credential on a YubiKey instead.
+struct TotpEditor: View {
+    let name: String
+    let item: FfiItem
+    let totp: FfiTotp?
+
+    @Environment(PasswordStoreViewModel.self) var vm
+    @State var adding = false
+    @State var secret = ""
+    @State var problem: String? = nil
+    @State var keyCredentials: [String]? = nil
+
+    var body: some View {
+        VStack {
+            if let credentials = keyCredentials {
+                ForEach(credentials, id: \.self) { credential in
+                    HStack {
+                        Button(credential) {
+                            vm.update(name: name, item: item.withTotp(yubikeyTotp(credential: credential)))
+                            keyCredentials = nil
+                        }
+                        Spacer()
+                    }
+                }
+                if credentials.isEmpty {
+                    Text("No OATH credentials on the key").foregroundColor(Display.secondary).font(.caption)
+                }
+                HStack {
+                    Button("Cancel") { keyCredentials = nil }
+                    Spacer()
+                }
+            } else if adding {
+                HStack {
+                    TextField("otpauth:// URI, QR image path or base32 secret", text: $secret)
+                }
+                if let problem {
+                    Text(problem).foregroundColor(ink(.red)).font(.caption)
+                }
+                HStack {
+                    Button("Cancel") {
+                        adding = false
+                        problem = nil
+                    }
+                    Spacer()
+                    Button("Save") { save() }
+                        .disabled(secret.isEmpty)
+                }
+            } else {
+                HStack {
+                    if totp == nil {
+                        Button("Add one-time code") { adding = true }
+                        Button("Use YubiKey") {
+                            do {
+                                keyCredentials = try yubikeyCredentials()
+                            } catch {
+                                vm.errorMessage = error.localizedDescription
+                            }
+                        }
+                    } else {
+                        Button("Remove one-time code") {
+                            vm.update(name: name, item: item.withTotp(nil))
+                        }
+                    }
+                    Spacer()
+                }
+                .padding(.top, 4)
+            }
+        }
+    }
+
+    private func save() {
+        let entered: FfiTotp
+        do {
+            entered = try importTotp(input: secret)
+            _ = try totpCode(totp: entered)
+        } catch {
+            problem = error.localizedDescription
+            return
+        }
+        vm.update(name: name, item: item.withTotp(entered))
+        secret = ""
+        problem = nil
+        adding = false
+    }
+}
+
+/// Adds pasted recovery codes to an account and clears out the used ones.
+struct RecoveryCodesEditor: View {
+    let name: String
+    let item: FfiItem
+    let codes: [FfiRecoveryCode]
+
+    @Environment(PasswordStoreViewModel.self) var vm
+    @State var adding = false
+    @State var pasted = ""
+
+    var body: some View {
+        VStack {
+            if adding {
+                HStack {
+                    TextField("codes, separated by commas", text: $pasted)
+                }
+                HStack {
+                    Button("Cancel") { adding = false }
+                    Spacer()
+                    Button("Save") {
+                        let added = parseRecoveryCodes(text: pasted)
+                        vm.update(name: name, item: item.withRecoveryCodes(codes + added))
+                        pasted = ""
+                        adding = false
+                    }
+                    .disabled(parseRecoveryCodes(text: pasted).isEmpty)
+                }
+            } else {
+                HStack {
+                    Button("Add recovery codes") { adding = true }
+                    if codes.contains(where: { $0.used }) {
+                        Button("Remove used codes") {
+                            vm.update(name: name, item: item.withRecoveryCodes(codes.filter { !$0.used }))
+                        }
+                    }
+                    Spacer()
+                }
+                .padding(.top, 4)
+            }
+        }
+    }
+}
+
+struct AttachmentsSection: View {
+    let name: String
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        let files = vm.attachments(for: name)
+        VStack {
+            if !files.isEmpty {
+                Divider().padding(.vertical, 4)
+                Text("Attachments").font(.caption).foregroundColor(Display.secondary)
+            }
+            ForEach(files, id: \.self) { file in
+                HStack {
+                    Text(file)
+                    Spacer()
+                    Button("Open") { vm.openAttachment(name: name, file: file) }
+                    Button("Remove") { vm.removeAttachment(name: name, file: file) }
+                }
+            }
+            HStack {
+                Button("Attach file\(Glyph.ellipsis)") {
+                    let panel = NSOpenPanel()
+                    panel.canChooseDirectories = false
+                    panel.allowsMultipleSelection = true
+                    guard panel.runModal() == .OK else { return }
+                    for url in panel.urls {
+                        vm.addAttachment(name: name, path: url.path)
+                    }
+                }
+                Spacer()
+            }
+            .padding(.top, 4)
+        }
+    }
+}
+
+// MARK: - Version history panel
+
+struct HistoryPanel: View {
+    let name: String
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        let log = vm.logHistory(for: name)
+        VStack {
+            HStack {
+                Text("History")
+                    .font(.caption)
+                    .foregroundColor(Display.secondary)
+                Spacer()
+            }
+            if log.isEmpty {
+                Text("No history recorded.").font(.caption).foregroundColor(Display.secondary)
+            } else {
+                ForEach(log, id: \.hash) { entry in
+                    VStack {
+                        HStack {
+                            VStack {
+                                HStack {
+                                    Text(entry.message).font(.caption)
+                                    Spacer()
+                                }
+                                HStack {
+                                    Text(String(entry.hash.prefix(12)))
+                                        .font(.caption)
+                                        .foregroundColor(Display.secondary)
+                                    Text(entry.timestamp)
+                                        .font(.caption)
+                                        .foregroundColor(Display.secondary)
+                                    Spacer()
+                                }
+                            }
+                            Button("Revert") {
+                                vm.revert(name: name, toHash: entry.hash)
+                            }
+                        }
+                        .padding(.vertical, 2)
+                        Divider()
+                    }
+                }
+            }
+        }
+    }
+}
+
+// MARK: - Field rows
+
+struct SensitiveHeader: View {
+    var body: some View {
+        HStack {
+            Text("Sensitive Information")
+                .foregroundColor(ink(.red))
+                .font(.caption)
+            Spacer()
+        }
+        .padding(.bottom, 4)
+    }
+}
+
+struct FieldRow: View {
+    let label: String
+    let value: String
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        HStack {
+            Button(label) { vm.focus(label: label, value: value) }
+                .foregroundColor(vm.focusedField == label ? ink(.blue) : Display.secondary)
+                .font(.caption)
+                .frame(width: 80)
+            Text(value)
+            Spacer()
+            Button("Copy") {
+                vm.focus(label: label, value: value)
+                vm.copy(label: label, value: value)
+            }
+        }
+        .padding(.vertical, 4)
+        .onAppear { vm.showField(label: label, value: value) }
+    }
+}
+
+struct PasswordRow: View {
+    let password: String
+
+    var body: some View {
+        SecretRow(label: "Password", secret: password)
+    }
+}
+
+/// A masked secret; revealing it shows the value for the configured number
+/// of seconds.
+struct SecretRow: View {
+    let label: String
+    let secret: String
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        HStack {
+            Button(label) { vm.focus(label: label, value: secret, secret: true) }
+                .foregroundColor(vm.focusedField == label ? ink(.blue) : Display.secondary)
+                .font(.caption)
+                .frame(width: 80)
+            Text(vm.isRevealed(label) ? secret : Glyph.mask(16))
+            Spacer()
+            RevealButton(label: label)
+            Button("Copy") {
+                vm.focus(label: label, value: secret, secret: true)
+                vm.copy(label: label, value: secret)
+            }
+        }
+        .padding(.vertical, 4)
+        .onAppear { vm.showField(label: label, value: secret, secret: true) }
+    }
+}
+
+/// The account's current one-time code and the seconds it has left, or for
+/// counter-based (HOTP) secrets a button handing out the next code. The
+/// provisioning QR code re-enrols the secret in an authenticator app.
+struct TotpRow: View {
+    let totp: FfiTotp
+    let account: String?
+
+    @Environment(PasswordStoreViewModel.self) var vm
+    @State var showQr = false
+
+    var body: some View {
+        VStack {
+            codeRow
+            if showQr {
+                let label = account ?? vm.selectedEntry ?? ""
+                if let qr = try? totpProvisioningQr(totp: totp, account: label, ascii: Display.config.ascii) {
+                    QrCodeView(rendered: qr, caption: "Scan to enrol an authenticator")
+                }
+            }
+        }
+    }
+
+    var codeRow: some View {
+        HStack {
+            Text("One-time code")
+                .foregroundColor(Display.secondary)
+                .font(.caption)
+                .frame(width: 80)
+            if totp.kind == "hotp" {
+                if let code = vm.hotpCode { Text(code) }
+                Text("counter \(totp.counter ?? 0)").foregroundColor(Display.secondary).font(.caption)
+                Spacer()
+                Button("Next code") { vm.nextHotp() }
+                Button(showQr ? "Hide QR" : "QR") { showQr.toggle() }
+            } else if let code = vm.currentTotp {
+                Text(code.code)
+                if let issuer = totp.issuer {
+                    Text(issuer).foregroundColor(Display.secondary).font(.caption)
+                }
+                Text("\(code.remaining)s")
+                    .foregroundColor(code.remaining <= 5 ? ink(.orange) : Display.secondary)
+                    .font(.caption)
+                Spacer()
+                Button("Copy") { vm.copyTotp(totp) }
+                Button(showQr ? "Hide QR" : "QR") { showQr.toggle() }
+            } else {
+                Text(Glyph.ellipsis).foregroundColor(Display.secondary)
+                Spacer()
+            }
+        }
+        .padding(.vertical, 4)
+        .onAppear {
+            if totp.kind != "hotp" { vm.watchTotp(totp) }
+        }
+    }
+}
+
+/// Show/Hide for the secret called `label`, with the seconds left while shown;
+/// only for secrets revealed by key.
+struct RevealButton: View {
+    let label: String
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        if vm.maskPolicy(label) == .keypress {
+            if vm.revealedField == label {
+                if let left = vm.revealCountdown {
+                    Text("\(left)s").foregroundColor(Display.secondary).font(.caption)
+                }
+                Button("Hide") { vm.hideSecret() }
+            } else {
+                Button("Show") { vm.reveal(label: label) }
+            }
+        }
+    }
+}
+
+struct NotesRow: View {
+    let notes: String
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            HStack {
+                Text("Notes").foregroundColor(Display.secondary).font(.caption)
+                Spacer()
+                Button(vm.showRawNotes ? "Formatted" : "Raw") { vm.perform(.toggleRawNotes) }
+                    .font(.caption)
+            }
+            MarkdownText(text: notes)
+                .padding(8)
+        }
+        .padding(.vertical, 4)
+    }
+}
+
+/// Note text rendered from markdown, or verbatim while raw notes are shown.
+struct MarkdownText: View {
+    let text: String
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        if vm.showRawNotes {
+            HStack {
+                Text(text)
+                Spacer()
+            }
+        } else {
+            VStack(alignment: .leading) {
+                ForEach(Array(renderMarkdown(text: text).enumerated()), id: \.offset) { _, block in
+                    MarkdownBlockView(block: block)
+                }
+            }
+        }
+    }
+}
+
+struct MarkdownBlockView: View {
+    let block: MarkdownBlock
+
+    var body: some View {
+        switch block.kind {
+        case .heading(let level):
+            MarkdownSpans(spans: block.spans)
+                .font(level == 1 ? .title : .headline)
+                .padding(.top, 4)
+        case .listItem(let depth, let marker):
+            HStack(alignment: .top) {
+                Text(marker == "•" ? Glyph.bullet : marker).foregroundColor(Display.secondary)
+                MarkdownSpans(spans: block.spans)
+            }
+            .padding(.leading, Int(depth) * 16)
+        case .quote:
+            HStack {
+                Text(Glyph.quoteBar).foregroundColor(Display.secondary)
+                MarkdownSpans(spans: block.spans).foregroundColor(Display.secondary)
+            }
+        case .codeBlock:
+            MarkdownSpans(spans: block.spans)
+                .font(.system(size: 12, design: .monospaced))
+                .padding(4)
+        case .rule:
+            Divider()
+        case .paragraph:
+            MarkdownSpans(spans: block.spans)
+        }
+    }
+}
+
+/// One block's styled runs; each line of a multi-line run is its own row.
+struct MarkdownSpans: View {
+    let spans: [MarkdownSpan]
+
+    var body: some View {
+        VStack(alignment: .leading, spacing: 0) {
+            ForEach(Array(lines.enumerated()), id: \.offset) { _, line in
+                HStack(spacing: 0) {
+                    ForEach(Array(line.enumerated()), id: \.offset) { _, span in
+                        spanText(span)
+                    }
+                    Spacer()
+                }
+            }
+        }
+    }
+
+    /// `spans` split at newlines, keeping each piece's style.
+    private var lines: [[MarkdownSpan]] {
+        var lines: [[MarkdownSpan]] = [[]]
+        for span in spans {
+            for (index, piece) in span.text.split(separator: "\n", omittingEmptySubsequences: false).enumerated() {
+                if index > 0 { lines.append([]) }
+                if piece.isEmpty { continue }
+                var styled = span
+                styled.text = String(piece)
+                lines[lines.count - 1].append(styled)
+            }
+        }
+        return lines
+    }
+
+    @ViewBuilder
+    private func spanText(_ span: MarkdownSpan) -> some View {
+        if span.code {
+            Text(span.text).font(.system(size: 12, design: .monospaced)).foregroundColor(ink(.orange))
+        } else if span.link != nil {
+            Text(span.text).foregroundColor(ink(.blue))
+        } else if span.bold {
+            Text(span.text).emphasized()
+        } else if span.italic {
+            Text(span.text).foregroundColor(Display.secondary)
+        } else {
+            Text(span.text)
+        }
+    }
+}
+
+// MARK: - Add entry sheet
+
+/// Two-step wizard: pick an item type, then fill in its fields.
+///
+/// Built-in kinds are validated by `itemFromForm`; user templates are
+/// validated by the store when the entry is added.
+struct AddEntrySheet: View {
+    @Binding var isPresented: Bool
+
+    @Environment(PasswordStoreViewModel.self) var vm
+    @State var name = ""
+    @State var form: ItemTemplate? = nil
+    @State var isTemplate = false
+    @State var values: [String: String] = [:]
+    @State var error: String? = nil
+
+    var body: some View {
+        VStack {
+            Text("Add Entry").font(.title).padding(.bottom)
+
+            if let form {
+                HStack {
+                    Text("Name").frame(width: 120)
+                    TextField("entry-name", text: $name)
+                }
+                ForEach(form.fields, id: \.name) { field in
+                    FormFieldRow(field: field, value: binding(for: field.name))
+                }
+                if let error {
+                    Text(error).foregroundColor(ink(.red)).font(.caption)
+                }
+                HStack {
+                    Button("Back") {
+                        self.form = nil
+                        values = [:]
+                        error = nil
+                    }
+                    Spacer()
+                    Button("Add") { submit(form) }
+                        .disabled(name.isEmpty)
+                }
+                .padding(.top)
+            } else {
+                Text("Choose a type").foregroundColor(Display.secondary)
+                ForEach(itemForms(), id: \.name) { form in
+                    Button(form.label ?? form.name) { choose(form, isTemplate: false) }
+                }
+                if !vm.templates.isEmpty {
+                    Divider()
+                    ForEach(vm.templates, id: \.name) { template in
+                        Button(template.label ?? template.name) { choose(template, isTemplate: true) }
+                    }
+                }
+                HStack {
+                    Button("Cancel") { isPresented = false }
+                    Spacer()
+                }
+                .padding(.top)
+            }
+        }
+        .padding()
+        .frame(minWidth: 400)
+    }
+
+    func binding(for field: String) -> Binding<String> {
+        Binding(
+            get: { values[field] ?? "" },
+            set: { values[field] = $0 }
+        )
+    }
+
+    func choose(_ form: ItemTemplate, isTemplate: Bool) {
+        self.form = form
+        self.isTemplate = isTemplate
+        values = [:]
+        error = nil
+    }
+
+    func submit(_ form: ItemTemplate) {
+        let filled = form.fields.compactMap { field -> FfiTemplateValue? in
+            guard let value = values[field.name], !value.isEmpty else { return nil }
+            return FfiTemplateValue(field: field.name, value: value)
+        }
+        let item: FfiItem
+        if isTemplate {
+            item = .template(item: FfiTemplateItem(template: form.name, values: filled, tags: [], customFields: [], appearance: nil))
+        } else {
+            do {
+                item = try itemFromForm(kind: form.name, values: filled)
+            } catch {
+                self.error = error.localizedDescription
+                return
+            }
+        }
+        vm.add(name: name, item: item)
+        if vm.errorMessage == nil { isPresented = false }
+    }
+}
+
+/// One labelled input in the add-entry wizard, chosen by the field's kind.
+struct FormFieldRow: View {
+    let field: TemplateField
+    @Binding var value: String
+    /// The site's password rules, followed by the generator.
+    var policy: FfiPasswordPolicy? = nil
+
+    /// Whether the generator panel is open under a secret field.
+    @State var generating = false
+
+    var body: some View {
+        HStack {
+            Text(field.required ? "\(field.name) *" : field.name).frame(width: 120)
+            switch field.kind {
+            case .enum:
+                ForEach(field.options, id: \.self) { option in
+                    Button(option) { value = value == option ? "" : option }
+                        .foregroundColor(value == option ? ink(.blue) : Display.secondary)
+                }
+                Spacer()
+            case .secret:
+                VStack(alignment: .leading) {
+                    HStack {
+                        TextField("", text: $value)
+                        Button(generating ? "Close generator" : "Generate\(Glyph.ellipsis)") {
+                            generating.toggle()
+                        }
+                    }
+                    if generating {
+                        GeneratorPanel(policy: policy) { password in
+                            value = password
+                            generating = false
+                        }
+                    }
+                }
+            case .date:
+                TextField("YYYY-MM-DD", text: $value)
+            case .url:
+                TextField("https://\(Glyph.ellipsis)", text: $value)
+            case .number:
+                TextField("0", text: $value)
+            case .text:
+                TextField("", text: $value)
+            }
+        }
+    }
+}
+
+// MARK: - Generator
+
+/// A mode and its settings, and a live preview of the secret they make. Every change draws a fresh secret from the system's
+/// secure random source.
+struct GeneratorPanel: View {
+    /// A site's password rules to generate within, when editing its entry.
+    var policy: FfiPasswordPolicy? = nil
+    /// Called with the previewed secret when it is accepted.
+    let onAccept: (String) -> Void
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    static let modes = ["Characters", "Passphrase", "Pronounceable", "PIN", "Recipe"]
+
+    @State var mode = "Characters"
+    @State var length = 20.0
+    @State var lower = true
+    @State var upper = true
+    @State var digits = true
+    @State var symbols = true
+    @State var exclude = ""
+    @State var unambiguous = false
+    @State var requireEach = true
+    @State var words = 6.0
+    @State var separator = "-"
+    @State var capitalize = false
+    @State var digit = false
+    @State var spoken = 14.0
+    @State var pinLength = 6.0
+    @State var recipe = "word-word-####-!"
+    @State var preview = ""
+    /// Why the recipe can't be used, when it can't.
+    @State var problem: String? = nil
+
+    var options: GeneratorOptions {
+        guard let policy else { return chosen }
+        return (try? generatorOptionsWithin(options: chosen, policy: policy)) ?? chosen
+    }
+
+    /// The options as set in the panel, before any site policy.
+    var chosen: GeneratorOptions {
+        switch mode {
+        case "Passphrase":
+            return .passphrase(options: PassphraseOptions(
+                words: UInt32(words), separator: separator, capitalize: capitalize, digit: digit))
+        case "Pronounceable":
+            return .pronounceable(options: PronounceableOptions(length: UInt32(spoken)))
+        case "PIN":
+            return .pin(options: PinOptions(length: UInt32(pinLength)))
+        case "Recipe":
+            return .recipe(recipe: recipe)
+        default:
+            return .password(options: PasswordOptions(
+                length: UInt32(length), lower: lower, upper: upper, digits: digits, symbols: symbols,
+                exclude: exclude, excludeAmbiguous: unambiguous, requireEach: requireEach))
+        }
+    }
+
+    var body: some View {
+        let strength = generatorStrength(options: options)
+        VStack(alignment: .leading) {
+            if !vm.generatorPresets.isEmpty {
+                HStack {
+                    Text("Presets").frame(width: 100)
+                    ForEach(vm.generatorPresets, id: \.name) { preset in
+                        Button(preset.name) { apply(preset.options) }
+                    }
+                }
+            }
+            HStack {
+                ForEach(Self.modes, id: \.self) { option in
+                    Button(option) { mode = option }
+                        .foregroundColor(mode == option ? ink(.blue) : Display.secondary)
+                }
+            }
+            switch mode {
+            case "Passphrase":
+                HStack {
+                    Text("Words \(Int(words))").frame(width: 100)
+                    Slider($words, minimum: 3, maximum: 12)
+                }
+                HStack {
+                    Text("Separator").frame(width: 100)
+                    TextField("-", text: $separator).frame(width: 40)
+                    Toggle("Capitalise", isOn: $capitalize)
+                    Toggle("Add a digit", isOn: $digit)
+                }
+            case "Pronounceable":
+                HStack {
+                    Text("Length \(Int(spoken))").frame(width: 100)
+                    Slider($spoken, minimum: 6, maximum: 32)
+                }
+            case "PIN":
+                HStack {
+                    Text("Digits \(Int(pinLength))").frame(width: 100)
+                    Slider($pinLength, minimum: 4, maximum: 12)
+                }
+            case "Recipe":
+                HStack {
+                    Text("Recipe").frame(width: 100)
+                    TextField("word-word-####-!", text: $recipe)
+                }
+                Text("word Word  # digit  a A letter  ! symbol  * letter or digit  [a-f] set  {n} repeat")
+                    .foregroundColor(Display.secondary)
+                    .font(.caption)
+            default:
+                HStack {
+                    Text("Length \(Int(length))").frame(width: 100)
+                    Slider($length, minimum: 4, maximum: 64)
+                }
+                HStack {
+                    Toggle("a-z", isOn: $lower)
+                    Toggle("A-Z", isOn: $upper)
+                    Toggle("0-9", isOn: $digits)
+                    Toggle("!#$", isOn: $symbols)
+                }
+                HStack {
+                    Toggle("No look-alikes (O 0 l 1)", isOn: $unambiguous)
+                    Toggle("One of each", isOn: $requireEach)
+                }
+            }
+            HStack {
+                Text(preview).font(.system(size: 13, design: .monospaced))
+                Spacer()
+                Button("Regenerate") { regenerate() }
+            }
+            if let problem {
+                Text(problem).foregroundColor(ink(.red)).font(.caption)
+            }
+            if let policy {
+                Text("Following the site's policy: \(policy.summary)")
+                    .foregroundColor(Display.secondary)
+                    .font(.caption)
+            }
+            Text("\(strength.label) \(Glyph.bullet) \(strength.bits) bits")
+                .foregroundColor(strengthInk(strength.strength))
+                .font(.caption)
+            Button("Accept") { onAccept(preview) }
+                .foregroundColor(ink(.blue))
+        }
+        .onAppear {
+            if let siteRecipe = policy?.recipe {
+                mode = "Recipe"
+                recipe = siteRecipe
+            }
+            regenerate()
+        }
+        .onChange(of: options) { regenerate() }
+    }
+
+    /// Set the panel's mode and settings to a preset's.
+    func apply(_ preset: GeneratorOptions) {
+        switch preset {
+        case .password(let options):
+            mode = "Characters"
+            length = Double(options.length)
+            lower = options.lower
+            upper = options.upper
+            digits = options.digits
+            symbols = options.symbols
+            exclude = options.exclude
+            unambiguous = options.excludeAmbiguous
+            requireEach = options.requireEach
+        case .passphrase(let options):
+            mode = "Passphrase"
+            words = Double(options.words)
+            separator = options.separator
+            capitalize = options.capitalize
+            digit = options.digit
+        case .pronounceable(let options):
+            mode = "Pronounceable"
+            spoken = Double(options.length)
+        case .pin(let options):
+            mode = "PIN"
+            pinLength = Double(options.length)
+        case .recipe(let text):
+            mode = "Recipe"
+            recipe = text
+        }
+    }
+
+    func regenerate() {
+        do {
+            preview = try generateSecret(options: options)
+            problem = nil
+        } catch {
+            preview = ""
+            problem = error.localizedDescription
+        }
+    }
+
+    func strengthInk(_ strength: Strength) -> Color {
+        switch strength {
+        case .veryWeak, .weak: return ink(.red)
+        case .fair: return ink(.orange)
+        case .strong, .veryStrong: return ink(.green)
+        }
+    }
+}
+
+/// The generator on its own; the accepted secret goes to the clipboard.
+struct GeneratorSheet: View {
+    @Binding var isPresented: Bool
+
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            Text("Generate").font(.title).padding(.bottom)
+            GeneratorPanel { password in
+                vm.copy(label: "generated secret", value: password)
+                isPresented = false
+            }
+            Button("Close") { isPresented = false }
+                .padding(.top)
+        }
+        .padding()
+        .frame(minWidth: 400)
+    }
+}
+
+// MARK: - Delete / trash
+
+/// Asks before moving an entry to the trash. Typing the entry's name and
+/// pressing Return also confirms.
+/// Asked before leaving an inline edit that hasn't been saved.
+struct UnsavedChangesSheet: View {
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            Text("Save your change to \(vm.editingField ?? "this field")?").font(.title).padding(.bottom)
+            Text("Discarding keeps the value that was saved before.")
+                .foregroundColor(Display.secondary)
+                .font(.caption)
+            HStack {
+                Button("Cancel") { vm.cancelLeave() }
+                Spacer()
+                Button("Discard") { vm.resolveUnsaved(save: false) }
+                    .foregroundColor(ink(.red))
+                Button("Save") { vm.resolveUnsaved(save: true) }
+                    .foregroundColor(ink(.blue))
+            }
+            .padding(.top)
         }
+        .padding()
+        .frame(minWidth: 400)
     }
 }
 
-// MARK: - Detail
-
-struct DetailView: View {
+struct DeleteConfirmSheet: View {
     let name: String
-    let item: FfiItem
+    @Binding var isPresented: Bool
 
     @Environment(PasswordStoreViewModel.self) var vm
-    @State var showPassword = false
-    @State var showHistory = false
+    @State var typed = ""
 
     var body: some View {
-        ScrollView {
-            VStack {
-                HStack {
-                    Text(name).font(.title).emphasized()
-                    Spacer()
-                    Text(item.displayName)
-                        .foregroundColor(.gray)
-                        .font(.caption)
-                    Button(showHistory ? "Hide History" : "History") {
-                        showHistory.toggle()
-                    }
-                }
-                .padding(.bottom, 8)
+        VStack {
+            Text("Move \"\(name)\" to the trash?").font(.title).padding(.bottom)
+            Text("It can be restored from the trash until the trash is emptied.")
+                .foregroundColor(Display.secondary)
+                .font(.caption)
+            TextField("Type the entry name or click Move to Trash", text: $typed)
+                .onSubmit { if typed == name { confirm() } }
+                .padding(.vertical)
+            HStack {
+                Button("Cancel") { isPresented = false }
+                Spacer()
+                Button("Move to Trash") { confirm() }
+                    .foregroundColor(ink(.red))
+            }
+        }
+        .padding()
+        .frame(minWidth: 400)
+    }
 
-                Divider()
+    func confirm() {
+        vm.remove(name: name)
+        isPresented = false
+    }
+}
 
-                switch item {
-                case .onlineAccount(let a):
-                    OnlineAccountDetail(name: name, account: a, showPassword: $showPassword)
-                case .socialSecurity(let s):
-                    SsnDetail(ssn: s)
-                }
+struct TrashSheet: View {
+    @Binding var isPresented: Bool
+
+    @Environment(PasswordStoreViewModel.self) var vm
+    @State var confirmEmpty = false
+
+    var body: some View {
+        let trashed = vm.trashed
+        VStack {
+            Text("Trash").font(.title).padding(.bottom)
 
-                if showHistory {
-                    Divider().padding(.top, 8)
-                    HistoryPanel(name: name)
-                        .environment(vm)
+            if trashed.isEmpty {
+                Text("The trash is empty").foregroundColor(Display.secondary)
+            }
+            ForEach(trashed, id: \.self) { name in
+                HStack {
+                    Text(name)
+                    Spacer()
+                    Button("Restore") { vm.restore(name: name) }
                 }
+            }
 
+            HStack {
+                if confirmEmpty {
+                    Text("Delete \(trashed.count) entries permanently?").foregroundColor(ink(.red))
+                    Button("Cancel") { confirmEmpty = false }
+                    Button("Empty Trash") {
+                        vm.emptyTrash()
+                        confirmEmpty = false
+                    }
+                    .foregroundColor(ink(.red))
+                } else {
+                    Button("Empty Trash\(Glyph.ellipsis)") { confirmEmpty = true }
+                        .disabled(trashed.isEmpty)
+                }
                 Spacer()
+                Button("Close") { isPresented = false }
             }
-            .padding()
+            .padding(.top)
         }
-        .frame(minWidth: 420)
+        .padding()
+        .frame(minWidth: 400)
     }
 }
 
-struct OnlineAccountDetail: View {
-    let name: String
-    let account: FfiOnlineAccount
-    @Binding var showPassword: Bool
+/// Accounts marked deactivated, to open or move to the trash one by one.
+struct GraveyardSheet: View {
+    @Binding var isPresented: Bool
 
     @Environment(PasswordStoreViewModel.self) var vm
-    @State var editing = false
-    @State var draft = FfiOnlineAccount.empty()
 
     var body: some View {
+        let deactivated = vm.graveyard
         VStack {
-            if editing {
-                EditOnlineAccountView(name: name, draft: $draft, editing: $editing)
-                    .environment(vm)
-            } else {
-                if let v = account.username { FieldRow(label: "Username", value: v) }
-                if let v = account.email { FieldRow(label: "Email", value: v) }
-                if let v = account.phone { FieldRow(label: "Phone", value: v) }
-                if let v = account.hostWebsite { FieldRow(label: "Website", value: v) }
-                if let v = account.password {
-                    PasswordRow(password: v, showPassword: $showPassword)
-                }
-                if let v = account.status { FieldRow(label: "Status", value: v) }
-                if let tfa = account.twoFactorEnabled {
-                    FieldRow(label: "2FA", value: tfa ? "Enabled" : "Disabled")
-                }
-                if let v = account.dateCreated { FieldRow(label: "Created", value: v) }
-                if let v = account.notes { NotesRow(notes: v) }
+            Text("Graveyard").font(.title).padding(.bottom)
 
-                Button("Edit") {
-                    draft = account
-                    editing = true
+            if deactivated.isEmpty {
+                Text("No deactivated accounts").foregroundColor(Display.secondary)
+            }
+            ForEach(deactivated, id: \.self) { name in
+                HStack {
+                    Text(name)
+                    Spacer()
+                    Button("Open") {
+                        isPresented = false
+                        vm.selectRow(name)
+                    }
+                    Button("Trash") { vm.remove(name: name) }
+                        .foregroundColor(ink(.red))
                 }
-                .padding(.top, 8)
             }
+
+            Button("Close") { isPresented = false }
+                .padding(.top)
         }
+        .padding()
+        .frame(minWidth: 400)
     }
 }
 
-struct EditOnlineAccountView: View {
-    let name: String
-    @Binding var draft: FfiOnlineAccount
-    @Binding var editing: Bool
+// MARK: - Help
+
+/// Every key binding, grouped by where it applies. Rendered from the same
+/// keymap the key handler uses.
+// MARK: - Open URL
+
+/// A new name for the selected entry; a name with a `/` moves it into
+/// that folder.
+struct RenameSheet: View {
+    @Binding var isPresented: Bool
 
     @Environment(PasswordStoreViewModel.self) var vm
-    @State var username = ""
-    @State var password = ""
-    @State var email = ""
-    @State var website = ""
-    @State var notes = ""
 
     var body: some View {
         VStack {
+            Text(vm.duplicating ? "Duplicate Entry" : "Rename Entry").font(.title).padding(.bottom)
             HStack {
-                Text("Username").frame(width: 80)
-                TextField("", text: $username)
-            }
-            HStack {
-                Text("Password").frame(width: 80)
-                TextField("", text: $password)
-            }
-            HStack {
-                Text("Email").frame(width: 80)
-                TextField("", text: $email)
+                Text("Name").frame(width: 80)
+                TextField("entry-name", text: vm.$renameDraft)
             }
-            HStack {
-                Text("Website").frame(width: 80)
-                TextField("", text: $website)
+            if let error = vm.errorMessage {
+                Text(error).foregroundColor(ink(.red)).font(.caption)
             }
             HStack {
-                Text("Notes").frame(width: 80)
-                TextField("", text: $notes)
+                Button("Cancel") { isPresented = false }
+                Spacer()
+                Button(vm.duplicating ? "Duplicate" : "Rename") { vm.commitRename() }
+                    .disabled(vm.renameDraft.isEmpty || vm.renameDraft == vm.selectedEntry)
             }
+            .padding(.top)
+        }
+        .padding()
+        .frame(minWidth: 360)
+    }
+}
+
+/// Two entries side by side: every field on which they differ, with the
+/// value to keep chosen per field, and which name survives.
+struct MergeSheet: View {
+    @Binding var isPresented: Bool
 
+    @Environment(PasswordStoreViewModel.self) var vm
+
+    var body: some View {
+        VStack {
+            Text("Merge Entries").font(.title).padding(.bottom)
+            if let left = vm.mergeLeft, let right = vm.mergeRight {
+                HStack {
+                    Text("").frame(width: 100)
+                    Text(left.name).emphasized().frame(width: 200)
+                    Text(right.name).emphasized().frame(width: 200)
+                }
+                if vm.mergeDifferences.isEmpty {
+                    Text("The entries' fields are identical.").foregroundColor(Display.secondary)
+                }
+                ForEach(vm.mergeDifferences, id: \.field.name) { diff in
+                    let right = vm.mergeTakeRight.contains(diff.field.name)
+                    HStack {
+                        Text(diff.field.name).font(.caption).frame(width: 100)
+                        choice(diff.left, secret: diff.field.kind == .secret, chosen: !right) {
+                            vm.mergeTakeRight.remove(diff.field.name)
+                        }
+                        choice(diff.right, secret: diff.field.kind == .secret, chosen: right) {
+                            vm.mergeTakeRight.insert(diff.field.name)
+                        }
+                    }
+                }
+                HStack {
+                    Text("Keep name").font(.caption).frame(width: 100)
+                    choice(left.name, secret: false, chosen: !vm.mergeKeepRight) { vm.mergeKeepRight = false }
+                    choice(right.name, secret: false, chosen: vm.mergeKeepRight) { vm.mergeKeepRight = true }
+                }
+                .padding(.top)
+                Text("The other entry is moved to the trash.").foregroundColor(Display.secondary).font(.caption)
+            }
             HStack {
-                Button("Cancel") { editing = false }
+                Button("Cancel") { isPresented = false }
                 Spacer()
-                Button("Save") {
-                    let updated = FfiOnlineAccount(
-                        username: username.isEmpty ? nil : username,
-                        password: password.isEmpty ? nil : password,
-                        email: email.isEmpty ? nil : email,
-                        phone: draft.phone,
-                        signInWith: draft.signInWith,
-                        status: draft.status,
-                        hostWebsite: website.isEmpty ? nil : website,
-                        loginPages: draft.loginPages,
-                        securityQuestions: draft.securityQuestions,
-                        twoFactorEnabled: draft.twoFactorEnabled,
-                        associatedItems: draft.associatedItems,
-                        dateCreated: draft.dateCreated,
-                        notes: notes.isEmpty ? nil : notes
-                    )
-                    vm.update(name: name, item: .onlineAccount(account: updated))
-                    editing = false
-                }
-            }
-            .padding(.top, 8)
-        }
-        .onAppear {
-            username = draft.username ?? ""
-            password = draft.password ?? ""
-            email = draft.email ?? ""
-            website = draft.hostWebsite ?? ""
-            notes = draft.notes ?? ""
+                Button("Merge") { vm.commitMerge() }
+            }
+            .padding(.top)
         }
+        .padding()
+        .frame(minWidth: 520)
+    }
+
+    private func choice(_ value: String, secret: Bool, chosen: Bool, pick: @escaping () -> Void) -> some View {
+        let shown = value.isEmpty ? "(empty)" : secret ? Glyph.mask(8) : value
+        return Button((chosen ? Glyph.bullet + " " : "") + shown, action: pick)
+            .foregroundColor(chosen ? ink(.blue) : Display.secondary)
+            .frame(width: 200)
     }
 }
 
-struct SsnDetail: View {
-    let ssn: FfiSocialSecurity
+/// Pick which of an entry's login pages to open.
+struct UrlPickerSheet: View {
+    @Binding var isPresented: Bool
+
+    @Environment(PasswordStoreViewModel.self) var vm
 
     var body: some View {
         VStack {
-            FieldRow(label: "Number", value: ssn.accountNumber)
-            if let v = ssn.legalName { FieldRow(label: "Name", value: v) }
-            if let v = ssn.countryOfIssue { FieldRow(label: "Country", value: v) }
-            if let v = ssn.issuanceDate { FieldRow(label: "Issued", value: v) }
-            if let v = ssn.notes { NotesRow(notes: v) }
+            Text("Open which page?").font(.title).padding(.bottom)
+            ForEach(vm.urlChoices, id: \.self) { url in
+                Button(url) {
+                    isPresented = false
+                    vm.openLink(url)
+                }
+            }
+            Button("Cancel") { isPresented = false }
+                .padding(.top)
         }
+        .padding()
     }
 }
 
-// MARK: - Version history panel
+/// The jump list: entries opened recently in this vault.
+struct RecentSheet: View {
+    @Binding var isPresented: Bool
 
-struct HistoryPanel: View {
-    let name: String
     @Environment(PasswordStoreViewModel.self) var vm
 
     var body: some View {
-        let log = vm.logHistory(for: name)
         VStack {
-            HStack {
-                Text("History")
-                    .font(.caption)
-                    .foregroundColor(.gray)
-                Spacer()
+            Text("Recently viewed").font(.title).padding(.bottom)
+            if vm.recentlyViewed.isEmpty {
+                Text("Entries you open will be listed here.").foregroundColor(Display.secondary)
             }
-            if log.isEmpty {
-                Text("No history recorded.").font(.caption).foregroundColor(.gray)
-            } else {
-                ForEach(log, id: \.hash) { entry in
-                    VStack {
-                        HStack {
-                            VStack {
-                                HStack {
-                                    Text(entry.message).font(.caption)
-                                    Spacer()
-                                }
-                                HStack {
-                                    Text(String(entry.hash.prefix(12)))
-                                        .font(.caption)
-                                        .foregroundColor(.gray)
-                                    Text(entry.timestamp)
-                                        .font(.caption)
-                                        .foregroundColor(.gray)
-                                    Spacer()
-                                }
-                            }
-                            Button("Revert") {
-                                vm.revert(name: name, toHash: entry.hash)
-                            }
-                        }
-                        .padding(.vertical, 2)
-                        Divider()
-                    }
+            ForEach(vm.recentlyViewed, id: \.self) { name in
+                Button(name) {
+                    isPresented = false
+                    vm.selectRow(name)
                 }
             }
+            Button("Close") { isPresented = false }
+                .padding(.top)
         }
+        .padding()
+        .frame(minWidth: 320)
     }
 }
 
-// MARK: - Field rows
+/// Counts across the vault: entries by kind, password health, two-factor
+/// coverage and recent activity.
+struct StatsSheet: View {
+    @Binding var isPresented: Bool
 
-struct FieldRow: View {
-    let label: String
-    let value: String
+    @Environment(PasswordStoreViewModel.self) var vm
 
     var body: some View {
-        HStack {
-            Text(label)
-                .foregroundColor(.gray)
-                .font(.caption)
-                .frame(width: 80)
-            Text(value)
-            Spacer()
-            Button("Copy") { copyToClipboard(value) }
+        VStack(alignment: .leading) {
+            Text("Statistics").font(.title).padding(.bottom)
+            if let stats = vm.stats {
+                Text("\(stats.total) entries").emphasized()
+                ForEach(stats.byKind, id: \.kind) { kind in
+                    HStack {
+                        Text(Glyph.icon(for: kind.kind)).frame(width: 24)
+                        Text(kind.label)
+                        Spacer()
+                        Text("\(kind.count)").font(.system(size: 12, design: .monospaced))
+                    }
+                }
+
+                Text("Passwords").emphasized().padding(.top)
+                if stats.withPassword == 0 {
+                    Text("No entries with a password yet.").foregroundColor(Display.secondary)
+                } else {
+                    gauge("Weak", stats.weak, of: stats.withPassword, color: .red)
+                    gauge("Reused", stats.reused, of: stats.withPassword, color: .orange)
+                    gauge("Unchanged for a year", stats.old, of: stats.withPassword, color: .yellow)
+                }
+                if stats.accounts > 0 {
+                    gauge("Accounts without 2FA", stats.missingTwoFactor, of: stats.accounts, color: .purple)
+                }
+
+                Text("Activity").emphasized().padding(.top)
+                HStack {
+                    Text(Glyph.sparkline(stats.weeklyChanges))
+                        .font(.system(size: 16, design: .monospaced))
+                        .foregroundColor(ink(.blue))
+                    Text("changes per week, last \(stats.weeklyChanges.count) weeks")
+                        .font(.caption)
+                        .foregroundColor(Display.secondary)
+                }
+                Text("Last sync: \(stats.lastSync ?? "never")").foregroundColor(Display.secondary)
+            }
+            HStack {
+                Spacer()
+                Button("Close") { isPresented = false }
+            }
+            .padding(.top)
         }
-        .padding(.vertical, 4)
+        .padding()
+        .frame(minWidth: 420)
     }
-}
 
-struct PasswordRow: View {
-    let password: String
-    @Binding var showPassword: Bool
-
-    var body: some View {
+    private func gauge(_ label: String, _ count: UInt32, of total: UInt32, color: Color) -> some View {
         HStack {
-            Text("Password")
-                .foregroundColor(.gray)
-                .font(.caption)
-                .frame(width: 80)
-            Text(showPassword ? password : String(repeating: "•", count: 16))
+            Text(label).frame(width: 180)
+            Text(Glyph.gauge(count, of: total))
+                .font(.system(size: 12, design: .monospaced))
+                .foregroundColor(ink(color))
+            Text("\(count) of \(total)").font(.caption)
             Spacer()
-            Button(showPassword ? "Hide" : "Show") { showPassword.toggle() }
-            Button("Copy") { copyToClipboard(password) }
         }
-        .padding(.vertical, 4)
     }
 }
 
-struct NotesRow: View {
-    let notes: String
+/// One field filling the window in black on white, for reading a code
+/// from across the room. Short numeric codes are drawn in block letters.
+struct LargeFieldSheet: View {
+    @Binding var isPresented: Bool
+
+    @Environment(PasswordStoreViewModel.self) var vm
 
     var body: some View {
         VStack {
-            HStack {
-                Text("Notes").foregroundColor(.gray).font(.caption)
-                Spacer()
-            }
-            HStack {
-                Text(notes)
-                Spacer()
+            if let field = vm.largeField {
+                Text(field.label).font(.title).foregroundColor(.black).padding(.bottom)
+                if let blocks = bigText(text: field.value, ascii: Display.config.ascii) {
+                    Text(blocks)
+                        .font(.system(size: 18, design: .monospaced))
+                        .foregroundColor(.black)
+                } else {
+                    Text(field.value)
+                        .font(.system(size: 48, design: .monospaced))
+                        .foregroundColor(.black)
+                }
             }
-            .padding(8)
+            Button("Close") { isPresented = false }
+                .padding(.top)
         }
-        .padding(.vertical, 4)
+        .padding(40)
+        .frame(minWidth: 640, minHeight: 360)
+        .background(Color.white)
     }
 }
 
-// MARK: - Add entry sheet
-
-struct AddEntrySheet: View {
+struct HelpSheet: View {
     @Binding var isPresented: Bool
 
     @Environment(PasswordStoreViewModel.self) var vm
-    @State var name = ""
-    @State var username = ""
-    @State var password = ""
-    @State var email = ""
-    @State var website = ""
-    @State var notes = ""
 
     var body: some View {
         VStack {
-            Text("Add Entry").font(.title).padding(.bottom)
-
-            HStack {
-                Text("Name").frame(width: 80)
-                TextField("entry-name", text: $name)
-            }
-            HStack {
-                Text("Username").frame(width: 80)
-                TextField("", text: $username)
-            }
-            HStack {
-                Text("Password").frame(width: 80)
-                TextField("", text: $password)
-            }
-            HStack {
-                Text("Email").frame(width: 80)
-                TextField("", text: $email)
-            }
-            HStack {
-                Text("Website").frame(width: 80)
-                TextField("https://…", text: $website)
-            }
-            HStack {
-                Text("Notes").frame(width: 80)
-                TextField("", text: $notes)
-            }
-
-            HStack {
-                Button("Cancel") { isPresented = false }
-                Spacer()
-                Button("Add") {
-                    guard !name.isEmpty else { return }
-                    let account = FfiOnlineAccount(
-                        username: username.isEmpty ? nil : username,
-                        password: password.isEmpty ? nil : password,
-                        email: email.isEmpty ? nil : email,
-                        phone: nil,
-                        signInWith: nil,
-                        status: "Active",
-                        hostWebsite: website.isEmpty ? nil : website,
-                        loginPages: nil,
-                        securityQuestions: nil,
-                        twoFactorEnabled: nil,
-                        associatedItems: nil,
-                        dateCreated: nil,
-                        notes: notes.isEmpty ? nil : notes
-                    )
-                    vm.add(name: name, item: .onlineAccount(account: account))
-                    isPresented = false
+            Text("Keyboard Shortcuts").font(.title).padding(.bottom)
+            ForEach(vm.keymap.help(), id: \.title) { section in
+                Text(section.title).emphasized().padding(.top, 8)
+                ForEach(section.rows, id: \.description) { row in
+                    HStack {
+                        Text(row.keys.joined(separator: " / "))
+                            .font(.system(size: 12, design: .monospaced))
+                            .frame(width: 120)
+                        Text(row.description)
+                        Spacer()
+                    }
                 }
-                .disabled(name.isEmpty)
             }
-            .padding(.top)
+            Button("Close") { isPresented = false }
+                .padding(.top)
         }
         .padding()
         .frame(minWidth: 400)
@@ -447,6 +2640,7 @@ struct AddEntrySheet: View {
 
 struct ShareSheet: View {
     @Binding var receiveTicket: String
+    @Binding var receiveCode: String
     @Binding var isPresented: Bool
 
     @Environment(PasswordStoreViewModel.self) var vm
@@ -455,27 +2649,45 @@ struct ShareSheet: View {
         VStack {
             Text("Share / Receive").font(.title).padding(.bottom)
 
+            if let status = vm.syncState.status {
+                Text(status).font(.caption).padding(.bottom)
+            }
+
             if let ticket = vm.shareTicket {
                 VStack {
                     Text("Share ticket (send this to the receiver):").font(.caption)
                     Text(ticket)
                         .font(.caption)
                         .padding(8)
-                    Button("Copy ticket") { copyToClipboard(ticket) }
+                    if let qr = try? qrCode(data: ticket, ascii: Display.config.ascii) {
+                        QrCodeView(rendered: qr, caption: "Or scan on the receiving device")
+                    }
+                    Button("Copy ticket") { vm.perform(.copyTicket) }
+                    if let code = vm.shareCode {
+                        Text("Share code (send this another way, not with the ticket):").font(.caption)
+                        Text(code)
+                            .font(.system(size: 12, design: .monospaced))
+                            .padding(8)
+                    }
+                    Text("The store is shared until you stop sharing.").font(.caption)
+                    Button("Stop sharing") { vm.stopSharing() }
                 }
                 .padding(.bottom)
+            } else if !vm.syncState.isBusy {
+                Button("Share this store") { vm.share() }
+                    .padding(.bottom)
             }
 
             Divider()
 
             VStack {
                 Text("Paste a ticket to receive:").font(.caption)
-                TextField("ticket…", text: $receiveTicket)
+                TextField("ticket\(Glyph.ellipsis)", text: $receiveTicket)
+                TextField("share code", text: $receiveCode)
                 Button("Receive") {
-                    vm.receive(ticket: receiveTicket)
-                    isPresented = false
+                    vm.receive(ticket: receiveTicket, code: receiveCode)
                 }
-                .disabled(receiveTicket.isEmpty)
+                .disabled(receiveTicket.isEmpty || receiveCode.isEmpty || vm.syncState.isBusy)
             }
 
             Button("Close") { isPresented = false }
@@ -485,10 +2697,3 @@ struct ShareSheet: View {
         .frame(minWidth: 480)
     }
 }
-
-// MARK: - Clipboard
-
-private func copyToClipboard(_ text: String) {
-    NSPasteboard.general.clearContents()
-    NSPasteboard.general.setString(text, forType: .string)
-}
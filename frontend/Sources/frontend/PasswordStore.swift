@@ -1,3 +1,4 @@
+import AppKit
 import Foundation
 import SwiftCrossUI
 
@@ -7,28 +8,373 @@ final class PasswordStoreViewModel {
     var selectedEntry: String? = nil
     var selectedItem: FfiItem? = nil
     var errorMessage: String? = nil
-    var shareTicket: String? = nil
+    /// Where the share sheet is in sharing or receiving the store.
+    var syncState: SyncState = .idle
+    /// The node serving the shared store; it has to stay up until the
+    /// receiver has fetched it.
+    private var shareHandle: P2pHandle? = nil
+    var templates: [ItemTemplate] = []
+    var searchQuery = ""
+    /// Also match usernames, emails, URLs and notes, not just entry names.
+    var deepSearch = false
+    /// When set, only entries carrying this tag are listed.
+    var tagFilter: String? = nil
+    var tags: [String] = []
+    /// The category tab above the list; `nil` is "All".
+    var categoryFilter: ItemCategory? = nil
+    var folders: [String] = []
+    /// Folders whose contents are hidden in the entry list.
+    var collapsedFolders: Set<String> = []
+    /// When set, search only looks inside this folder and its subfolders.
+    var folderScope: String? = nil
+    var sortMode: SortMode = .name
+    /// Leave accounts marked deactivated out of the entry list; they are
+    /// still listed in the graveyard.
+    var hideDeactivated = false
+    /// Entries opened recently in this vault, most recent first; kept
+    /// across launches.
+    var recentlyViewed: [String] = []
+    /// The selected list row: an entry name, or a folder followed by "/".
+    var listSelection: String? = nil
+    /// The folder whose header row is selected, if a folder is selected.
+    var selectedFolder: String? = nil
+    /// Letters typed since the jump key; the first entry starting with them
+    /// is selected. `nil` when not jumping.
+    var jumpPrefix: String? = nil
+    private var jumpTimer: Timer? = nil
+    /// Rows moved by Page Up / Page Down.
+    static let pageSize = 10
 
-    private let store: PwdStore
+    var showAddSheet = false
+    var showDeleteConfirm = false
+    var showTrash = false
+    var showShareSheet = false
+    var showHelp = false
+    /// The entry's web addresses, offered when it has more than one.
+    var showUrlPicker = false
+    var urlChoices: [String] = []
+    var showHistory = false
+    var showRecent = false
+    var showGraveyard = false
+    var showGenerator = false
+    /// Asking for a new name for the selected entry, to rename it or, when
+    /// `duplicating`, to copy it.
+    var showRenameSheet = false
+    var duplicating = false
+    var renameDraft = ""
+    /// The entry marked to be merged with the next one chosen, and the
+    /// merge being reviewed: per differing field, whether to take the
+    /// value from `mergeRight`.
+    var mergeSource: String? = nil
+    var showMergeSheet = false
+    private(set) var mergeLeft: (name: String, item: FfiItem)? = nil
+    private(set) var mergeRight: (name: String, item: FfiItem)? = nil
+    private(set) var mergeDifferences: [FieldDifference] = []
+    var mergeTakeRight: Set<String> = []
+    /// Keep the right entry's name (and trash the left one) instead.
+    var mergeKeepRight = false
+    /// The statistics dashboard and the figures it shows, gathered when it
+    /// is opened.
+    var showStats = false
+    private(set) var stats: FfiVaultStats? = nil
+    /// The focused field shown full screen in large letters.
+    var showLargeField = false
+    private(set) var largeField: (label: String, value: String)? = nil
+    /// Show notes as typed instead of rendering their markdown.
+    var showRawNotes = false
+    /// List each security question of the entry instead of just a count.
+    var showSecurityQuestions = false
+    /// Whether the selected account's recovery codes are listed one by one.
+    var showRecoveryCodes = false
+    static let securityQuestionsLabel = "Questions"
+    /// The detail view shows the inline field editor.
+    var editingDetail = false
+    /// The field being edited inline, its draft value and last validation error.
+    var editingField: String? = nil
+    var editDraft = ""
+    var editError: String? = nil
+    private var editOriginal = ""
+    /// Asking whether to save the edit in progress before `pendingLeave`.
+    var showUnsavedPrompt = false
+    private var pendingLeave: (() -> Void)? = nil
+    /// The detail field last clicked; the copy key copies it.
+    var focusedField: String? = nil
+    private var focusedValue: String? = nil
+    private var focusedSecret = false
+    /// The selected entry's field rows in display order, as they appear;
+    /// field navigation steps through these.
+    private(set) var detailFields: [(label: String, value: String, secret: Bool)] = []
+    /// Labels of the field rows that link to another entry.
+    private var linkFields: Set<String> = []
+    /// Entries left by following links, most recent last.
+    private(set) var backStack: [String] = []
+    /// The secret currently shown in clear and seconds until it is masked again.
+    var revealedField: String? = nil
+    var revealCountdown: UInt64? = nil
+    private var revealTimer: Timer? = nil
+    /// Short confirmation shown under the list, e.g. "Copied password".
+    var statusMessage: String? = nil
+    /// Seconds until the copied secret is cleared from the clipboard.
+    var clipboardCountdown: UInt32? = nil
+    private var countdownTimer: Timer? = nil
+    /// The selected account's one-time code, refreshed every second while
+    /// it is shown.
+    var currentTotp: FfiTotpCode? = nil
+    private var totpTimer: Timer? = nil
+    private var totpCopyTimer: Timer? = nil
+    /// The HOTP code last handed out for the selected entry.
+    var hotpCode: String? = nil
+
+    let keymap: KeymapHandle
+    let clipboard: ClipboardHandle
+    let secrets: SecretsConfig
+    /// Named generator settings from the config, offered in the generator.
+    let generatorPresets: [FfiGeneratorPreset]
+    /// `nil` until the vault is created or unlocked; the onboarding screen
+    /// is shown meanwhile.
+    private var store: PwdStore! = nil
+    private var keyMonitor: Any? = nil
+    private let listCache = ListCache()
+
+    /// Where the vault lives, and whether one has been created there yet.
+    var vaultPath: String
+    var vaultExists: Bool
+    /// Set after creating a vault, until the first item is added or skipped.
+    var offerFirstItem = false
+
+    var isLocked: Bool { store == nil }
 
     init() {
-        let dir = ProcessInfo.processInfo.environment["PASSWORD_STORE_PATH"]
+        vaultPath = ProcessInfo.processInfo.environment["PASSWORD_STORE_PATH"]
             ?? (FileManager.default.homeDirectoryForCurrentUser.path + "/.pwd")
-        let passphrase = ProcessInfo.processInfo.environment["PWD_STORE_PASSPHRASE"] ?? ""
-        store = try! PwdStore.open(storeDir: dir, branch: "main", passphrase: passphrase)
-        reload()
+        vaultExists = storeExists(storeDir: vaultPath)
+        let configPath = ProcessInfo.processInfo.environment["PWD_CONFIG"]
+        do {
+            keymap = try KeymapHandle.load(path: configPath)
+            clipboard = try ClipboardHandle.load(path: configPath)
+            secrets = try loadSecretsConfig(path: configPath)
+            Display.config = try loadDisplayConfig(path: configPath)
+            generatorPresets = try loadGeneratorPresets(path: configPath)
+        } catch {
+            keymap = KeymapHandle()
+            clipboard = ClipboardHandle()
+            secrets = SecretsConfig(revealSecs: 10, mask: .keypress, maskFields: [:])
+            generatorPresets = []
+            errorMessage = "Ignoring invalid config: \(error.localizedDescription)"
+        }
+        if vaultExists, let passphrase = ProcessInfo.processInfo.environment["PWD_STORE_PASSPHRASE"] {
+            unlock(passphrase: passphrase)
+        }
+        keyMonitor = NSEvent.addLocalMonitorForEvents(matching: .keyDown) { [weak self] event in
+            guard let self, let key = keyName(event) else { return event }
+            return self.handleKey(key) ? nil : event
+        }
+    }
+
+    // MARK: Vault
+
+    /// Open the existing vault; a wrong passphrase leaves it locked.
+    func unlock(passphrase: String) {
+        do {
+            store = try PwdStore.open(storeDir: vaultPath, branch: "main", passphrase: passphrase)
+            reload()
+        } catch {
+            errorMessage = "Could not unlock \(vaultPath): \(error.localizedDescription)"
+        }
+    }
+
+    /// Create a vault at `path` protected by `passphrase`, then offer to add
+    /// a first item.
+    func createVault(path: String, passphrase: String) {
+        guard !storeExists(storeDir: path) else {
+            errorMessage = "A vault already exists at \(path)"
+            return
+        }
+        do {
+            store = try PwdStore.open(storeDir: path, branch: "main", passphrase: passphrase)
+            vaultPath = path
+            vaultExists = true
+            offerFirstItem = true
+            reload()
+        } catch {
+            errorMessage = "Could not create a vault at \(path): \(error.localizedDescription)"
+        }
+    }
+
+    func template(named name: String) -> ItemTemplate? {
+        templates.first { $0.name == name }
     }
 
     func reload() {
+        listCache.generation += 1
+        templates = store.listTemplates()
+        tags = store.listTags()
+        if let tag = tagFilter, !tags.contains(tag) { tagFilter = nil }
+        folders = store.listFolders()
+        collapsedFolders.formIntersection(folders)
+        if let folder = folderScope, !folders.contains(folder) { folderScope = nil }
         do {
             entries = try store.listEntries()
+            recentlyViewed = try store.recentEntries()
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    /// Entries matching the search bar, with per-field matches for display.
+    var searchResults: [FfiSearchHit] {
+        store.search(
+            query: searchQuery,
+            deep: deepSearch,
+            filter: FfiSearchFilter(
+                tag: tagFilter,
+                category: categoryFilter,
+                folder: folderScope,
+                deactivated: hideDeactivated ? false : nil
+            ),
+            sort: sortMode,
+            viewed: recentlyViewed
+        )
+    }
+
+    /// The entry list: search results ranked as-is while a query is typed,
+    /// otherwise favorites then the rest grouped under folder headers,
+    /// skipping collapsed folders.
+    ///
+    /// Built once per change of the query, filters, sort or store contents
+    /// rather than on every redraw.
+    var listRows: [ListRow] {
+        let key = ListKey(
+            query: searchQuery,
+            deep: deepSearch,
+            tag: tagFilter,
+            category: categoryFilter,
+            folder: folderScope,
+            hideDeactivated: hideDeactivated,
+            sort: sortMode,
+            viewed: sortMode == .viewed ? recentlyViewed : [],
+            collapsed: collapsedFolders,
+            generation: listCache.generation
+        )
+        if let cached = listCache.rows, listCache.key == key { return cached }
+        let rows = buildListRows()
+        listCache.key = key
+        listCache.rows = rows
+        return rows
+    }
+
+    /// At most `listWindow` rows around the selection, with how many rows
+    /// are left out above and below; only these are turned into views.
+    var visibleRows: (rows: ArraySlice<ListRow>, above: Int, below: Int) {
+        let rows = listRows
+        guard rows.count > Self.listWindow else { return (rows[...], 0, 0) }
+        let selected = listSelection.flatMap { id in rows.firstIndex { $0.id == id } } ?? 0
+        let start = min(max(selected - Self.listWindow / 2, 0), rows.count - Self.listWindow)
+        let end = start + Self.listWindow
+        return (rows[start..<end], start, rows.count - end)
+    }
+
+    static let listWindow = 200
+
+    private func buildListRows() -> [ListRow] {
+        let hits = searchResults
+        if !searchQuery.trimmingCharacters(in: .whitespaces).isEmpty {
+            return hits.map { ListRow(id: $0.name, depth: 0, folder: nil, hit: $0) }
+        }
+        // Favorites are pinned above the folders, under their full name.
+        var rows = hits.filter(\.favorite).map { ListRow(id: $0.name, depth: 0, folder: nil, hit: $0) }
+        appendTree(hits.filter { !$0.favorite }[...], under: "", depth: 0, to: &rows)
+        return rows
+    }
+
+    /// Append `hits` below the folder `prefix` ("" at the top level), each
+    /// subfolder where its first entry falls in the sort order, followed by
+    /// its contents unless collapsed.
+    private func appendTree(
+        _ hits: ArraySlice<FfiSearchHit>, under prefix: String, depth: Int, to rows: inout [ListRow]
+    ) {
+        // Group each subfolder's hits in one pass, keeping the sort order.
+        var order: [String] = []
+        var inside: [String: [FfiSearchHit]] = [:]
+        var items: [(folder: String?, hit: FfiSearchHit?)] = []
+        for hit in hits {
+            let rest = hit.name.dropFirst(prefix.count)
+            guard let slash = rest.firstIndex(of: "/") else {
+                items.append((nil, hit))
+                continue
+            }
+            let folder = prefix + String(rest[..<slash])
+            if inside[folder] == nil {
+                order.append(folder)
+                items.append((folder, nil))
+            }
+            inside[folder, default: []].append(hit)
+        }
+        for item in items {
+            if let hit = item.hit {
+                rows.append(ListRow(id: hit.name, depth: depth, folder: nil, hit: hit))
+            } else if let folder = item.folder {
+                rows.append(ListRow(id: folder + "/", depth: depth, folder: folder, hit: nil))
+                if !collapsedFolders.contains(folder) {
+                    appendTree(inside[folder]![...], under: folder + "/", depth: depth + 1, to: &rows)
+                }
+            }
+        }
+    }
+
+    /// Select a list row by id: a folder header or an entry. Leaving an
+    /// entry with an unsaved edit asks first.
+    func selectRow(_ id: String) {
+        let current = selectedFolder.map { $0 + "/" } ?? selectedEntry
+        if id != current && hasUnsavedEdit {
+            listSelection = current
+            return guardUnsaved { self.selectRow(id) }
+        }
+        if id.hasSuffix("/") {
+            listSelection = id
+            selectedFolder = String(id.dropLast())
+        } else if id != selectedEntry || selectedFolder != nil {
+            select(id)
+        }
+    }
+
+    /// The folder the selection is in, for collapsing and scoped search.
+    var currentFolder: String? {
+        selectedFolder ?? selectedEntry.flatMap(folderOf)
+    }
+
+    func toggleFavorite(name: String) {
+        let favorite = !(searchResults.first { $0.name == name }?.favorite ?? false)
+        do {
+            try store.setFavorite(name: name, favorite: favorite)
+            listCache.generation += 1
+            tags = store.listTags()
+            if selectedEntry == name { select(name) }
+            statusMessage = favorite ? "Starred \(name)" : "Unstarred \(name)"
         } catch {
             errorMessage = error.localizedDescription
         }
     }
 
+    func toggleFolder(_ folder: String) {
+        if collapsedFolders.remove(folder) == nil { collapsedFolders.insert(folder) }
+    }
+
     func select(_ name: String) {
+        editingField = nil
+        editError = nil
+        focusedField = nil
+        focusedValue = nil
+        detailFields = []
+        linkFields = []
+        showSecurityQuestions = false
+        showRecoveryCodes = false
+        hideSecret()
+        stopTotp()
+        selectedFolder = nil
+        listSelection = name
         selectedEntry = name
+        if let recent = try? store.markViewed(name: name) { recentlyViewed = recent }
         do {
             selectedItem = try store.getEntry(name: name)
         } catch {
@@ -39,6 +385,7 @@ final class PasswordStoreViewModel {
     func add(name: String, item: FfiItem, message: String = "") {
         do {
             try store.addEntry(name: name, item: item, message: message)
+            offerFirstItem = false
             reload()
             select(name)
         } catch {
@@ -49,20 +396,157 @@ final class PasswordStoreViewModel {
     func update(name: String, item: FfiItem, message: String = "") {
         do {
             try store.updateEntry(name: name, item: item, message: message)
+            listCache.generation += 1
+            tags = store.listTags()
             if selectedEntry == name { select(name) }
         } catch {
             errorMessage = error.localizedDescription
         }
     }
 
+    /// Move an entry to the trash; it can be restored until the trash is
+    /// emptied.
     func remove(name: String) {
         do {
-            _ = try store.removeEntry(name: name, message: "")
+            _ = try store.trashEntry(name: name, message: "")
             if selectedEntry == name {
                 selectedEntry = nil
                 selectedItem = nil
+                listSelection = nil
+            }
+            reload()
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    /// Rename the selected entry to `renameDraft`, or copy it there and
+    /// open the copy for editing.
+    func commitRename() {
+        guard let name = selectedEntry else { return }
+        let newName = renameDraft.trimmingCharacters(in: .whitespaces)
+        do {
+            if duplicating {
+                try store.duplicateEntry(from: name, to: newName)
+            } else {
+                try store.renameEntry(from: name, to: newName)
+                backStack = backStack.map { $0 == name ? newName : $0 }
             }
+            showRenameSheet = false
             reload()
+            select(newName)
+            if duplicating { editingDetail = true }
+            statusMessage = duplicating ? "Copied \(name) to \(newName)" : "Renamed \(name) to \(newName)"
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    // MARK: Merge
+
+    private func startMerge(left: String, right: String) {
+        mergeSource = nil
+        do {
+            guard let l = try store.getEntry(name: left), let r = try store.getEntry(name: right) else { return }
+            mergeDifferences = try itemDifferences(left: l, right: r)
+            mergeLeft = (left, l)
+            mergeRight = (right, r)
+            mergeTakeRight = []
+            mergeKeepRight = false
+            showMergeSheet = true
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    /// Write the merged entry under the kept name and trash the other.
+    func commitMerge() {
+        guard let left = mergeLeft, let right = mergeRight else { return }
+        let (keep, other) = mergeKeepRight ? (right, left) : (left, right)
+        let fromOther = mergeDifferences.map(\.field.name).filter { mergeKeepRight != mergeTakeRight.contains($0) }
+        do {
+            let merged = try mergedItem(left: keep.item, right: other.item, takeRight: fromOther)
+            try store.mergeEntries(keep: keep.name, discard: other.name, item: merged)
+            showMergeSheet = false
+            reload()
+            select(keep.name)
+            statusMessage = "Merged \(other.name) into \(keep.name)"
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    // MARK: Trash
+
+    var trashed: [String] {
+        (try? store.listTrash()) ?? []
+    }
+
+    func restore(name: String) {
+        do {
+            try store.restoreEntry(name: name)
+            reload()
+            select(name)
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    func emptyTrash() {
+        do {
+            _ = try store.emptyTrash()
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    // MARK: Graveyard
+
+    /// Accounts marked deactivated, whether or not the list hides them.
+    var graveyard: [String] {
+        store.search(
+            query: "",
+            deep: false,
+            filter: FfiSearchFilter(tag: nil, category: nil, folder: nil, deactivated: true),
+            sort: .name,
+            viewed: []
+        ).map(\.name)
+    }
+
+    // MARK: Attachments
+
+    func attachments(for name: String) -> [String] {
+        (try? store.listAttachments(name: name)) ?? []
+    }
+
+    func addAttachment(name: String, path: String) {
+        do {
+            _ = try store.addAttachment(name: name, path: path)
+            if selectedEntry == name { select(name) }
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    /// Decrypts to a private temporary file and hands it to the system opener,
+    /// deleting the file once the viewer has had time to load it.
+    func openAttachment(name: String, file: String) {
+        do {
+            let path = try store.exportAttachment(name: name, file: file)
+            NSWorkspace.shared.open(URL(fileURLWithPath: path))
+            let store = store!
+            DispatchQueue.main.asyncAfter(deadline: .now() + 60) {
+                try? store.removeExportedAttachment(path: path)
+            }
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    func removeAttachment(name: String, file: String) {
+        do {
+            _ = try store.removeAttachment(name: name, file: file)
+            if selectedEntry == name { select(name) }
         } catch {
             errorMessage = error.localizedDescription
         }
@@ -81,50 +565,1016 @@ final class PasswordStoreViewModel {
         }
     }
 
-    // MARK: P2P
+    // MARK: Keyboard
 
-    func share() {
-        let handle = P2pHandle()
+    /// What the user is doing, for the status bar.
+    var mode: String {
+        if showShareSheet { return "sync" }
+        if editingDetail || showAddSheet { return "edit" }
+        if !searchQuery.isEmpty { return "search" }
+        return "browse"
+    }
+
+    /// The keymap contexts in effect, most specific first.
+    var activeContexts: [KeyContext] {
+        if showAddSheet || showDeleteConfirm || showTrash || showHelp || showRecent || showUnsavedPrompt || showLargeField || showRenameSheet || showMergeSheet || showStats || showGraveyard || showGenerator {
+            return []
+        }
+        if showShareSheet { return [.sync] }
+        if editingField != nil { return [.edit, .detail, .list] }
+        if selectedItem != nil { return [.detail, .list] }
+        return [.list]
+    }
+
+    /// Run the bound action for `key`, or remember it as the start of a
+    /// chord; returns whether the key was used.
+    ///
+    /// While a text field has focus only Return and Escape are looked up, so
+    /// typing is never swallowed.
+    func handleKey(_ key: String) -> Bool {
+        guard !isLocked else { return false }
+        let typing = NSApp.keyWindow?.firstResponder is NSText
+        if jumpPrefix != nil && !typing { return typeAhead(key) }
+        if typing && key != "enter" && key != "esc" && key != "cmd-q" { return false }
+        let contexts = activeContexts
+        if typing && key == "enter" && contexts.first != .edit { return false }
+        switch keymap.press(contexts: contexts, key: key) {
+        case .action(let action):
+            perform(action)
+            return true
+        case .pending:
+            return true
+        case .unbound:
+            return false
+        }
+    }
+
+    func perform(_ action: Action) {
+        switch action {
+        case .help:
+            showHelp = true
+        case .close:
+            if showUnsavedPrompt { cancelLeave() }
+            else if showHelp { showHelp = false }
+            else if showAddSheet { showAddSheet = false }
+            else if showDeleteConfirm { showDeleteConfirm = false }
+            else if showTrash { showTrash = false }
+            else if showUrlPicker { showUrlPicker = false }
+            else if showRecent { showRecent = false }
+            else if showLargeField { showLargeField = false }
+            else if showRenameSheet { showRenameSheet = false }
+            else if showMergeSheet { showMergeSheet = false }
+            else if showStats { showStats = false }
+            else if showGraveyard { showGraveyard = false }
+            else if showGenerator { showGenerator = false }
+            else if mergeSource != nil { mergeSource = nil }
+            else if showShareSheet { showShareSheet = false }
+            else if editingDetail { finishEditing() }
+        case .quit:
+            guardUnsaved { NSApp.terminate(nil) }
+        case .nextEntry:
+            moveSelection(by: 1)
+        case .previousEntry:
+            moveSelection(by: -1)
+        case .firstEntry:
+            if let id = listRows.first?.id { selectRow(id) }
+        case .lastEntry:
+            if let id = listRows.last?.id { selectRow(id) }
+        case .pageDown:
+            moveSelection(by: Self.pageSize)
+        case .pageUp:
+            moveSelection(by: -Self.pageSize)
+        case .showRecent:
+            showRecent = true
+        case .showStats:
+            do {
+                stats = try store.vaultStats()
+                showStats = true
+            } catch {
+                errorMessage = error.localizedDescription
+            }
+        case .jumpToName:
+            jumpPrefix = ""
+            restartJumpTimer()
+        case .newEntry:
+            showAddSheet = true
+        case .deleteEntry:
+            if selectedEntry != nil { showDeleteConfirm = true }
+        case .showTrash:
+            showTrash = true
+        case .showGraveyard:
+            showGraveyard = true
+        case .openGenerator:
+            showGenerator = true
+        case .toggleDeactivated:
+            hideDeactivated.toggle()
+            statusMessage = hideDeactivated ? "Hiding deactivated accounts" : "Showing deactivated accounts"
+        case .openSync:
+            share()
+            showShareSheet = true
+        case .copyTicket:
+            if let ticket = shareTicket { copy(label: "share ticket", value: ticket, secret: false) }
+        case .copyField:
+            if let label = focusedField, let value = focusedValue {
+                copy(label: label, value: value)
+            } else if let item = selectedItem, let secret = try? itemPrimarySecret(item: item) {
+                copy(label: secret.field, value: secret.value)
+            } else {
+                statusMessage = "Nothing to copy"
+            }
+        case .revealSecret:
+            if let label = focusedField, linkFields.contains(label), let name = focusedValue {
+                followLink(name)
+            } else if focusedField == Self.securityQuestionsLabel {
+                showSecurityQuestions.toggle()
+            } else if focusedSecret, let label = focusedField {
+                reveal(label: label)
+            } else {
+                statusMessage = "Focus a secret to reveal it"
+            }
+        case .showLargeField:
+            if let label = focusedField, let value = focusedValue, label != Self.securityQuestionsLabel {
+                largeField = (label, value)
+                showLargeField = true
+            } else {
+                statusMessage = "Focus a field to enlarge it"
+            }
+        case .goBack:
+            goBack()
+        case .renameEntry, .duplicateEntry:
+            guard let name = selectedEntry else { break }
+            guardUnsaved {
+                self.duplicating = action == .duplicateEntry
+                self.renameDraft = self.duplicating ? name + "-copy" : name
+                self.errorMessage = nil
+                self.showRenameSheet = true
+            }
+        case .mergeEntry:
+            guard let name = selectedEntry else { break }
+            if let source = mergeSource, source != name {
+                guardUnsaved { self.startMerge(left: source, right: name) }
+            } else {
+                mergeSource = name
+                statusMessage = "Select the entry to merge \(name) with"
+            }
+        case .nextField:
+            moveFieldFocus(by: 1)
+        case .previousField:
+            moveFieldFocus(by: -1)
+        case .editEntry:
+            if let item = selectedItem, !item.isTemplate { editingDetail = true }
+        case .toggleHistory:
+            showHistory.toggle()
+        case .toggleRawNotes:
+            showRawNotes.toggle()
+        case .openUrl:
+            let urls = selectedItem.flatMap { try? itemUrls(item: $0) } ?? []
+            if urls.count > 1 {
+                urlChoices = urls
+                showUrlPicker = true
+            } else if let url = urls.first {
+                openLink(url)
+            } else {
+                statusMessage = "No website to open"
+            }
+        case .save:
+            commitEdit()
+        case .cancel:
+            guardUnsaved { self.discardEdit() }
+        case .nextCategory:
+            moveCategory(by: 1)
+        case .previousCategory:
+            moveCategory(by: -1)
+        case .showAll:
+            categoryFilter = nil
+        case .showLogins:
+            categoryFilter = .logins
+        case .showIdentity:
+            categoryFilter = .identity
+        case .showCards:
+            categoryFilter = .cards
+        case .showNotes:
+            categoryFilter = .notes
+        case .expandFolder:
+            if let folder = selectedFolder { collapsedFolders.remove(folder) }
+        case .toggleFavorite:
+            if let name = selectedEntry { toggleFavorite(name: name) }
+        case .cycleSort:
+            let modes: [SortMode] = [.name, .modified, .viewed, .kind]
+            sortMode = modes[((modes.firstIndex(of: sortMode) ?? 0) + 1) % modes.count]
+        case .collapseFolder:
+            if let folder = currentFolder {
+                collapsedFolders.insert(folder)
+                selectRow(folder + "/")
+            }
+        }
+    }
+
+    /// The tabs above the list, "All" first.
+    static let categoryTabs: [ItemCategory?] = [nil, .logins, .identity, .cards, .notes]
+
+    private func moveCategory(by offset: Int) {
+        let tabs = Self.categoryTabs
+        let index = tabs.firstIndex(of: categoryFilter) ?? 0
+        categoryFilter = tabs[(index + offset + tabs.count) % tabs.count]
+    }
+
+    func openLink(_ url: String) {
         do {
-            shareTicket = try handle.shareStore(store: store)
+            try openUrl(url: url)
+            statusMessage = "Opened \(url)"
         } catch {
             errorMessage = error.localizedDescription
         }
-        _ = try? handle.shutdown()
     }
 
-    func receive(ticket: String) {
-        let handle = P2pHandle()
+    private func moveSelection(by offset: Int) {
+        let ids = listRows.map(\.id)
+        guard !ids.isEmpty else { return }
+        let index = listSelection.flatMap { ids.firstIndex(of: $0) }.map { $0 + offset } ?? 0
+        selectRow(ids[min(max(index, 0), ids.count - 1)])
+    }
+
+    // MARK: Type-ahead
+
+    /// Extend the jump prefix with `key` and select the first entry whose
+    /// name (ignoring its folder) starts with it. Return and Escape end the
+    /// jump, as does a pause of a second and a half.
+    private func typeAhead(_ key: String) -> Bool {
+        let key = key.hasPrefix("shift-") && key.count == 7 ? String(key.suffix(1)) : key
+        switch key {
+        case "enter", "esc":
+            endJump()
+        case "backspace":
+            jumpPrefix = String((jumpPrefix ?? "").dropLast())
+            restartJumpTimer()
+        case _ where key.count == 1:
+            let prefix = (jumpPrefix ?? "") + key
+            jumpPrefix = prefix
+            restartJumpTimer()
+            let match = listRows.first { row in
+                guard let name = row.hit?.name else { return false }
+                let leaf = name.split(separator: "/").last.map(String.init) ?? name
+                return leaf.lowercased().hasPrefix(prefix.lowercased())
+            }
+            if let match { selectRow(match.id) } else { statusMessage = "No entry starts with \"\(prefix)\"" }
+        default:
+            endJump()
+            return false
+        }
+        return true
+    }
+
+    private func restartJumpTimer() {
+        jumpTimer?.invalidate()
+        jumpTimer = Timer.scheduledTimer(withTimeInterval: 1.5, repeats: false) { [weak self] _ in
+            self?.endJump()
+        }
+    }
+
+    private func endJump() {
+        jumpTimer?.invalidate()
+        jumpTimer = nil
+        jumpPrefix = nil
+    }
+
+    // MARK: Clipboard
+
+    func focus(label: String, value: String, secret: Bool = false) {
+        focusedField = label
+        focusedValue = value
+        focusedSecret = secret
+    }
+
+    /// Called as each field row of the detail view appears.
+    func showField(label: String, value: String, secret: Bool = false) {
+        guard !detailFields.contains(where: { $0.label == label }) else { return }
+        detailFields.append((label, value, secret))
+    }
+
+    /// Like `showField`, for a row naming another entry; Return on it
+    /// follows the link.
+    func showLink(label: String, name: String) {
+        linkFields.insert(label)
+        showField(label: label, value: name)
+    }
+
+    /// Select the linked entry `name`, remembering the current one for
+    /// `goBack`.
+    func followLink(_ name: String) {
+        guard entries.contains(name) else {
+            statusMessage = "No entry named \(name)"
+            return
+        }
+        guard let current = selectedEntry, current != name else { return }
+        guardUnsaved {
+            self.backStack.append(current)
+            self.select(name)
+        }
+    }
+
+    /// Return to the entry the last followed link came from, skipping any
+    /// that have since been deleted.
+    func goBack() {
+        guardUnsaved {
+            while let name = self.backStack.popLast() {
+                if self.entries.contains(name) { return self.select(name) }
+            }
+            self.statusMessage = "Nothing to go back to"
+        }
+    }
+
+    private func moveFieldFocus(by offset: Int) {
+        guard !detailFields.isEmpty else { return }
+        let index = focusedField
+            .flatMap { label in detailFields.firstIndex { $0.label == label } }
+            .map { ($0 + offset + detailFields.count) % detailFields.count }
+            ?? (offset > 0 ? 0 : detailFields.count - 1)
+        let field = detailFields[index]
+        focus(label: field.label, value: field.value, secret: field.secret)
+    }
+
+    /// One plain sentence for the top of the detail pane in accessible
+    /// mode: the focused field and its value, or how to reach the fields.
+    func announcement(name: String, item: FfiItem) -> String {
+        let key = { (action: Action) in self.keymap.keysFor(context: .detail, action: action).first ?? "" }
+        guard let label = focusedField,
+              let index = detailFields.firstIndex(where: { $0.label == label }) else {
+            return "\(name), \(item.displayName), \(detailFields.count) fields. "
+                + "Press \(key(.nextField)) for the first field."
+        }
+        let field = detailFields[index]
+        let value = !field.secret || isRevealed(label) ? field.value
+            : maskPolicy(label) == .keypress ? "hidden, press \(key(.revealSecret)) to reveal"
+            : "hidden"
+        return "\(label): \(value). Field \(index + 1) of \(detailFields.count)."
+    }
+
+    // MARK: Reveal
+
+    /// How the secret labelled `label` is masked, from the `[secrets]` config.
+    func maskPolicy(_ label: String) -> MaskPolicy {
+        secretMaskPolicy(secrets: secrets, label: label)
+    }
+
+    /// Whether the secret labelled `label` is shown in clear right now.
+    func isRevealed(_ label: String) -> Bool {
+        switch maskPolicy(label) {
+        case .never: return true
+        case .always: return false
+        case .focus: return focusedField == label
+        case .keypress: return revealedField == label
+        }
+    }
+
+    /// Show the secret labelled `label` in clear, masking it again after the
+    /// configured number of seconds. Secrets that are not revealed by key
+    /// are left as they are.
+    func reveal(label: String) {
+        switch maskPolicy(label) {
+        case .keypress: break
+        case .always:
+            statusMessage = "\(label) is always hidden; copy it instead"
+            return
+        case .focus, .never:
+            return
+        }
+        revealTimer?.invalidate()
+        revealTimer = nil
+        revealedField = label
+        revealCountdown = nil
+        guard secrets.revealSecs > 0 else { return }
+        revealCountdown = secrets.revealSecs
+        revealTimer = Timer.scheduledTimer(withTimeInterval: 1, repeats: true) { [weak self] _ in
+            guard let self, let left = self.revealCountdown else { return }
+            if left <= 1 {
+                self.hideSecret()
+            } else {
+                self.revealCountdown = left - 1
+            }
+        }
+    }
+
+    func hideSecret() {
+        revealTimer?.invalidate()
+        revealTimer = nil
+        revealedField = nil
+        revealCountdown = nil
+    }
+
+    /// Copy `value`, confirming with its label only so secrets never show.
+    /// Secrets are cleared from the clipboard after the configured timeout.
+    func copy(label: String, value: String, secret: Bool = true) {
         do {
-            let count = try handle.receiveInto(ticket: ticket, targetStore: store)
-            reload()
-            errorMessage = "Received \(count) entries."
+            if secret {
+                try clipboard.copy(text: value)
+            } else {
+                try clipboard.copyPlain(text: value)
+            }
+            statusMessage = "Copied \(label)"
+            startClipboardCountdown()
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    /// Keep `currentTotp` up to date for `totp` until `stopTotp`.
+    func watchTotp(_ totp: FfiTotp) {
+        totpTimer?.invalidate()
+        refreshTotp(totp)
+        guard currentTotp != nil else { return }
+        totpTimer = Timer.scheduledTimer(withTimeInterval: 1, repeats: true) { [weak self] _ in
+            self?.refreshTotp(totp)
+        }
+    }
+
+    func stopTotp() {
+        totpTimer?.invalidate()
+        totpTimer = nil
+        currentTotp = nil
+        hotpCode = nil
+    }
+
+    /// Copy `totp`'s current code, or when it is about to expire wait for the
+    /// next one and copy that. The status bar names the window copied.
+    func copyTotp(_ totp: FfiTotp) {
+        totpCopyTimer?.invalidate()
+        do {
+            let code = try totpCode(totp: totp)
+            guard code.expiresSoon else { return copyTotpCode(code) }
+            statusMessage = "Waiting \(code.remaining)s for the next code"
+            totpCopyTimer = Timer.scheduledTimer(
+                withTimeInterval: TimeInterval(code.remaining), repeats: false
+            ) { [weak self] _ in
+                guard let self else { return }
+                do {
+                    self.copyTotpCode(try totpCode(totp: totp))
+                } catch {
+                    self.errorMessage = error.localizedDescription
+                }
+            }
         } catch {
             errorMessage = error.localizedDescription
         }
-        _ = try? handle.shutdown()
+    }
+
+    private func copyTotpCode(_ code: FfiTotpCode) {
+        do {
+            try clipboard.copy(text: code.code)
+            statusMessage = "Copied one-time code for \(code.window)"
+            startClipboardCountdown()
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    /// Mark the selected account's recovery code at `index` as used.
+    func markRecoveryCodeUsed(_ index: Int) {
+        guard let name = selectedEntry, case .onlineAccount(var account) = selectedItem,
+              account.recoveryCodes.indices.contains(index) else { return }
+        account.recoveryCodes[index].used = true
+        let left = account.recoveryCodes.filter { !$0.used }.count
+        update(name: name, item: .onlineAccount(account: account), message: "use a recovery code for \(name)")
+        showRecoveryCodes = true
+        statusMessage = left == 1 ? "1 recovery code left" : "\(left) recovery codes left"
+    }
+
+    /// Hand out and copy the selected entry's next HOTP code; the store saves
+    /// the advanced counter.
+    func nextHotp() {
+        guard let name = selectedEntry else { return }
+        do {
+            let code = try store.nextHotpCode(name: name)
+            selectedItem = try store.getEntry(name: name)
+            hotpCode = code
+            copy(label: "one-time code", value: code)
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    private func refreshTotp(_ totp: FfiTotp) {
+        // A YubiKey is asked once per code; the countdown runs here.
+        if totp.yubikey != nil, var code = currentTotp, code.remaining > 1 {
+            code.remaining -= 1
+            currentTotp = code
+            return
+        }
+        do {
+            currentTotp = try totpCode(totp: totp)
+        } catch {
+            stopTotp()
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    private func startClipboardCountdown() {
+        countdownTimer?.invalidate()
+        clipboardCountdown = clipboard.remainingSeconds()
+        guard clipboardCountdown != nil else { return }
+        countdownTimer = Timer.scheduledTimer(withTimeInterval: 1, repeats: true) { [weak self] timer in
+            guard let self else { return timer.invalidate() }
+            self.clipboardCountdown = self.clipboard.remainingSeconds()
+            if self.clipboardCountdown == nil {
+                timer.invalidate()
+                self.statusMessage = "Clipboard cleared"
+            }
+        }
+    }
+
+    // MARK: Inline editing
+
+    func beginEdit(field: String, value: String) {
+        editingField = field
+        editDraft = value
+        editOriginal = value
+        editError = nil
+    }
+
+    /// Whether the field being edited differs from its saved value.
+    var hasUnsavedEdit: Bool { editingField != nil && editDraft != editOriginal }
+
+    func discardEdit() {
+        editingField = nil
+        editError = nil
+    }
+
+    /// Leave the inline editor, asking first if an edit is unsaved.
+    func finishEditing() {
+        guardUnsaved {
+            self.discardEdit()
+            self.editingDetail = false
+        }
+    }
+
+    /// Run `leave` now, or once the user has saved or discarded the edit in
+    /// progress; nothing happens if they cancel.
+    func guardUnsaved(_ leave: @escaping () -> Void) {
+        guard hasUnsavedEdit else { return leave() }
+        pendingLeave = leave
+        showUnsavedPrompt = true
+    }
+
+    /// Answer the unsaved-edit prompt. A save that fails validation keeps
+    /// the editor open with its error instead of leaving.
+    func resolveUnsaved(save: Bool) {
+        showUnsavedPrompt = false
+        if save {
+            commitEdit()
+            if editingField != nil {
+                pendingLeave = nil
+                return
+            }
+        } else {
+            discardEdit()
+        }
+        let leave = pendingLeave
+        pendingLeave = nil
+        leave?()
+    }
+
+    func cancelLeave() {
+        showUnsavedPrompt = false
+        pendingLeave = nil
+    }
+
+    func commitEdit() {
+        guard let field = editingField, let name = selectedEntry, let item = selectedItem else { return }
+        do {
+            let updated = try itemWithField(item: item, field: field, value: editDraft)
+            update(name: name, item: updated)
+            editingField = nil
+            editError = nil
+        } catch {
+            editError = error.localizedDescription
+        }
+    }
+
+    // MARK: P2P
+
+    var shareTicket: String? {
+        if case .shared(let ticket, _) = syncState { return ticket }
+        return nil
+    }
+
+    var shareCode: String? {
+        if case .shared(_, let code) = syncState { return code }
+        return nil
+    }
+
+    /// Publish the store, off the main thread, and keep serving it until
+    /// `stopSharing`.
+    func share() {
+        stopSharing()
+        syncState = .sharing
+        let handle = P2pHandle()
+        shareHandle = handle
+        let store = store!
+        DispatchQueue.global().async {
+            let result = Result { try handle.shareStore(store: store) }
+            DispatchQueue.main.async {
+                switch result {
+                case .success(let share):
+                    self.syncState = .shared(ticket: share.ticket, code: share.code)
+                case .failure(let error):
+                    self.stopSharing()
+                    self.syncState = .failed(error.localizedDescription)
+                }
+            }
+        }
+    }
+
+    func stopSharing() {
+        if let handle = shareHandle {
+            _ = try? handle.shutdown()
+            shareHandle = nil
+        }
+        if case .shared = syncState { syncState = .idle }
+    }
+
+    /// Fetch and merge a shared store, off the main thread.
+    func receive(ticket: String, code: String) {
+        syncState = .receiving
+        let store = store!
+        DispatchQueue.global().async {
+            let handle = P2pHandle()
+            let result = Result { try handle.receiveInto(ticket: ticket, code: code, targetStore: store) }
+            _ = try? handle.shutdown()
+            DispatchQueue.main.async {
+                switch result {
+                case .success(let count):
+                    self.reload()
+                    self.syncState = .received(count: count)
+                case .failure(let error):
+                    self.syncState = .failed(error.localizedDescription)
+                }
+            }
+        }
     }
 }
 
+/// The steps of sharing or receiving the store, as the share sheet shows
+/// them.
+enum SyncState {
+    case idle
+    case sharing
+    case shared(ticket: String, code: String)
+    case receiving
+    case received(count: UInt64)
+    case failed(String)
+
+    /// A line saying what is going on, or `nil` when there is nothing to say.
+    var status: String? {
+        switch self {
+        case .idle, .shared: return nil
+        case .sharing: return "Publishing the store\(Glyph.ellipsis)"
+        case .receiving: return "Fetching the store from the other device\(Glyph.ellipsis)"
+        case .received(let count): return "Received \(count) entries."
+        case .failed(let message): return "Failed: \(message)"
+        }
+    }
+
+    var isBusy: Bool {
+        switch self {
+        case .sharing, .receiving: return true
+        default: return false
+        }
+    }
+}
+
+// MARK: - Entry list rows
+
+extension SortMode {
+    var title: String {
+        switch self {
+        case .name: return "name"
+        case .modified: return "recently modified"
+        case .viewed: return "recently viewed"
+        case .kind: return "type"
+        }
+    }
+}
+
+/// One row of the entry list: a folder header or an entry.
+struct ListRow {
+    let id: String
+    let depth: Int
+    let folder: String?
+    let hit: FfiSearchHit?
+}
+
+/// Everything the entry list depends on; the rows are rebuilt when it changes.
+private struct ListKey: Equatable {
+    let query: String
+    let deep: Bool
+    let tag: String?
+    let category: ItemCategory?
+    let folder: String?
+    let hideDeactivated: Bool
+    let sort: SortMode
+    let viewed: [String]
+    let collapsed: Set<String>
+    let generation: Int
+}
+
+/// Holds the built entry list outside the observed view-model state, so
+/// filling it while drawing doesn't trigger another redraw.
+private final class ListCache {
+    var key: ListKey? = nil
+    var rows: [ListRow]? = nil
+    /// Bumped whenever the store's contents change.
+    var generation = 0
+}
+
+/// `work` for `work/github`, `nil` for a top-level name.
+func folderOf(_ name: String) -> String? {
+    name.lastIndex(of: "/").map { String(name[..<$0]) }
+}
+
+// MARK: - Key names
+
+/// The keymap name for a key press, e.g. "a", "?", "enter" or "ctrl-s".
+private func keyName(_ event: NSEvent) -> String? {
+    let base: String
+    switch event.keyCode {
+    case 36, 76: base = "enter"
+    case 48: base = "tab"
+    case 53: base = "esc"
+    case 123: base = "left"
+    case 124: base = "right"
+    case 125: base = "down"
+    case 126: base = "up"
+    case 115: base = "home"
+    case 119: base = "end"
+    case 116: base = "pageup"
+    case 121: base = "pagedown"
+    case 51: base = "backspace"
+    default:
+        guard let chars = event.charactersIgnoringModifiers, !chars.isEmpty else { return nil }
+        base = chars.lowercased()
+    }
+    let flags = event.modifierFlags
+    if flags.contains(.command) { return "cmd-\(base)" }
+    if flags.contains(.control) { return "ctrl-\(base)" }
+    if flags.contains(.shift) && (base == "tab" || base.first?.isLetter == true && base.count == 1) {
+        return "shift-\(base)"
+    }
+    return base
+}
+
+// MARK: - Display
+
+/// The `[display]` settings, read once at launch. Drawing code reads them
+/// through `ink` and `Glyph` instead of passing them down every view.
+enum Display {
+    static var config = DisplayConfig()
+
+    /// Colour for secondary text; full contrast in accessible mode.
+    static var secondary: Color { config.accessible ? .black : .gray }
+}
+
+/// `color`, or plain black when colour is turned off.
+func ink(_ color: Color) -> Color {
+    Display.config.noColor ? .black : color
+}
+
+/// Symbols with an ASCII stand-in for fonts that lack them.
+enum Glyph {
+    static var star: String { Display.config.ascii ? "*" : "★" }
+    static var bullet: String { Display.config.ascii ? "-" : "•" }
+    static var maskDot: String { Display.config.ascii ? "*" : "•" }
+    static var expanded: String { Display.config.ascii ? "v" : "▾" }
+    static var collapsed: String { Display.config.ascii ? ">" : "▸" }
+    static var up: String { Display.config.ascii ? "^" : "↑" }
+    static var down: String { Display.config.ascii ? "v" : "↓" }
+    static var back: String { Display.config.ascii ? "<" : "←" }
+    static var ellipsis: String { Display.config.ascii ? "..." : "…" }
+    static var remove: String { Display.config.ascii ? "x" : "×" }
+    static var quoteBar: String { Display.config.ascii ? "|" : "│" }
+
+    /// A bar `width` cells wide, filled in proportion to `count` of `total`.
+    static func gauge(_ count: UInt32, of total: UInt32, width: Int = 20) -> String {
+        let filled = total == 0 ? 0 : Int(count) * width / Int(total)
+        let (full, empty) = Display.config.ascii ? ("#", ".") : ("█", "░")
+        return String(repeating: full, count: filled) + String(repeating: empty, count: width - filled)
+    }
+
+    /// `values` as a row of bars scaled to the largest, for sparklines.
+    static func sparkline(_ values: [UInt32]) -> String {
+        let bars = Display.config.ascii ? Array("_.-=#") : Array("▁▂▃▄▅▆▇█")
+        let top = max(values.max() ?? 0, 1)
+        return String(values.map { bars[Int($0) * (bars.count - 1) / Int(top)] })
+    }
+
+    /// The list icon for an item kind, as reported by `FfiSearchHit.kind`.
+    static func icon(for kind: String) -> String {
+        let icons: [String: (String, String)] = [
+            "online_account": ("🌐", "@"),
+            "social_security": ("🪪", "#"),
+            "api_credential": ("🔑", "K"),
+            "identity_document": ("🛂", "ID"),
+            "wifi_network": ("📶", "W"),
+            "crypto_wallet": ("👛", "$"),
+            "software_license": ("📦", "L"),
+            "secure_note": ("📝", "N"),
+            "server_credential": ("🖥", ">"),
+            "email_account": ("✉️", "M"),
+            "membership": ("🎫", "C"),
+            "insurance_policy": ("☂️", "I"),
+            "passkey": ("🔐", "P"),
+            "pgp_key": ("🗝", "G"),
+            "drivers_license": ("🚗", "D"),
+            "template": ("🧩", "T"),
+        ]
+        let (symbol, ascii) = icons[kind] ?? ("•", "-")
+        return Display.config.ascii ? ascii : symbol
+    }
+
+    /// `count` mask characters standing in for a hidden secret.
+    static func mask(_ count: Int) -> String { String(repeating: maskDot, count: count) }
+}
+
 // MARK: - Convenience extensions on generated types
 
-extension FfiOnlineAccount {
-    static func empty() -> FfiOnlineAccount {
-        FfiOnlineAccount(
-            username: nil, password: nil, email: nil, phone: nil,
-            signInWith: nil, status: "Active", hostWebsite: nil,
-            loginPages: nil, securityQuestions: nil,
-            twoFactorEnabled: nil, associatedItems: nil,
-            dateCreated: nil, notes: nil
-        )
+extension FfiPasswordPolicy {
+    /// The rules in a few words, such as "at most 16 • needs digits".
+    var summary: String {
+        var parts: [String] = []
+        if let max = maxLength { parts.append("at most \(max)") }
+        if let forbidden = forbiddenCharacters, !forbidden.isEmpty { parts.append("no \(forbidden)") }
+        if !requiredClasses.isEmpty { parts.append("needs \(requiredClasses.joined(separator: ", "))") }
+        if let recipe { parts.append("recipe \(recipe)") }
+        return parts.isEmpty ? "none" : parts.joined(separator: " \(Glyph.bullet) ")
     }
 }
 
 extension FfiItem {
+    /// Template items are edited through their template, not inline.
+    var isTemplate: Bool {
+        if case .template = self { return true }
+        return false
+    }
+
     var displayName: String {
         switch self {
         case .onlineAccount: return "Online Account"
         case .socialSecurity: return "Social Security"
+        case .apiCredential: return "API Credential"
+        case .identityDocument(let d):
+            switch d.documentType {
+            case "Passport": return "Passport"
+            case "NationalId": return "National ID"
+            case "ResidencePermit": return "Residence Permit"
+            default: return "Identity Document"
+            }
+        case .wifiNetwork: return "WiFi Network"
+        case .cryptoWallet: return "Crypto Wallet"
+        case .softwareLicense: return "Software License"
+        case .secureNote: return "Secure Note"
+        case .serverCredential: return "Server"
+        case .emailAccount: return "Email Account"
+        case .membership: return "Membership"
+        case .insurancePolicy: return "Insurance Policy"
+        case .passkey: return "Passkey"
+        case .pgpKey: return "PGP Key"
+        case .driversLicense: return "Driver's License"
+        case .template(let t): return t.template
+        }
+    }
+
+    /// The accent colour and icon the item declares in its file, if any.
+    var appearance: FfiAppearance? {
+        switch self {
+        case .onlineAccount(let i): return i.appearance
+        case .socialSecurity(let i): return i.appearance
+        case .apiCredential(let i): return i.appearance
+        case .identityDocument(let i): return i.appearance
+        case .wifiNetwork(let i): return i.appearance
+        case .cryptoWallet(let i): return i.appearance
+        case .softwareLicense(let i): return i.appearance
+        case .secureNote(let i): return i.appearance
+        case .serverCredential(let i): return i.appearance
+        case .emailAccount(let i): return i.appearance
+        case .membership(let i): return i.appearance
+        case .insurancePolicy(let i): return i.appearance
+        case .passkey(let i): return i.appearance
+        case .pgpKey(let i): return i.appearance
+        case .driversLicense(let i): return i.appearance
+        case .template(let i): return i.appearance
+        }
+    }
+
+    /// The site's password rules, for online accounts that record them.
+    var passwordPolicy: FfiPasswordPolicy? {
+        if case .onlineAccount(let account) = self { return account.passwordPolicy }
+        return nil
+    }
+
+    var customFields: [FfiCustomField] {
+        switch self {
+        case .onlineAccount(let i): return i.customFields
+        case .socialSecurity(let i): return i.customFields
+        case .apiCredential(let i): return i.customFields
+        case .identityDocument(let i): return i.customFields
+        case .wifiNetwork(let i): return i.customFields
+        case .cryptoWallet(let i): return i.customFields
+        case .softwareLicense(let i): return i.customFields
+        case .secureNote(let i): return i.customFields
+        case .serverCredential(let i): return i.customFields
+        case .emailAccount(let i): return i.customFields
+        case .membership(let i): return i.customFields
+        case .insurancePolicy(let i): return i.customFields
+        case .passkey(let i): return i.customFields
+        case .pgpKey(let i): return i.customFields
+        case .driversLicense(let i): return i.customFields
+        case .template(let i): return i.customFields
+        }
+    }
+
+    /// An online account with its recovery codes replaced; other items are
+    /// returned unchanged.
+    func withRecoveryCodes(_ codes: [FfiRecoveryCode]) -> FfiItem {
+        guard case .onlineAccount(var account) = self else { return self }
+        account.recoveryCodes = codes
+        return .onlineAccount(account: account)
+    }
+
+    /// An online account with its TOTP secret set, or removed with `nil`;
+    /// other items are returned unchanged.
+    func withTotp(_ totp: FfiTotp?) -> FfiItem {
+        guard case .onlineAccount(var account) = self else { return self }
+        account.totp = totp
+        return .onlineAccount(account: account)
+    }
+
+    /// An online account with its security questions replaced; other
+    /// items are returned unchanged.
+    func withSecurityQuestions(_ questions: [FfiSecurityQuestion]) -> FfiItem {
+        guard case .onlineAccount(var account) = self else { return self }
+        account.securityQuestions = questions.isEmpty ? nil : questions
+        return .onlineAccount(account: account)
+    }
+
+    func withCustomFields(_ fields: [FfiCustomField]) -> FfiItem {
+        switch self {
+        case .onlineAccount(var i): i.customFields = fields; return .onlineAccount(account: i)
+        case .socialSecurity(var i): i.customFields = fields; return .socialSecurity(ssn: i)
+        case .apiCredential(var i): i.customFields = fields; return .apiCredential(credential: i)
+        case .identityDocument(var i): i.customFields = fields; return .identityDocument(document: i)
+        case .wifiNetwork(var i): i.customFields = fields; return .wifiNetwork(network: i)
+        case .cryptoWallet(var i): i.customFields = fields; return .cryptoWallet(wallet: i)
+        case .softwareLicense(var i): i.customFields = fields; return .softwareLicense(license: i)
+        case .secureNote(var i): i.customFields = fields; return .secureNote(note: i)
+        case .serverCredential(var i): i.customFields = fields; return .serverCredential(server: i)
+        case .emailAccount(var i): i.customFields = fields; return .emailAccount(mailbox: i)
+        case .membership(var i): i.customFields = fields; return .membership(membership: i)
+        case .insurancePolicy(var i): i.customFields = fields; return .insurancePolicy(policy: i)
+        case .passkey(var i): i.customFields = fields; return .passkey(passkey: i)
+        case .pgpKey(var i): i.customFields = fields; return .pgpKey(key: i)
+        case .driversLicense(var i): i.customFields = fields; return .driversLicense(license: i)
+        case .template(var i): i.customFields = fields; return .template(item: i)
+        }
+    }
+
+    var tags: [String] {
+        switch self {
+        case .onlineAccount(let i): return i.tags
+        case .socialSecurity(let i): return i.tags
+        case .apiCredential(let i): return i.tags
+        case .identityDocument(let i): return i.tags
+        case .wifiNetwork(let i): return i.tags
+        case .cryptoWallet(let i): return i.tags
+        case .softwareLicense(let i): return i.tags
+        case .secureNote(let i): return i.tags
+        case .serverCredential(let i): return i.tags
+        case .emailAccount(let i): return i.tags
+        case .membership(let i): return i.tags
+        case .insurancePolicy(let i): return i.tags
+        case .passkey(let i): return i.tags
+        case .pgpKey(let i): return i.tags
+        case .driversLicense(let i): return i.tags
+        case .template(let i): return i.tags
+        }
+    }
+
+    func withTags(_ tags: [String]) -> FfiItem {
+        switch self {
+        case .onlineAccount(var i): i.tags = tags; return .onlineAccount(account: i)
+        case .socialSecurity(var i): i.tags = tags; return .socialSecurity(ssn: i)
+        case .apiCredential(var i): i.tags = tags; return .apiCredential(credential: i)
+        case .identityDocument(var i): i.tags = tags; return .identityDocument(document: i)
+        case .wifiNetwork(var i): i.tags = tags; return .wifiNetwork(network: i)
+        case .cryptoWallet(var i): i.tags = tags; return .cryptoWallet(wallet: i)
+        case .softwareLicense(var i): i.tags = tags; return .softwareLicense(license: i)
+        case .secureNote(var i): i.tags = tags; return .secureNote(note: i)
+        case .serverCredential(var i): i.tags = tags; return .serverCredential(server: i)
+        case .emailAccount(var i): i.tags = tags; return .emailAccount(mailbox: i)
+        case .membership(var i): i.tags = tags; return .membership(membership: i)
+        case .insurancePolicy(var i): i.tags = tags; return .insurancePolicy(policy: i)
+        case .passkey(var i): i.tags = tags; return .passkey(passkey: i)
+        case .pgpKey(var i): i.tags = tags; return .pgpKey(key: i)
+        case .driversLicense(var i): i.tags = tags; return .driversLicense(license: i)
+        case .template(var i): i.tags = tags; return .template(item: i)
         }
     }
 
@@ -138,3 +1588,81 @@ extension FfiItem {
         return nil
     }
 }
+
+/// A stable colour per tag, so the same tag looks the same everywhere.
+func tagColor(_ tag: String) -> Color {
+    if Display.config.noColor { return .gray }
+    let palette: [Color] = [.blue, .green, .orange, .purple, .red, .yellow]
+    let hash = tag.lowercased().unicodeScalars.reduce(0) { ($0 &* 31 &+ Int($1.value)) & 0xffff }
+    return palette[hash % palette.count]
+}
+
+/// An item's declared accent: a colour name or "#rrggbb"; `nil` when it is
+/// neither, or when colour is turned off.
+func accentColor(_ spec: String?) -> Color? {
+    guard let spec = spec?.trimmingCharacters(in: .whitespaces).lowercased(), !Display.config.noColor else {
+        return nil
+    }
+    let named: [String: Color] = [
+        "red": .red, "orange": .orange, "yellow": .yellow, "green": .green,
+        "blue": .blue, "purple": .purple, "gray": .gray, "grey": .gray, "black": .black,
+    ]
+    if let color = named[spec] { return color }
+    guard spec.hasPrefix("#"), spec.count == 7, let rgb = Int(spec.dropFirst(), radix: 16) else { return nil }
+    return Color(
+        Float((rgb >> 16) & 0xff) / 255,
+        Float((rgb >> 8) & 0xff) / 255,
+        Float(rgb & 0xff) / 255
+    )
+}
+
+extension String {
+    /// Split around a UTF-8 byte range, as reported by search matches.
+    func splitForHighlight(start: UInt32, end: UInt32) -> (String, String, String) {
+        let bytes = Array(utf8)
+        let lo = min(Int(start), bytes.count)
+        let hi = min(max(Int(end), lo), bytes.count)
+        return (
+            String(decoding: bytes[..<lo], as: UTF8.self),
+            String(decoding: bytes[lo..<hi], as: UTF8.self),
+            String(decoding: bytes[hi...], as: UTF8.self)
+        )
+    }
+}
+
+extension FfiMailServer {
+    /// "host:port (security)", omitting whatever is unset.
+    var summary: String {
+        var out = host
+        if let port { out += ":\(port)" }
+        if let security { out += " (\(security))" }
+        return out
+    }
+}
+
+extension FfiPgpKey {
+    /// Mirrors `PgpKey::formatted_fingerprint`: blocks of four, split in half.
+    var formattedFingerprint: String? {
+        guard let raw = fingerprint else { return nil }
+        let hex = raw.filter { !$0.isWhitespace }.uppercased()
+        var blocks: [String] = []
+        var rest = Substring(hex)
+        while !rest.isEmpty {
+            blocks.append(String(rest.prefix(4)))
+            rest = rest.dropFirst(4)
+        }
+        let half = blocks.count / 2
+        return (blocks[..<half].joined(separator: " ") + "  " + blocks[half...].joined(separator: " "))
+            .trimmingCharacters(in: .whitespaces)
+    }
+}
+
+extension FfiApiCredential {
+    /// ISO 8601 civil dates compare correctly as strings.
+    var isExpired: Bool {
+        guard let expiry = expiryDate else { return false }
+        let formatter = DateFormatter()
+        formatter.dateFormat = "yyyy-MM-dd"
+        return expiry < formatter.string(from: Date())
+    }
+}
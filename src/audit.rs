@@ -0,0 +1,218 @@
+//! Headless health checks for `pwd audit`: weak, reused, breached and
+//! expired secrets, and how many of each the `[audit]` config tolerates.
+//!
+//! Breaches are looked up in the Have I Been Pwned password range API by
+//! k-anonymity: only the first five hex digits of a password's SHA-1 leave
+//! the machine, and the matching suffix is found locally.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha1::Digest as _;
+
+use crate::{Error, Result, models::{AccountName, PasswordStore}, stats};
+
+const RANGE_API: &str = "https://api.pwnedpasswords.com/range/";
+
+/// One kind of problem an audit looks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+	Weak,
+	Reused,
+	Breached,
+	Expired,
+}
+
+impl Check {
+	pub const ALL: [Self; 4] = [Self::Weak, Self::Reused, Self::Breached, Self::Expired];
+
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Weak => "weak passwords",
+			Self::Reused => "reused passwords",
+			Self::Breached => "breached passwords",
+			Self::Expired => "expired items",
+		}
+	}
+}
+
+/// A problem with one entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+	pub name:   AccountName,
+	pub check:  Check,
+	pub detail: String,
+}
+
+/// The `[audit]` table: how many findings of each check are tolerated
+/// before `pwd audit` fails.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+	pub max_weak:     u32,
+	pub max_reused:   u32,
+	pub max_breached: u32,
+	pub max_expired:  u32,
+}
+
+impl AuditConfig {
+	pub fn limit(&self, check: Check) -> u32 {
+		match check {
+			Check::Weak => self.max_weak,
+			Check::Reused => self.max_reused,
+			Check::Breached => self.max_breached,
+			Check::Expired => self.max_expired,
+		}
+	}
+}
+
+/// Every finding, grouped by check and then by entry name.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditReport {
+	pub findings: Vec<Finding>,
+}
+
+impl AuditReport {
+	pub fn count(&self, check: Check) -> u32 {
+		self.findings.iter().filter(|f| f.check == check).count() as u32
+	}
+
+	/// The checks with more findings than `config` tolerates.
+	pub fn exceeded(&self, config: &AuditConfig) -> Vec<Check> {
+		Check::ALL.into_iter().filter(|&check| self.count(check) > config.limit(check)).collect()
+	}
+}
+
+/// Audit every entry of `store` as of `today`. `breaches` tells how often a
+/// password turns up in known breaches, normally [`pwned_count`]; without
+/// it the breach check is skipped. Each distinct password is looked up once.
+pub fn audit(
+	store: &PasswordStore,
+	today: jiff::civil::Date,
+	breaches: Option<&dyn Fn(&str) -> Result<u64>>,
+) -> Result<AuditReport> {
+	let mut findings = vec![];
+	let mut by_password: BTreeMap<String, Vec<AccountName>> = BTreeMap::new();
+	let mut entries: Vec<_> = store.items.iter().collect();
+	entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+	for (name, item) in &entries {
+		if let Some(expiry) = item.expiry_date().filter(|expiry| *expiry < today) {
+			findings.push(Finding {
+				name:   (*name).clone(),
+				check:  Check::Expired,
+				detail: format!("expired on {expiry}"),
+			});
+		}
+		let Some(password) = stats::password(item) else { continue };
+		if stats::is_weak(&password) {
+			findings.push(Finding {
+				name:   (*name).clone(),
+				check:  Check::Weak,
+				detail: format!("scores {} of 4", stats::password_score(&password)),
+			});
+		}
+		by_password.entry(password).or_default().push((*name).clone());
+	}
+
+	for (password, names) in &by_password {
+		if names.len() > 1 {
+			for name in names {
+				let others: Vec<&str> =
+					names.iter().filter(|n| *n != name).map(AccountName::as_str).collect();
+				findings.push(Finding {
+					name:   name.clone(),
+					check:  Check::Reused,
+					detail: format!("also used by {}", others.join(", ")),
+				});
+			}
+		}
+		let Some(breaches) = breaches else { continue };
+		let count = breaches(password)?;
+		if count > 0 {
+			for name in names {
+				findings.push(Finding {
+					name:   name.clone(),
+					check:  Check::Breached,
+					detail: format!("seen {count} times in known breaches"),
+				});
+			}
+		}
+	}
+
+	findings.sort_by(|a, b| a.check.cmp(&b.check).then_with(|| a.name.cmp(&b.name)));
+	Ok(AuditReport { findings })
+}
+
+/// How often `password` appears in the Have I Been Pwned corpus.
+pub fn pwned_count(password: &str) -> Result<u64> {
+	let hash = hex_upper(&sha1::Sha1::digest(password.as_bytes()));
+	let (prefix, suffix) = hash.split_at(5);
+	let body = ureq::get(&format!("{RANGE_API}{prefix}"))
+		.set("Add-Padding", "true")
+		.call()
+		.map_err(|e| Error::BreachLookup(e.to_string()))?
+		.into_string()?;
+	Ok(count_in_range(&body, suffix))
+}
+
+/// The count beside `suffix` in a range response of `SUFFIX:COUNT` lines.
+/// Padding lines carry a count of 0.
+fn count_in_range(body: &str, suffix: &str) -> u64 {
+	body
+		.lines()
+		.filter_map(|line| line.trim().split_once(':'))
+		.find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+		.and_then(|(_, count)| count.parse().ok())
+		.unwrap_or(0)
+}
+
+fn hex_upper(bytes: &[u8]) -> String { bytes.iter().map(|b| format!("{b:02X}")).collect() }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Item;
+
+	fn account(password: &str) -> Item {
+		let values = BTreeMap::from([("password".to_string(), password.to_string())]);
+		crate::forms::build_item("online_account", &values).unwrap()
+	}
+
+	#[test]
+	fn findings_cover_weak_reused_breached_and_expired() {
+		let name = |s: &str| AccountName::new(s).unwrap();
+		let mut store = PasswordStore::new();
+		store.items.insert(name("a"), account("hunter2"));
+		store.items.insert(name("b"), account("hunter2"));
+		store.items.insert(name("c"), account("correct horse battery staple"));
+		let values = BTreeMap::from([
+			("token".to_string(), "t".to_string()),
+			("expiry_date".to_string(), "2024-01-01".to_string()),
+		]);
+		store.items.insert(name("d"), crate::forms::build_item("api_credential", &values).unwrap());
+
+		let breaches = |password: &str| Ok(if password == "hunter2" { 17 } else { 0 });
+		let today = jiff::civil::date(2025, 1, 1);
+		let report = audit(&store, today, Some(&breaches)).unwrap();
+		assert_eq!(report.count(Check::Weak), 2);
+		assert_eq!(report.count(Check::Reused), 2);
+		assert_eq!(report.count(Check::Breached), 2);
+		assert_eq!(report.count(Check::Expired), 1);
+		assert_eq!(report.findings[2].detail, "also used by b");
+
+		let config = AuditConfig { max_weak: 2, max_reused: 2, ..Default::default() };
+		assert_eq!(report.exceeded(&config), [Check::Breached, Check::Expired]);
+		assert_eq!(audit(&store, today, None).unwrap().count(Check::Breached), 0);
+	}
+
+	#[test]
+	fn range_responses_are_matched_by_suffix() {
+		let body =
+			"0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493\r\n";
+		assert_eq!(count_in_range(body, "1E4C9B93F3F0682250B6CF8331B7EE68FD8"), 3_861_493);
+		assert_eq!(count_in_range(body, "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF"), 0);
+		assert_eq!(hex_upper(&sha1::Sha1::digest(b"password"))[..5], *"5BAA6");
+	}
+}
@@ -0,0 +1,75 @@
+//! Oversized block-letter rendering for short codes (TOTP, PINs), so they
+//! can be read from across the room.
+//!
+//! Only digits, spaces and dashes have glyphs: letters are left to a plain
+//! large font, since a block font that drops case would misreport a secret.
+
+/// The longest value drawn in block letters; anything longer is shown as
+/// plain text.
+pub const MAX_LEN: usize = 12;
+
+const ROWS: usize = 5;
+
+fn glyph(c: char) -> Option<[&'static str; ROWS]> {
+	Some(match c {
+		'0' => ["###", "# #", "# #", "# #", "###"],
+		'1' => [" # ", "## ", " # ", " # ", "###"],
+		'2' => ["###", "  #", "###", "#  ", "###"],
+		'3' => ["###", "  #", "###", "  #", "###"],
+		'4' => ["# #", "# #", "###", "  #", "  #"],
+		'5' => ["###", "#  ", "###", "  #", "###"],
+		'6' => ["###", "#  ", "###", "# #", "###"],
+		'7' => ["###", "  #", "  #", "  #", "  #"],
+		'8' => ["###", "# #", "###", "# #", "###"],
+		'9' => ["###", "# #", "###", "  #", "###"],
+		'-' => ["   ", "   ", "###", "   ", "   "],
+		' ' => ["   ", "   ", "   ", "   ", "   "],
+		_ => return None,
+	})
+}
+
+/// Draw `text` in five-row block letters made of `fill`, each pixel two
+/// characters wide so the digits keep their shape in a terminal cell.
+///
+/// Returns `None` if `text` is empty, longer than [`MAX_LEN`], or contains
+/// anything without a glyph.
+pub fn render(text: &str, fill: char) -> Option<String> {
+	let text = text.trim();
+	if text.is_empty() || text.chars().count() > MAX_LEN {
+		return None;
+	}
+	let glyphs = text.chars().map(glyph).collect::<Option<Vec<_>>>()?;
+	let rows = (0..ROWS).map(|row| {
+		let line = glyphs
+			.iter()
+			.map(|g| {
+				g[row].chars().flat_map(|p| [if p == '#' { fill } else { ' ' }; 2]).collect::<String>()
+			})
+			.collect::<Vec<_>>()
+			.join("  ");
+		line.trim_end().to_owned()
+	});
+	Some(rows.collect::<Vec<_>>().join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn digits_are_drawn_in_blocks() {
+		assert_eq!(
+			render("17", '#').unwrap(),
+			["  ##    ######", "####        ##", "  ##        ##", "  ##        ##", "######        ##",]
+				.join("\n")
+		);
+	}
+
+	#[test]
+	fn unsupported_or_long_values_are_left_alone() {
+		assert_eq!(render("abc123", '#'), None);
+		assert_eq!(render("", '#'), None);
+		assert_eq!(render("1234567890123", '#'), None);
+		assert!(render(" 123 456 ", '█').is_some());
+	}
+}
@@ -0,0 +1,36 @@
+//! Opening item URLs in the default browser.
+
+use crate::{Error, Result};
+
+/// Open `url` with the system's default handler (`open`, `xdg-open`, …).
+///
+/// Only `http` and `https` URLs are opened, so a stored value can never
+/// launch a local file or another application.
+pub fn open(url: &str) -> Result<()> {
+	let url = check(url)?;
+	open::that_detached(url.as_str())?;
+	Ok(())
+}
+
+fn check(url: &str) -> Result<url::Url> {
+	let invalid = |reason: String| Error::Validation { field: "url".into(), reason };
+	let parsed = url::Url::parse(url).map_err(|e| invalid(format!("{url:?}: {e}")))?;
+	match parsed.scheme() {
+		"http" | "https" => Ok(parsed),
+		scheme => Err(invalid(format!("refusing to open a {scheme}: URL"))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::check;
+
+	#[test]
+	fn only_web_urls_are_opened() {
+		assert!(check("https://example.com/login").is_ok());
+		assert!(check("http://router.local").is_ok());
+		for url in ["file:///etc/passwd", "javascript:alert(1)", "example.com", ""] {
+			assert!(check(url).is_err(), "{url}");
+		}
+	}
+}
@@ -0,0 +1,238 @@
+//! System clipboard access for copying secrets.
+//!
+//! Callers copy labelled values and report only the label back to the user;
+//! the value itself is never echoed.
+//!
+//! Over SSH, or anywhere the native clipboard is unreachable, text can still
+//! be copied with an OSC 52 escape sequence, which asks the local terminal
+//! emulator to set its clipboard. [`ClipboardMode`] picks the mechanism.
+//!
+//! [`SecretClipboard`] additionally clears a copied secret after a timeout,
+//! but only if the clipboard still holds it. Terminals can't be asked what
+//! their clipboard holds, so a copy made over OSC 52 is cleared
+//! unconditionally.
+
+use std::{io::{IsTerminal as _, Write as _}, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use base64::Engine as _;
+use serde::Deserialize;
+
+use crate::{Error, Result};
+
+/// How [`copy`] reaches the clipboard.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, uniffi::Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardMode {
+	/// The native clipboard, falling back to OSC 52 when it is unavailable.
+	#[default]
+	Auto,
+	/// Only the native clipboard.
+	Native,
+	/// Only OSC 52 on the controlling terminal.
+	Osc52,
+	/// Both, succeeding if either does.
+	Both,
+}
+
+/// The `[clipboard]` table of the config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClipboardConfig {
+	pub mode:             ClipboardMode,
+	/// Seconds before a copied secret is cleared; 0 keeps it.
+	pub clear_after_secs: u64,
+}
+
+impl Default for ClipboardConfig {
+	fn default() -> Self { Self { mode: ClipboardMode::default(), clear_after_secs: 30 } }
+}
+
+/// Which mechanisms a copy went through, so clearing can use the same ones.
+#[derive(Debug, Clone, Copy, Default)]
+struct Delivery {
+	native: bool,
+	osc52:  bool,
+}
+
+fn clipboard() -> Result<arboard::Clipboard> {
+	arboard::Clipboard::new().map_err(|e| Error::Clipboard(e.to_string()))
+}
+
+fn copy_native(text: &str) -> Result<()> {
+	clipboard()?.set_text(text).map_err(|e| Error::Clipboard(e.to_string()))
+}
+
+/// The OSC 52 "set clipboard" sequence for `text`, wrapped for tmux's
+/// passthrough when running inside tmux.
+fn osc52_sequence(text: &str, tmux: bool) -> String {
+	let payload = base64::engine::general_purpose::STANDARD.encode(text);
+	if tmux {
+		format!("\x1bPtmux;\x1b\x1b]52;c;{payload}\x07\x1b\\")
+	} else {
+		format!("\x1b]52;c;{payload}\x07")
+	}
+}
+
+/// Write the OSC 52 sequence to the controlling terminal, or to stdout if
+/// that is a terminal.
+fn copy_osc52(text: &str) -> Result<()> {
+	let sequence = osc52_sequence(text, std::env::var_os("TMUX").is_some());
+	#[cfg(unix)]
+	if let Ok(mut tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+		tty.write_all(sequence.as_bytes())?;
+		return Ok(tty.flush()?);
+	}
+	let mut stdout = std::io::stdout();
+	if !stdout.is_terminal() {
+		return Err(Error::Clipboard("no terminal to send OSC 52 to".into()));
+	}
+	stdout.write_all(sequence.as_bytes())?;
+	Ok(stdout.flush()?)
+}
+
+fn deliver(text: &str, mode: ClipboardMode) -> Result<Delivery> {
+	match mode {
+		ClipboardMode::Native => copy_native(text).map(|()| Delivery { native: true, osc52: false }),
+		ClipboardMode::Osc52 => copy_osc52(text).map(|()| Delivery { native: false, osc52: true }),
+		ClipboardMode::Auto => match copy_native(text) {
+			Ok(()) => Ok(Delivery { native: true, osc52: false }),
+			Err(_) => copy_osc52(text).map(|()| Delivery { native: false, osc52: true }),
+		},
+		ClipboardMode::Both => {
+			let native = copy_native(text);
+			let osc52 = copy_osc52(text);
+			if native.is_err() && osc52.is_err() {
+				return native.map(|()| Delivery::default());
+			}
+			Ok(Delivery { native: native.is_ok(), osc52: osc52.is_ok() })
+		}
+	}
+}
+
+/// Place `text` on the clipboard using `mode`.
+pub fn copy(text: &str, mode: ClipboardMode) -> Result<()> { deliver(text, mode).map(drop) }
+
+/// Clear what a copy of `expected` put on the clipboard, leaving the native
+/// clipboard alone if something else has been copied since.
+fn clear_if_unchanged(expected: &str, delivery: Delivery) -> Result<()> {
+	if delivery.native {
+		let mut clipboard = clipboard()?;
+		if clipboard.get_text().is_ok_and(|current| current == expected) {
+			clipboard.clear().map_err(|e| Error::Clipboard(e.to_string()))?;
+		}
+	}
+	if delivery.osc52 {
+		copy_osc52("")?;
+	}
+	Ok(())
+}
+
+/// Copy `text`, call `copied`, then block until it is cleared after
+/// [`ClipboardConfig::clear_after_secs`]. The calling process stays the
+/// clipboard's owner meanwhile, which X11 and Wayland need: there a copy
+/// disappears when the process that made it exits. This is the background
+/// half of a command-line copy.
+pub fn copy_and_clear(text: &str, config: &ClipboardConfig, copied: impl FnOnce()) -> Result<()> {
+	let _owner = clipboard().ok();
+	let delivery = deliver(text, config.mode)?;
+	copied();
+	if config.clear_after_secs == 0 {
+		return Ok(());
+	}
+	std::thread::sleep(Duration::from_secs(config.clear_after_secs));
+	clear_if_unchanged(text, delivery)
+}
+
+struct PendingClear {
+	id:       u64,
+	value:    String,
+	delivery: Delivery,
+	deadline: Instant,
+}
+
+/// Copies secrets and clears them again after
+/// [`ClipboardConfig::clear_after_secs`].
+///
+/// Each copy replaces the pending clear of the previous one.
+pub struct SecretClipboard {
+	config:  ClipboardConfig,
+	pending: Arc<Mutex<Option<PendingClear>>>,
+	next_id: Mutex<u64>,
+}
+
+impl SecretClipboard {
+	pub fn new(config: ClipboardConfig) -> Self {
+		Self { config, pending: Arc::default(), next_id: Mutex::new(0) }
+	}
+
+	pub fn config(&self) -> &ClipboardConfig { &self.config }
+
+	/// Copy `text` and, if clearing is enabled, schedule it to be cleared on
+	/// a background thread.
+	pub fn copy_secret(&self, text: &str) -> Result<()> {
+		let delivery = deliver(text, self.config.mode)?;
+		if self.config.clear_after_secs == 0 {
+			*self.pending.lock().unwrap() = None;
+			return Ok(());
+		}
+		let delay = Duration::from_secs(self.config.clear_after_secs);
+		let id = {
+			let mut next = self.next_id.lock().unwrap();
+			*next += 1;
+			*next
+		};
+		*self.pending.lock().unwrap() =
+			Some(PendingClear { id, value: text.to_owned(), delivery, deadline: Instant::now() + delay });
+
+		let pending = Arc::clone(&self.pending);
+		std::thread::spawn(move || {
+			std::thread::sleep(delay);
+			let mut pending = pending.lock().unwrap();
+			if pending.as_ref().is_some_and(|p| p.id == id)
+				&& let Some(clear) = pending.take()
+			{
+				let _ = clear_if_unchanged(&clear.value, clear.delivery);
+			}
+		});
+		Ok(())
+	}
+
+	/// Copy non-secret text without scheduling a clear.
+	pub fn copy_plain(&self, text: &str) -> Result<()> { copy(text, self.config.mode) }
+
+	/// Time left before the pending clear, if one is scheduled.
+	pub fn remaining(&self) -> Option<Duration> {
+		self
+			.pending
+			.lock()
+			.unwrap()
+			.as_ref()
+			.map(|p| p.deadline.saturating_duration_since(Instant::now()))
+	}
+
+	/// Clear the pending secret right away.
+	pub fn clear_now(&self) -> Result<()> {
+		match self.pending.lock().unwrap().take() {
+			Some(clear) => clear_if_unchanged(&clear.value, clear.delivery),
+			None => Ok(()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn osc52_sequences_encode_the_payload() {
+		assert_eq!(osc52_sequence("hunter2", false), "\x1b]52;c;aHVudGVyMg==\x07");
+		assert_eq!(osc52_sequence("hunter2", true), "\x1bPtmux;\x1b\x1b]52;c;aHVudGVyMg==\x07\x1b\\");
+	}
+
+	#[test]
+	fn clearing_defaults_to_thirty_seconds() {
+		let config: ClipboardConfig = toml::from_str(r#"mode = "both""#).unwrap();
+		assert_eq!(config.mode, ClipboardMode::Both);
+		assert_eq!(config.clear_after_secs, 30);
+	}
+}
@@ -0,0 +1,281 @@
+//! User preferences for the front ends, read from `pwd/config.toml` in the
+//! platform config directory.
+//!
+//! ```toml
+//! [keys.list]
+//! next_entry = ["j", "down", "ctrl-n"]
+//! new_entry = ["g n"]   # a chord: press g, then n
+//!
+//! [keys.detail]
+//! toggle_history = []   # unbind
+//!
+//! [clipboard]
+//! mode = "osc52"        # auto (default), native, osc52 or both
+//! clear_after_secs = 45 # 30 by default; 0 never clears
+//!
+//! [secrets]
+//! reveal_secs = 5       # 10 by default; 0 stays revealed until hidden
+//! mask = "keypress"     # always, focus, keypress (default) or never
+//!
+//! [secrets.mask_fields] # per field, by its label in snake case
+//! password = "always"
+//! pin = "focus"
+//!
+//! [display]
+//! ascii = true          # plain ASCII instead of symbols like ★ and ▸
+//! no_color = true       # also implied by a non-empty NO_COLOR variable
+//! accessible = true     # high contrast, plain-sentence focus announcements
+//!
+//! [audit]               # findings `pwd audit` tolerates; 0 by default
+//! max_weak = 3
+//! max_expired = 1
+//!
+//! [stores]              # named stores for `pwd --store work`
+//! work = "~/work/.pwd"
+//!
+//! [generator.presets.banking]
+//! mode = "password"     # password, passphrase, pronounceable, pin or recipe
+//! length = 16
+//! symbols = false
+//!
+//! [generator.presets.wifi]
+//! mode = "passphrase"
+//! words = 5
+//! separator = " "
+//! ```
+//!
+//! A missing file means every default applies.
+
+use std::{collections::{BTreeMap, HashMap}, path::{Path, PathBuf}};
+
+use serde::Deserialize;
+
+use crate::{Result, audit::AuditConfig, clipboard::ClipboardConfig, generator::GeneratorOptions, keymap::{KeyOverrides, Keymap}};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+	/// Per-context key binding overrides.
+	pub keys:      KeyOverrides,
+	pub clipboard: ClipboardConfig,
+	pub secrets:   SecretsConfig,
+	pub display:   DisplayConfig,
+	pub generator: GeneratorConfig,
+	pub audit:     AuditConfig,
+	/// Store directories by name; a leading `~/` is the home directory.
+	pub stores:    BTreeMap<String, PathBuf>,
+}
+
+/// The `[secrets]` table: how masked values behave on screen.
+#[derive(Debug, Clone, Deserialize, uniffi::Record)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecretsConfig {
+	/// Seconds a revealed secret stays visible before it is masked again;
+	/// 0 leaves it revealed until hidden by hand.
+	#[uniffi(default = 10)]
+	pub reveal_secs: u64,
+	/// How secrets are masked unless `mask_fields` says otherwise.
+	pub mask:        MaskPolicy,
+	/// Per-field masking, keyed by [`field_key`].
+	pub mask_fields: HashMap<String, MaskPolicy>,
+}
+
+impl Default for SecretsConfig {
+	fn default() -> Self {
+		Self { reveal_secs: 10, mask: MaskPolicy::default(), mask_fields: HashMap::new() }
+	}
+}
+
+impl SecretsConfig {
+	/// How the secret field labelled `label` is masked.
+	pub fn mask_policy(&self, label: &str) -> MaskPolicy {
+		self.mask_fields.get(&field_key(label)).copied().unwrap_or(self.mask)
+	}
+}
+
+/// When a secret is shown in clear.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, uniffi::Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum MaskPolicy {
+	/// Never shown; it can still be copied.
+	Always,
+	/// Shown while its field has focus.
+	Focus,
+	/// Shown for `reveal_secs` after the reveal key or button.
+	#[default]
+	Keypress,
+	/// Always shown.
+	Never,
+}
+
+/// A field label as a `mask_fields` key: snake case, with the number of a
+/// numbered row ("Answer 2") dropped, so "Seed phrase" is `seed_phrase` and
+/// every security answer is `answer`.
+pub fn field_key(label: &str) -> String {
+	label
+		.trim_end_matches(|c: char| c.is_ascii_digit() || c.is_whitespace())
+		.split_whitespace()
+		.map(str::to_lowercase)
+		.collect::<Vec<_>>()
+		.join("_")
+}
+
+/// The `[display]` table, for terminals and fonts that lack colour or
+/// render symbols as boxes.
+#[derive(Debug, Clone, Default, Deserialize, uniffi::Record)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+	/// Draw with plain ASCII instead of symbols such as ★, ▸ and •.
+	#[uniffi(default = false)]
+	pub ascii:      bool,
+	/// Draw without colour.
+	#[uniffi(default = false)]
+	pub no_color:   bool,
+	/// High contrast with less decoration, and the focused field read out
+	/// as one plain sentence at the top of the details, for screen readers.
+	#[uniffi(default = false)]
+	pub accessible: bool,
+}
+
+impl DisplayConfig {
+	/// Also turn colour off when `NO_COLOR` is set to anything non-empty,
+	/// as <https://no-color.org> asks.
+	pub fn with_env(mut self) -> Self {
+		self.no_color |= std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+		self
+	}
+}
+
+/// The `[generator]` table.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneratorConfig {
+	/// Named settings, one `[generator.presets.<name>]` table each.
+	pub presets: BTreeMap<String, GeneratorOptions>,
+}
+
+impl Config {
+	/// `<config dir>/pwd/config.toml`, if the platform has a config directory.
+	pub fn default_path() -> Option<PathBuf> {
+		dirs::config_dir().map(|d| d.join("pwd").join("config.toml"))
+	}
+
+	/// Read the config at `path`, or the defaults if it doesn't exist.
+	pub fn load(path: &Path) -> Result<Self> {
+		match std::fs::read_to_string(path) {
+			Ok(text) => Ok(toml::from_str(&text)?),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	/// The directory of the store `name_or_path`: a store named in
+	/// `[stores]`, or else a path.
+	pub fn store_dir(&self, name_or_path: &str) -> PathBuf {
+		let path =
+			self.stores.get(name_or_path).map_or_else(|| PathBuf::from(name_or_path), Clone::clone);
+		match (path.strip_prefix("~"), dirs::home_dir()) {
+			(Ok(rest), Some(home)) => home.join(rest),
+			_ => path,
+		}
+	}
+
+	/// The default keymap with this config's overrides applied.
+	pub fn keymap(&self) -> Result<Keymap> { Keymap::with_overrides(&self.keys) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn missing_file_yields_defaults_and_unknown_keys_are_rejected() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("config.toml");
+		assert_eq!(Config::load(&path).unwrap().keymap().unwrap(), Keymap::default());
+
+		std::fs::write(&path, "[keys.list]\nnew_entry = [\"ctrl-n\"]\n").unwrap();
+		assert!(Config::load(&path).unwrap().keymap().unwrap() != Keymap::default());
+
+		std::fs::write(&path, "[secrets]\nreveal_secs = 3\n").unwrap();
+		let secrets = Config::load(&path).unwrap().secrets;
+		assert_eq!(secrets.reveal_secs, 3);
+		assert_eq!(secrets.mask_policy("Password"), MaskPolicy::Keypress);
+
+		std::fs::write(&path, "[display]\nascii = true\n").unwrap();
+		let display = Config::load(&path).unwrap().display;
+		assert!(display.ascii && !display.no_color);
+
+		std::fs::write(&path, "[keys.sidebar]\nnew_entry = [\"n\"]\n").unwrap();
+		assert!(Config::load(&path).is_err());
+	}
+
+	#[test]
+	fn named_stores_resolve_to_their_directories() {
+		let config: Config = toml::from_str("[stores]\nwork = \"/srv/work/.pwd\"\n").unwrap();
+		assert_eq!(config.store_dir("work"), PathBuf::from("/srv/work/.pwd"));
+		assert_eq!(config.store_dir("/tmp/other"), PathBuf::from("/tmp/other"));
+		if let Some(home) = dirs::home_dir() {
+			assert_eq!(config.store_dir("~/.pwd"), home.join(".pwd"));
+		}
+	}
+
+	#[test]
+	fn mask_policy_falls_back_to_the_default_per_field() {
+		let secrets: SecretsConfig = toml::from_str(
+			r#"
+			mask = "focus"
+			[mask_fields]
+			password = "always"
+			seed_phrase = "never"
+			answer = "always"
+			"#,
+		)
+		.unwrap();
+		assert_eq!(secrets.mask_policy("Password"), MaskPolicy::Always);
+		assert_eq!(secrets.mask_policy("Seed phrase"), MaskPolicy::Never);
+		assert_eq!(secrets.mask_policy("Answer 2"), MaskPolicy::Always);
+		assert_eq!(secrets.mask_policy("PIN"), MaskPolicy::Focus);
+	}
+
+	#[test]
+	fn generator_presets_name_a_mode_and_its_settings() {
+		let generator: GeneratorConfig = toml::from_str(
+			r#"
+			[presets.banking]
+			mode = "password"
+			length = 16
+			symbols = false
+
+			[presets.wifi]
+			mode = "passphrase"
+			words = 5
+
+			[presets.door]
+			mode = "recipe"
+			recipe = "[0-9]{6}"
+			"#,
+		)
+		.unwrap();
+		assert_eq!(generator.presets.keys().collect::<Vec<_>>(), ["banking", "door", "wifi"]);
+		let GeneratorOptions::Password { options } = &generator.presets["banking"] else {
+			panic!("banking is a password preset");
+		};
+		assert_eq!((options.length, options.symbols, options.digits), (16, false, true));
+		assert!(matches!(
+			&generator.presets["wifi"],
+			GeneratorOptions::Passphrase { options } if options.words == 5 && options.separator == "-"
+		));
+		assert_eq!(generator.presets["door"].generate().unwrap().len(), 6);
+
+		assert!(
+			toml::from_str::<GeneratorConfig>(
+				"[presets.x]
+mode = \"pin\"
+words = 3
+"
+			)
+			.is_err()
+		);
+	}
+}
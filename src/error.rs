@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use crate::models::AccountName;
 
 #[derive(thiserror::Error, Debug)]
@@ -10,7 +12,7 @@ pub enum Error {
 	#[error("entry already exists: {name}")]
 	EntryAlreadyExists { name: AccountName },
 
-	/// The supplied account name is invalid (e.g. empty, contains slashes).
+	/// The supplied account name is invalid (e.g. empty, contains dots).
 	#[error("invalid account name: {0}")]
 	InvalidAccountName(String),
 
@@ -50,6 +52,10 @@ pub enum Error {
 	#[error("invalid ticket: {0}")]
 	InvalidTicket(String),
 
+	/// A shared payload could not be opened with the code given.
+	#[error("wrong share code, or the payload was tampered with")]
+	WrongShareCode,
+
 	/// The sync operation did not complete within the allotted time.
 	#[error("sync timed out after {secs}s")]
 	SyncTimeout { secs: u64 },
@@ -70,6 +76,38 @@ pub enum Error {
 	#[error("peer disconnected")]
 	PeerDisconnected,
 
+	/// The requested attachment does not exist on the entry.
+	#[error("attachment not found: {name}/{file}")]
+	AttachmentNotFound { name: AccountName, file: String },
+
+	/// The supplied attachment file name is invalid.
+	#[error("invalid attachment name: {0}")]
+	InvalidAttachmentName(String),
+
+	/// The payload could not be encoded as a QR code (usually too long).
+	#[error("qr: {0}")]
+	Qr(String),
+
+	/// The system clipboard could not be read or written.
+	#[error("clipboard: {0}")]
+	Clipboard(String),
+
+	/// A YubiKey could not be reached or refused an OATH request.
+	#[error("yubikey: {0}")]
+	YubiKey(String),
+
+	/// Another password manager's export could not be read.
+	#[error("import: {0}")]
+	Import(String),
+
+	/// Entries could not be written out for another password manager.
+	#[error("export: {0}")]
+	Export(String),
+
+	/// The breached-password lookup failed.
+	#[error("breach lookup: {0}")]
+	BreachLookup(String),
+
 	/// TOML serialisation error.
 	#[error("serialize: {0}")]
 	Serialize(#[from] toml::ser::Error),
@@ -94,6 +132,10 @@ pub enum Error {
 	#[error("validation — {field}: {reason}")]
 	Validation { field: String, reason: String },
 
+	/// A user-defined item template could not be loaded.
+	#[error("invalid template {}: {reason}", path.display())]
+	InvalidTemplate { path: PathBuf, reason: String },
+
 	/// A required field was absent.
 	#[error("missing required field: {field}")]
 	MissingField { field: String },
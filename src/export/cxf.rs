@@ -0,0 +1,181 @@
+//! Passkeys and logins written in the FIDO Alliance's Credential Exchange
+//! Format, for managers that import it. The document is plain JSON; nothing
+//! in it is encrypted.
+//!
+//! Passkeys keep their keys and IDs as they are, since both sides encode
+//! them the same way. Online accounts become a basic-auth credential with
+//! their TOTP and notes alongside. Other kinds have no CXF counterpart worth
+//! writing and are left out. Folders become collections.
+
+use std::{collections::BTreeMap, io::Write};
+
+use base64::Engine as _;
+use serde_json::Value;
+
+use crate::{Error, Item, Result, import::cxf::{Account, Collection, Credential, CxfItem, Field, Header, LinkedItem, PasskeyCredential, Scope, TotpCredential, Version}, models::AccountName};
+
+/// Who the export says wrote it.
+const EXPORTER: &str = "pwd";
+
+/// Write the passkeys and online accounts among `entries` to `out`,
+/// returning how many were written.
+pub fn write(out: impl Write, entries: &[(AccountName, Item)]) -> Result<usize> {
+	let mut items = vec![];
+	let mut folders: BTreeMap<&str, Vec<LinkedItem>> = BTreeMap::new();
+	for (name, item) in entries {
+		let Some(credentials) = credentials(name, item)? else { continue };
+		let id = random_id();
+		if let Some(folder) = name.folder() {
+			folders.entry(folder).or_default().push(LinkedItem { item: id.clone() });
+		}
+		let creation_at = match item {
+			Item::Passkey(passkey) => match passkey.creation_date {
+				Some(date) => Some(
+					date
+						.to_zoned(jiff::tz::TimeZone::UTC)
+						.map_err(|e| Error::Export(e.to_string()))?
+						.timestamp()
+						.as_second(),
+				),
+				None => None,
+			},
+			_ => None,
+		};
+		let scope = match item {
+			Item::OnlineAccount(account) => {
+				account.host_website.as_ref().map(|url| Scope { urls: vec![url.to_string()] })
+			}
+			_ => None,
+		};
+		items.push(CxfItem {
+			id,
+			creation_at,
+			title: name.leaf().to_string(),
+			scope,
+			credentials: credentials
+				.into_iter()
+				.map(serde_json::to_value)
+				.collect::<serde_json::Result<Vec<Value>>>()?,
+			tags: item.tags().to_vec(),
+		});
+	}
+	let written = items.len();
+	let collections = folders
+		.into_iter()
+		.map(|(folder, items)| Collection {
+			id: random_id(),
+			title: folder.to_string(),
+			items,
+			subcollections: vec![],
+		})
+		.collect();
+	let header = Header {
+		version:               Version { major: 1, minor: 0 },
+		exporter_rp_id:        EXPORTER.into(),
+		exporter_display_name: EXPORTER.into(),
+		timestamp:             jiff::Timestamp::now().as_second(),
+		accounts:              vec![Account {
+			id: random_id(),
+			username: String::new(),
+			email: String::new(),
+			collections,
+			items,
+		}],
+	};
+	let mut out = out;
+	serde_json::to_writer_pretty(&mut out, &header)?;
+	writeln!(out)?;
+	Ok(written)
+}
+
+/// The credentials for one entry, or `None` for kinds CXF isn't used for.
+fn credentials(name: &AccountName, item: &Item) -> Result<Option<Vec<Credential>>> {
+	let mut credentials = vec![];
+	let notes = match item {
+		Item::Passkey(passkey) => {
+			credentials.push(Credential::Passkey(PasskeyCredential {
+				credential_id:     passkey.credential_id.clone(),
+				rp_id:             passkey.rp_id.to_string(),
+				username:          passkey.user_name.clone().unwrap_or_default(),
+				user_display_name: passkey.user_display_name.clone().unwrap_or_default(),
+				user_handle:       passkey.user_handle.clone().unwrap_or_default(),
+				key:               passkey.private_key.clone(),
+			}));
+			&passkey.notes
+		}
+		Item::OnlineAccount(account) => {
+			credentials.push(Credential::BasicAuth {
+				username: account.username.as_ref().map(|u| Field::new("string", u)),
+				password: account.password.as_ref().map(|p| Field::new("concealed-string", p)),
+			});
+			// Codes computed on a YubiKey have no secret to export.
+			if let Some(totp) = account.totp.as_ref().filter(|totp| totp.yubikey.is_none()) {
+				let params = totp.params()?;
+				credentials.push(Credential::Totp(TotpCredential {
+					secret:    base32::encode(base32::Alphabet::Rfc4648 { padding: false }, &params.secret),
+					period:    params.period,
+					digits:    params.digits,
+					algorithm: params.algorithm.to_string().to_lowercase(),
+					issuer:    totp.issuer.clone().or_else(|| Some(name.leaf().to_string())),
+				}));
+			}
+			&account.notes
+		}
+		_ => return Ok(None),
+	};
+	if let Some(notes) = notes.as_ref().filter(|n| !n.is_empty()) {
+		credentials.push(Credential::Note { content: Field::new("string", notes) });
+	}
+	Ok(Some(credentials))
+}
+
+/// A fresh item, collection or account ID: 16 random bytes, base64url.
+fn random_id() -> String {
+	base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(rand::random::<[u8; 16]>())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{forms::build_item, import::cxf::read_json};
+
+	#[test]
+	fn passkeys_and_logins_survive_a_round_trip() {
+		let values = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+			pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+		};
+		let passkey = build_item(
+			"passkey",
+			&values(&[
+				("rp_id", "github.com"),
+				("credential_id", "Y3JlZA"),
+				("private_key", "a2V5"),
+				("user_name", "alice"),
+			]),
+		)
+		.unwrap();
+		let login = build_item(
+			"online_account",
+			&values(&[("username", "alice"), ("password", "hunter2"), ("notes", "work")]),
+		)
+		.unwrap();
+		let wifi =
+			build_item("wifi_network", &values(&[("ssid", "home"), ("passphrase", "pw")])).unwrap();
+		let entries = vec![
+			(AccountName::new("dev/GitHub").unwrap(), login),
+			(AccountName::new("dev/GitHub passkey").unwrap(), passkey.clone()),
+			(AccountName::new("Wi-Fi").unwrap(), wifi),
+		];
+
+		let mut buffer = vec![];
+		assert_eq!(write(&mut buffer, &entries).unwrap(), 2);
+		let read_back = read_json(buffer.as_slice()).unwrap();
+		let names: Vec<_> = read_back.iter().map(|e| e.name.as_str()).collect();
+		assert_eq!(names, ["dev/GitHub", "dev/GitHub passkey"]);
+		let Item::OnlineAccount(account) = &read_back[0].item else { panic!("not a login") };
+		assert_eq!(account.notes.as_deref(), Some("work"));
+		let Item::Passkey(read) = &read_back[1].item else { panic!("not a passkey") };
+		let Item::Passkey(written) = &passkey else { unreachable!() };
+		assert_eq!((&read.private_key, &read.user_name), (&written.private_key, &written.user_name));
+	}
+}
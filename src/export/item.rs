@@ -0,0 +1,54 @@
+//! One entry as a file of its own, for carrying it to another store: the
+//! same TOML an entry is stored as, wrapped with its name and attachments,
+//! and sealed with a passphrase unless it is wanted in plaintext.
+//! [`crate::import::item`] reads it back.
+
+use std::collections::BTreeMap;
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+
+use crate::{EncryptionMethod, Item, Result, models::AccountName};
+
+/// The `format` of every item file, so other TOML is told apart.
+pub(crate) const FORMAT: &str = "pwd-item/1";
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Bundle {
+	pub format:      String,
+	pub name:        String,
+	pub item:        Item,
+	/// File name to base64 contents.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub attachments: BTreeMap<String, String>,
+}
+
+/// The item file for `name`, as TOML.
+pub fn to_toml(
+	name: &AccountName,
+	item: &Item,
+	attachments: &[(String, Vec<u8>)],
+) -> Result<String> {
+	let bundle = Bundle {
+		format:      FORMAT.into(),
+		name:        name.to_string(),
+		item:        item.clone(),
+		attachments: attachments
+			.iter()
+			.map(|(file, contents)| {
+				(file.clone(), base64::engine::general_purpose::STANDARD.encode(contents))
+			})
+			.collect(),
+	};
+	Ok(toml::to_string_pretty(&bundle)?)
+}
+
+/// The item file for `name`, encrypted with `method`.
+pub fn seal(
+	name: &AccountName,
+	item: &Item,
+	attachments: &[(String, Vec<u8>)],
+	method: &impl EncryptionMethod,
+) -> Result<Vec<u8>> {
+	method.encrypt(to_toml(name, item, attachments)?.as_bytes())
+}
@@ -0,0 +1,159 @@
+//! Entries written to a KeePass KDBX 4 database, for KeePassXC and the other
+//! KeePass ports.
+//!
+//! Folders become groups. Online accounts fill the standard fields, with
+//! their TOTP secret as KeePassXC's `otp` URI. Other kinds keep each form
+//! field under its own name and put their main secret in Password, so it
+//! can be copied the usual way; a [`KIND_FIELD`] records the kind for
+//! [`crate::import::kdbx`]. Custom fields follow, secret ones protected.
+//! Attachments are not written.
+
+use std::{io::Write, path::Path};
+
+use keepass::{Database, DatabaseKey, config::DatabaseConfig, db::{Entry, Group, Node, Value}};
+
+use crate::{Error, Item, Result, forms::{editable_fields, primary_secret}, import::kdbx::{KIND_FIELD, OTP_FIELD, notes_field}, models::AccountName, templates::FieldKind};
+
+/// Write `entries` to a new database at `path`, replacing any file there.
+pub fn write_database(
+	path: &Path,
+	entries: &[(AccountName, Item)],
+	key: DatabaseKey,
+) -> Result<()> {
+	let mut file = std::fs::File::create(path)?;
+	write(&mut file, entries, key)?;
+	Ok(file.sync_all()?)
+}
+
+/// Write `entries` as a database to `out`.
+pub fn write(
+	out: &mut impl Write,
+	entries: &[(AccountName, Item)],
+	key: DatabaseKey,
+) -> Result<()> {
+	let mut db = Database::new(DatabaseConfig::default());
+	for (name, item) in entries {
+		let group = match name.folder() {
+			Some(folder) => group_for(&mut db.root, folder),
+			None => &mut db.root,
+		};
+		group.children.push(Node::Entry(to_entry(name, item)?));
+	}
+	db.save(out, key).map_err(|e| Error::Export(e.to_string()))
+}
+
+/// The group for `folder` under `root`, creating it as needed.
+fn group_for<'a>(mut group: &'a mut Group, folder: &str) -> &'a mut Group {
+	for segment in folder.split('/') {
+		let index = match group
+			.children
+			.iter()
+			.position(|node| matches!(node, Node::Group(g) if g.name == segment))
+		{
+			Some(index) => index,
+			None => {
+				group.children.push(Node::Group(Group::new(segment)));
+				group.children.len() - 1
+			}
+		};
+		let Node::Group(child) = &mut group.children[index] else { unreachable!() };
+		group = child;
+	}
+	group
+}
+
+fn to_entry(name: &AccountName, item: &Item) -> Result<Entry> {
+	let mut entry = Entry::new();
+	let mut set = |key: &str, value: String, secret: bool| {
+		if value.is_empty() || entry.fields.contains_key(key) {
+			return;
+		}
+		let value = if secret { Value::Protected(value.into()) } else { Value::Unprotected(value) };
+		entry.fields.insert(key.to_string(), value);
+	};
+	set("Title", name.leaf().to_string(), false);
+
+	let fields = editable_fields(item);
+	let value_of = |name: &str| {
+		fields.iter().find(|(f, _)| f.name == name).map(|(_, v)| v.clone()).unwrap_or_default()
+	};
+	match item {
+		Item::OnlineAccount(account) => {
+			set("UserName", value_of("username"), false);
+			set("Password", value_of("password"), true);
+			set("URL", value_of("host_website"), false);
+			set("Notes", value_of("notes"), false);
+			if let Some(totp) = &account.totp {
+				set(OTP_FIELD, totp.to_uri(name.leaf())?, true);
+			}
+			for (field, value) in &fields {
+				if !["username", "password", "host_website", "notes"].contains(&field.name.as_str()) {
+					set(&field.name, value.clone(), field.kind == FieldKind::Secret);
+				}
+			}
+		}
+		_ => {
+			set(KIND_FIELD, item.kind_str().to_string(), false);
+			set("Notes", value_of(notes_field(item.kind_str())), false);
+			set("Password", primary_secret(item).map(|(_, v)| v).unwrap_or_default(), true);
+			for (field, value) in &fields {
+				if field.name != notes_field(item.kind_str()) {
+					set(&field.name, value.clone(), field.kind == FieldKind::Secret);
+				}
+			}
+		}
+	}
+	for field in item.custom_fields() {
+		set(&field.label, field.value.clone(), field.secret);
+	}
+	entry.tags = item.tags().to_vec();
+	Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{collections::BTreeMap, io::Cursor};
+
+	use super::*;
+	use crate::{forms::build_item, import::kdbx::{database_key, read}};
+
+	#[test]
+	fn entries_survive_a_round_trip() {
+		let login = build_item(
+			"online_account",
+			&BTreeMap::from([
+				("username".to_string(), "alice".to_string()),
+				("password".to_string(), "hunter2".to_string()),
+				("email".to_string(), "alice@example.com".to_string()),
+			]),
+		)
+		.unwrap();
+		let mut wifi = build_item(
+			"wifi_network",
+			&BTreeMap::from([
+				("ssid".to_string(), "home-5g".to_string()),
+				("passphrase".to_string(), "s3cret".to_string()),
+			]),
+		)
+		.unwrap();
+		wifi.tags_mut().push("home".into());
+		let entries = vec![
+			(AccountName::new("web/GitHub").unwrap(), login),
+			(AccountName::new("Wi-Fi").unwrap(), wifi.clone()),
+		];
+
+		let mut buffer = vec![];
+		write(&mut buffer, &entries, database_key(Some("pw"), None).unwrap()).unwrap();
+		let read_back =
+			read(&mut Cursor::new(buffer), database_key(Some("pw"), None).unwrap()).unwrap();
+
+		let names: Vec<_> = read_back.iter().map(|e| e.name.as_str()).collect();
+		assert!(names.contains(&"web/GitHub") && names.contains(&"Wi-Fi"));
+		let github = read_back.iter().find(|e| e.name.as_str() == "web/GitHub").unwrap();
+		let Item::OnlineAccount(account) = &github.item else { panic!("not a login") };
+		assert_eq!(account.password.as_deref(), Some("hunter2"));
+		assert_eq!(account.email.as_ref().map(|e| e.to_string()).as_deref(), Some("alice@example.com"));
+		let home = read_back.iter().find(|e| e.name.as_str() == "Wi-Fi").unwrap();
+		assert_eq!(serde_json::to_value(&home.item).unwrap(), serde_json::to_value(&wifi).unwrap());
+	}
+}
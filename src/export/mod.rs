@@ -0,0 +1,142 @@
+//! Exporters that write a branch's entries out for other password managers.
+//!
+//! Each exporter takes the entries from [`entries`], sorted by name and
+//! narrowed down with [`select`] when only some should go, and writes them
+//! in its format; nothing in the store changes.
+
+pub mod cxf;
+pub mod item;
+pub mod kdbx;
+pub mod plain;
+
+use std::str::FromStr;
+
+use crate::{Error, Result, access_control::{BranchKind, BranchPath}, encryption::{EncryptionMethod, Unlocked}, models::{AccountName, Item}, store::StoreBackend, versioning::PijulStore};
+
+/// Every entry of `branch`, sorted by name.
+pub fn entries<M: EncryptionMethod, K: BranchKind>(
+	store: &PijulStore<Unlocked<M>>,
+	branch: &BranchPath<K>,
+) -> Result<Vec<(AccountName, Item)>> {
+	let mut entries: Vec<_> = store.load(branch)?.items.into_iter().collect();
+	entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+	Ok(entries)
+}
+
+/// One condition on the entries to export, written `tag:work`,
+/// `folder:clients/acme`, `kind:passkey` or `name:web/GitHub`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+	/// Entries carrying the tag (case-insensitive).
+	Tag(String),
+	/// Entries inside the folder or its subfolders.
+	Folder(String),
+	/// Entries of the kind, as in [`Item::kind_str`].
+	Kind(String),
+	/// The entry of that name.
+	Name(AccountName),
+}
+
+impl FromStr for Filter {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let invalid = || {
+			Error::Export(format!("bad filter '{s}'; use tag:TAG, folder:FOLDER, kind:KIND or name:NAME"))
+		};
+		let (key, value) = s.split_once(':').ok_or_else(invalid)?;
+		let value = value.trim();
+		if value.is_empty() {
+			return Err(invalid());
+		}
+		match key.trim() {
+			"tag" => Ok(Self::Tag(value.into())),
+			"folder" => Ok(Self::Folder(value.trim_matches('/').into())),
+			"kind" => Ok(Self::Kind(value.into())),
+			"name" => Ok(Self::Name(AccountName::new(value)?)),
+			_ => Err(invalid()),
+		}
+	}
+}
+
+impl Filter {
+	fn admits(&self, name: &AccountName, item: &Item) -> bool {
+		match self {
+			Self::Tag(tag) => item.has_tag(tag),
+			Self::Folder(folder) => {
+				name.as_str().strip_prefix(folder.as_str()).is_some_and(|rest| rest.starts_with('/'))
+			}
+			Self::Kind(kind) => item.kind_str() == kind,
+			Self::Name(wanted) => name == wanted,
+		}
+	}
+
+	fn same_kind(&self, other: &Self) -> bool {
+		std::mem::discriminant(self) == std::mem::discriminant(other)
+	}
+}
+
+/// The entries `filters` admit. Filters of the same sort widen the
+/// selection and filters of different sorts narrow it, so `tag:work
+/// tag:acme kind:online_account` takes the online accounts tagged either.
+/// Without filters every entry is kept; a selection matching nothing is an
+/// error, so a typo doesn't make an empty export.
+pub fn select(
+	entries: Vec<(AccountName, Item)>,
+	filters: &[Filter],
+) -> Result<Vec<(AccountName, Item)>> {
+	if filters.is_empty() {
+		return Ok(entries);
+	}
+	let selected: Vec<_> = entries
+		.into_iter()
+		.filter(|(name, item)| {
+			filters
+				.iter()
+				.all(|filter| filters.iter().filter(|f| f.same_kind(filter)).any(|f| f.admits(name, item)))
+		})
+		.collect();
+	if selected.is_empty() {
+		return Err(Error::Export("no entries match the filters".into()));
+	}
+	Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use super::*;
+	use crate::forms::build_item;
+
+	fn entry(name: &str, kind: &str, values: &[(&str, &str)], tags: &[&str]) -> (AccountName, Item) {
+		let values: BTreeMap<_, _> =
+			values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+		let mut item = build_item(kind, &values).unwrap();
+		item.tags_mut().extend(tags.iter().map(|t| t.to_string()));
+		(AccountName::new(name).unwrap(), item)
+	}
+
+	#[test]
+	fn filters_of_a_sort_widen_and_of_different_sorts_narrow() {
+		let entries = vec![
+			entry("acme/jira", "online_account", &[("password", "a")], &["work"]),
+			entry("acme/wifi", "wifi_network", &[("ssid", "acme"), ("passphrase", "b")], &["Work"]),
+			entry("home/bank", "online_account", &[("password", "c")], &["personal"]),
+			entry("gym", "online_account", &[("password", "d")], &["contractor"]),
+		];
+		let filters =
+			|filters: &[&str]| -> Vec<Filter> { filters.iter().map(|f| f.parse().unwrap()).collect() };
+		let names = |filters: &[Filter]| -> Vec<String> {
+			select(entries.clone(), filters).unwrap().into_iter().map(|(n, _)| n.to_string()).collect()
+		};
+
+		assert_eq!(names(&filters(&["tag:work"])), ["acme/jira", "acme/wifi"]);
+		assert_eq!(names(&filters(&["tag:work", "tag:contractor"])), ["acme/jira", "acme/wifi", "gym"]);
+		assert_eq!(names(&filters(&["tag:work", "kind:online_account"])), ["acme/jira"]);
+		assert_eq!(names(&filters(&["folder:acme/"])), ["acme/jira", "acme/wifi"]);
+		assert_eq!(names(&filters(&["name:home/bank", "name:gym"])), ["home/bank", "gym"]);
+		assert!(select(entries.clone(), &filters(&["kind:passkey"])).is_err());
+		assert!("work".parse::<Filter>().is_err() && "colour:red".parse::<Filter>().is_err());
+	}
+}
@@ -0,0 +1,151 @@
+//! Plaintext CSV and JSON exports, for moving to a manager without a better
+//! format or feeding other tools. Nothing here is encrypted.
+//!
+//! Each entry becomes one row: its `name`, `kind` and `tags`, its form
+//! fields under their own names, `totp` as an `otpauth://` URI, and custom
+//! fields as `custom:<label>`. Columns appear in that order, each the first
+//! time an entry has it. Secret columns can be left out altogether.
+
+use std::{collections::BTreeMap, io::Write, str::FromStr};
+
+use crate::{Error, FieldKind, Item, Result, forms::editable_fields, models::AccountName};
+
+/// The prefix of custom field columns.
+pub const CUSTOM_PREFIX: &str = "custom:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Csv,
+	/// An array of objects, one per entry, without empty values.
+	Json,
+}
+
+impl FromStr for Format {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		match s {
+			"csv" => Ok(Self::Csv),
+			"json" => Ok(Self::Json),
+			other => Err(Error::Export(format!("unknown format '{other}'; use csv or json"))),
+		}
+	}
+}
+
+/// Entries laid out as rows of named columns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+	pub columns: Vec<String>,
+	pub rows:    Vec<BTreeMap<String, String>>,
+}
+
+/// The row for one entry, with whether each column holds a secret.
+fn row(name: &AccountName, item: &Item) -> Result<Vec<(String, String, bool)>> {
+	let mut row = vec![
+		("name".to_string(), name.to_string(), false),
+		("kind".to_string(), item.kind_str().to_string(), false),
+		("tags".to_string(), item.tags().join(", "), false),
+	];
+	for (field, value) in editable_fields(item) {
+		row.push((field.name, value, field.kind == FieldKind::Secret));
+	}
+	if let Some(totp) = item.totp() {
+		row.push(("totp".into(), totp.to_uri(name.leaf())?, true));
+	}
+	for field in item.custom_fields() {
+		row.push((format!("{CUSTOM_PREFIX}{}", field.label), field.value.clone(), field.secret));
+	}
+	Ok(row)
+}
+
+/// Lay `entries` out as a table. With `fields`, only those columns are kept,
+/// in that order; without `secrets`, secret values are left out.
+pub fn table(
+	entries: &[(AccountName, Item)],
+	fields: Option<&[String]>,
+	secrets: bool,
+) -> Result<Table> {
+	let mut table = Table::default();
+	for (name, item) in entries {
+		let mut cells = BTreeMap::new();
+		for (column, value, secret) in row(name, item)? {
+			if value.is_empty() || (secret && !secrets) {
+				continue;
+			}
+			if fields.is_some_and(|fields| !fields.contains(&column)) {
+				continue;
+			}
+			if !table.columns.contains(&column) {
+				table.columns.push(column.clone());
+			}
+			cells.insert(column, value);
+		}
+		table.rows.push(cells);
+	}
+	if let Some(fields) = fields {
+		if let Some(missing) = fields.iter().find(|f| !table.columns.contains(f)) {
+			let hint = if secrets { "" } else { " that isn't secret" };
+			return Err(Error::Export(format!("no entry has a field '{missing}'{hint}")));
+		}
+		table.columns = fields.to_vec();
+	}
+	Ok(table)
+}
+
+/// Write `table` to `out` as `format`.
+pub fn write(table: &Table, format: Format, out: impl Write) -> Result<()> {
+	match format {
+		Format::Csv => {
+			let csv_err = |e: ::csv::Error| Error::Export(e.to_string());
+			let mut writer = ::csv::Writer::from_writer(out);
+			writer.write_record(&table.columns).map_err(csv_err)?;
+			for row in &table.rows {
+				let cells = table.columns.iter().map(|c| row.get(c).map_or("", String::as_str));
+				writer.write_record(cells).map_err(csv_err)?;
+			}
+			Ok(writer.flush()?)
+		}
+		Format::Json => {
+			let mut out = out;
+			serde_json::to_writer_pretty(&mut out, &table.rows)?;
+			Ok(writeln!(out)?)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::forms::build_item;
+
+	fn entries() -> Vec<(AccountName, Item)> {
+		let values = BTreeMap::from([
+			("username".to_string(), "alice".to_string()),
+			("password".to_string(), "hunter2".to_string()),
+		]);
+		let mut item = build_item("online_account", &values).unwrap();
+		item.tags_mut().extend(["dev".to_string(), "work".to_string()]);
+		vec![(AccountName::new("web/GitHub").unwrap(), item)]
+	}
+
+	#[test]
+	fn secrets_can_be_left_out() {
+		let table = table(&entries(), None, false).unwrap();
+		assert_eq!(table.columns, ["name", "kind", "tags", "username"]);
+		assert_eq!(table.rows[0]["tags"], "dev, work");
+
+		let mut csv = vec![];
+		write(&table, Format::Csv, &mut csv).unwrap();
+		let csv = String::from_utf8(csv).unwrap();
+		assert_eq!(csv, "name,kind,tags,username\nweb/GitHub,online_account,\"dev, work\",alice\n");
+	}
+
+	#[test]
+	fn fields_are_picked_in_order() {
+		let fields = ["password".to_string(), "name".to_string()];
+		let table = table(&entries(), Some(&fields), true).unwrap();
+		assert_eq!(table.columns, fields);
+		assert_eq!(table.rows[0].len(), 2);
+		assert!(super::table(&entries(), Some(&fields), false).is_err());
+	}
+}
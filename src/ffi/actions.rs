@@ -0,0 +1,22 @@
+use super::{error::FfiError, types::{FfiItem, FfiServerCredential}};
+use crate::models::{Item, ServerCredential};
+
+/// Expand a server's connection-string template with its secret filled in.
+///
+/// Returns `None` when the item has no template.
+#[uniffi::export]
+pub fn server_connection_string(server: FfiServerCredential) -> Result<Option<String>, FfiError> {
+	Ok(ServerCredential::try_from(server)?.connection_string())
+}
+
+/// The item's web addresses, main one first; see [`Item::urls`].
+#[uniffi::export]
+pub fn item_urls(item: FfiItem) -> Result<Vec<String>, FfiError> {
+	Ok(Item::try_from(item)?.urls())
+}
+
+/// Open an `http`/`https` URL in the default browser.
+#[uniffi::export]
+pub fn open_url(url: String) -> Result<(), FfiError> {
+	crate::browser::open(&url).map_err(FfiError::from)
+}
@@ -0,0 +1,7 @@
+/// Draw a short code in block letters for the full-screen field viewer, in
+/// solid blocks or, with `ascii`, `#`; see [`crate::bigtext`]. `None` means
+/// the value should be shown as plain large text instead.
+#[uniffi::export]
+pub fn big_text(text: String, ascii: bool) -> Option<String> {
+	crate::bigtext::render(&text, if ascii { '#' } else { '█' })
+}
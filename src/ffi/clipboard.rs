@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use super::{config::load_config, error::FfiError};
+use crate::clipboard::{ClipboardConfig, ClipboardMode, SecretClipboard};
+
+/// Clipboard access configured from the `[clipboard]` table of the config
+/// file, clearing copied secrets after the configured timeout.
+#[derive(uniffi::Object)]
+pub struct ClipboardHandle {
+	inner: SecretClipboard,
+}
+
+#[uniffi::export]
+impl ClipboardHandle {
+	/// The default settings.
+	#[uniffi::constructor]
+	pub fn new() -> Arc<Self> {
+		Arc::new(Self { inner: SecretClipboard::new(ClipboardConfig::default()) })
+	}
+
+	/// Read settings from the config file at `path`, or the default location
+	/// when `None`.
+	#[uniffi::constructor]
+	pub fn load(path: Option<String>) -> Result<Arc<Self>, FfiError> {
+		Ok(Arc::new(Self { inner: SecretClipboard::new(load_config(path)?.clipboard) }))
+	}
+
+	pub fn mode(&self) -> ClipboardMode { self.inner.config().mode }
+
+	/// Copy a secret; it is cleared after the configured timeout if the
+	/// clipboard still holds it.
+	pub fn copy(&self, text: String) -> Result<(), FfiError> { Ok(self.inner.copy_secret(&text)?) }
+
+	/// Copy non-secret text, which is never cleared.
+	pub fn copy_plain(&self, text: String) -> Result<(), FfiError> {
+		Ok(self.inner.copy_plain(&text)?)
+	}
+
+	/// Whole seconds until the pending clear, if one is scheduled.
+	pub fn remaining_seconds(&self) -> Option<u32> {
+		self.inner.remaining().map(|d| d.as_secs_f64().ceil() as u32)
+	}
+
+	/// Clear the pending secret now.
+	pub fn clear(&self) -> Result<(), FfiError> { Ok(self.inner.clear_now()?) }
+}
@@ -0,0 +1,49 @@
+use super::error::FfiError;
+use crate::{config::{Config, DisplayConfig, MaskPolicy, SecretsConfig}, generator::GeneratorOptions};
+
+/// The config file at `path`, or at the default location when `None`.
+pub(super) fn load_config(path: Option<String>) -> Result<Config, FfiError> {
+	match path.map(std::path::PathBuf::from).or_else(Config::default_path) {
+		Some(path) => Ok(Config::load(&path)?),
+		None => Ok(Config::default()),
+	}
+}
+
+/// The `[secrets]` settings from the config at `path`, or the default one.
+#[uniffi::export]
+pub fn load_secrets_config(path: Option<String>) -> Result<SecretsConfig, FfiError> {
+	Ok(load_config(path)?.secrets)
+}
+
+/// How the secret field labelled `label` is masked under `secrets`.
+#[uniffi::export]
+pub fn secret_mask_policy(secrets: SecretsConfig, label: String) -> MaskPolicy {
+	secrets.mask_policy(&label)
+}
+
+/// The `[display]` settings from the config at `path`, with `NO_COLOR`
+/// applied.
+#[uniffi::export]
+pub fn load_display_config(path: Option<String>) -> Result<DisplayConfig, FfiError> {
+	Ok(load_config(path)?.display.with_env())
+}
+
+/// A named `[generator.presets]` entry.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiGeneratorPreset {
+	pub name:    String,
+	pub options: GeneratorOptions,
+}
+
+/// The generator presets from the config at `path`, by name.
+#[uniffi::export]
+pub fn load_generator_presets(path: Option<String>) -> Result<Vec<FfiGeneratorPreset>, FfiError> {
+	Ok(
+		load_config(path)?
+			.generator
+			.presets
+			.into_iter()
+			.map(|(name, options)| FfiGeneratorPreset { name, options })
+			.collect(),
+	)
+}
@@ -0,0 +1,89 @@
+use super::{error::FfiError, types::{FfiItem, FfiPasswordPolicy, FfiTemplateValue}};
+use crate::{Item, generator::{GeneratorOptions, Strength}, templates::{ItemTemplate, TemplateField}};
+
+/// The entry form for every built-in item kind.
+#[uniffi::export]
+pub fn item_forms() -> Vec<ItemTemplate> { crate::forms::builtin_forms() }
+
+/// Build and validate a built-in item from filled-in form values.
+#[uniffi::export]
+pub fn item_from_form(kind: String, values: Vec<FfiTemplateValue>) -> Result<FfiItem, FfiError> {
+	let values = values.into_iter().map(|v| (v.field, v.value)).collect();
+	Ok(crate::forms::build_item(&kind, &values)?.into())
+}
+
+/// One form field of an item with its current value, for inline editing.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiEditableField {
+	pub field: TemplateField,
+	pub value: String,
+}
+
+/// The editable form fields of `item` and their current values.
+#[uniffi::export]
+pub fn item_editable_fields(item: FfiItem) -> Result<Vec<FfiEditableField>, FfiError> {
+	let item = Item::try_from(item)?;
+	Ok(
+		crate::forms::editable_fields(&item)
+			.into_iter()
+			.map(|(field, value)| FfiEditableField { field, value })
+			.collect(),
+	)
+}
+
+/// Return `item` with `field` set to `value`, validated; an empty value
+/// clears an optional field.
+#[uniffi::export]
+pub fn item_with_field(item: FfiItem, field: String, value: String) -> Result<FfiItem, FfiError> {
+	let item = Item::try_from(item)?;
+	Ok(crate::forms::set_field(&item, &field, &value)?.into())
+}
+
+/// The item's main secret as a field name and value, if it has one.
+#[uniffi::export]
+pub fn item_primary_secret(item: FfiItem) -> Result<Option<FfiTemplateValue>, FfiError> {
+	let item = Item::try_from(item)?;
+	Ok(crate::forms::primary_secret(&item).map(|(field, value)| FfiTemplateValue { field, value }))
+}
+
+/// Generate a random password with every character class represented.
+#[uniffi::export]
+pub fn generate_password(length: u32, symbols: bool) -> Result<String, FfiError> {
+	Ok(crate::generator::generate_password(length as usize, symbols)?)
+}
+
+/// Generate a password or passphrase as `options` describe, for the
+/// generator panel.
+#[uniffi::export]
+pub fn generate_secret(options: GeneratorOptions) -> Result<String, FfiError> {
+	Ok(options.generate()?)
+}
+
+/// A random answer for a security question.
+#[uniffi::export]
+pub fn generate_security_answer() -> String { crate::generator::security_answer() }
+
+/// `options` narrowed to what a site's password `policy` accepts.
+#[uniffi::export]
+pub fn generator_options_within(
+	options: GeneratorOptions,
+	policy: FfiPasswordPolicy,
+) -> Result<GeneratorOptions, FfiError> {
+	Ok(options.within(&policy.try_into()?))
+}
+
+/// The estimated strength of secrets generated with `options`.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiGeneratorStrength {
+	pub bits:     u32,
+	pub strength: Strength,
+	pub label:    String,
+}
+
+/// How strong secrets generated with `options` are.
+#[uniffi::export]
+pub fn generator_strength(options: GeneratorOptions) -> FfiGeneratorStrength {
+	let bits = options.entropy_bits();
+	let strength = Strength::from_bits(bits);
+	FfiGeneratorStrength { bits, strength, label: strength.label().to_string() }
+}
@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex};
+
+use super::{config::load_config, error::FfiError};
+use crate::keymap::{Action, KeyContext, KeyResolution, Keymap};
+
+/// What a key press did; see [`KeymapHandle::press`].
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiKeyOutcome {
+	Action {
+		action: Action,
+	},
+	/// The key started or continued a chord.
+	Pending,
+	Unbound,
+}
+
+/// One row of the help overlay: every key bound to one action.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiHelpRow {
+	pub keys:        Vec<String>,
+	pub description: String,
+}
+
+/// The help rows for one [`KeyContext`].
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiHelpSection {
+	pub context: KeyContext,
+	pub title:   String,
+	pub rows:    Vec<FfiHelpRow>,
+}
+
+/// The key bindings in effect, shared by key handling and the help overlay.
+/// Tracks the keys of a chord in progress.
+#[derive(uniffi::Object)]
+pub struct KeymapHandle {
+	keymap:  Keymap,
+	pending: Mutex<Vec<String>>,
+}
+
+#[uniffi::export]
+impl KeymapHandle {
+	/// The built-in bindings.
+	#[uniffi::constructor]
+	pub fn new() -> Arc<Self> {
+		Arc::new(Self { keymap: Keymap::default(), pending: Mutex::default() })
+	}
+
+	/// The bindings from the config file at `path`, or the default config
+	/// location when `None`.
+	#[uniffi::constructor]
+	pub fn load(path: Option<String>) -> Result<Arc<Self>, FfiError> {
+		Ok(Arc::new(Self { keymap: load_config(path)?.keymap()?, pending: Mutex::default() }))
+	}
+
+	/// Feed one key press with `contexts` active, most specific first.
+	///
+	/// Keys accumulate while they form the start of a chord; any other
+	/// outcome clears them.
+	pub fn press(&self, contexts: Vec<KeyContext>, key: String) -> FfiKeyOutcome {
+		let mut pending = self.pending.lock().unwrap();
+		pending.push(key);
+		let mut resolution = self.keymap.resolve(&contexts, &pending);
+		if resolution == KeyResolution::Unbound && pending.len() > 1 {
+			// A broken chord: try the last key on its own.
+			let last = pending.split_off(pending.len() - 1);
+			*pending = last;
+			resolution = self.keymap.resolve(&contexts, &pending);
+		}
+		match resolution {
+			KeyResolution::Action(action) => {
+				pending.clear();
+				FfiKeyOutcome::Action { action }
+			}
+			KeyResolution::Pending => FfiKeyOutcome::Pending,
+			KeyResolution::Unbound => {
+				pending.clear();
+				FfiKeyOutcome::Unbound
+			}
+		}
+	}
+
+	/// The keys of the chord in progress, for display.
+	pub fn pending(&self) -> Vec<String> { self.pending.lock().unwrap().clone() }
+
+	/// The keys bound to `action` in `context`, for prompts like "press r
+	/// to reveal".
+	pub fn keys_for(&self, context: KeyContext, action: Action) -> Vec<String> {
+		self.keymap.keys_for(context, action)
+	}
+
+	/// Up to `limit` key hints for the status bar, with short labels.
+	pub fn hints(&self, contexts: Vec<KeyContext>, limit: u32) -> Vec<FfiHelpRow> {
+		self
+			.keymap
+			.hints(&contexts, limit as usize)
+			.into_iter()
+			.map(|(keys, action)| FfiHelpRow { keys, description: action.short_label().to_owned() })
+			.collect()
+	}
+
+	pub fn help(&self) -> Vec<FfiHelpSection> {
+		self
+			.keymap
+			.help()
+			.into_iter()
+			.map(|(context, rows)| FfiHelpSection {
+				context,
+				title: context.label().to_owned(),
+				rows: rows
+					.into_iter()
+					.map(|(keys, action)| FfiHelpRow { keys, description: action.description().to_owned() })
+					.collect(),
+			})
+			.collect()
+	}
+}
@@ -0,0 +1,5 @@
+use crate::markdown::MarkdownBlock;
+
+/// Split a note into styled blocks for display; see [`crate::markdown`].
+#[uniffi::export]
+pub fn render_markdown(text: String) -> Vec<MarkdownBlock> { crate::markdown::render(&text) }
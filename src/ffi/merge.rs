@@ -0,0 +1,21 @@
+use super::{error::FfiError, types::FfiItem};
+use crate::{Item, merge::FieldDifference};
+
+/// The form fields on which two items of the same kind disagree.
+#[uniffi::export]
+pub fn item_differences(left: FfiItem, right: FfiItem) -> Result<Vec<FieldDifference>, FfiError> {
+	let (left, right) = (Item::try_from(left)?, Item::try_from(right)?);
+	crate::merge::differences(&left, &right).map_err(FfiError::from)
+}
+
+/// `left` with the fields named in `take_right` taken from `right`; see
+/// [`crate::merge`].
+#[uniffi::export]
+pub fn merged_item(
+	left: FfiItem,
+	right: FfiItem,
+	take_right: Vec<String>,
+) -> Result<FfiItem, FfiError> {
+	let (left, right) = (Item::try_from(left)?, Item::try_from(right)?);
+	Ok(crate::merge::merge(&left, &right, &take_right)?.into())
+}
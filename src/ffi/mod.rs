@@ -1,9 +1,31 @@
+mod actions;
+mod bigtext;
+mod clipboard;
+mod config;
 pub mod error;
+mod forms;
+mod keymap;
+mod markdown;
+mod merge;
+mod otp;
 mod p2p;
+mod qr;
+mod stats;
 mod store;
 pub mod types;
 
+pub use actions::{item_urls, open_url, server_connection_string};
+pub use bigtext::big_text;
+pub use clipboard::ClipboardHandle;
+pub use config::{FfiGeneratorPreset, load_display_config, load_generator_presets, load_secrets_config, secret_mask_policy};
 pub use error::FfiError;
-pub use p2p::P2PHandle;
-pub use store::PwdStore;
-pub use types::{FfiChangeEntry, FfiItem, FfiOnlineAccount, FfiSecurityQuestion, FfiSocialSecurity};
+pub use forms::{FfiEditableField, FfiGeneratorStrength, generate_password, generate_secret, generate_security_answer, generator_options_within, generator_strength, item_editable_fields, item_forms, item_from_form, item_primary_secret, item_with_field};
+pub use keymap::{FfiHelpRow, FfiHelpSection, FfiKeyOutcome, KeymapHandle};
+pub use markdown::render_markdown;
+pub use merge::{item_differences, merged_item};
+pub use otp::{FfiTotpCode, import_totp, parse_recovery_codes, totp_code, yubikey_credentials, yubikey_totp};
+pub use p2p::{FfiShare, P2PHandle};
+pub use qr::{qr_code, totp_provisioning_qr, wifi_join_qr};
+pub use stats::FfiVaultStats;
+pub use store::{PwdStore, store_exists};
+pub use types::{FfiApiCredential, FfiChangeEntry, FfiCryptoWallet, FfiDriversLicense, FfiEmailAccount, FfiFieldMatch, FfiIdentityDocument, FfiInsurancePolicy, FfiItem, FfiMailServer, FfiMembership, FfiOnlineAccount, FfiPasskey, FfiPasswordPolicy, FfiPgpKey, FfiRecoveryCode, FfiSearchFilter, FfiSearchHit, FfiSecureNote, FfiSecurityQuestion, FfiServerCredential, FfiSocialSecurity, FfiSoftwareLicense, FfiTemplateItem, FfiTemplateValue, FfiTotp, FfiWifiNetwork};
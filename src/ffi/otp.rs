@@ -0,0 +1,57 @@
+use super::{error::FfiError, types::{FfiRecoveryCode, FfiTotp}};
+use crate::{models::{RecoveryCode, Totp}, otp::TotpCode};
+
+/// Mirror of [`TotpCode`].
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct FfiTotpCode {
+	pub code:         String,
+	/// Seconds until the next code.
+	pub remaining:    u32,
+	pub period:       u32,
+	/// Too little time is left to copy the code; wait for the next one.
+	pub expires_soon: bool,
+	/// When the code is valid, in local time: `14:02:30–14:03:00`.
+	pub window:       String,
+}
+
+impl From<TotpCode> for FfiTotpCode {
+	fn from(c: TotpCode) -> Self {
+		Self {
+			expires_soon: c.expires_soon(),
+			window:       c.window(),
+			code:         c.code,
+			remaining:    c.remaining,
+			period:       c.period,
+		}
+	}
+}
+
+/// Read a TOTP from an `otpauth://` URI, the path of an image of its QR
+/// code, or a base32 secret; see [`Totp::import`].
+#[uniffi::export]
+pub fn import_totp(input: String) -> Result<FfiTotp, FfiError> { Ok(Totp::import(&input)?.into()) }
+
+/// The code `totp` gives right now.
+#[uniffi::export]
+pub fn totp_code(totp: FfiTotp) -> Result<FfiTotpCode, FfiError> {
+	Ok(Totp::try_from(totp)?.code()?.into())
+}
+
+/// Read pasted recovery codes, one per line or separated by commas or
+/// semicolons; see [`RecoveryCode::parse_list`].
+#[uniffi::export]
+pub fn parse_recovery_codes(text: String) -> Vec<FfiRecoveryCode> {
+	RecoveryCode::parse_list(&text).into_iter().map(Into::into).collect()
+}
+
+/// A TOTP whose codes come from the YubiKey OATH credential `credential`;
+/// see [`Totp::on_yubikey`].
+#[uniffi::export]
+pub fn yubikey_totp(credential: String) -> FfiTotp { Totp::on_yubikey(credential).into() }
+
+/// The names of the OATH credentials on the connected YubiKey, to pick one
+/// for an account.
+#[uniffi::export]
+pub fn yubikey_credentials() -> Result<Vec<String>, FfiError> {
+	Ok(crate::otp::yubikey::credentials()?)
+}
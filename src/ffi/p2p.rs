@@ -1,7 +1,17 @@
 use std::sync::Arc;
 
+use jiff::Timestamp;
+
 use super::{error::FfiError, store::PwdStore};
-use crate::{p2p::{IrohSyncHandle, decode_store, encode_store}, store::{ShareTicket, StoreBackend}};
+use crate::{p2p::{IrohSyncHandle, ShareCode, decode_store, encode_store}, store::{ShareTicket, StoreBackend}};
+
+/// A published store: the ticket to fetch it with and the code it is
+/// encrypted with, to be sent to the receiver separately.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiShare {
+	pub ticket: String,
+	pub code:   String,
+}
 
 /// Synchronous handle to the Iroh P2P stack.
 ///
@@ -24,21 +34,33 @@ impl P2PHandle {
 		Arc::new(Self { inner: IrohSyncHandle::new(), rt })
 	}
 
-	/// Serialise and publish the store; returns an Iroh ticket string.
-	pub fn share_store(&self, store: Arc<PwdStore>) -> Result<String, FfiError> {
+	/// Serialise, encrypt and publish the store; returns the Iroh ticket
+	/// and the share code.
+	pub fn share_store(&self, store: Arc<PwdStore>) -> Result<FfiShare, FfiError> {
 		let payload = {
 			let inner = store.inner.lock().unwrap();
 			let loaded = inner.load(&store.branch).map_err(FfiError::from)?;
 			encode_store(&loaded).map_err(FfiError::from)?
 		};
-		let ticket = self.rt.block_on(self.inner.share(payload)).map_err(FfiError::from)?;
-		Ok(ticket.to_string())
+		let code = ShareCode::generate();
+		let ticket = self.rt.block_on(self.inner.share(payload, &code)).map_err(FfiError::from)?;
+		let inner = store.inner.lock().unwrap();
+		inner.mark_synced(&store.branch, Timestamp::now()).map_err(FfiError::from)?;
+		Ok(FfiShare { ticket: ticket.to_string(), code: code.to_string() })
 	}
 
-	/// Download the store from `ticket` and merge it into `target_store`.
-	pub fn receive_into(&self, ticket: String, target_store: Arc<PwdStore>) -> Result<u64, FfiError> {
+	/// Download the store from `ticket`, decrypt it with `code` and merge it
+	/// into `target_store`.
+	pub fn receive_into(
+		&self,
+		ticket: String,
+		code: String,
+		target_store: Arc<PwdStore>,
+	) -> Result<u64, FfiError> {
 		let share_ticket = ShareTicket(ticket);
-		let payload = self.rt.block_on(self.inner.receive(&share_ticket)).map_err(FfiError::from)?;
+		let code: ShareCode = code.parse().map_err(FfiError::from)?;
+		let payload =
+			self.rt.block_on(self.inner.receive(&share_ticket, &code)).map_err(FfiError::from)?;
 		let received = decode_store(payload).map_err(FfiError::from)?;
 		let count = received.items.len() as u64;
 		{
@@ -48,6 +70,8 @@ impl P2PHandle {
 				current.items.insert(name, item);
 			}
 			inner.save(&target_store.branch, &current).map_err(FfiError::from)?;
+			inner.mark_synced(&target_store.branch, Timestamp::now()).map_err(FfiError::from)?;
+			target_store.reindex(&inner)?;
 		}
 		Ok(count)
 	}
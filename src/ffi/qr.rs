@@ -0,0 +1,32 @@
+use super::{error::FfiError, types::{FfiTotp, FfiWifiNetwork}};
+use crate::models::{Totp, WifiNetwork};
+
+fn render(data: &str, ascii: bool) -> Result<String, FfiError> {
+	let rendered =
+		if ascii { crate::qr::render_ascii(data) } else { crate::qr::render_half_blocks(data) };
+	rendered.map_err(FfiError::from)
+}
+
+/// Render a WiFi network's `WIFI:` join string as a QR code, in half blocks
+/// or, with `ascii`, plain characters.
+#[uniffi::export]
+pub fn wifi_join_qr(network: FfiWifiNetwork, ascii: bool) -> Result<String, FfiError> {
+	let network = WifiNetwork::try_from(network)?;
+	render(&network.join_string(), ascii)
+}
+
+/// Render the `otpauth://` URI enrolling `totp` under `account` as a QR
+/// code, to set up an authenticator app from the vault.
+#[uniffi::export]
+pub fn totp_provisioning_qr(
+	totp: FfiTotp,
+	account: String,
+	ascii: bool,
+) -> Result<String, FfiError> {
+	render(&Totp::try_from(totp)?.to_uri(&account)?, ascii)
+}
+
+/// Render any text (a share ticket, a URI, …) as a QR code, in half blocks
+/// or, with `ascii`, plain characters.
+#[uniffi::export]
+pub fn qr_code(data: String, ascii: bool) -> Result<String, FfiError> { render(&data, ascii) }
@@ -0,0 +1,33 @@
+use crate::stats::{KindCount, VaultStats};
+
+/// Mirror of [`VaultStats`] with the last sync as a string.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiVaultStats {
+	pub total:              u32,
+	pub by_kind:            Vec<KindCount>,
+	pub with_password:      u32,
+	pub weak:               u32,
+	pub reused:             u32,
+	pub old:                u32,
+	pub accounts:           u32,
+	pub missing_two_factor: u32,
+	pub last_sync:          Option<String>,
+	pub weekly_changes:     Vec<u32>,
+}
+
+impl From<VaultStats> for FfiVaultStats {
+	fn from(s: VaultStats) -> Self {
+		Self {
+			total:              s.total,
+			by_kind:            s.by_kind,
+			with_password:      s.with_password,
+			weak:               s.weak,
+			reused:             s.reused,
+			old:                s.old,
+			accounts:           s.accounts,
+			missing_two_factor: s.missing_two_factor,
+			last_sync:          s.last_sync.map(|t| t.to_string()),
+			weekly_changes:     s.weekly_changes,
+		}
+	}
+}
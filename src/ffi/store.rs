@@ -1,15 +1,18 @@
 use std::{path::PathBuf, sync::Arc};
 
+use jiff::Timestamp;
 use pijul_at_core::Base32;
 
-use super::{error::FfiError, types::{FfiChangeEntry, FfiItem}};
-use crate::{AgeScrypt, BranchPath, BranchSegment, PersonalBranch, Unlocked, models::AccountName, store::{DiffResult, StoreBackend, StoreChange, VersionedEntry}, versioning::PijulStore};
+use super::{error::FfiError, stats::FfiVaultStats, types::{FfiChangeEntry, FfiItem, FfiSearchFilter, FfiSearchHit}};
+use crate::{AgeScrypt, BranchPath, BranchSegment, PersonalBranch, Unlocked, models::{AccountName, Item}, search::{SearchIndex, SortMode}, stats::VaultStats, store::{DiffResult, StoreBackend, StoreChange, VersionedEntry}, templates::{ItemTemplate, TemplateRegistry}, versioning::PijulStore};
 
 /// Thread-safe handle to a Pijul-backed credential store on a single branch.
 #[derive(uniffi::Object)]
 pub struct PwdStore {
-	pub(super) inner:  std::sync::Mutex<PijulStore<Unlocked<AgeScrypt>>>,
-	pub(super) branch: BranchPath<PersonalBranch>,
+	pub(super) inner:     std::sync::Mutex<PijulStore<Unlocked<AgeScrypt>>>,
+	pub(super) branch:    BranchPath<PersonalBranch>,
+	pub(super) templates: TemplateRegistry,
+	pub(super) index:     std::sync::Mutex<SearchIndex>,
 }
 
 #[uniffi::export]
@@ -27,7 +30,15 @@ impl PwdStore {
 			.map_err(FfiError::from)?
 			.unlock_with(AgeScrypt::new(passphrase).map_err(FfiError::from)?);
 		store.init(&branch).map_err(FfiError::from)?;
-		Ok(Arc::new(Self { inner: std::sync::Mutex::new(store), branch }))
+		let templates = TemplateRegistry::for_store(&store.store_dir).map_err(FfiError::from)?;
+		let index = SearchIndex::build(&store.load(&branch).map_err(FfiError::from)?)
+			.with_modified(&store.last_modified(&branch).map_err(FfiError::from)?);
+		Ok(Arc::new(Self {
+			inner: std::sync::Mutex::new(store),
+			branch,
+			templates,
+			index: std::sync::Mutex::new(index),
+		}))
 	}
 
 	pub fn branch(&self) -> String { self.branch.to_string() }
@@ -36,6 +47,9 @@ impl PwdStore {
 		self.inner.lock().unwrap().store_dir.to_string_lossy().into_owned()
 	}
 
+	/// User-defined item types available to this store.
+	pub fn list_templates(&self) -> Vec<ItemTemplate> { self.templates.iter().cloned().collect() }
+
 	// ── read ──────────────────────────────────────────────────────────────────
 
 	pub fn list_entries(&self) -> Result<Vec<String>, FfiError> {
@@ -56,29 +70,257 @@ impl PwdStore {
 		Ok(inner.get(&self.branch, &name).map_err(FfiError::from)?.map(FfiItem::from))
 	}
 
+	/// Entries whose name contains `query`; with `deep`, also entries whose
+	/// non-secret contents (usernames, emails, URLs, notes, …) contain it.
+	/// `filter` restricts results by tag, category and folder. Without a
+	/// query, results are ordered by `sort`; `viewed` holds entry names most
+	/// recently opened first.
+	pub fn search(
+		&self,
+		query: String,
+		deep: bool,
+		filter: FfiSearchFilter,
+		sort: SortMode,
+		viewed: Vec<String>,
+	) -> Vec<FfiSearchHit> {
+		let index = self.index.lock().unwrap();
+		let mut hits = index.search(&query, deep, &filter.into());
+		if query.trim().is_empty() {
+			let viewed: Vec<AccountName> =
+				viewed.into_iter().filter_map(|n| AccountName::new(n).ok()).collect();
+			index.sort(&mut hits, sort, &viewed);
+		}
+		hits.into_iter().map(FfiSearchHit::from).collect()
+	}
+
+	/// Every tag used on this branch.
+	pub fn list_tags(&self) -> Vec<String> { self.index.lock().unwrap().tags() }
+
+	/// Every folder on this branch, each followed by its subfolders.
+	pub fn list_folders(&self) -> Vec<String> { self.index.lock().unwrap().folders() }
+
 	// ── write ─────────────────────────────────────────────────────────────────
 
 	pub fn add_entry(&self, name: String, item: FfiItem, message: String) -> Result<(), FfiError> {
 		let name = AccountName::new(&name).map_err(FfiError::from)?;
-		let item = crate::models::Item::try_from(item)?;
+		let item = self.checked_item(item)?;
 		let msg = if message.is_empty() { format!("add {name}") } else { message };
 		let inner = self.inner.lock().unwrap();
-		inner.insert(&self.branch, name, item, StoreChange::Custom(msg)).map_err(FfiError::from)
+		inner.insert(&self.branch, name, item, StoreChange::Custom(msg)).map_err(FfiError::from)?;
+		self.reindex(&inner)
 	}
 
 	pub fn update_entry(&self, name: String, item: FfiItem, message: String) -> Result<(), FfiError> {
 		let name = AccountName::new(&name).map_err(FfiError::from)?;
-		let item = crate::models::Item::try_from(item)?;
+		let item = self.checked_item(item)?;
 		let msg = if message.is_empty() { format!("update {name}") } else { message };
 		let inner = self.inner.lock().unwrap();
-		inner.update(&self.branch, &name, item, StoreChange::Custom(msg)).map_err(FfiError::from)
+		inner.update(&self.branch, &name, item, StoreChange::Custom(msg)).map_err(FfiError::from)?;
+		self.reindex(&inner)
+	}
+
+	/// Star or unstar an entry; favorites are pinned to the top of the list.
+	pub fn set_favorite(&self, name: String, favorite: bool) -> Result<(), FfiError> {
+		let name = AccountName::new(&name).map_err(FfiError::from)?;
+		let inner = self.inner.lock().unwrap();
+		let mut item = inner
+			.get(&self.branch, &name)
+			.map_err(FfiError::from)?
+			.ok_or_else(|| FfiError::EntryNotFound { name: name.to_string() })?;
+		item.set_favorite(favorite);
+		let msg = format!("{} {name}", if favorite { "star" } else { "unstar" });
+		inner.update(&self.branch, &name, item, StoreChange::Custom(msg)).map_err(FfiError::from)?;
+		self.reindex(&inner)
+	}
+
+	/// The entry's next HOTP code, saving the advanced counter so the code
+	/// is not handed out again.
+	pub fn next_hotp_code(&self, name: String) -> Result<String, FfiError> {
+		let name = AccountName::new(&name).map_err(FfiError::from)?;
+		let inner = self.inner.lock().unwrap();
+		let mut item = inner
+			.get(&self.branch, &name)
+			.map_err(FfiError::from)?
+			.ok_or_else(|| FfiError::EntryNotFound { name: name.to_string() })?;
+		let totp = item
+			.totp_mut()
+			.ok_or_else(|| FfiError::Other { msg: format!("{name} has no one-time password") })?;
+		let code = totp.advance()?;
+		let msg = format!("advance HOTP counter for {name}");
+		inner.update(&self.branch, &name, item, StoreChange::Custom(msg)).map_err(FfiError::from)?;
+		self.reindex(&inner)?;
+		Ok(code)
 	}
 
 	pub fn remove_entry(&self, name: String, message: String) -> Result<bool, FfiError> {
 		let name = AccountName::new(&name).map_err(FfiError::from)?;
 		let msg = if message.is_empty() { format!("remove {name}") } else { message };
 		let inner = self.inner.lock().unwrap();
-		inner.remove(&self.branch, &name, StoreChange::Custom(msg)).map_err(FfiError::from)
+		let removed =
+			inner.remove(&self.branch, &name, StoreChange::Custom(msg)).map_err(FfiError::from)?;
+		self.reindex(&inner)?;
+		Ok(removed)
+	}
+
+	/// Move an entry to the trash instead of deleting it outright.
+	pub fn trash_entry(&self, name: String, message: String) -> Result<bool, FfiError> {
+		let name = AccountName::new(&name).map_err(FfiError::from)?;
+		let msg = if message.is_empty() { format!("trash {name}") } else { message };
+		let inner = self.inner.lock().unwrap();
+		let trashed =
+			inner.trash(&self.branch, &name, StoreChange::Custom(msg)).map_err(FfiError::from)?;
+		self.reindex(&inner)?;
+		Ok(trashed)
+	}
+
+	/// Rename an entry, moving its attachments and updating links to it.
+	pub fn rename_entry(&self, from: String, to: String) -> Result<(), FfiError> {
+		let from = AccountName::new(&from).map_err(FfiError::from)?;
+		let to = AccountName::new(&to).map_err(FfiError::from)?;
+		let inner = self.inner.lock().unwrap();
+		inner
+			.rename(&self.branch, &from, &to, StoreChange::Custom(format!("rename {from} to {to}")))
+			.map_err(FfiError::from)?;
+		self.reindex(&inner)
+	}
+
+	/// Copy an entry under a new name, as a starting point for a similar one.
+	pub fn duplicate_entry(&self, from: String, to: String) -> Result<(), FfiError> {
+		let from = AccountName::new(&from).map_err(FfiError::from)?;
+		let to = AccountName::new(&to).map_err(FfiError::from)?;
+		let inner = self.inner.lock().unwrap();
+		inner.duplicate(&self.branch, &from, to).map_err(FfiError::from)?;
+		self.reindex(&inner)
+	}
+
+	/// Save the merged `item` as `keep` and trash `discard`; see
+	/// [`crate::merge`].
+	pub fn merge_entries(
+		&self,
+		keep: String,
+		discard: String,
+		item: FfiItem,
+	) -> Result<(), FfiError> {
+		let keep = AccountName::new(&keep).map_err(FfiError::from)?;
+		let discard = AccountName::new(&discard).map_err(FfiError::from)?;
+		let item = self.checked_item(item)?;
+		let inner = self.inner.lock().unwrap();
+		inner.merge_into(&self.branch, &keep, &discard, item).map_err(FfiError::from)?;
+		self.reindex(&inner)
+	}
+
+	pub fn list_trash(&self) -> Result<Vec<String>, FfiError> {
+		let inner = self.inner.lock().unwrap();
+		Ok(
+			inner
+				.trashed(&self.branch)
+				.map_err(FfiError::from)?
+				.into_iter()
+				.map(|n| n.to_string())
+				.collect(),
+		)
+	}
+
+	pub fn restore_entry(&self, name: String) -> Result<(), FfiError> {
+		let name = AccountName::new(&name).map_err(FfiError::from)?;
+		let inner = self.inner.lock().unwrap();
+		inner
+			.restore(&self.branch, &name, StoreChange::Custom(format!("restore {name}")))
+			.map_err(FfiError::from)?;
+		self.reindex(&inner)
+	}
+
+	/// Permanently delete every trashed entry; returns how many there were.
+	pub fn empty_trash(&self) -> Result<u32, FfiError> {
+		let inner = self.inner.lock().unwrap();
+		Ok(inner.empty_trash(&self.branch).map_err(FfiError::from)? as u32)
+	}
+
+	// ── recently viewed ───────────────────────────────────────────────────────
+
+	/// Entries opened recently on this device, most recent first.
+	pub fn recent_entries(&self) -> Result<Vec<String>, FfiError> {
+		let inner = self.inner.lock().unwrap();
+		Ok(
+			inner
+				.recent(&self.branch)
+				.map_err(FfiError::from)?
+				.into_iter()
+				.map(|n| n.to_string())
+				.collect(),
+		)
+	}
+
+	/// Record that `name` was opened; returns the updated recent list.
+	pub fn mark_viewed(&self, name: String) -> Result<Vec<String>, FfiError> {
+		let name = AccountName::new(&name).map_err(FfiError::from)?;
+		let inner = self.inner.lock().unwrap();
+		Ok(
+			inner
+				.mark_viewed(&self.branch, &name)
+				.map_err(FfiError::from)?
+				.into_iter()
+				.map(|n| n.to_string())
+				.collect(),
+		)
+	}
+
+	// ── statistics ────────────────────────────────────────────────────────────
+
+	/// Figures for the dashboard; see [`crate::stats`].
+	pub fn vault_stats(&self) -> Result<FfiVaultStats, FfiError> {
+		let inner = self.inner.lock().unwrap();
+		let store = inner.load(&self.branch).map_err(FfiError::from)?;
+		let modified = inner.last_modified(&self.branch).map_err(FfiError::from)?;
+		let log = inner.log_impl(&self.branch, None).map_err(FfiError::from)?;
+		let last_sync = inner.last_sync(&self.branch).map_err(FfiError::from)?;
+		let scores = self.index.lock().unwrap().password_scores();
+		Ok(VaultStats::compute(&store, &scores, &modified, &log, last_sync, Timestamp::now()).into())
+	}
+
+	// ── attachments ───────────────────────────────────────────────────────────
+
+	pub fn list_attachments(&self, name: String) -> Result<Vec<String>, FfiError> {
+		let name = AccountName::new(&name).map_err(FfiError::from)?;
+		let inner = self.inner.lock().unwrap();
+		inner.attachments(&self.branch, &name).map_err(FfiError::from)
+	}
+
+	/// Encrypt the file at `path` and attach it to `name` under its file name.
+	pub fn add_attachment(&self, name: String, path: String) -> Result<String, FfiError> {
+		let name = AccountName::new(&name).map_err(FfiError::from)?;
+		let path = PathBuf::from(path);
+		let file = path
+			.file_name()
+			.and_then(|f| f.to_str())
+			.ok_or_else(|| FfiError::Other { msg: format!("not a file: {}", path.display()) })?
+			.to_owned();
+		let contents = std::fs::read(&path).map_err(|e| FfiError::Io { msg: e.to_string() })?;
+		let inner = self.inner.lock().unwrap();
+		inner.attach(&self.branch, &name, &file, &contents).map_err(FfiError::from)?;
+		Ok(file)
+	}
+
+	/// Decrypt an attachment to a private temporary file and return its path.
+	pub fn export_attachment(&self, name: String, file: String) -> Result<String, FfiError> {
+		let name = AccountName::new(&name).map_err(FfiError::from)?;
+		let inner = self.inner.lock().unwrap();
+		let path =
+			inner.export_attachment_to_temp(&self.branch, &name, &file).map_err(FfiError::from)?;
+		Ok(path.to_string_lossy().into_owned())
+	}
+
+	/// Delete a file returned by [`Self::export_attachment`], once the viewer
+	/// has opened it.
+	pub fn remove_exported_attachment(&self, path: String) -> Result<(), FfiError> {
+		let inner = self.inner.lock().unwrap();
+		inner.remove_temp_attachment(std::path::Path::new(&path)).map_err(FfiError::from)
+	}
+
+	pub fn remove_attachment(&self, name: String, file: String) -> Result<bool, FfiError> {
+		let name = AccountName::new(&name).map_err(FfiError::from)?;
+		let inner = self.inner.lock().unwrap();
+		inner.detach(&self.branch, &name, &file).map_err(FfiError::from)
 	}
 
 	// ── history ───────────────────────────────────────────────────────────────
@@ -101,7 +343,8 @@ impl PwdStore {
 		let hash = pijul_at_core::Hash::from_base32(to_hash.as_bytes())
 			.ok_or_else(|| FfiError::Other { msg: format!("invalid hash: {to_hash}") })?;
 		let inner = self.inner.lock().unwrap();
-		inner.entry(&self.branch, name).revert_to(&hash).map_err(FfiError::from)
+		inner.entry(&self.branch, name).revert_to(&hash).map_err(FfiError::from)?;
+		self.reindex(&inner)
 	}
 
 	/// `to_hash`: pass `None` to diff against the current on-disk state.
@@ -136,3 +379,29 @@ impl PwdStore {
 		)
 	}
 }
+
+/// Whether a store has been created at `store_dir`, so a front end can
+/// offer to create one instead of opening an empty directory.
+#[uniffi::export]
+pub fn store_exists(store_dir: String) -> bool { PijulStore::exists(store_dir) }
+
+impl PwdStore {
+	/// Rebuild the search index after the branch changed on disk.
+	pub(super) fn reindex(&self, inner: &PijulStore<Unlocked<AgeScrypt>>) -> Result<(), FfiError> {
+		let modified = inner.last_modified(&self.branch).map_err(FfiError::from)?;
+		let index = SearchIndex::build(&inner.load(&self.branch).map_err(FfiError::from)?)
+			.with_modified(&modified);
+		*self.index.lock().unwrap() = index;
+		Ok(())
+	}
+
+	/// Convert an inbound item, validating template items against their
+	/// template.
+	fn checked_item(&self, item: FfiItem) -> Result<Item, FfiError> {
+		let item = Item::try_from(item)?;
+		if let Item::Template(t) = &item {
+			self.templates.validate(t).map_err(FfiError::from)?;
+		}
+		Ok(item)
+	}
+}
@@ -4,7 +4,7 @@ use super::error::FfiError;
 /// All domain-typed fields (URL, email, phone, date, country) are exposed as
 /// plain Strings.  Validation of inbound data happens inside `PwdStore` before
 /// it reaches the internal `OnlineAccount`/`SocialSecurity` types.
-use crate::{models::{Item, OnlineAccount, OnlineAccountSecurityQuestionsItem, OnlineAccountSignInWithItem, OnlineAccountStatus, SocialSecurity}, versioning::ChangeEntry};
+use crate::{models::{ApiCredential, Appearance, CharacterClass, CryptoWallet, CustomField, DriversLicense, EmailAccount, EmailAccountImap, EmailAccountImapSecurity, EmailAccountSmtp, EmailAccountSmtpSecurity, IdentityDocument, IdentityDocumentDocumentType, InsurancePolicy, Item, ItemCategory, Membership, OnlineAccount, OnlineAccountSecurityQuestionsItem, OnlineAccountSignInWithItem, OnlineAccountStatus, Passkey, PasswordPolicy, RecoveryCode, SecureNote, ServerCredential, SocialSecurity, SoftwareLicense, Totp, TotpAlgorithm, TotpKind, WifiNetwork, WifiNetworkSecurity}, versioning::ChangeEntry};
 
 // ── item types
 // ────────────────────────────────────────────────────────────────
@@ -13,6 +13,20 @@ use crate::{models::{Item, OnlineAccount, OnlineAccountSecurityQuestionsItem, On
 pub enum FfiItem {
 	OnlineAccount { account: FfiOnlineAccount },
 	SocialSecurity { ssn: FfiSocialSecurity },
+	ApiCredential { credential: FfiApiCredential },
+	IdentityDocument { document: FfiIdentityDocument },
+	WifiNetwork { network: FfiWifiNetwork },
+	CryptoWallet { wallet: FfiCryptoWallet },
+	SoftwareLicense { license: FfiSoftwareLicense },
+	SecureNote { note: FfiSecureNote },
+	ServerCredential { server: FfiServerCredential },
+	EmailAccount { mailbox: FfiEmailAccount },
+	Membership { membership: FfiMembership },
+	InsurancePolicy { policy: FfiInsurancePolicy },
+	Passkey { passkey: FfiPasskey },
+	PgpKey { key: FfiPgpKey },
+	DriversLicense { license: FfiDriversLicense },
+	Template { item: FfiTemplateItem },
 }
 
 /// String-field mirror of [`OnlineAccount`].
@@ -31,10 +45,16 @@ pub struct FfiOnlineAccount {
 	pub host_website:       Option<String>,
 	pub login_pages:        Option<Vec<String>>,
 	pub security_questions: Option<Vec<FfiSecurityQuestion>>,
+	pub recovery_codes:     Vec<FfiRecoveryCode>,
+	pub password_policy:    Option<FfiPasswordPolicy>,
+	pub totp:               Option<FfiTotp>,
 	pub two_factor_enabled: Option<bool>,
 	pub associated_items:   Option<Vec<String>>,
 	pub date_created:       Option<String>,
 	pub notes:              Option<String>,
+	pub tags:               Vec<String>,
+	pub custom_fields:      Vec<FfiCustomField>,
+	pub appearance:         Option<FfiAppearance>,
 }
 
 #[derive(Debug, Clone, uniffi::Record)]
@@ -51,6 +71,336 @@ pub struct FfiSocialSecurity {
 	pub issuance_date:    Option<String>,
 	pub country_of_issue: Option<String>,
 	pub notes:            Option<String>,
+	pub tags:             Vec<String>,
+	pub custom_fields:    Vec<FfiCustomField>,
+	pub appearance:       Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`ApiCredential`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiApiCredential {
+	pub token:         String,
+	pub secret:        Option<String>,
+	pub scopes:        Option<Vec<String>>,
+	pub expiry_date:   Option<String>,
+	pub service_url:   Option<String>,
+	pub environment:   Option<String>,
+	pub notes:         Option<String>,
+	pub tags:          Vec<String>,
+	pub custom_fields: Vec<FfiCustomField>,
+	pub appearance:    Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`IdentityDocument`].
+///
+/// `document_type` is one of "Passport"/"NationalId"/"ResidencePermit"/"Other".
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiIdentityDocument {
+	pub document_type:   String,
+	pub number:          String,
+	pub legal_name:      Option<String>,
+	pub issuing_country: Option<String>,
+	pub issue_date:      Option<String>,
+	pub expiry_date:     Option<String>,
+	pub mrz:             Option<Vec<String>>,
+	pub notes:           Option<String>,
+	pub tags:            Vec<String>,
+	pub custom_fields:   Vec<FfiCustomField>,
+	pub appearance:      Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`WifiNetwork`].
+///
+/// `security` is one of "WPA"/"WPA3"/"WEP"/"Open".
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiWifiNetwork {
+	pub ssid:          String,
+	pub security:      Option<String>,
+	pub passphrase:    Option<String>,
+	pub hidden:        Option<bool>,
+	pub notes:         Option<String>,
+	pub tags:          Vec<String>,
+	pub custom_fields: Vec<FfiCustomField>,
+	pub appearance:    Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`CryptoWallet`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiCryptoWallet {
+	pub seed_phrase:      Option<String>,
+	pub passphrase:       Option<String>,
+	pub chain:            Option<String>,
+	pub derivation_paths: Option<Vec<String>>,
+	pub addresses:        Option<Vec<String>>,
+	pub notes:            Option<String>,
+	pub tags:             Vec<String>,
+	pub custom_fields:    Vec<FfiCustomField>,
+	pub appearance:       Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`SoftwareLicense`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiSoftwareLicense {
+	pub product:       String,
+	pub license_key:   Option<String>,
+	pub licensee:      Option<String>,
+	pub purchase_date: Option<String>,
+	pub order_number:  Option<String>,
+	pub download_url:  Option<String>,
+	pub seats:         Option<u32>,
+	pub notes:         Option<String>,
+	pub tags:          Vec<String>,
+	pub custom_fields: Vec<FfiCustomField>,
+	pub appearance:    Option<FfiAppearance>,
+}
+
+/// Mirror of [`SecureNote`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiSecureNote {
+	pub title:         String,
+	pub body:          String,
+	pub tags:          Vec<String>,
+	pub custom_fields: Vec<FfiCustomField>,
+	pub appearance:    Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`ServerCredential`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiServerCredential {
+	pub host:              String,
+	pub port:              Option<u16>,
+	pub protocol:          Option<String>,
+	pub username:          Option<String>,
+	pub password:          Option<String>,
+	pub connection_string: Option<String>,
+	pub notes:             Option<String>,
+	pub tags:              Vec<String>,
+	pub custom_fields:     Vec<FfiCustomField>,
+	pub appearance:        Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`EmailAccount`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiEmailAccount {
+	pub address:       String,
+	pub username:      Option<String>,
+	pub password:      Option<String>,
+	pub app_password:  Option<String>,
+	pub imap:          Option<FfiMailServer>,
+	pub smtp:          Option<FfiMailServer>,
+	pub notes:         Option<String>,
+	pub tags:          Vec<String>,
+	pub custom_fields: Vec<FfiCustomField>,
+	pub appearance:    Option<FfiAppearance>,
+}
+
+/// Mirror of the IMAP/SMTP server settings on [`EmailAccount`].
+///
+/// `security` is one of "Tls"/"StartTls"/"None".
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiMailServer {
+	pub host:     String,
+	pub port:     Option<u16>,
+	pub security: Option<String>,
+}
+
+/// String-field mirror of [`Membership`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiMembership {
+	pub program:       String,
+	pub member_id:     Option<String>,
+	pub tier:          Option<String>,
+	pub member_name:   Option<String>,
+	pub expiry_date:   Option<String>,
+	pub barcode:       Option<String>,
+	pub pin:           Option<String>,
+	pub notes:         Option<String>,
+	pub tags:          Vec<String>,
+	pub custom_fields: Vec<FfiCustomField>,
+	pub appearance:    Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`InsurancePolicy`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiInsurancePolicy {
+	pub provider:        String,
+	pub policy_number:   Option<String>,
+	pub group_number:    Option<String>,
+	pub member_id:       Option<String>,
+	pub coverage_start:  Option<String>,
+	pub coverage_end:    Option<String>,
+	pub emergency_phone: Option<String>,
+	pub notes:           Option<String>,
+	pub tags:            Vec<String>,
+	pub custom_fields:   Vec<FfiCustomField>,
+	pub appearance:      Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`Passkey`]; binary values stay base64url-encoded.
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiPasskey {
+	pub rp_id:             String,
+	pub rp_name:           Option<String>,
+	pub credential_id:     String,
+	pub private_key:       String,
+	pub user_handle:       Option<String>,
+	pub user_name:         Option<String>,
+	pub user_display_name: Option<String>,
+	pub creation_date:     Option<String>,
+	pub notes:             Option<String>,
+	pub tags:              Vec<String>,
+	pub custom_fields:     Vec<FfiCustomField>,
+	pub appearance:        Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`PgpKey`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiPgpKey {
+	pub private_key:   String,
+	pub public_key:    Option<String>,
+	pub passphrase:    Option<String>,
+	pub fingerprint:   Option<String>,
+	pub key_id:        Option<String>,
+	pub uids:          Option<Vec<String>>,
+	pub expiry_date:   Option<String>,
+	pub notes:         Option<String>,
+	pub tags:          Vec<String>,
+	pub custom_fields: Vec<FfiCustomField>,
+	pub appearance:    Option<FfiAppearance>,
+}
+
+/// String-field mirror of [`DriversLicense`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiDriversLicense {
+	pub number:          String,
+	pub legal_name:      Option<String>,
+	pub license_class:   Option<String>,
+	pub issuing_region:  Option<String>,
+	pub issuing_country: Option<String>,
+	pub issue_date:      Option<String>,
+	pub expiry_date:     Option<String>,
+	pub restrictions:    Option<Vec<String>>,
+	pub notes:           Option<String>,
+	pub tags:            Vec<String>,
+	pub custom_fields:   Vec<FfiCustomField>,
+	pub appearance:      Option<FfiAppearance>,
+}
+
+/// Mirror of [`TemplateItem`]; values are listed in storage order, so callers
+/// should render them in the order given by the matching [`ItemTemplate`].
+///
+/// [`TemplateItem`]: crate::templates::TemplateItem
+/// [`ItemTemplate`]: crate::templates::ItemTemplate
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiTemplateItem {
+	pub template:      String,
+	pub values:        Vec<FfiTemplateValue>,
+	pub tags:          Vec<String>,
+	pub custom_fields: Vec<FfiCustomField>,
+	pub appearance:    Option<FfiAppearance>,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiTemplateValue {
+	pub field: String,
+	pub value: String,
+}
+
+/// Mirror of [`CustomField`].
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiCustomField {
+	pub label:  String,
+	pub value:  String,
+	pub secret: bool,
+}
+
+/// Mirror of [`PasswordPolicy`]. `required_classes` holds
+/// "lower"/"upper"/"digits"/"symbols".
+#[derive(Debug, Clone, Default, PartialEq, Eq, uniffi::Record)]
+pub struct FfiPasswordPolicy {
+	pub max_length:           Option<u32>,
+	pub forbidden_characters: Option<String>,
+	pub required_classes:     Vec<String>,
+	pub recipe:               Option<String>,
+}
+
+/// Mirror of [`RecoveryCode`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, uniffi::Record)]
+pub struct FfiRecoveryCode {
+	pub code: String,
+	pub used: bool,
+}
+
+/// Mirror of [`Totp`]. `kind` is "totp"/"hotp"/"steam" and `algorithm`
+/// "SHA1"/"SHA256"/"SHA512".
+#[derive(Debug, Clone, Default, PartialEq, Eq, uniffi::Record)]
+pub struct FfiTotp {
+	pub secret:    String,
+	pub kind:      Option<String>,
+	pub counter:   Option<u64>,
+	/// OATH credential on a YubiKey computing the codes; `secret` is then
+	/// empty.
+	pub yubikey:   Option<String>,
+	pub issuer:    Option<String>,
+	pub digits:    Option<u8>,
+	pub period:    Option<u32>,
+	pub algorithm: Option<String>,
+}
+
+/// Mirror of [`Appearance`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, uniffi::Record)]
+pub struct FfiAppearance {
+	pub accent: Option<String>,
+	pub icon:   Option<String>,
+}
+
+/// Mirror of [`SearchHit`](crate::search::SearchHit). Ranges are UTF-8 byte
+/// offsets into `name` / `value`.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiSearchHit {
+	pub name:           String,
+	pub name_start:     Option<u32>,
+	pub name_end:       Option<u32>,
+	pub matches:        Vec<FfiFieldMatch>,
+	pub tags:           Vec<String>,
+	pub category:       ItemCategory,
+	pub favorite:       bool,
+	pub kind:           String,
+	pub subtitle:       Option<String>,
+	pub accent:         Option<String>,
+	pub icon:           Option<String>,
+	/// zxcvbn score of the entry's password, 0-4; below 3 is weak.
+	pub password_score: Option<u8>,
+	/// When the entry last changed, relative to now ("3 days ago").
+	pub modified:       Option<String>,
+}
+
+/// Mirror of [`SearchFilter`](crate::search::SearchFilter).
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct FfiSearchFilter {
+	pub tag:         Option<String>,
+	pub category:    Option<ItemCategory>,
+	pub folder:      Option<String>,
+	pub deactivated: Option<bool>,
+}
+
+impl From<FfiSearchFilter> for crate::search::SearchFilter {
+	fn from(f: FfiSearchFilter) -> Self {
+		Self {
+			tag:         f.tag,
+			category:    f.category,
+			folder:      f.folder,
+			deactivated: f.deactivated,
+		}
+	}
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiFieldMatch {
+	pub field: String,
+	pub value: String,
+	pub start: u32,
+	pub end:   u32,
 }
 
 /// String-field mirror of [`ChangeEntry`].
@@ -71,6 +421,32 @@ impl From<Item> for FfiItem {
 		match item {
 			Item::OnlineAccount(a) => FfiItem::OnlineAccount { account: a.into() },
 			Item::SocialSecurity(s) => FfiItem::SocialSecurity { ssn: s.into() },
+			Item::ApiCredential(c) => FfiItem::ApiCredential { credential: c.into() },
+			Item::IdentityDocument(d) => FfiItem::IdentityDocument { document: d.into() },
+			Item::WifiNetwork(w) => FfiItem::WifiNetwork { network: w.into() },
+			Item::CryptoWallet(w) => FfiItem::CryptoWallet { wallet: w.into() },
+			Item::SoftwareLicense(l) => FfiItem::SoftwareLicense { license: l.into() },
+			Item::SecureNote(n) => FfiItem::SecureNote { note: n.into() },
+			Item::ServerCredential(s) => FfiItem::ServerCredential { server: s.into() },
+			Item::EmailAccount(e) => FfiItem::EmailAccount { mailbox: e.into() },
+			Item::Membership(m) => FfiItem::Membership { membership: m.into() },
+			Item::InsurancePolicy(p) => FfiItem::InsurancePolicy { policy: p.into() },
+			Item::Passkey(p) => FfiItem::Passkey { passkey: p.into() },
+			Item::PgpKey(k) => FfiItem::PgpKey { key: k.into() },
+			Item::DriversLicense(l) => FfiItem::DriversLicense { license: l.into() },
+			Item::Template(t) => FfiItem::Template {
+				item: FfiTemplateItem {
+					template:      t.template,
+					values:        t
+						.fields
+						.into_iter()
+						.map(|(field, value)| FfiTemplateValue { field, value })
+						.collect(),
+					tags:          t.tags,
+					custom_fields: ffi_custom_fields(t.custom_fields),
+					appearance:    t.appearance.map(Into::into),
+				},
+			},
 		}
 	}
 }
@@ -94,10 +470,16 @@ impl From<OnlineAccount> for FfiOnlineAccount {
 					.map(|q| FfiSecurityQuestion { question: q.question, answer: q.answer })
 					.collect()
 			}),
+			recovery_codes:     a.recovery_codes.into_iter().map(Into::into).collect(),
+			password_policy:    a.password_policy.map(Into::into),
+			totp:               a.totp.map(Into::into),
 			two_factor_enabled: a.two_factor_enabled,
 			associated_items:   a.associated_items,
 			date_created:       a.date_created.as_ref().map(|d| d.to_string()),
 			notes:              a.notes,
+			tags:               a.tags,
+			custom_fields:      ffi_custom_fields(a.custom_fields),
+			appearance:         a.appearance.map(Into::into),
 		}
 	}
 }
@@ -110,6 +492,271 @@ impl From<SocialSecurity> for FfiSocialSecurity {
 			issuance_date:    s.issuance_date.as_ref().map(|d| d.to_string()),
 			country_of_issue: s.country_of_issue.as_ref().map(country_alpha2),
 			notes:            s.notes,
+			tags:             s.tags,
+			custom_fields:    ffi_custom_fields(s.custom_fields),
+			appearance:       s.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<ApiCredential> for FfiApiCredential {
+	fn from(c: ApiCredential) -> Self {
+		Self {
+			token:         c.token,
+			secret:        c.secret,
+			scopes:        c.scopes,
+			expiry_date:   c.expiry_date.as_ref().map(|d| d.to_string()),
+			service_url:   c.service_url.as_ref().map(|u| u.to_string()),
+			environment:   c.environment,
+			notes:         c.notes,
+			tags:          c.tags,
+			custom_fields: ffi_custom_fields(c.custom_fields),
+			appearance:    c.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<IdentityDocument> for FfiIdentityDocument {
+	fn from(d: IdentityDocument) -> Self {
+		Self {
+			document_type:   d.document_type.to_string(),
+			number:          d.number.to_string(),
+			legal_name:      d.legal_name,
+			issuing_country: d.issuing_country.as_ref().map(country_alpha2),
+			issue_date:      d.issue_date.as_ref().map(|d| d.to_string()),
+			expiry_date:     d.expiry_date.as_ref().map(|d| d.to_string()),
+			mrz:             d.mrz,
+			notes:           d.notes,
+			tags:            d.tags,
+			custom_fields:   ffi_custom_fields(d.custom_fields),
+			appearance:      d.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<WifiNetwork> for FfiWifiNetwork {
+	fn from(w: WifiNetwork) -> Self {
+		Self {
+			ssid:          w.ssid.to_string(),
+			security:      w.security.as_ref().map(|s| s.to_string()),
+			passphrase:    w.passphrase,
+			hidden:        w.hidden,
+			notes:         w.notes,
+			tags:          w.tags,
+			custom_fields: ffi_custom_fields(w.custom_fields),
+			appearance:    w.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<CryptoWallet> for FfiCryptoWallet {
+	fn from(w: CryptoWallet) -> Self {
+		Self {
+			seed_phrase:      w.seed_phrase,
+			passphrase:       w.passphrase,
+			chain:            w.chain,
+			derivation_paths: w.derivation_paths,
+			addresses:        w.addresses,
+			notes:            w.notes,
+			tags:             w.tags,
+			custom_fields:    ffi_custom_fields(w.custom_fields),
+			appearance:       w.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<SoftwareLicense> for FfiSoftwareLicense {
+	fn from(l: SoftwareLicense) -> Self {
+		Self {
+			product:       l.product.to_string(),
+			license_key:   l.license_key,
+			licensee:      l.licensee,
+			purchase_date: l.purchase_date.as_ref().map(|d| d.to_string()),
+			order_number:  l.order_number,
+			download_url:  l.download_url.as_ref().map(|u| u.to_string()),
+			seats:         l.seats,
+			notes:         l.notes,
+			tags:          l.tags,
+			custom_fields: ffi_custom_fields(l.custom_fields),
+			appearance:    l.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<SecureNote> for FfiSecureNote {
+	fn from(n: SecureNote) -> Self {
+		Self {
+			title:         n.title,
+			body:          n.body,
+			tags:          n.tags,
+			custom_fields: ffi_custom_fields(n.custom_fields),
+			appearance:    n.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<ServerCredential> for FfiServerCredential {
+	fn from(s: ServerCredential) -> Self {
+		Self {
+			host:              s.host.to_string(),
+			port:              s.port,
+			protocol:          s.protocol,
+			username:          s.username,
+			password:          s.password,
+			connection_string: s.connection_string,
+			notes:             s.notes,
+			tags:              s.tags,
+			custom_fields:     ffi_custom_fields(s.custom_fields),
+			appearance:        s.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<EmailAccount> for FfiEmailAccount {
+	fn from(e: EmailAccount) -> Self {
+		Self {
+			address:       e.address.to_string(),
+			username:      e.username,
+			password:      e.password,
+			app_password:  e.app_password,
+			imap:          e.imap.map(|s| FfiMailServer {
+				host:     s.host,
+				port:     s.port,
+				security: s.security.as_ref().map(|t| t.to_string()),
+			}),
+			smtp:          e.smtp.map(|s| FfiMailServer {
+				host:     s.host,
+				port:     s.port,
+				security: s.security.as_ref().map(|t| t.to_string()),
+			}),
+			notes:         e.notes,
+			tags:          e.tags,
+			custom_fields: ffi_custom_fields(e.custom_fields),
+			appearance:    e.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<Membership> for FfiMembership {
+	fn from(m: Membership) -> Self {
+		Self {
+			program:       m.program.to_string(),
+			member_id:     m.member_id,
+			tier:          m.tier,
+			member_name:   m.member_name,
+			expiry_date:   m.expiry_date.as_ref().map(|d| d.to_string()),
+			barcode:       m.barcode,
+			pin:           m.pin,
+			notes:         m.notes,
+			tags:          m.tags,
+			custom_fields: ffi_custom_fields(m.custom_fields),
+			appearance:    m.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<InsurancePolicy> for FfiInsurancePolicy {
+	fn from(p: InsurancePolicy) -> Self {
+		Self {
+			provider:        p.provider.to_string(),
+			policy_number:   p.policy_number,
+			group_number:    p.group_number,
+			member_id:       p.member_id,
+			coverage_start:  p.coverage_start.as_ref().map(|d| d.to_string()),
+			coverage_end:    p.coverage_end.as_ref().map(|d| d.to_string()),
+			emergency_phone: p
+				.emergency_phone
+				.as_ref()
+				.map(|n| phonenumber::format(n).mode(phonenumber::Mode::E164).to_string()),
+			notes:           p.notes,
+			tags:            p.tags,
+			custom_fields:   ffi_custom_fields(p.custom_fields),
+			appearance:      p.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<Passkey> for FfiPasskey {
+	fn from(p: Passkey) -> Self {
+		Self {
+			rp_id:             p.rp_id.to_string(),
+			rp_name:           p.rp_name,
+			credential_id:     p.credential_id,
+			private_key:       p.private_key,
+			user_handle:       p.user_handle,
+			user_name:         p.user_name,
+			user_display_name: p.user_display_name,
+			creation_date:     p.creation_date.as_ref().map(|d| d.to_string()),
+			notes:             p.notes,
+			tags:              p.tags,
+			custom_fields:     ffi_custom_fields(p.custom_fields),
+			appearance:        p.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<PgpKey> for FfiPgpKey {
+	fn from(k: PgpKey) -> Self {
+		Self {
+			private_key:   k.private_key,
+			public_key:    k.public_key,
+			passphrase:    k.passphrase,
+			fingerprint:   k.fingerprint,
+			key_id:        k.key_id,
+			uids:          k.uids,
+			expiry_date:   k.expiry_date.as_ref().map(|d| d.to_string()),
+			notes:         k.notes,
+			tags:          k.tags,
+			custom_fields: ffi_custom_fields(k.custom_fields),
+			appearance:    k.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<DriversLicense> for FfiDriversLicense {
+	fn from(l: DriversLicense) -> Self {
+		Self {
+			number:          l.number.to_string(),
+			legal_name:      l.legal_name,
+			license_class:   l.license_class,
+			issuing_region:  l.issuing_region,
+			issuing_country: l.issuing_country.as_ref().map(country_alpha2),
+			issue_date:      l.issue_date.as_ref().map(|d| d.to_string()),
+			expiry_date:     l.expiry_date.as_ref().map(|d| d.to_string()),
+			restrictions:    l.restrictions,
+			notes:           l.notes,
+			tags:            l.tags,
+			custom_fields:   ffi_custom_fields(l.custom_fields),
+			appearance:      l.appearance.map(Into::into),
+		}
+	}
+}
+
+impl From<crate::search::SearchHit> for FfiSearchHit {
+	fn from(h: crate::search::SearchHit) -> Self {
+		Self {
+			name:           h.name.to_string(),
+			name_start:     h.name_match.as_ref().map(|r| r.start as u32),
+			name_end:       h.name_match.as_ref().map(|r| r.end as u32),
+			matches:        h
+				.fields
+				.into_iter()
+				.map(|m| FfiFieldMatch {
+					field: m.field,
+					start: m.range.start as u32,
+					end:   m.range.end as u32,
+					value: m.value,
+				})
+				.collect(),
+			tags:           h.tags,
+			category:       h.category,
+			favorite:       h.favorite,
+			kind:           h.kind.to_owned(),
+			subtitle:       h.subtitle,
+			accent:         h.accent,
+			icon:           h.icon,
+			password_score: h.password_score,
+			modified:       h.modified.map(|t| crate::search::relative_age(t, jiff::Timestamp::now())),
 		}
 	}
 }
@@ -136,6 +783,26 @@ impl TryFrom<FfiItem> for Item {
 		match item {
 			FfiItem::OnlineAccount { account } => Ok(Item::OnlineAccount(account.try_into()?)),
 			FfiItem::SocialSecurity { ssn } => Ok(Item::SocialSecurity(ssn.try_into()?)),
+			FfiItem::ApiCredential { credential } => Ok(Item::ApiCredential(credential.try_into()?)),
+			FfiItem::IdentityDocument { document } => Ok(Item::IdentityDocument(document.try_into()?)),
+			FfiItem::WifiNetwork { network } => Ok(Item::WifiNetwork(network.try_into()?)),
+			FfiItem::CryptoWallet { wallet } => Ok(Item::CryptoWallet(wallet.try_into()?)),
+			FfiItem::SoftwareLicense { license } => Ok(Item::SoftwareLicense(license.try_into()?)),
+			FfiItem::SecureNote { note } => Ok(Item::SecureNote(note.into())),
+			FfiItem::ServerCredential { server } => Ok(Item::ServerCredential(server.try_into()?)),
+			FfiItem::EmailAccount { mailbox } => Ok(Item::EmailAccount(mailbox.try_into()?)),
+			FfiItem::Membership { membership } => Ok(Item::Membership(membership.try_into()?)),
+			FfiItem::InsurancePolicy { policy } => Ok(Item::InsurancePolicy(policy.try_into()?)),
+			FfiItem::Passkey { passkey } => Ok(Item::Passkey(passkey.try_into()?)),
+			FfiItem::PgpKey { key } => Ok(Item::PgpKey(key.try_into()?)),
+			FfiItem::DriversLicense { license } => Ok(Item::DriversLicense(license.try_into()?)),
+			FfiItem::Template { item } => Ok(Item::Template(crate::templates::TemplateItem {
+				template:      item.template,
+				fields:        item.values.into_iter().map(|v| (v.field, v.value)).collect(),
+				tags:          item.tags,
+				custom_fields: custom_fields(item.custom_fields),
+				appearance:    item.appearance.map(Into::into),
+			})),
 		}
 	}
 }
@@ -231,10 +898,16 @@ impl TryFrom<FfiOnlineAccount> for OnlineAccount {
 			host_website,
 			login_pages,
 			security_questions,
+			recovery_codes: a.recovery_codes.into_iter().map(Into::into).collect(),
+			password_policy: a.password_policy.map(TryInto::try_into).transpose()?,
+			totp: a.totp.map(TryInto::try_into).transpose()?,
 			two_factor_enabled: a.two_factor_enabled,
 			associated_items: a.associated_items,
 			date_created,
 			notes: a.notes,
+			tags: a.tags,
+			custom_fields: custom_fields(a.custom_fields),
+			appearance: a.appearance.map(Into::into),
 		})
 	}
 }
@@ -270,6 +943,473 @@ impl TryFrom<FfiSocialSecurity> for SocialSecurity {
 			issuance_date,
 			country_of_issue,
 			notes: s.notes,
+			tags: s.tags,
+			custom_fields: custom_fields(s.custom_fields),
+			appearance: s.appearance.map(Into::into),
+		})
+	}
+}
+
+impl TryFrom<FfiApiCredential> for ApiCredential {
+	type Error = FfiError;
+
+	fn try_from(c: FfiApiCredential) -> Result<Self, FfiError> {
+		let expiry_date = c
+			.expiry_date
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		let service_url = c
+			.service_url
+			.map(|s| {
+				s.parse::<url::Url>().map_err(|e| FfiError::Other { msg: format!("invalid URL: {e}") })
+			})
+			.transpose()?;
+
+		Ok(ApiCredential {
+			token: c.token,
+			secret: c.secret,
+			scopes: c.scopes,
+			expiry_date,
+			service_url,
+			environment: c.environment,
+			notes: c.notes,
+			tags: c.tags,
+			custom_fields: custom_fields(c.custom_fields),
+			appearance: c.appearance.map(Into::into),
+		})
+	}
+}
+
+impl TryFrom<FfiIdentityDocument> for IdentityDocument {
+	type Error = FfiError;
+
+	fn try_from(d: FfiIdentityDocument) -> Result<Self, FfiError> {
+		let document_type = d.document_type.parse::<IdentityDocumentDocumentType>().map_err(|_| {
+			FfiError::Other { msg: format!("unknown document type: {}", d.document_type) }
+		})?;
+
+		let number = d
+			.number
+			.parse::<crate::models::IdentityDocumentNumber>()
+			.map_err(|e| FfiError::Other { msg: format!("invalid document number: {e}") })?;
+
+		let issuing_country = d
+			.issuing_country
+			.map(|c| {
+				c.parse::<celes::Country>()
+					.map_err(|_| FfiError::Other { msg: format!("unknown country code: {c}") })
+			})
+			.transpose()?;
+
+		let issue_date = d
+			.issue_date
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		let expiry_date = d
+			.expiry_date
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		Ok(IdentityDocument {
+			document_type,
+			number,
+			legal_name: d.legal_name,
+			issuing_country,
+			issue_date,
+			expiry_date,
+			mrz: d.mrz,
+			notes: d.notes,
+			tags: d.tags,
+			custom_fields: custom_fields(d.custom_fields),
+			appearance: d.appearance.map(Into::into),
+		})
+	}
+}
+
+impl TryFrom<FfiWifiNetwork> for WifiNetwork {
+	type Error = FfiError;
+
+	fn try_from(w: FfiWifiNetwork) -> Result<Self, FfiError> {
+		let ssid = w
+			.ssid
+			.parse::<crate::models::WifiNetworkSsid>()
+			.map_err(|e| FfiError::Other { msg: format!("invalid SSID: {e}") })?;
+
+		let security = w
+			.security
+			.map(|s| {
+				s.parse::<WifiNetworkSecurity>()
+					.map_err(|_| FfiError::Other { msg: format!("unknown security type: {s}") })
+			})
+			.transpose()?;
+
+		Ok(WifiNetwork {
+			ssid,
+			security,
+			passphrase: w.passphrase,
+			hidden: w.hidden,
+			notes: w.notes,
+			tags: w.tags,
+			custom_fields: custom_fields(w.custom_fields),
+			appearance: w.appearance.map(Into::into),
+		})
+	}
+}
+
+impl TryFrom<FfiCryptoWallet> for CryptoWallet {
+	type Error = FfiError;
+
+	fn try_from(w: FfiCryptoWallet) -> Result<Self, FfiError> {
+		let wallet = CryptoWallet {
+			seed_phrase:      w
+				.seed_phrase
+				.map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+				.filter(|p| !p.is_empty()),
+			passphrase:       w.passphrase,
+			chain:            w.chain,
+			derivation_paths: w.derivation_paths,
+			addresses:        w.addresses,
+			notes:            w.notes,
+			tags:             w.tags,
+			custom_fields:    custom_fields(w.custom_fields),
+			appearance:       w.appearance.map(Into::into),
+		};
+		wallet.validate()?;
+		Ok(wallet)
+	}
+}
+
+impl TryFrom<FfiSoftwareLicense> for SoftwareLicense {
+	type Error = FfiError;
+
+	fn try_from(l: FfiSoftwareLicense) -> Result<Self, FfiError> {
+		let product = l
+			.product
+			.parse::<crate::models::SoftwareLicenseProduct>()
+			.map_err(|e| FfiError::Other { msg: format!("invalid product: {e}") })?;
+
+		let purchase_date = l
+			.purchase_date
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		let download_url = l
+			.download_url
+			.map(|s| {
+				s.parse::<url::Url>().map_err(|e| FfiError::Other { msg: format!("invalid URL: {e}") })
+			})
+			.transpose()?;
+
+		Ok(SoftwareLicense {
+			product,
+			license_key: l.license_key,
+			licensee: l.licensee,
+			purchase_date,
+			order_number: l.order_number,
+			download_url,
+			seats: l.seats,
+			notes: l.notes,
+			tags: l.tags,
+			custom_fields: custom_fields(l.custom_fields),
+			appearance: l.appearance.map(Into::into),
+		})
+	}
+}
+
+impl From<FfiSecureNote> for SecureNote {
+	fn from(n: FfiSecureNote) -> Self {
+		Self {
+			title:         n.title,
+			body:          n.body,
+			tags:          n.tags,
+			custom_fields: custom_fields(n.custom_fields),
+			appearance:    n.appearance.map(Into::into),
+		}
+	}
+}
+
+impl TryFrom<FfiServerCredential> for ServerCredential {
+	type Error = FfiError;
+
+	fn try_from(s: FfiServerCredential) -> Result<Self, FfiError> {
+		let host = s
+			.host
+			.parse::<crate::models::ServerCredentialHost>()
+			.map_err(|e| FfiError::Other { msg: format!("invalid host: {e}") })?;
+
+		Ok(ServerCredential {
+			host,
+			port: s.port,
+			protocol: s.protocol,
+			username: s.username,
+			password: s.password,
+			connection_string: s.connection_string,
+			notes: s.notes,
+			tags: s.tags,
+			custom_fields: custom_fields(s.custom_fields),
+			appearance: s.appearance.map(Into::into),
+		})
+	}
+}
+
+impl TryFrom<FfiEmailAccount> for EmailAccount {
+	type Error = FfiError;
+
+	fn try_from(e: FfiEmailAccount) -> Result<Self, FfiError> {
+		let address = e
+			.address
+			.parse::<email_address::EmailAddress>()
+			.map_err(|err| FfiError::Other { msg: format!("invalid email: {err}") })?;
+
+		let imap = e
+			.imap
+			.map(|s| {
+				let security = s
+					.security
+					.map(|t| {
+						t.parse::<EmailAccountImapSecurity>()
+							.map_err(|_| FfiError::Other { msg: format!("unknown IMAP security: {t}") })
+					})
+					.transpose()?;
+				Ok::<_, FfiError>(EmailAccountImap { host: s.host, port: s.port, security })
+			})
+			.transpose()?;
+
+		let smtp = e
+			.smtp
+			.map(|s| {
+				let security = s
+					.security
+					.map(|t| {
+						t.parse::<EmailAccountSmtpSecurity>()
+							.map_err(|_| FfiError::Other { msg: format!("unknown SMTP security: {t}") })
+					})
+					.transpose()?;
+				Ok::<_, FfiError>(EmailAccountSmtp { host: s.host, port: s.port, security })
+			})
+			.transpose()?;
+
+		Ok(EmailAccount {
+			address,
+			username: e.username,
+			password: e.password,
+			app_password: e.app_password,
+			imap,
+			smtp,
+			notes: e.notes,
+			tags: e.tags,
+			custom_fields: custom_fields(e.custom_fields),
+			appearance: e.appearance.map(Into::into),
+		})
+	}
+}
+
+impl TryFrom<FfiMembership> for Membership {
+	type Error = FfiError;
+
+	fn try_from(m: FfiMembership) -> Result<Self, FfiError> {
+		let program = m
+			.program
+			.parse::<crate::models::MembershipProgram>()
+			.map_err(|e| FfiError::Other { msg: format!("invalid program: {e}") })?;
+
+		let expiry_date = m
+			.expiry_date
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		Ok(Membership {
+			program,
+			member_id: m.member_id,
+			tier: m.tier,
+			member_name: m.member_name,
+			expiry_date,
+			barcode: m.barcode,
+			pin: m.pin,
+			notes: m.notes,
+			tags: m.tags,
+			custom_fields: custom_fields(m.custom_fields),
+			appearance: m.appearance.map(Into::into),
+		})
+	}
+}
+
+impl TryFrom<FfiInsurancePolicy> for InsurancePolicy {
+	type Error = FfiError;
+
+	fn try_from(p: FfiInsurancePolicy) -> Result<Self, FfiError> {
+		let provider = p
+			.provider
+			.parse::<crate::models::InsurancePolicyProvider>()
+			.map_err(|e| FfiError::Other { msg: format!("invalid provider: {e}") })?;
+
+		let coverage_start = p
+			.coverage_start
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		let coverage_end = p
+			.coverage_end
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		let emergency_phone = p
+			.emergency_phone
+			.map(|s| {
+				phonenumber::parse(None, &s)
+					.map_err(|e| FfiError::Other { msg: format!("invalid phone: {e}") })
+			})
+			.transpose()?;
+
+		Ok(InsurancePolicy {
+			provider,
+			policy_number: p.policy_number,
+			group_number: p.group_number,
+			member_id: p.member_id,
+			coverage_start,
+			coverage_end,
+			emergency_phone,
+			notes: p.notes,
+			tags: p.tags,
+			custom_fields: custom_fields(p.custom_fields),
+			appearance: p.appearance.map(Into::into),
+		})
+	}
+}
+
+impl TryFrom<FfiPasskey> for Passkey {
+	type Error = FfiError;
+
+	fn try_from(p: FfiPasskey) -> Result<Self, FfiError> {
+		let rp_id = p
+			.rp_id
+			.parse::<crate::models::PasskeyRpId>()
+			.map_err(|e| FfiError::Other { msg: format!("invalid relying party: {e}") })?;
+
+		let creation_date = p
+			.creation_date
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		Ok(Passkey {
+			rp_id,
+			rp_name: p.rp_name,
+			credential_id: p.credential_id,
+			private_key: p.private_key,
+			user_handle: p.user_handle,
+			user_name: p.user_name,
+			user_display_name: p.user_display_name,
+			creation_date,
+			notes: p.notes,
+			tags: p.tags,
+			custom_fields: custom_fields(p.custom_fields),
+			appearance: p.appearance.map(Into::into),
+		})
+	}
+}
+
+impl TryFrom<FfiPgpKey> for PgpKey {
+	type Error = FfiError;
+
+	fn try_from(k: FfiPgpKey) -> Result<Self, FfiError> {
+		let expiry_date = k
+			.expiry_date
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		let key = PgpKey {
+			private_key: k.private_key,
+			public_key: k.public_key,
+			passphrase: k.passphrase,
+			fingerprint: k.fingerprint,
+			key_id: k.key_id,
+			uids: k.uids,
+			expiry_date,
+			notes: k.notes,
+			tags: k.tags,
+			custom_fields: custom_fields(k.custom_fields),
+			appearance: k.appearance.map(Into::into),
+		};
+		key.validate()?;
+		Ok(key)
+	}
+}
+
+impl TryFrom<FfiDriversLicense> for DriversLicense {
+	type Error = FfiError;
+
+	fn try_from(l: FfiDriversLicense) -> Result<Self, FfiError> {
+		let number = l
+			.number
+			.parse::<crate::models::DriversLicenseNumber>()
+			.map_err(|e| FfiError::Other { msg: format!("invalid licence number: {e}") })?;
+
+		let issuing_country = l
+			.issuing_country
+			.map(|c| {
+				c.parse::<celes::Country>()
+					.map_err(|_| FfiError::Other { msg: format!("unknown country code: {c}") })
+			})
+			.transpose()?;
+
+		let issue_date = l
+			.issue_date
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		let expiry_date = l
+			.expiry_date
+			.map(|d| {
+				d.parse::<jiff::civil::Date>()
+					.map_err(|e| FfiError::Other { msg: format!("invalid date: {e}") })
+			})
+			.transpose()?;
+
+		Ok(DriversLicense {
+			number,
+			legal_name: l.legal_name,
+			license_class: l.license_class,
+			issuing_region: l.issuing_region,
+			issuing_country,
+			issue_date,
+			expiry_date,
+			restrictions: l.restrictions,
+			notes: l.notes,
+			tags: l.tags,
+			custom_fields: custom_fields(l.custom_fields),
+			appearance: l.appearance.map(Into::into),
 		})
 	}
 }
@@ -284,3 +1424,111 @@ fn country_alpha2(c: &celes::Country) -> String {
 		.and_then(|v| v.as_str().map(String::from))
 		.unwrap_or_else(|| format!("{c}"))
 }
+
+fn ffi_custom_fields(fields: Vec<CustomField>) -> Vec<FfiCustomField> {
+	fields
+		.into_iter()
+		.map(|f| FfiCustomField { label: f.label, value: f.value, secret: f.secret })
+		.collect()
+}
+
+fn custom_fields(fields: Vec<FfiCustomField>) -> Vec<CustomField> {
+	fields
+		.into_iter()
+		.map(|f| CustomField { label: f.label, value: f.value, secret: f.secret })
+		.collect()
+}
+
+impl From<PasswordPolicy> for FfiPasswordPolicy {
+	fn from(p: PasswordPolicy) -> Self {
+		Self {
+			max_length:           p.max_length,
+			forbidden_characters: p.forbidden_characters,
+			required_classes:     p.required_classes.iter().map(ToString::to_string).collect(),
+			recipe:               p.recipe,
+		}
+	}
+}
+
+impl TryFrom<FfiPasswordPolicy> for PasswordPolicy {
+	type Error = FfiError;
+
+	fn try_from(p: FfiPasswordPolicy) -> Result<Self, FfiError> {
+		let required_classes = p
+			.required_classes
+			.into_iter()
+			.map(|s| {
+				s.parse::<CharacterClass>()
+					.map_err(|_| FfiError::Other { msg: format!("unknown character class: {s}") })
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		Ok(PasswordPolicy {
+			max_length: p.max_length,
+			forbidden_characters: p.forbidden_characters,
+			required_classes,
+			recipe: p.recipe,
+		})
+	}
+}
+
+impl From<RecoveryCode> for FfiRecoveryCode {
+	fn from(c: RecoveryCode) -> Self { Self { code: c.code, used: c.used } }
+}
+
+impl From<FfiRecoveryCode> for RecoveryCode {
+	fn from(c: FfiRecoveryCode) -> Self { Self { code: c.code, used: c.used } }
+}
+
+impl From<Totp> for FfiTotp {
+	fn from(t: Totp) -> Self {
+		Self {
+			secret:    t.secret,
+			kind:      t.kind.map(|k| k.to_string()),
+			counter:   t.counter,
+			yubikey:   t.yubikey,
+			issuer:    t.issuer,
+			digits:    t.digits,
+			period:    t.period,
+			algorithm: t.algorithm.map(|a| a.to_string()),
+		}
+	}
+}
+
+impl TryFrom<FfiTotp> for Totp {
+	type Error = FfiError;
+
+	fn try_from(t: FfiTotp) -> Result<Self, FfiError> {
+		let kind = t
+			.kind
+			.map(|s| {
+				s.parse::<TotpKind>()
+					.map_err(|_| FfiError::Other { msg: format!("unknown one-time password kind: {s}") })
+			})
+			.transpose()?;
+		let algorithm = t
+			.algorithm
+			.map(|s| {
+				s.parse::<TotpAlgorithm>()
+					.map_err(|_| FfiError::Other { msg: format!("unknown TOTP algorithm: {s}") })
+			})
+			.transpose()?;
+		Ok(Totp {
+			secret: t.secret,
+			kind,
+			counter: t.counter,
+			yubikey: t.yubikey,
+			issuer: t.issuer,
+			digits: t.digits,
+			period: t.period,
+			algorithm,
+		})
+	}
+}
+
+impl From<Appearance> for FfiAppearance {
+	fn from(a: Appearance) -> Self { Self { accent: a.accent, icon: a.icon } }
+}
+
+impl From<FfiAppearance> for Appearance {
+	fn from(a: FfiAppearance) -> Self { Self { accent: a.accent, icon: a.icon } }
+}
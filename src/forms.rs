@@ -0,0 +1,330 @@
+//! Field-by-field forms for the built-in item types.
+//!
+//! Each built-in kind is described with the same [`ItemTemplate`] shape used
+//! for user-defined types, so a front end can render one generic form for
+//! every kind. [`build_item`] turns the filled-in values back into a
+//! validated [`Item`], and [`set_field`] edits one field of an existing item
+//! in place.
+//!
+//! Forms cover the scalar fields a person types in; lists, nested mail
+//! server settings and the like are left for editing afterwards.
+
+use std::collections::BTreeMap;
+
+use crate::{Error, Item, Result, templates::{FieldKind, ItemTemplate, TemplateField}};
+
+fn field(name: &str, kind: FieldKind, required: bool) -> TemplateField {
+	TemplateField { name: name.into(), kind, required, options: vec![] }
+}
+
+fn choice(name: &str, required: bool, options: &[&str]) -> TemplateField {
+	TemplateField {
+		name: name.into(),
+		kind: FieldKind::Enum,
+		required,
+		options: options.iter().map(|o| o.to_string()).collect(),
+	}
+}
+
+fn form(kind: &str, label: &str, fields: Vec<TemplateField>) -> ItemTemplate {
+	ItemTemplate { name: kind.into(), label: Some(label.into()), fields }
+}
+
+/// The form for every built-in item kind, keyed by [`Item::kind_str`].
+pub fn builtin_forms() -> Vec<ItemTemplate> {
+	use FieldKind::*;
+	let notes = || field("notes", Text, false);
+	vec![
+		form("online_account", "Online Account", vec![
+			field("username", Text, false),
+			field("password", Secret, false),
+			field("email", Text, false),
+			field("phone", Text, false),
+			field("host_website", Url, false),
+			notes(),
+		]),
+		form("social_security", "Social Security", vec![
+			field("account_number", Secret, true),
+			field("legal_name", Text, false),
+			field("issuance_date", Date, false),
+			field("country_of_issue", Text, false),
+			notes(),
+		]),
+		form("api_credential", "API Credential", vec![
+			field("token", Secret, true),
+			field("secret", Secret, false),
+			field("service_url", Url, false),
+			field("environment", Text, false),
+			field("expiry_date", Date, false),
+			notes(),
+		]),
+		form("identity_document", "Identity Document", vec![
+			choice("document_type", true, &["Passport", "NationalId", "ResidencePermit", "Other"]),
+			field("number", Secret, true),
+			field("legal_name", Text, false),
+			field("issuing_country", Text, false),
+			field("issue_date", Date, false),
+			field("expiry_date", Date, false),
+			notes(),
+		]),
+		form("wifi_network", "WiFi Network", vec![
+			field("ssid", Text, true),
+			choice("security", false, &["WPA", "WPA3", "WEP", "Open"]),
+			field("passphrase", Secret, false),
+			notes(),
+		]),
+		form("crypto_wallet", "Crypto Wallet", vec![
+			field("seed_phrase", Secret, false),
+			field("passphrase", Secret, false),
+			field("chain", Text, false),
+			notes(),
+		]),
+		form("software_license", "Software License", vec![
+			field("product", Text, true),
+			field("license_key", Secret, false),
+			field("licensee", Text, false),
+			field("purchase_date", Date, false),
+			field("order_number", Text, false),
+			field("download_url", Url, false),
+			field("seats", Number, false),
+			notes(),
+		]),
+		form("secure_note", "Secure Note", vec![field("title", Text, true), field("body", Text, true)]),
+		form("server_credential", "Server", vec![
+			field("host", Text, true),
+			field("port", Number, false),
+			field("protocol", Text, false),
+			field("username", Text, false),
+			field("password", Secret, false),
+			field("connection_string", Text, false),
+			notes(),
+		]),
+		form("email_account", "Email Account", vec![
+			field("address", Text, true),
+			field("username", Text, false),
+			field("password", Secret, false),
+			field("app_password", Secret, false),
+			notes(),
+		]),
+		form("membership", "Membership", vec![
+			field("program", Text, true),
+			field("member_id", Text, false),
+			field("tier", Text, false),
+			field("member_name", Text, false),
+			field("expiry_date", Date, false),
+			field("barcode", Text, false),
+			field("pin", Secret, false),
+			notes(),
+		]),
+		form("insurance_policy", "Insurance Policy", vec![
+			field("provider", Text, true),
+			field("policy_number", Text, false),
+			field("group_number", Text, false),
+			field("member_id", Text, false),
+			field("coverage_start", Date, false),
+			field("coverage_end", Date, false),
+			field("emergency_phone", Text, false),
+			notes(),
+		]),
+		form("passkey", "Passkey", vec![
+			field("rp_id", Text, true),
+			field("rp_name", Text, false),
+			field("credential_id", Text, true),
+			field("private_key", Secret, true),
+			field("user_handle", Text, false),
+			field("user_name", Text, false),
+			field("user_display_name", Text, false),
+			field("creation_date", Date, false),
+			notes(),
+		]),
+		form("pgp_key", "PGP Key", vec![
+			field("private_key", Secret, true),
+			field("public_key", Text, false),
+			field("passphrase", Secret, false),
+			field("fingerprint", Text, false),
+			field("key_id", Text, false),
+			field("expiry_date", Date, false),
+			notes(),
+		]),
+		form("drivers_license", "Driver's License", vec![
+			field("number", Secret, true),
+			field("legal_name", Text, false),
+			field("license_class", Text, false),
+			field("issuing_region", Text, false),
+			field("issuing_country", Text, false),
+			field("issue_date", Date, false),
+			field("expiry_date", Date, false),
+			notes(),
+		]),
+	]
+}
+
+/// The form for one built-in kind.
+pub fn builtin_form(kind: &str) -> Option<ItemTemplate> {
+	builtin_forms().into_iter().find(|f| f.name == kind)
+}
+
+/// Build a validated item of built-in `kind` from form values.
+///
+/// Empty values are treated as absent. Values are checked against the form
+/// first, for field-level messages, then deserialized through the item's own
+/// schema so domain types (emails, phone numbers, countries) are enforced.
+pub fn build_item(kind: &str, values: &BTreeMap<String, String>) -> Result<Item> {
+	let form = builtin_form(kind).ok_or_else(|| Error::Validation {
+		field:  "kind".into(),
+		reason: format!("unknown item kind '{kind}'"),
+	})?;
+	let values: BTreeMap<String, String> = values
+		.iter()
+		.filter(|(_, v)| !v.trim().is_empty())
+		.map(|(k, v)| (k.clone(), v.clone()))
+		.collect();
+	form.validate_fields(&values)?;
+
+	let mut object = serde_json::Map::new();
+	object.insert("kind".into(), kind.into());
+	for (name, value) in values {
+		let json = match form.field(&name).map(|f| f.kind) {
+			Some(FieldKind::Number) => {
+				serde_json::Value::from(value.trim().parse::<i64>().unwrap_or_default())
+			}
+			_ => serde_json::Value::from(value),
+		};
+		object.insert(name, json);
+	}
+	let item: Item = serde_json::from_value(object.into())
+		.map_err(|e| Error::Validation { field: kind.into(), reason: e.to_string() })?;
+	item.validate()?;
+	Ok(item)
+}
+
+/// The form fields of `item` paired with their current values, empty when
+/// unset. Items without a built-in form (template items) have none.
+pub fn editable_fields(item: &Item) -> Vec<(TemplateField, String)> {
+	let Some(form) = builtin_form(item.kind_str()) else { return vec![] };
+	let object = serde_json::to_value(item).unwrap_or_default();
+	form
+		.fields
+		.into_iter()
+		.map(|field| {
+			let value = match object.get(&field.name) {
+				Some(serde_json::Value::String(s)) => s.clone(),
+				Some(serde_json::Value::Null) | None => String::new(),
+				Some(other) => other.to_string(),
+			};
+			(field, value)
+		})
+		.collect()
+}
+
+/// The first non-empty secret form field of `item` (its password, number,
+/// key, ...), used when copying without a specific field chosen.
+pub fn primary_secret(item: &Item) -> Option<(String, String)> {
+	editable_fields(item)
+		.into_iter()
+		.find(|(field, value)| field.kind == FieldKind::Secret && !value.is_empty())
+		.map(|(field, value)| (field.name, value))
+}
+
+/// Return `item` with one form field replaced by `value`, leaving every other
+/// field (including ones the form doesn't cover) untouched.
+///
+/// An empty value clears an optional field. The new value is checked the same
+/// way [`build_item`] checks it.
+pub fn set_field(item: &Item, field: &str, value: &str) -> Result<Item> {
+	let form = builtin_form(item.kind_str()).ok_or_else(|| Error::Validation {
+		field:  field.into(),
+		reason: format!("'{}' items have no editable form", item.kind_str()),
+	})?;
+	let spec = form.field(field).ok_or_else(|| Error::Validation {
+		field:  field.into(),
+		reason: format!("not a field of '{}'", form.name),
+	})?;
+
+	let mut object = serde_json::to_value(item)?;
+	let object = object.as_object_mut().expect("items serialize as objects");
+	if value.trim().is_empty() {
+		if spec.required {
+			return Err(Error::MissingField { field: field.into() });
+		}
+		object.remove(field);
+	} else {
+		spec.validate(value)?;
+		let json = match spec.kind {
+			FieldKind::Number => serde_json::Value::from(value.trim().parse::<i64>().unwrap_or_default()),
+			_ => serde_json::Value::from(value),
+		};
+		object.insert(field.into(), json);
+	}
+	let item: Item = serde_json::from_value(serde_json::Value::Object(object.clone()))
+		.map_err(|e| Error::Validation { field: field.into(), reason: e.to_string() })?;
+	item.validate()?;
+	Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+		pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+	}
+
+	#[test]
+	fn every_builtin_kind_has_a_form_that_builds() {
+		for form in builtin_forms() {
+			let filled = form
+				.fields
+				.iter()
+				.filter(|f| f.required)
+				.map(|f| {
+					let value = match f.kind {
+						FieldKind::Enum => f.options[0].clone(),
+						_ => "user@example.com".to_string(),
+					};
+					(f.name.clone(), value)
+				})
+				.collect();
+			let item = build_item(&form.name, &filled).unwrap();
+			assert_eq!(item.kind_str(), form.name);
+		}
+	}
+
+	#[test]
+	fn build_item_reports_the_failing_field() {
+		let err = build_item("server_credential", &values(&[("host", "db"), ("port", "ssh")]));
+		assert!(matches!(err, Err(Error::Validation { field, .. }) if field == "port"));
+
+		let server = build_item("server_credential", &values(&[("host", "db"), ("port", "22")]));
+		assert!(matches!(server, Ok(Item::ServerCredential(s)) if s.port == Some(22)));
+
+		assert!(build_item("secure_note", &values(&[("title", "t"), ("body", " ")])).is_err());
+	}
+
+	#[test]
+	fn set_field_edits_one_field_and_keeps_the_rest() {
+		let mut account = build_item("online_account", &values(&[("username", "alice")])).unwrap();
+		account.tags_mut().push("work".into());
+
+		let edited = set_field(&account, "email", "alice@example.com").unwrap();
+		let fields: BTreeMap<_, _> =
+			editable_fields(&edited).into_iter().map(|(f, v)| (f.name, v)).collect();
+		assert_eq!(fields["email"], "alice@example.com");
+		assert_eq!(fields["username"], "alice");
+		assert_eq!(edited.tags(), ["work"]);
+
+		assert!(matches!(
+			set_field(&edited, "email", "not an email"),
+			Err(Error::Validation { field, .. }) if field == "email"
+		));
+		assert!(set_field(&edited, "host_website", "example").is_err());
+
+		let cleared = set_field(&edited, "username", "").unwrap();
+		let fields: BTreeMap<_, _> =
+			editable_fields(&cleared).into_iter().map(|(f, v)| (f.name, v)).collect();
+		assert_eq!(fields["username"], "");
+		assert_eq!(primary_secret(&cleared), None);
+		let with_password = set_field(&cleared, "password", "hunter2").unwrap();
+		assert_eq!(primary_secret(&with_password), Some(("password".into(), "hunter2".into())));
+	}
+}
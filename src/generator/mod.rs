@@ -0,0 +1,593 @@
+//! Random password and passphrase generation.
+//!
+//! Secrets are drawn from the operating system's random number generator,
+//! never from a seeded or thread-local one (`SysRng`, rand's `OsRng`).
+
+mod recipe;
+
+use std::sync::OnceLock;
+
+use rand::{RngExt, rand_core::UnwrapErr, rngs::SysRng, seq::IndexedRandom};
+use serde::Deserialize;
+
+pub use self::recipe::Recipe;
+use crate::{Error, Result, models::{CharacterClass, PasswordPolicy}};
+
+const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"0123456789";
+const SYMBOLS: &[u8] = b"!#$%&()*+,-./:;<=>?@[]^_{|}~";
+/// Characters easily mistaken for one another in many fonts.
+const AMBIGUOUS: &[u8] = b"0O1lI|";
+/// Consonants that read unambiguously aloud; no `q`, `x` or `y`.
+const CONSONANTS: &[u8] = b"bcdfghjklmnprstvwz";
+const VOWELS: &[u8] = b"aeiou";
+
+/// Which kind of secret to generate, and how.
+///
+/// In the config a preset is one table naming its `mode` beside that mode's
+/// settings, e.g. `mode = "passphrase"` with `words = 5`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, uniffi::Enum)]
+#[serde(from = "PresetTable")]
+pub enum GeneratorOptions {
+	Password {
+		options: PasswordOptions,
+	},
+	Passphrase {
+		options: PassphraseOptions,
+	},
+	Pronounceable {
+		options: PronounceableOptions,
+	},
+	Pin {
+		options: PinOptions,
+	},
+	/// A [`Recipe`] pattern such as `word-word-####-!`.
+	Recipe {
+		recipe: String,
+	},
+}
+
+impl GeneratorOptions {
+	/// Generate a secret; fails for a malformed recipe or a length too short
+	/// for what the options ask of it.
+	pub fn generate(&self) -> Result<String> {
+		Ok(match self {
+			GeneratorOptions::Password { options } => generate(options)?,
+			GeneratorOptions::Passphrase { options } => generate_passphrase(options),
+			GeneratorOptions::Pronounceable { options } => {
+				check_length(options.length)?;
+				generate_pronounceable(options)
+			}
+			GeneratorOptions::Pin { options } => {
+				check_length(options.length)?;
+				generate_pin(options)
+			}
+			GeneratorOptions::Recipe { recipe } => recipe.parse::<Recipe>()?.generate(),
+		})
+	}
+
+	pub fn entropy_bits(&self) -> u32 {
+		match self {
+			GeneratorOptions::Password { options } => options.entropy_bits(),
+			GeneratorOptions::Passphrase { options } => options.entropy_bits(),
+			GeneratorOptions::Pronounceable { options } => options.entropy_bits(),
+			GeneratorOptions::Pin { options } => options.entropy_bits(),
+			GeneratorOptions::Recipe { recipe } => {
+				recipe.parse::<Recipe>().map_or(0, |recipe| recipe.entropy_bits())
+			}
+		}
+	}
+
+	/// These options making secrets `length` long, switching to a password
+	/// for a mode without a length.
+	pub fn with_length(self, length: u32) -> Self {
+		match self {
+			GeneratorOptions::Password { options } => {
+				GeneratorOptions::Password { options: PasswordOptions { length, ..options } }
+			}
+			GeneratorOptions::Pronounceable { .. } => {
+				GeneratorOptions::Pronounceable { options: PronounceableOptions { length } }
+			}
+			GeneratorOptions::Pin { .. } => GeneratorOptions::Pin { options: PinOptions { length } },
+			GeneratorOptions::Passphrase { .. } | GeneratorOptions::Recipe { .. } => {
+				GeneratorOptions::Password { options: PasswordOptions { length, ..Default::default() } }
+			}
+		}
+	}
+
+	/// These options making passphrases of `words` words, switching to a
+	/// passphrase for any other mode.
+	pub fn with_words(self, words: u32) -> Self {
+		let options = match self {
+			GeneratorOptions::Passphrase { options } => options,
+			_ => PassphraseOptions::default(),
+		};
+		GeneratorOptions::Passphrase { options: PassphraseOptions { words, ..options } }
+	}
+
+	/// These options narrowed to what a site's `policy` accepts. A policy
+	/// with a recipe replaces them outright; passphrases have no length to
+	/// cap and are otherwise left as they are.
+	pub fn within(&self, policy: &PasswordPolicy) -> Self {
+		if let Some(recipe) = &policy.recipe {
+			return GeneratorOptions::Recipe { recipe: recipe.clone() };
+		}
+		let cap = |length: u32| policy.max_length.map_or(length, |max| length.min(max));
+		match self {
+			GeneratorOptions::Password { options } => {
+				GeneratorOptions::Password { options: options.within(policy) }
+			}
+			GeneratorOptions::Passphrase { .. } | GeneratorOptions::Recipe { .. } => self.clone(),
+			GeneratorOptions::Pronounceable { options } => GeneratorOptions::Pronounceable {
+				options: PronounceableOptions { length: cap(options.length) },
+			},
+			GeneratorOptions::Pin { options } => {
+				GeneratorOptions::Pin { options: PinOptions { length: cap(options.length) } }
+			}
+		}
+	}
+}
+
+/// [`GeneratorOptions`] as a config table.
+#[derive(Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case", deny_unknown_fields)]
+enum PresetTable {
+	Password(PasswordOptions),
+	Passphrase(PassphraseOptions),
+	Pronounceable(PronounceableOptions),
+	Pin(PinOptions),
+	Recipe { recipe: String },
+}
+
+impl From<PresetTable> for GeneratorOptions {
+	fn from(table: PresetTable) -> Self {
+		match table {
+			PresetTable::Password(options) => GeneratorOptions::Password { options },
+			PresetTable::Passphrase(options) => GeneratorOptions::Passphrase { options },
+			PresetTable::Pronounceable(options) => GeneratorOptions::Pronounceable { options },
+			PresetTable::Pin(options) => GeneratorOptions::Pin { options },
+			PresetTable::Recipe { recipe } => GeneratorOptions::Recipe { recipe },
+		}
+	}
+}
+
+/// What a generated password is made of.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, uniffi::Record)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasswordOptions {
+	pub length:            u32,
+	pub lower:             bool,
+	pub upper:             bool,
+	pub digits:            bool,
+	pub symbols:           bool,
+	/// Characters never to use, whatever class they are in.
+	pub exclude:           String,
+	/// Leave out look-alikes such as `O`/`0` and `l`/`1`.
+	pub exclude_ambiguous: bool,
+	/// Include at least one character of every enabled class.
+	pub require_each:      bool,
+}
+
+impl Default for PasswordOptions {
+	fn default() -> Self {
+		Self {
+			length:            20,
+			lower:             true,
+			upper:             true,
+			digits:            true,
+			symbols:           true,
+			exclude:           String::new(),
+			exclude_ambiguous: false,
+			require_each:      true,
+		}
+	}
+}
+
+impl PasswordOptions {
+	/// The enabled character classes without excluded (or ambiguous)
+	/// characters, leaving out any that end up empty; lowercase letters when
+	/// none remain.
+	fn classes(&self) -> Vec<Vec<u8>> {
+		let classes: Vec<Vec<u8>> =
+			[(self.lower, LOWER), (self.upper, UPPER), (self.digits, DIGITS), (self.symbols, SYMBOLS)]
+				.into_iter()
+				.filter(|(on, _)| *on)
+				.map(|(_, class)| {
+					let kept = class.iter().copied().filter(|b| !self.excludes(*b));
+					kept.collect::<Vec<_>>()
+				})
+				.filter(|class| !class.is_empty())
+				.collect();
+		if classes.is_empty() { vec![LOWER.to_vec()] } else { classes }
+	}
+
+	fn excludes(&self, b: u8) -> bool {
+		self.exclude.contains(char::from(b)) || (self.exclude_ambiguous && AMBIGUOUS.contains(&b))
+	}
+
+	/// These options narrowed to what a site's `policy` accepts: no longer
+	/// than its maximum, with every class it requires, and without the
+	/// characters it forbids.
+	pub fn within(&self, policy: &PasswordPolicy) -> Self {
+		let mut options = self.clone();
+		if let Some(max) = policy.max_length {
+			options.length = options.length.min(max);
+		}
+		options.require_each |= !policy.required_classes.is_empty();
+		for class in &policy.required_classes {
+			match class {
+				CharacterClass::Lower => options.lower = true,
+				CharacterClass::Upper => options.upper = true,
+				CharacterClass::Digits => options.digits = true,
+				CharacterClass::Symbols => options.symbols = true,
+			}
+		}
+		if let Some(forbidden) = &policy.forbidden_characters {
+			options.exclude.push_str(forbidden);
+		}
+		options
+	}
+
+	/// Bits of entropy in a password generated with these options.
+	pub fn entropy_bits(&self) -> u32 {
+		let alphabet: usize = self.classes().iter().map(|c| c.len()).sum();
+		(self.length as f64 * (alphabet as f64).log2()) as u32
+	}
+}
+
+/// How a diceware passphrase is put together.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, uniffi::Record)]
+#[serde(default, deny_unknown_fields)]
+pub struct PassphraseOptions {
+	pub words:      u32,
+	pub separator:  String,
+	/// Capitalise the first letter of every word.
+	pub capitalize: bool,
+	/// Append a random digit to one randomly chosen word.
+	pub digit:      bool,
+}
+
+impl Default for PassphraseOptions {
+	fn default() -> Self {
+		Self { words: 6, separator: "-".to_string(), capitalize: false, digit: false }
+	}
+}
+
+impl PassphraseOptions {
+	/// Bits of entropy in a passphrase generated with these options.
+	/// Capitalising every word adds none.
+	pub fn entropy_bits(&self) -> u32 {
+		let mut bits = self.words as f64 * (wordlist().len() as f64).log2();
+		if self.digit && self.words > 0 {
+			bits += 10f64.log2() + (self.words as f64).log2();
+		}
+		bits as u32
+	}
+}
+
+/// The embedded diceware wordlist: the EFF long list, 7776 distinct words
+/// picked to be easy to type and remember, so each word is worth about 12.9
+/// bits. Lines may carry their dice roll before the word, as EFF ships them.
+fn wordlist() -> &'static [&'static str] {
+	static WORDS: OnceLock<Vec<&str>> = OnceLock::new();
+	WORDS.get_or_init(|| {
+		include_str!("eff_large_wordlist.txt")
+			.lines()
+			.filter_map(|line| line.split_whitespace().last())
+			.collect()
+	})
+}
+
+/// A lowercase password of alternating consonants and vowels, easy to read
+/// out or type on a TV remote.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, uniffi::Record)]
+#[serde(default, deny_unknown_fields)]
+pub struct PronounceableOptions {
+	pub length: u32,
+}
+
+impl Default for PronounceableOptions {
+	fn default() -> Self { Self { length: 14 } }
+}
+
+impl PronounceableOptions {
+	pub fn entropy_bits(&self) -> u32 {
+		let consonants = self.length.div_ceil(2) as f64 * (CONSONANTS.len() as f64).log2();
+		let vowels = (self.length / 2) as f64 * (VOWELS.len() as f64).log2();
+		(consonants + vowels) as u32
+	}
+}
+
+/// A numeric PIN.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, uniffi::Record)]
+#[serde(default, deny_unknown_fields)]
+pub struct PinOptions {
+	pub length: u32,
+}
+
+impl Default for PinOptions {
+	fn default() -> Self { Self { length: 6 } }
+}
+
+impl PinOptions {
+	pub fn entropy_bits(&self) -> u32 { (self.length as f64 * 10f64.log2()) as u32 }
+}
+
+/// A rough verdict on how hard a password is to guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, uniffi::Enum)]
+pub enum Strength {
+	VeryWeak,
+	Weak,
+	Fair,
+	Strong,
+	VeryStrong,
+}
+
+impl Strength {
+	/// The verdict for a secret with `bits` of entropy.
+	pub fn from_bits(bits: u32) -> Self {
+		match bits {
+			0..28 => Strength::VeryWeak,
+			28..36 => Strength::Weak,
+			36..60 => Strength::Fair,
+			60..128 => Strength::Strong,
+			_ => Strength::VeryStrong,
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			Strength::VeryWeak => "very weak",
+			Strength::Weak => "weak",
+			Strength::Fair => "fair",
+			Strength::Strong => "strong",
+			Strength::VeryStrong => "very strong",
+		}
+	}
+}
+
+/// Generate a random password as `options` describe.
+///
+/// With [`require_each`](PasswordOptions::require_each), every enabled
+/// character class appears at least once, and a length too short to hold
+/// one of each is an error; passwords missing a class are drawn again
+/// rather than patched, so every acceptable password stays equally likely.
+pub fn generate(options: &PasswordOptions) -> Result<String> {
+	check_length(options.length)?;
+	let classes = options.classes();
+	let alphabet: Vec<u8> = classes.concat();
+	let length = options.length as usize;
+	if options.require_each && length < classes.len() {
+		return Err(Error::Validation {
+			field:  "length".into(),
+			reason: format!(
+				"{length} characters cannot hold one of each of the {} required character classes",
+				classes.len()
+			),
+		});
+	}
+	let mut rng = UnwrapErr(SysRng);
+	loop {
+		let password: Vec<u8> =
+			(0..length).map(|_| *alphabet.choose(&mut rng).expect("alphabet is not empty")).collect();
+		let complete = !options.require_each
+			|| classes.iter().all(|class| password.iter().any(|b| class.contains(b)));
+		if complete {
+			return Ok(String::from_utf8(password).expect("alphabet is ASCII"));
+		}
+	}
+}
+
+/// Refuse to generate an empty secret.
+fn check_length(length: u32) -> Result<()> {
+	if length == 0 {
+		return Err(Error::Validation { field: "length".into(), reason: "must be at least 1".into() });
+	}
+	Ok(())
+}
+
+/// Generate a diceware passphrase as `options` describe.
+pub fn generate_passphrase(options: &PassphraseOptions) -> String {
+	let mut rng = UnwrapErr(SysRng);
+	let mut words: Vec<String> = (0..options.words)
+		.map(|_| {
+			let word = *wordlist().choose(&mut rng).expect("wordlist is not empty");
+			if options.capitalize { capitalize(word) } else { word.to_string() }
+		})
+		.collect();
+	if options.digit && !words.is_empty() {
+		let at = rng.random_range(0..words.len());
+		words[at].push(char::from(b'0' + rng.random_range(0..10u8)));
+	}
+	words.join(&options.separator)
+}
+
+/// A made-up answer for a security question: four lowercase words joined
+/// with spaces, easy to read out to a support line yet impossible to look
+/// up, unlike the truth.
+pub fn security_answer() -> String {
+	generate_passphrase(&PassphraseOptions { words: 4, separator: " ".into(), ..Default::default() })
+}
+
+/// Generate a pronounceable password as `options` describe.
+pub fn generate_pronounceable(options: &PronounceableOptions) -> String {
+	let mut rng = UnwrapErr(SysRng);
+	(0..options.length)
+		.map(|i| {
+			let class = if i % 2 == 0 { CONSONANTS } else { VOWELS };
+			char::from(*class.choose(&mut rng).expect("class is not empty"))
+		})
+		.collect()
+}
+
+/// Generate a numeric PIN as `options` describe.
+pub fn generate_pin(options: &PinOptions) -> String {
+	let mut rng = UnwrapErr(SysRng);
+	(0..options.length).map(|_| char::from(*DIGITS.choose(&mut rng).expect("digits"))).collect()
+}
+
+fn capitalize(word: &str) -> String {
+	let mut chars = word.chars();
+	chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+/// Generate a random password of `length` characters from letters and
+/// digits, plus symbols when `symbols` is set.
+pub fn generate_password(length: usize, symbols: bool) -> Result<String> {
+	generate(&PasswordOptions { length: length as u32, symbols, ..Default::default() })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn generated_passwords_use_every_class() {
+		for _ in 0..50 {
+			let password = generate_password(12, true);
+			assert_eq!(password.len(), 12);
+			for class in [LOWER, UPPER, DIGITS, SYMBOLS] {
+				assert!(password.bytes().any(|b| class.contains(&b)), "{password}");
+			}
+		}
+		assert!(generate_password(16, false).bytes().all(|b| b.is_ascii_alphanumeric()));
+	}
+
+	#[test]
+	fn length_and_word_overrides_switch_modes() {
+		let pin = GeneratorOptions::Pin { options: PinOptions::default() };
+		assert_eq!(pin.with_length(8), GeneratorOptions::Pin { options: PinOptions { length: 8 } });
+
+		let recipe = GeneratorOptions::Recipe { recipe: "word-####".into() };
+		let GeneratorOptions::Password { options } = recipe.with_length(24) else { panic!() };
+		assert_eq!(options.length, 24);
+
+		let passphrase = GeneratorOptions::Passphrase {
+			options: PassphraseOptions { separator: " ".into(), ..Default::default() },
+		};
+		let GeneratorOptions::Passphrase { options } = passphrase.with_words(5) else { panic!() };
+		assert_eq!((options.words, options.separator.as_str()), (5, " "));
+	}
+
+	#[test]
+	fn options_choose_classes_and_strength() {
+		let digits = PasswordOptions {
+			length: 6,
+			lower: false,
+			upper: false,
+			symbols: false,
+			..Default::default()
+		};
+		assert!(generate(&digits).unwrap().bytes().all(|b| b.is_ascii_digit()));
+		assert_eq!(digits.entropy_bits(), 19);
+		assert_eq!(Strength::from_bits(digits.entropy_bits()), Strength::VeryWeak);
+
+		let none = PasswordOptions { digits: false, ..digits.clone() };
+		assert!(generate(&none).unwrap().bytes().all(|b| b.is_ascii_lowercase()));
+		let default = PasswordOptions::default();
+		assert_eq!(Strength::from_bits(default.entropy_bits()), Strength::VeryStrong);
+	}
+
+	#[test]
+	fn ambiguous_characters_can_be_left_out() {
+		let options = PasswordOptions { length: 64, exclude_ambiguous: true, ..Default::default() };
+		for _ in 0..20 {
+			let password = generate(&options).unwrap();
+			assert!(!password.bytes().any(|b| AMBIGUOUS.contains(&b)), "{password}");
+		}
+		let clear = PasswordOptions { exclude_ambiguous: true, ..Default::default() };
+		assert!(clear.entropy_bits() < PasswordOptions::default().entropy_bits());
+
+		let plain = PasswordOptions { require_each: false, ..Default::default() };
+		assert_eq!(generate(&plain).unwrap().len(), 20);
+	}
+
+	#[test]
+	fn a_site_policy_narrows_the_options() {
+		let policy = PasswordPolicy {
+			max_length:           Some(12),
+			forbidden_characters: Some("<>&\"'".into()),
+			required_classes:     vec![CharacterClass::Symbols],
+			recipe:               None,
+		};
+		let options =
+			PasswordOptions { symbols: false, require_each: false, ..Default::default() }.within(&policy);
+		assert_eq!(options.length, 12);
+		assert!(options.symbols && options.require_each);
+		for _ in 0..50 {
+			let password = generate(&options).unwrap();
+			assert_eq!(password.len(), 12);
+			assert!(!password.contains(['<', '>', '&', '"', '\'']), "{password}");
+			assert!(password.bytes().any(|b| SYMBOLS.contains(&b)), "{password}");
+		}
+
+		let pin = GeneratorOptions::Pin { options: PinOptions { length: 16 } }.within(&policy);
+		assert_eq!(pin, GeneratorOptions::Pin { options: PinOptions { length: 12 } });
+
+		let quirky = PasswordPolicy { recipe: Some("[0-9]{8}".into()), ..policy };
+		let recipe = GeneratorOptions::Recipe { recipe: "[0-9]{8}".into() };
+		assert_eq!(pin.within(&quirky), recipe);
+		assert_eq!(recipe.entropy_bits(), 26);
+
+		let tiny = PasswordPolicy { max_length: Some(2), recipe: None, ..quirky };
+		let narrowed = PasswordOptions::default().within(&tiny);
+		assert!(
+			matches!(generate(&narrowed), Err(Error::Validation { field, .. }) if field == "length")
+		);
+	}
+
+	#[test]
+	fn empty_secrets_are_refused() {
+		assert!(generate(&PasswordOptions { length: 0, ..Default::default() }).is_err());
+		assert!(GeneratorOptions::Pin { options: PinOptions { length: 0 } }.generate().is_err());
+		let short = PasswordOptions { length: 1, require_each: false, ..Default::default() };
+		assert_eq!(generate(&short).unwrap().len(), 1);
+	}
+
+	#[test]
+	fn passphrases_follow_their_options() {
+		let options = PassphraseOptions {
+			words:      5,
+			separator:  " ".to_string(),
+			capitalize: true,
+			digit:      true,
+		};
+		let phrase = generate_passphrase(&options);
+		let words: Vec<&str> = phrase.split(' ').collect();
+		assert_eq!(words.len(), 5);
+		assert!(words.iter().all(|w| w.starts_with(|c: char| c.is_ascii_uppercase())));
+		assert_eq!(phrase.chars().filter(char::is_ascii_digit).count(), 1);
+		assert_eq!(options.entropy_bits(), 70);
+		assert_eq!(PassphraseOptions::default().entropy_bits(), 77);
+	}
+
+	#[test]
+	fn the_wordlist_is_the_eff_long_list() {
+		let words = wordlist();
+		assert_eq!(words.len(), 7776);
+		assert_eq!((words[0], words[7775]), ("abacus", "zoom"));
+		assert!(words.windows(2).all(|pair| pair[0] < pair[1]));
+	}
+
+	#[test]
+	fn security_answers_are_four_plain_words() {
+		let answer = security_answer();
+		assert_eq!(answer.split(' ').count(), 4);
+		assert!(answer.bytes().all(|b| b.is_ascii_lowercase() || b == b' ' || b == b'-'), "{answer}");
+		assert_ne!(answer, security_answer());
+	}
+
+	#[test]
+	fn pronounceable_passwords_alternate_and_pins_are_digits() {
+		let word = generate_pronounceable(&PronounceableOptions { length: 9 });
+		assert_eq!(word.len(), 9);
+		for (i, b) in word.bytes().enumerate() {
+			assert!(if i % 2 == 0 { CONSONANTS } else { VOWELS }.contains(&b), "{word}");
+		}
+		let pin = generate_pin(&PinOptions { length: 8 });
+		assert_eq!(pin.len(), 8);
+		assert!(pin.bytes().all(|b| b.is_ascii_digit()));
+		assert_eq!(PinOptions { length: 4 }.entropy_bits(), 13);
+	}
+}
@@ -0,0 +1,205 @@
+//! Generator recipes: a pattern spelling out a password's shape, for sites
+//! that insist on a particular format.
+//!
+//! | Token           | Produces                                        |
+//! |-----------------|-------------------------------------------------|
+//! | `word`, `Word`  | a word from the wordlist, capitalised for `Word` |
+//! | `#`             | a digit                                         |
+//! | `a`, `A`        | a lowercase or uppercase letter                 |
+//! | `!`             | a symbol                                        |
+//! | `*`             | a letter or digit                               |
+//! | `[a-f0-9]`      | one character from the set; ranges allowed      |
+//! | `{n}`           | the previous token, `n` times in all            |
+//! | `\x`            | `x` itself                                      |
+//!
+//! Anything else stands for itself, so `word-word-####-!` gives something
+//! like `apple-orbit-2741-%`, and `[0-9a-f]{32}` a 32-digit hex string.
+
+use std::str::FromStr;
+
+use rand::{rand_core::UnwrapErr, rngs::SysRng, seq::IndexedRandom};
+
+use super::{DIGITS, LOWER, SYMBOLS, UPPER, capitalize, wordlist};
+use crate::{Error, Result};
+
+/// The most times `{n}` may repeat a token.
+const MAX_REPEAT: usize = 256;
+
+/// A parsed recipe, ready to generate from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+	tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+	Word { capitalize: bool },
+	Class(Vec<u8>),
+	Literal(char),
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+	Error::Validation { field: "recipe".into(), reason: reason.into() }
+}
+
+impl FromStr for Recipe {
+	type Err = Error;
+
+	fn from_str(recipe: &str) -> Result<Self> {
+		let mut tokens = Vec::new();
+		let mut rest = recipe;
+		while let Some(c) = rest.chars().next() {
+			if let Some(after) = rest.strip_prefix("word") {
+				tokens.push(Token::Word { capitalize: false });
+				rest = after;
+				continue;
+			}
+			if let Some(after) = rest.strip_prefix("Word") {
+				tokens.push(Token::Word { capitalize: true });
+				rest = after;
+				continue;
+			}
+			rest = &rest[c.len_utf8()..];
+			let token = match c {
+				'\\' => {
+					let escaped = rest.chars().next().ok_or_else(|| invalid("ends mid-escape"))?;
+					rest = &rest[escaped.len_utf8()..];
+					Token::Literal(escaped)
+				}
+				'#' => Token::Class(DIGITS.to_vec()),
+				'a' => Token::Class(LOWER.to_vec()),
+				'A' => Token::Class(UPPER.to_vec()),
+				'!' => Token::Class(SYMBOLS.to_vec()),
+				'*' => Token::Class([LOWER, UPPER, DIGITS].concat()),
+				'[' => {
+					let (set, after) =
+						rest.split_once(']').ok_or_else(|| invalid("has a '[' without its ']'"))?;
+					rest = after;
+					Token::Class(charset(set)?)
+				}
+				'{' => {
+					let (count, after) =
+						rest.split_once('}').ok_or_else(|| invalid("has a '{' without its '}'"))?;
+					rest = after;
+					let times = count.trim().parse::<usize>().ok();
+					let times = times.filter(|n| (1..=MAX_REPEAT).contains(n)).ok_or_else(|| {
+						invalid(format!("has a repeat count '{count}' outside 1-{MAX_REPEAT}"))
+					})?;
+					let last = tokens.last().cloned();
+					let last = last.ok_or_else(|| invalid("starts with a repeat"))?;
+					tokens.extend(std::iter::repeat_n(last, times - 1));
+					continue;
+				}
+				other => Token::Literal(other),
+			};
+			tokens.push(token);
+		}
+		if tokens.is_empty() {
+			return Err(invalid("is empty"));
+		}
+		Ok(Self { tokens })
+	}
+}
+
+/// The characters of a `[...]` set, with `a-z` style ranges expanded.
+fn charset(set: &str) -> Result<Vec<u8>> {
+	if !set.is_ascii() {
+		return Err(invalid(format!("character sets take ASCII only, not '[{set}]'")));
+	}
+	let bytes = set.as_bytes();
+	let mut chars = Vec::new();
+	let mut i = 0;
+	while i < bytes.len() {
+		if i + 2 < bytes.len() && bytes[i + 1] == b'-' {
+			let (from, to) = (bytes[i], bytes[i + 2]);
+			if from > to {
+				let range = &set[i..i + 3];
+				return Err(invalid(format!("has a backwards range '{range}'")));
+			}
+			chars.extend(from..=to);
+			i += 3;
+		} else {
+			chars.push(bytes[i]);
+			i += 1;
+		}
+	}
+	chars.sort_unstable();
+	chars.dedup();
+	if chars.is_empty() {
+		return Err(invalid("has an empty character set '[]'"));
+	}
+	Ok(chars)
+}
+
+impl Recipe {
+	/// Generate a password following the recipe.
+	pub fn generate(&self) -> String {
+		let mut rng = UnwrapErr(SysRng);
+		let mut out = String::new();
+		for token in &self.tokens {
+			match token {
+				Token::Word { capitalize: upper } => {
+					let word = *wordlist().choose(&mut rng).expect("wordlist is not empty");
+					out.push_str(&if *upper { capitalize(word) } else { word.to_string() });
+				}
+				Token::Class(class) => {
+					out.push(char::from(*class.choose(&mut rng).expect("classes are not empty")))
+				}
+				Token::Literal(c) => out.push(*c),
+			}
+		}
+		out
+	}
+
+	/// Bits of entropy in a password generated from the recipe.
+	pub fn entropy_bits(&self) -> u32 {
+		let bits: f64 = self
+			.tokens
+			.iter()
+			.map(|token| match token {
+				Token::Word { .. } => (wordlist().len() as f64).log2(),
+				Token::Class(class) => (class.len() as f64).log2(),
+				Token::Literal(_) => 0.0,
+			})
+			.sum();
+		bits as u32
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn recipes_shape_the_password() {
+		let recipe: Recipe = "Word-word-####-!".parse().unwrap();
+		for _ in 0..20 {
+			let password = recipe.generate();
+			let parts: Vec<&str> = password.split('-').collect();
+			assert!(parts[0].starts_with(|c: char| c.is_ascii_uppercase()), "{password}");
+			assert!(parts[1].bytes().all(|b| b.is_ascii_lowercase()), "{password}");
+			assert!(parts[2].len() == 4 && parts[2].bytes().all(|b| b.is_ascii_digit()));
+			assert!(SYMBOLS.contains(&password.as_bytes()[password.len() - 1]), "{password}");
+		}
+		assert_eq!(recipe.entropy_bits(), 43);
+
+		let hex: Recipe = "[0-9a-f]{32}".parse().unwrap();
+		let password = hex.generate();
+		assert_eq!(password.len(), 32);
+		assert!(password.bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase()));
+		assert_eq!(hex.entropy_bits(), 128);
+
+		assert_eq!("\\#\\a".parse::<Recipe>().unwrap().generate(), "#a");
+	}
+
+	#[test]
+	fn malformed_recipes_are_rejected() {
+		for recipe in ["", "[a-z", "#{", "{3}", "#{0}", "#{999}", "[z-a]", "ab\\"] {
+			let parsed = recipe.parse::<Recipe>();
+			assert!(
+				matches!(parsed, Err(Error::Validation { field, .. }) if field == "recipe"),
+				"{recipe:?}"
+			);
+		}
+	}
+}
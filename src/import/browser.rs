@@ -0,0 +1,147 @@
+//! Entries from the password CSV a browser exports: Chrome, Edge and the
+//! other Chromium browsers write `name,url,username,password,note`, Firefox
+//! writes `url,username,password,httpRealm,...,timePasswordChanged`. The
+//! header tells them apart, so one reader takes both.
+//!
+//! Browsers keep one row per sign-in form, so the same account often shows
+//! up several times; rows with the same site and username are merged,
+//! keeping the most recently changed password where the export says when.
+//! Entries are named after the site's host, without `www.`, and Android
+//! apps after the name the browser shows.
+
+use std::{collections::HashMap, io::Read, path::Path};
+
+use super::{Imported, Login, entry_name};
+use crate::{Error, Result};
+
+struct Row {
+	name:     String,
+	url:      String,
+	username: String,
+	password: String,
+	note:     String,
+	/// When the password last changed, in milliseconds since the epoch.
+	changed:  Option<i64>,
+}
+
+/// Read the browser export at `path`.
+pub fn read_file(path: &Path) -> Result<Vec<Imported>> { read_csv(std::fs::File::open(path)?) }
+
+/// Read a browser password export from `reader`.
+pub fn read_csv(reader: impl Read) -> Result<Vec<Imported>> {
+	let csv_err = |e: ::csv::Error| Error::Import(e.to_string());
+	let mut reader = ::csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+	let headers = reader.headers().map_err(csv_err)?.clone();
+	let column = |name: &str| headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+	let (Some(url), Some(username), Some(password)) =
+		(column("url"), column("username"), column("password"))
+	else {
+		return Err(Error::Import(
+			"not a browser password export: no url, username and password columns".into(),
+		));
+	};
+	let (name, note, changed) = (column("name"), column("note"), column("timePasswordChanged"));
+
+	let mut rows: Vec<Row> = vec![];
+	let mut seen: HashMap<(String, String), usize> = HashMap::new();
+	for record in reader.records() {
+		let record = record.map_err(csv_err)?;
+		let get =
+			|index: Option<usize>| index.and_then(|i| record.get(i)).unwrap_or_default().to_string();
+		let row = Row {
+			name:     get(name),
+			url:      get(Some(url)),
+			username: get(Some(username)),
+			password: get(Some(password)),
+			note:     get(note),
+			changed:  get(changed).trim().parse().ok(),
+		};
+		if row.username.is_empty() && row.password.is_empty() {
+			continue;
+		}
+		let key = (site(&row.url), row.username.clone());
+		match seen.get(&key) {
+			Some(&index) if row.changed > rows[index].changed => rows[index] = row,
+			Some(_) => {}
+			None => {
+				seen.insert(key, rows.len());
+				rows.push(row);
+			}
+		}
+	}
+
+	rows
+		.into_iter()
+		.map(|row| {
+			// Android apps have URLs like android://<hash>@com.example/;
+			// their name column is the better name.
+			let host = url::Url::parse(&row.url)
+				.ok()
+				.filter(|url| matches!(url.scheme(), "http" | "https"))
+				.and_then(|url| url.host_str().map(|h| h.trim_start_matches("www.").to_string()))
+				.filter(|host| !host.is_empty());
+			let name = match host.as_deref() {
+				Some(host) => entry_name(host)?,
+				None if !row.name.trim().is_empty() => entry_name(&row.name)?,
+				None => entry_name("Untitled")?,
+			};
+			let login = Login {
+				username: Some(row.username),
+				password: Some(row.password),
+				url: Some(row.url),
+				notes: Some(row.note),
+				..Default::default()
+			};
+			Ok(Imported::new(name, login.into_item()))
+		})
+		.collect()
+}
+
+/// The part of `url` two rows must share to be the same site: its origin,
+/// or the whole URL when it has none.
+fn site(url: &str) -> String {
+	match url::Url::parse(url.trim()) {
+		Ok(parsed) if parsed.has_host() => parsed.origin().ascii_serialization(),
+		_ => url.trim().to_lowercase(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Item;
+
+	fn password(entry: &Imported) -> Option<&str> {
+		let Item::OnlineAccount(account) = &entry.item else { panic!("not a login") };
+		account.password.as_deref()
+	}
+
+	#[test]
+	fn chrome_rows_for_the_same_account_are_merged() {
+		let csv = "name,url,username,password,note\n\
+			github.com,https://github.com/login,alice,hunter2,\n\
+			github.com,https://github.com/session,alice,hunter2,\n\
+			github.com,https://www.github.com/,bob,letmein,work\n\
+			Slack,android://abc@com.Slack/,carol,pw,\n";
+		let entries = read_csv(csv.as_bytes()).unwrap();
+		let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+		assert_eq!(names, ["github-com", "github-com", "Slack"]);
+		assert_eq!(password(&entries[1]), Some("letmein"));
+	}
+
+	#[test]
+	fn firefox_keeps_the_newest_password() {
+		let csv = "\"url\",\"username\",\"password\",\"httpRealm\",\"formActionOrigin\",\"guid\",\"timeCreated\",\"timeLastUsed\",\"timePasswordChanged\"\n\
+			\"https://example.com\",\"alice\",\"old\",,\"https://example.com\",\"{1}\",\"1\",\"1\",\"1000\"\n\
+			\"https://example.com\",\"alice\",\"new\",,\"https://example.com\",\"{2}\",\"1\",\"1\",\"2000\"\n";
+		let entries = read_csv(csv.as_bytes()).unwrap();
+		assert_eq!(entries.len(), 1);
+		assert_eq!(entries[0].name.as_str(), "example-com");
+		assert_eq!(password(&entries[0]), Some("new"));
+	}
+
+	#[test]
+	fn other_csv_files_are_refused() {
+		assert!(read_csv("title,secret\na,b\n".as_bytes()).is_err());
+	}
+}
@@ -0,0 +1,276 @@
+//! Entries from any CSV file, for managers without a dedicated importer.
+//!
+//! Each column is mapped to a [`Column`]: the entry name, its folder, its
+//! tags, a form field of the chosen kind, a custom field named after the
+//! header, or nothing. [`guess`] proposes a mapping from the headers, which
+//! the user then corrects before [`entries`] turns the rows into items.
+//! Online accounts are built like the other importers' logins, so they take
+//! a `totp` column as well.
+
+use std::{collections::BTreeMap, fmt, io::Read, path::Path, str::FromStr};
+
+use super::{Imported, Login, build_leniently, entry_name};
+use crate::{Error, FieldKind, Item, Result, forms::builtin_form, models::CustomField};
+
+/// What one column of the file becomes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+	/// The entry name; with several, the first non-empty one.
+	Name,
+	/// A folder to put the entry in.
+	Folder,
+	/// Tags, separated by commas or semicolons.
+	Tags,
+	/// A form field of the kind being imported.
+	Field(String),
+	/// A custom field labelled with the column's header.
+	Custom,
+	/// Left out.
+	Skip,
+}
+
+impl FromStr for Column {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		Ok(match s.trim() {
+			"name" => Self::Name,
+			"folder" => Self::Folder,
+			"tags" => Self::Tags,
+			"custom" => Self::Custom,
+			"skip" => Self::Skip,
+			"" => return Err(Error::Import("empty column target".into())),
+			field => Self::Field(field.to_string()),
+		})
+	}
+}
+
+impl fmt::Display for Column {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Name => f.write_str("name"),
+			Self::Folder => f.write_str("folder"),
+			Self::Tags => f.write_str("tags"),
+			Self::Field(field) => f.write_str(field),
+			Self::Custom => f.write_str("custom"),
+			Self::Skip => f.write_str("skip"),
+		}
+	}
+}
+
+/// A CSV file read into memory: its header and data rows.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+	pub headers: Vec<String>,
+	pub rows:    Vec<Vec<String>>,
+}
+
+/// Read the CSV file at `path`.
+pub fn read_file(path: &Path) -> Result<Table> { read_table(std::fs::File::open(path)?) }
+
+/// Read a CSV file, with a header row, from `reader`.
+pub fn read_table(reader: impl Read) -> Result<Table> {
+	let csv_err = |e: ::csv::Error| Error::Import(e.to_string());
+	let mut reader = ::csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+	let headers = reader.headers().map_err(csv_err)?.iter().map(str::to_string).collect();
+	let rows = reader
+		.records()
+		.map(|record| Ok(record.map_err(csv_err)?.iter().map(str::to_string).collect()))
+		.collect::<Result<_>>()?;
+	Ok(Table { headers, rows })
+}
+
+/// The form fields a column can be mapped to for items of `kind`.
+pub fn fields(kind: &str) -> Result<Vec<String>> {
+	let form =
+		builtin_form(kind).ok_or_else(|| Error::Import(format!("unknown item kind '{kind}'")))?;
+	let mut fields: Vec<String> = form.fields.into_iter().map(|f| f.name).collect();
+	if kind == "online_account" {
+		fields.push("totp".into());
+	}
+	Ok(fields)
+}
+
+/// A first guess at what the column headed `header` holds.
+pub fn guess(kind: &str, header: &str) -> Column {
+	let key: String =
+		header.chars().filter(char::is_ascii_alphanumeric).collect::<String>().to_lowercase();
+	let field = |name: &str| {
+		let valid = fields(kind).is_ok_and(|fields| fields.iter().any(|f| f == name));
+		if valid { Column::Field(name.into()) } else { Column::Custom }
+	};
+	match key.as_str() {
+		"name" | "title" | "account" | "entry" | "item" => Column::Name,
+		"folder" | "group" | "grouping" | "path" | "vault" | "category" => Column::Folder,
+		"tags" | "tag" | "labels" => Column::Tags,
+		"username" | "user" | "login" | "loginname" | "userid" | "loginusername" => field("username"),
+		"password" | "pass" | "loginpassword" => field("password"),
+		"email" | "mail" | "emailaddress" => field("email"),
+		"url" | "website" | "site" | "loginuri" | "loginurl" | "web" => field("host_website"),
+		"notes" | "note" | "comments" | "comment" | "extra" | "description" => field("notes"),
+		"totp" | "otp" | "otpauth" | "2fa" | "logintotp" | "authenticatorkey" => field("totp"),
+		_ => {
+			let fields = fields(kind).unwrap_or_default();
+			match fields.into_iter().find(|f| f.replace('_', "") == key) {
+				Some(field) => Column::Field(field),
+				None => Column::Custom,
+			}
+		}
+	}
+}
+
+/// Check `mapping` against the file and `kind`: one entry per header, a
+/// name column, and only fields the kind has.
+pub fn check(table: &Table, kind: &str, mapping: &[Column]) -> Result<()> {
+	if mapping.len() != table.headers.len() {
+		return Err(Error::Import(format!(
+			"{} columns mapped, but the file has {}",
+			mapping.len(),
+			table.headers.len()
+		)));
+	}
+	if !mapping.contains(&Column::Name) {
+		return Err(Error::Import("no column is mapped to the entry name".into()));
+	}
+	let fields = fields(kind)?;
+	match mapping.iter().find(|c| matches!(c, Column::Field(f) if !fields.contains(f))) {
+		Some(unknown) => {
+			Err(Error::Import(format!("'{kind}' has no field '{unknown}'; it has {}", fields.join(", "))))
+		}
+		None => Ok(()),
+	}
+}
+
+/// The entries for every row of `table`, mapped by `mapping`. Rows without
+/// a name are called "Untitled"; values a field rejects are kept as custom
+/// fields.
+pub fn entries(table: &Table, kind: &str, mapping: &[Column]) -> Result<Vec<Imported>> {
+	check(table, kind, mapping)?;
+	let mut out = vec![];
+	for (index, row) in table.rows.iter().enumerate() {
+		if row.iter().all(|cell| cell.trim().is_empty()) {
+			continue;
+		}
+		let mut name = None;
+		let mut folder = None;
+		let mut tags = vec![];
+		let mut values = BTreeMap::new();
+		let mut custom_fields = vec![];
+		for ((header, column), value) in table.headers.iter().zip(mapping).zip(row) {
+			let value = value.trim();
+			if value.is_empty() {
+				continue;
+			}
+			match column {
+				Column::Name => {
+					name.get_or_insert(value);
+				}
+				Column::Folder => {
+					folder.get_or_insert(value);
+				}
+				Column::Tags => tags.extend(
+					value.split([',', ';']).map(str::trim).filter(|t| !t.is_empty()).map(str::to_string),
+				),
+				Column::Field(field) => {
+					values.insert(field.clone(), value.to_string());
+				}
+				Column::Custom => custom_fields.push(CustomField {
+					label:  header.clone(),
+					value:  value.to_string(),
+					secret: false,
+				}),
+				Column::Skip => {}
+			}
+		}
+		let name = name.unwrap_or("Untitled");
+		let name = match folder {
+			Some(folder) => entry_name(&format!("{folder}/{name}"))?,
+			None => entry_name(name)?,
+		};
+		// The header is line 1.
+		let mut item = build(kind, values, &mut custom_fields)
+			.map_err(|e| Error::Import(format!("line {}: {e}", index + 2)))?;
+		item.custom_fields_mut().extend(custom_fields);
+		item.tags_mut().extend(tags);
+		out.push(Imported::new(name, item));
+	}
+	Ok(out)
+}
+
+/// The item of `kind` for one row's field values, adding any value it
+/// rejects to `custom_fields`.
+fn build(
+	kind: &str,
+	mut values: BTreeMap<String, String>,
+	custom_fields: &mut Vec<CustomField>,
+) -> Result<Item> {
+	if kind == "online_account" {
+		let mut take = |field: &str| values.remove(field);
+		return Ok(
+			Login {
+				username: take("username"),
+				password: take("password"),
+				email: take("email"),
+				url: take("host_website"),
+				totp: take("totp"),
+				notes: take("notes"),
+				custom_fields: values
+					.into_iter()
+					.map(|(label, value)| CustomField { label, value, secret: false })
+					.collect(),
+				..Default::default()
+			}
+			.into_item(),
+		);
+	}
+	let secret = |field: &str| {
+		builtin_form(kind)
+			.and_then(|form| form.field(field).map(|f| f.kind == FieldKind::Secret))
+			.unwrap_or(false)
+	};
+	let (item, rejected) = build_leniently(kind, values)?;
+	custom_fields.extend(rejected.into_iter().map(|(label, value)| CustomField {
+		secret: secret(&label),
+		label,
+		value,
+	}));
+	Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn headers_are_guessed() {
+		let guesses: Vec<_> = ["Title", "Login Name", "Password", "Web Site", "Group", "Favorite"]
+			.iter()
+			.map(|h| guess("online_account", h).to_string())
+			.collect();
+		assert_eq!(guesses, ["name", "username", "password", "host_website", "folder", "custom"]);
+		assert_eq!(guess("wifi_network", "SSID"), Column::Field("ssid".into()));
+		assert_eq!(guess("wifi_network", "username"), Column::Custom);
+	}
+
+	#[test]
+	fn rows_become_entries() {
+		let table = read_table(
+			"Title,User,Pass,Group,Labels,PIN\nBank,alice,hunter2,money,\"finance, home\",1234\n,bob,pw,,,\n"
+				.as_bytes(),
+		)
+		.unwrap();
+		let mapping: Vec<Column> = ["name", "username", "password", "folder", "tags", "custom"]
+			.iter()
+			.map(|c| c.parse().unwrap())
+			.collect();
+		let entries = entries(&table, "online_account", &mapping).unwrap();
+		assert_eq!(entries[0].name.as_str(), "money/Bank");
+		assert_eq!(entries[0].item.tags(), ["finance", "home"]);
+		assert_eq!(entries[0].item.custom_fields()[0].label, "PIN");
+		assert_eq!(entries[1].name.as_str(), "Untitled");
+
+		let unknown = ["name", "ssid", "password", "folder", "tags", "custom"];
+		let unknown: Vec<Column> = unknown.iter().map(|c| c.parse().unwrap()).collect();
+		assert!(check(&table, "online_account", &unknown).is_err());
+	}
+}
@@ -0,0 +1,376 @@
+//! Entries from the FIDO Alliance's Credential Exchange Format (CXF), the
+//! JSON document managers such as Apple Passwords, 1Password and Bitwarden
+//! hand each other passkeys and passwords in. Only the format is read here;
+//! the Credential Exchange Protocol that carries it between apps is not
+//! spoken, so the export has to reach us as a file.
+//!
+//! Each item's basic-auth, TOTP and note credentials become one online
+//! account, and each passkey credential a passkey named after the item, with
+//! ` passkey` added when the item has a login too. An item with only a note
+//! becomes a secure note. Credentials of other types are kept, as JSON, in
+//! secret custom fields. Collections become folders.
+//!
+//! The types here are shared with [`crate::export::cxf`].
+
+use std::{collections::{BTreeMap, HashMap}, io::Read, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Imported, Login, entry_name};
+use crate::{Error, Item, Result, forms::build_item, models::{CustomField, Totp}};
+
+/// The document a CXF export holds.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Header {
+	pub version:               Version,
+	pub exporter_rp_id:        String,
+	pub exporter_display_name: String,
+	/// Seconds since the epoch.
+	pub timestamp:             i64,
+	pub accounts:              Vec<Account>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Version {
+	pub major: u8,
+	pub minor: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Account {
+	pub id:          String,
+	#[serde(default)]
+	pub username:    String,
+	#[serde(default)]
+	pub email:       String,
+	#[serde(default)]
+	pub collections: Vec<Collection>,
+	#[serde(default)]
+	pub items:       Vec<CxfItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Collection {
+	pub id:             String,
+	pub title:          String,
+	#[serde(default)]
+	pub items:          Vec<LinkedItem>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub subcollections: Vec<Collection>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct LinkedItem {
+	pub item: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CxfItem {
+	pub id:          String,
+	/// Seconds since the epoch.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub creation_at: Option<i64>,
+	pub title:       String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub scope:       Option<Scope>,
+	/// Kept as JSON so credentials of types not read here survive.
+	pub credentials: Vec<Value>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags:        Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Scope {
+	#[serde(default)]
+	pub urls: Vec<String>,
+}
+
+/// The credential types read and written here.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub(crate) enum Credential {
+	BasicAuth {
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		username: Option<Field>,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		password: Option<Field>,
+	},
+	Passkey(PasskeyCredential),
+	Totp(TotpCredential),
+	Note {
+		content: Field,
+	},
+}
+
+/// A value with its type; early drafts of the format wrote bare strings.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum Field {
+	Typed {
+		#[serde(rename = "fieldType")]
+		field_type: String,
+		value:      String,
+	},
+	Bare(String),
+}
+
+impl Field {
+	pub fn new(field_type: &str, value: impl Into<String>) -> Self {
+		Self::Typed { field_type: field_type.into(), value: value.into() }
+	}
+
+	pub fn value(&self) -> &str {
+		match self {
+			Self::Typed { value, .. } | Self::Bare(value) => value,
+		}
+	}
+}
+
+/// Binary values are base64url-encoded without padding, as in our
+/// passkeys, and `key` is the PKCS#8 private key.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PasskeyCredential {
+	pub credential_id:     String,
+	pub rp_id:             String,
+	#[serde(default)]
+	pub username:          String,
+	#[serde(default)]
+	pub user_display_name: String,
+	#[serde(default)]
+	pub user_handle:       String,
+	pub key:               String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct TotpCredential {
+	/// Base32.
+	pub secret:    String,
+	#[serde(default = "default_period")]
+	pub period:    u32,
+	#[serde(default = "default_digits")]
+	pub digits:    u8,
+	#[serde(default = "default_algorithm")]
+	pub algorithm: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub issuer:    Option<String>,
+}
+
+fn default_period() -> u32 { 30 }
+
+fn default_digits() -> u8 { 6 }
+
+fn default_algorithm() -> String { "sha1".into() }
+
+/// Read the CXF export at `path`.
+pub fn read_file(path: &Path) -> Result<Vec<Imported>> { read_json(std::fs::File::open(path)?) }
+
+/// Read a CXF export from `reader`.
+pub fn read_json(reader: impl Read) -> Result<Vec<Imported>> {
+	let header: Header =
+		serde_json::from_reader(reader).map_err(|e| Error::Import(format!("not a CXF export: {e}")))?;
+	if header.version.major != 1 {
+		return Err(Error::Import(format!(
+			"CXF version {}.{} is not supported",
+			header.version.major, header.version.minor
+		)));
+	}
+	let mut entries = vec![];
+	for account in header.accounts {
+		let mut folders = HashMap::new();
+		for collection in &account.collections {
+			collect_folders(collection, "", &mut folders);
+		}
+		for item in account.items {
+			let folder = folders.get(&item.id).cloned();
+			entries.extend(from_item(item, folder.as_deref())?);
+		}
+	}
+	Ok(entries)
+}
+
+/// Record the folder of every item in `collection` and its subcollections,
+/// under `parent`. An item in several collections keeps the first.
+fn collect_folders(collection: &Collection, parent: &str, folders: &mut HashMap<String, String>) {
+	let path = match parent {
+		"" => collection.title.clone(),
+		parent => format!("{parent}/{}", collection.title),
+	};
+	for linked in &collection.items {
+		folders.entry(linked.item.clone()).or_insert_with(|| path.clone());
+	}
+	for sub in &collection.subcollections {
+		collect_folders(sub, &path, folders);
+	}
+}
+
+/// The entries for one item.
+fn from_item(item: CxfItem, folder: Option<&str>) -> Result<Vec<Imported>> {
+	let mut login = Login { tags: item.tags.clone(), ..Default::default() };
+	let mut totp = None;
+	let mut note = None;
+	let mut passkeys = vec![];
+	let mut others = vec![];
+	for value in item.credentials {
+		match serde_json::from_value(value.clone()) {
+			Ok(Credential::BasicAuth { username, password }) => {
+				login.username = username.map(|f| f.value().to_string());
+				login.password = password.map(|f| f.value().to_string());
+			}
+			Ok(Credential::Passkey(passkey)) => passkeys.push(passkey),
+			Ok(Credential::Totp(credential)) => totp = Some(credential),
+			Ok(Credential::Note { content }) => note = Some(content.value().to_string()),
+			Err(_) => others.push(value),
+		}
+	}
+	let has_login = login.username.is_some() || login.password.is_some() || totp.is_some();
+	login.url = item.scope.and_then(|scope| scope.urls.into_iter().next());
+
+	let title = match item.title.trim() {
+		"" => passkeys.first().map_or("Untitled", |p| p.rp_id.as_str()).to_string(),
+		title => title.to_string(),
+	};
+	let name = |suffix: &str| match folder {
+		Some(folder) => entry_name(&format!("{folder}/{title}{suffix}")),
+		None => entry_name(&format!("{title}{suffix}")),
+	};
+
+	let mut items = vec![];
+	if has_login {
+		login.notes = note.take();
+		let mut account = login.into_item();
+		if let (Item::OnlineAccount(account), Some(credential)) = (&mut account, totp) {
+			account.totp = Some(to_totp(credential)?);
+		}
+		items.push((name("")?, account));
+	}
+	let created = item
+		.creation_at
+		.and_then(|s| jiff::Timestamp::from_second(s).ok())
+		.map(|t| t.to_zoned(jiff::tz::TimeZone::UTC).date().to_string());
+	for passkey in passkeys {
+		let mut values = BTreeMap::from([
+			("rp_id".to_string(), passkey.rp_id),
+			("credential_id".to_string(), passkey.credential_id),
+			("private_key".to_string(), passkey.key),
+			("user_handle".to_string(), passkey.user_handle),
+			("user_name".to_string(), passkey.username),
+			("user_display_name".to_string(), passkey.user_display_name),
+			("rp_name".to_string(), item.title.trim().to_string()),
+			("notes".to_string(), note.take().unwrap_or_default()),
+			("creation_date".to_string(), created.clone().unwrap_or_default()),
+		]);
+		values.retain(|_, value| !value.is_empty());
+		let mut passkey = build_item("passkey", &values)?;
+		passkey.tags_mut().extend(item.tags.iter().cloned());
+		items.push((name(if has_login { " passkey" } else { "" })?, passkey));
+	}
+	if items.is_empty() {
+		let values = BTreeMap::from([
+			("title".to_string(), title.clone()),
+			("body".to_string(), note.unwrap_or_else(|| title.clone())),
+		]);
+		let mut secure_note = build_item("secure_note", &values)?;
+		secure_note.tags_mut().extend(item.tags);
+		items.push((name("")?, secure_note));
+	}
+
+	if let Some((_, first)) = items.first_mut() {
+		first.custom_fields_mut().extend(others.into_iter().map(|value| CustomField {
+			label:  value.get("type").and_then(Value::as_str).unwrap_or("credential").to_string(),
+			value:  value.to_string(),
+			secret: true,
+		}));
+	}
+	Ok(items.into_iter().map(|(name, item)| Imported::new(name, item)).collect())
+}
+
+fn to_totp(credential: TotpCredential) -> Result<Totp> {
+	let algorithm =
+		credential.algorithm.to_ascii_uppercase().parse().map_err(|_| {
+			Error::Import(format!("unsupported TOTP algorithm '{}'", credential.algorithm))
+		})?;
+	Ok(Totp {
+		digits: Some(credential.digits),
+		period: Some(credential.period),
+		algorithm: Some(algorithm),
+		issuer: credential.issuer,
+		..Totp::new(credential.secret)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const EXPORT: &str = r#"{
+		"version": { "major": 1, "minor": 0 },
+		"exporterRpId": "example.com",
+		"exporterDisplayName": "Example",
+		"timestamp": 1705228800,
+		"accounts": [{
+			"id": "YWNjb3VudA",
+			"username": "",
+			"email": "alice@example.com",
+			"collections": [{ "id": "Y29s", "title": "Work", "items": [{ "item": "aXRlbTE" }] }],
+			"items": [
+				{
+					"id": "aXRlbTE",
+					"creationAt": 1705228800,
+					"title": "GitHub",
+					"scope": { "urls": ["https://github.com/"], "androidApps": [] },
+					"credentials": [
+						{
+							"type": "basic-auth",
+							"username": { "fieldType": "string", "value": "alice" },
+							"password": { "fieldType": "concealed-string", "value": "hunter2" }
+						},
+						{ "type": "totp", "secret": "JBSWY3DPEHPK3PXP", "period": 30, "digits": 6, "algorithm": "sha1" },
+						{
+							"type": "passkey",
+							"credentialId": "Y3JlZA",
+							"rpId": "github.com",
+							"username": "alice",
+							"userDisplayName": "Alice",
+							"userHandle": "dXNlcg",
+							"key": "a2V5"
+						}
+					]
+				},
+				{
+					"id": "aXRlbTI",
+					"title": "Visa",
+					"credentials": [{ "type": "credit-card", "number": { "fieldType": "string", "value": "4111" } }]
+				}
+			]
+		}]
+	}"#;
+
+	#[test]
+	fn logins_and_passkeys_are_split() {
+		let entries = read_json(EXPORT.as_bytes()).unwrap();
+		let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+		assert_eq!(names, ["Work/GitHub", "Work/GitHub passkey", "Visa"]);
+
+		let Item::OnlineAccount(account) = &entries[0].item else { panic!("not a login") };
+		assert_eq!(account.password.as_deref(), Some("hunter2"));
+		assert_eq!(account.totp.as_ref().map(|t| t.secret.as_str()), Some("JBSWY3DPEHPK3PXP"));
+
+		let Item::Passkey(passkey) = &entries[1].item else { panic!("not a passkey") };
+		assert_eq!(passkey.private_key, "a2V5");
+		assert_eq!(passkey.creation_date.map(|d| d.to_string()).as_deref(), Some("2024-01-14"));
+
+		assert_eq!(entries[2].item.kind_str(), "secure_note");
+		assert_eq!(entries[2].item.custom_fields()[0].label, "credit-card");
+	}
+
+	#[test]
+	fn other_versions_are_refused() {
+		let export = EXPORT.replace("\"major\": 1", "\"major\": 2");
+		assert!(read_json(export.as_bytes()).is_err());
+	}
+}
@@ -0,0 +1,58 @@
+//! An entry exported on its own by [`crate::export::item`], sealed or in
+//! plaintext.
+
+use base64::Engine as _;
+
+use super::Imported;
+use crate::{EncryptionMethod, Error, Result, export::item::{Bundle, FORMAT}, models::AccountName};
+
+/// Whether `bytes` are a sealed item file rather than plaintext TOML.
+pub fn is_sealed(bytes: &[u8]) -> bool { bytes.starts_with(b"age-encryption.org/") }
+
+/// Read a plaintext item file.
+pub fn read_toml(text: &str) -> Result<Imported> {
+	let bundle: Bundle = toml::from_str(text)?;
+	if bundle.format != FORMAT {
+		return Err(Error::Import(format!("not a pwd item file (format '{}')", bundle.format)));
+	}
+	let mut entry = Imported::new(AccountName::new(bundle.name)?, bundle.item);
+	for (file, contents) in bundle.attachments {
+		let contents = base64::engine::general_purpose::STANDARD
+			.decode(contents)
+			.map_err(|e| Error::Import(format!("attachment '{file}': {e}")))?;
+		entry.attachments.push((file, contents));
+	}
+	Ok(entry)
+}
+
+/// Read a sealed item file, decrypting it with `method`.
+pub fn read_sealed(bytes: &[u8], method: &impl EncryptionMethod) -> Result<Imported> {
+	read_toml(std::str::from_utf8(&method.decrypt(bytes)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use super::*;
+	use crate::{AgeScrypt, export::item::{seal, to_toml}, forms::build_item};
+
+	#[test]
+	fn items_survive_the_trip() {
+		let values = BTreeMap::from([("ssid".to_string(), "home-5g".to_string())]);
+		let item = build_item("wifi_network", &values).unwrap();
+		let name = AccountName::new("home/Wi-Fi").unwrap();
+		let attachments = [("router.txt".to_string(), b"admin".to_vec())];
+
+		let plain = read_toml(&to_toml(&name, &item, &attachments).unwrap()).unwrap();
+		assert_eq!(plain.name, name);
+		assert_eq!(plain.attachments, attachments);
+
+		let method = AgeScrypt::new("transfer").unwrap();
+		let sealed = seal(&name, &item, &attachments, &method).unwrap();
+		assert!(is_sealed(&sealed));
+		assert_eq!(read_sealed(&sealed, &method).unwrap().item.kind_str(), "wifi_network");
+		assert!(read_sealed(&sealed, &AgeScrypt::new("wrong").unwrap()).is_err());
+		assert!(read_toml("format = \"other\"\nname = \"x\"").is_err());
+	}
+}
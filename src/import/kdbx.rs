@@ -0,0 +1,122 @@
+//! Entries from a KeePass database (KDBX 3.1 or 4), the format KeePassXC,
+//! KeePassDX and Strongbox share.
+//!
+//! Groups become folders and the standard Title, UserName, Password, URL and
+//! Notes fields map onto an online account, with KeePassXC's `otp` field as
+//! its TOTP secret and an `email` field as its address. Other fields become
+//! custom fields, protected ones secret. Entries written by
+//! [`crate::export::kdbx`] carry a [`KIND_FIELD`] and come back as the kind
+//! they were. The recycle bin is left out.
+
+use std::{collections::BTreeMap, io::Read, path::Path};
+
+use keepass::{Database, DatabaseKey, db::{Entry, Group, Node, Value}};
+
+use super::{Imported, Login, entry_name};
+use crate::{Error, Item, Result, forms::{build_item, builtin_form}, models::CustomField};
+
+/// The field naming the item kind of entries exported from here, so a
+/// passport survives the round trip as a passport.
+pub const KIND_FIELD: &str = "pwd:kind";
+
+/// KeePassXC's field for an `otpauth://` URI.
+pub const OTP_FIELD: &str = "otp";
+
+const STANDARD_FIELDS: [&str; 5] = ["Title", "UserName", "Password", "URL", "Notes"];
+
+/// The form field kept in Notes for entries of `kind`.
+pub fn notes_field(kind: &str) -> &'static str {
+	if kind == "secure_note" { "body" } else { "notes" }
+}
+
+/// The key to a database: its password, a key file, or both.
+pub fn database_key(password: Option<&str>, keyfile: Option<&Path>) -> Result<DatabaseKey> {
+	let mut key = DatabaseKey::new();
+	if let Some(password) = password {
+		key = key.with_password(password);
+	}
+	if let Some(keyfile) = keyfile {
+		key = key.with_keyfile(&mut std::fs::File::open(keyfile)?)?;
+	}
+	Ok(key)
+}
+
+/// Read every entry of the database at `path`.
+pub fn read_database(path: &Path, key: DatabaseKey) -> Result<Vec<Imported>> {
+	read(&mut std::fs::File::open(path)?, key)
+}
+
+/// Read every entry of the database in `source`.
+pub fn read(source: &mut impl Read, key: DatabaseKey) -> Result<Vec<Imported>> {
+	let db = Database::open(source, key).map_err(|e| Error::Import(e.to_string()))?;
+	let mut out = vec![];
+	collect(&db, &db.root, "", &mut out)?;
+	Ok(out)
+}
+
+fn collect(db: &Database, group: &Group, folder: &str, out: &mut Vec<Imported>) -> Result<()> {
+	for node in &group.children {
+		match node {
+			Node::Group(child) if Some(child.uuid) == db.meta.recyclebin_uuid => {}
+			Node::Group(child) => collect(db, child, &format!("{folder}{}/", child.name), out)?,
+			Node::Entry(entry) => {
+				let title = entry.get_title().filter(|t| !t.trim().is_empty()).unwrap_or("Untitled");
+				out.push(Imported::new(entry_name(&format!("{folder}{title}"))?, convert(entry)));
+			}
+		}
+	}
+	Ok(())
+}
+
+fn text(value: &Value) -> Option<(String, bool)> {
+	match value {
+		Value::Unprotected(s) => Some((s.clone(), false)),
+		Value::Protected(s) => Some((String::from_utf8_lossy(s.unsecure()).into_owned(), true)),
+		Value::Bytes(_) => None,
+	}
+}
+
+fn convert(entry: &Entry) -> Item {
+	let mut extra: BTreeMap<&str, (String, bool)> = entry
+		.fields
+		.iter()
+		.filter(|(key, _)| !STANDARD_FIELDS.contains(&key.as_str()))
+		.filter_map(|(key, value)| Some((key.as_str(), text(value)?)))
+		.filter(|(_, (value, _))| !value.is_empty())
+		.collect();
+	let get = |key: &str| entry.get(key).filter(|v| !v.is_empty()).map(str::to_string);
+
+	let kind = extra.remove(KIND_FIELD).map(|(kind, _)| kind);
+	if let Some(form) = kind.as_deref().and_then(builtin_form) {
+		let (fields, rest): (Vec<_>, Vec<_>) =
+			extra.clone().into_iter().partition(|(key, _)| form.field(key).is_some());
+		let mut values: BTreeMap<String, String> =
+			fields.into_iter().map(|(key, (value, _))| (key.to_string(), value)).collect();
+		values.extend(get("Notes").map(|notes| (notes_field(&form.name).to_string(), notes)));
+		if let Ok(mut item) = build_item(&form.name, &values) {
+			item.custom_fields_mut().extend(rest.into_iter().map(|(key, (value, secret))| CustomField {
+				label: key.into(),
+				value,
+				secret,
+			}));
+			item.tags_mut().extend(entry.tags.iter().cloned());
+			return item;
+		}
+	}
+
+	Login {
+		username: get("UserName"),
+		password: get("Password"),
+		email: extra.remove("email").map(|(email, _)| email),
+		url: get("URL"),
+		totp: extra.remove(OTP_FIELD).map(|(otp, _)| otp),
+		notes: get("Notes"),
+		custom_fields: extra
+			.into_iter()
+			.map(|(key, (value, secret))| CustomField { label: key.into(), value, secret })
+			.collect(),
+		tags: entry.tags.clone(),
+		..Default::default()
+	}
+	.into_item()
+}
@@ -0,0 +1,332 @@
+//! Importers that bring entries over from other password managers.
+//!
+//! Each importer reads its source into [`Imported`] entries, a name and an
+//! item each, without touching the store. [`plan`] then decides what
+//! happens to each, settling names already taken with a [`MergeStrategy`],
+//! and [`apply`] carries the plan out; a dry run stops after planning. Names
+//! are made valid with [`entry_name`], so `github.com` becomes `github-com`,
+//! and folders in the source become folders in the name.
+
+pub mod browser;
+pub mod csv;
+pub mod cxf;
+pub mod item;
+pub mod kdbx;
+pub mod onepux;
+pub mod pass;
+pub mod safari;
+
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{Error, Result, access_control::{BranchKind, BranchPath}, encryption::{EncryptionMethod, Unlocked}, forms::{build_item, builtin_form}, models::{AccountName, AccountStatus, CustomField, Item, OnlineAccount, Totp}, store::{StoreBackend, StoreChange}, versioning::PijulStore};
+
+/// An entry read from another password manager, not yet in the store.
+#[derive(Debug, Clone)]
+pub struct Imported {
+	pub name:        AccountName,
+	pub item:        Item,
+	/// Files to attach, as `(file name, contents)`.
+	pub attachments: Vec<(String, Vec<u8>)>,
+}
+
+impl Imported {
+	pub fn new(name: AccountName, item: Item) -> Self { Self { name, item, attachments: vec![] } }
+}
+
+/// What to do with an imported entry whose name is already taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+	/// Leave the existing entry alone and drop the imported one.
+	#[default]
+	Skip,
+	/// Replace the existing entry with the imported one.
+	Overwrite,
+	/// Add the imported entry under a numbered name: "GitHub 2".
+	KeepBoth,
+}
+
+impl std::str::FromStr for MergeStrategy {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		match s {
+			"skip" => Ok(Self::Skip),
+			"overwrite" => Ok(Self::Overwrite),
+			"keep-both" => Ok(Self::KeepBoth),
+			other => Err(Error::Import(format!(
+				"unknown merge strategy '{other}'; use skip, overwrite or keep-both"
+			))),
+		}
+	}
+}
+
+/// What [`apply`] does with one entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+	Create,
+	/// Replace the existing entry of that name.
+	Overwrite,
+	/// Leave it out; the name is taken.
+	Skip,
+	/// Add it under this name instead, as its own is taken.
+	Rename(AccountName),
+}
+
+/// An imported entry and what will be done with it.
+#[derive(Debug, Clone)]
+pub struct Planned {
+	pub entry:    Imported,
+	pub action:   Action,
+	/// Whether the entry's name is taken on the branch, so `action` came
+	/// from the merge strategy.
+	pub conflict: bool,
+}
+
+/// What [`apply`] did with each entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+	/// Entries created, under their final names.
+	pub added:   Vec<AccountName>,
+	/// Existing entries replaced.
+	pub updated: Vec<AccountName>,
+	/// Entries left out because the name is already taken.
+	pub skipped: Vec<AccountName>,
+}
+
+/// Work out what importing `entries` into `branch` would do, without
+/// changing anything. `resolve` picks the strategy for each entry whose name
+/// is already taken on the branch. Entries of the same import that share a
+/// name are numbered: "GitHub", "GitHub 2", ...
+pub fn plan<M: EncryptionMethod, K: BranchKind>(
+	store: &PijulStore<Unlocked<M>>,
+	branch: &BranchPath<K>,
+	entries: Vec<Imported>,
+	mut resolve: impl FnMut(&Imported) -> MergeStrategy,
+) -> Result<Vec<Planned>> {
+	let existing: HashSet<AccountName> = store.list(branch)?.into_iter().collect();
+	let mut taken: HashSet<AccountName> = HashSet::new();
+	let mut planned = vec![];
+	for entry in entries {
+		let conflict = existing.contains(&entry.name);
+		let strategy = if conflict { resolve(&entry) } else { MergeStrategy::KeepBoth };
+		let action = match strategy {
+			MergeStrategy::Skip => Action::Skip,
+			MergeStrategy::Overwrite if taken.insert(entry.name.clone()) => Action::Overwrite,
+			// Overwriting an entry this import already wrote would lose one
+			// of the two; keep both instead.
+			MergeStrategy::Overwrite | MergeStrategy::KeepBoth => {
+				let mut name = entry.name.clone();
+				for n in 2.. {
+					if !taken.contains(&name) && !existing.contains(&name) {
+						break;
+					}
+					name = AccountName::new(format!("{} {n}", entry.name))?;
+				}
+				taken.insert(name.clone());
+				if name == entry.name { Action::Create } else { Action::Rename(name) }
+			}
+		};
+		planned.push(Planned { entry, action, conflict });
+	}
+	Ok(planned)
+}
+
+/// Carry out `plan` on `branch`.
+pub fn apply<M: EncryptionMethod, K: BranchKind>(
+	store: &PijulStore<Unlocked<M>>,
+	branch: &BranchPath<K>,
+	plan: Vec<Planned>,
+) -> Result<ImportSummary> {
+	let mut summary = ImportSummary::default();
+	for Planned { entry: Imported { name, item, attachments }, action, .. } in plan {
+		let (name, overwrite) = match action {
+			Action::Skip => {
+				summary.skipped.push(name);
+				continue;
+			}
+			Action::Overwrite => (name, true),
+			Action::Create => (name, false),
+			Action::Rename(renamed) => (renamed, false),
+		};
+		if overwrite {
+			let change = StoreChange::Custom(format!("overwrite {name} from import"));
+			store.update(branch, &name, item, change)?;
+			summary.updated.push(name.clone());
+		} else {
+			let change = StoreChange::add_entry(name.clone(), &item);
+			store.insert(branch, name.clone(), item, change)?;
+			summary.added.push(name.clone());
+		}
+		for (file, contents) in attachments {
+			store.attach(branch, &name, &file, &contents)?;
+		}
+	}
+	Ok(summary)
+}
+
+/// Add `entries` to `branch`, settling every taken name with `strategy`.
+pub fn import<M: EncryptionMethod, K: BranchKind>(
+	store: &PijulStore<Unlocked<M>>,
+	branch: &BranchPath<K>,
+	entries: Vec<Imported>,
+	strategy: MergeStrategy,
+) -> Result<ImportSummary> {
+	apply(store, branch, plan(store, branch, entries, |_| strategy)?)
+}
+
+/// `raw` as a valid entry name: dots and backslashes become dashes, empty
+/// folders are dropped and overlong names are cut short.
+pub fn entry_name(raw: &str) -> Result<AccountName> {
+	let cleaned: Vec<String> = raw
+		.split('/')
+		.map(|segment| segment.trim().replace(['.', '\\'], "-"))
+		.filter(|segment| !segment.is_empty())
+		.collect();
+	let mut name = cleaned.join("/");
+	while name.len() > 255 {
+		name.pop();
+	}
+	let name = name.trim_end_matches('/');
+	if name.is_empty() {
+		return Err(Error::Import(format!("no usable name in '{raw}'")));
+	}
+	AccountName::new(name)
+}
+
+/// Build a `kind` item from as many of `values` as it will take.
+///
+/// Required values go in first; each optional value is then added only if
+/// the item still validates, so one date or country the schema rejects
+/// doesn't lose the whole item. Returns the item and the values left out.
+pub fn build_leniently(
+	kind: &str,
+	values: BTreeMap<String, String>,
+) -> Result<(Item, BTreeMap<String, String>)> {
+	let form =
+		builtin_form(kind).ok_or_else(|| Error::Import(format!("unknown item kind '{kind}'")))?;
+	let (mut kept, optional): (BTreeMap<_, _>, BTreeMap<_, _>) =
+		values.into_iter().partition(|(name, _)| form.field(name).is_some_and(|f| f.required));
+	let mut item = build_item(kind, &kept)?;
+	let mut rejected = BTreeMap::new();
+	for (name, value) in optional {
+		let mut tried = kept.clone();
+		tried.insert(name.clone(), value.clone());
+		match build_item(kind, &tried) {
+			Ok(built) => {
+				item = built;
+				kept = tried;
+			}
+			Err(_) => {
+				rejected.insert(name, value);
+			}
+		}
+	}
+	Ok((item, rejected))
+}
+
+/// The parts of a login that most sources have, made into an online
+/// account by [`Login::into_item`].
+#[derive(Debug, Clone, Default)]
+pub struct Login {
+	pub username:      Option<String>,
+	pub password:      Option<String>,
+	pub email:         Option<String>,
+	pub url:           Option<String>,
+	/// An `otpauth://` URI or a base32 secret.
+	pub totp:          Option<String>,
+	pub notes:         Option<String>,
+	pub custom_fields: Vec<CustomField>,
+	pub tags:          Vec<String>,
+}
+
+impl Login {
+	/// The online account for this login. A URL, e-mail address or TOTP
+	/// secret that doesn't parse is kept as a custom field rather than lost.
+	pub fn into_item(self) -> Item {
+		let nonempty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+		let mut custom_fields = self.custom_fields;
+		let mut keep = |label: &str, value: String, secret: bool| {
+			custom_fields.push(CustomField { label: label.into(), value, secret });
+		};
+
+		let host_website = nonempty(self.url).and_then(|url| match parse_url(&url) {
+			Some(parsed) => Some(parsed),
+			None => {
+				keep("website", url, false);
+				None
+			}
+		});
+		let email = nonempty(self.email).and_then(|email| match email.trim().parse() {
+			Ok(parsed) => Some(parsed),
+			Err(_) => {
+				keep("email", email, false);
+				None
+			}
+		});
+		let totp = nonempty(self.totp).and_then(|totp| match Totp::import(&totp) {
+			Ok(parsed) => Some(parsed),
+			Err(_) => {
+				keep("totp", totp, true);
+				None
+			}
+		});
+
+		Item::OnlineAccount(OnlineAccount {
+			username: nonempty(self.username),
+			password: nonempty(self.password),
+			email,
+			phone: None,
+			sign_in_with: None,
+			status: Some(AccountStatus::Active),
+			host_website,
+			login_pages: None,
+			security_questions: None,
+			recovery_codes: vec![],
+			password_policy: None,
+			totp,
+			date_created: None,
+			two_factor_enabled: None,
+			associated_items: None,
+			notes: nonempty(self.notes),
+			tags: self.tags,
+			custom_fields,
+			appearance: None,
+		})
+	}
+}
+
+/// `raw` as a URL, taking a bare host like `example.com/login` as https.
+fn parse_url(raw: &str) -> Option<url::Url> {
+	let raw = raw.trim();
+	url::Url::parse(raw)
+		.ok()
+		.filter(|url| url.has_host())
+		.or_else(|| url::Url::parse(&format!("https://{raw}")).ok().filter(|url| url.has_host()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn names_are_made_valid() {
+		assert_eq!(entry_name("web/github.com").unwrap().as_str(), "web/github-com");
+		assert_eq!(entry_name("/a//b/").unwrap().as_str(), "a/b");
+		assert!(entry_name("//").is_err());
+	}
+
+	#[test]
+	fn unparsable_login_parts_become_custom_fields() {
+		let login = Login {
+			url: Some("example.com/login".into()),
+			email: Some("not an address".into()),
+			totp: Some("!!".into()),
+			..Default::default()
+		};
+		let Item::OnlineAccount(account) = login.into_item() else { unreachable!() };
+		assert_eq!(account.host_website.unwrap().as_str(), "https://example.com/login");
+		let labels: Vec<_> = account.custom_fields.iter().map(|f| f.label.as_str()).collect();
+		assert_eq!(labels, ["email", "totp"]);
+		assert!(account.custom_fields[1].secret);
+	}
+}
@@ -0,0 +1,537 @@
+//! Entries from a 1Password `.1pux` export: a zip holding `export.data`, a
+//! JSON document of accounts, vaults and items, and a `files/` directory
+//! with the documents and file fields those items refer to.
+//!
+//! Each vault becomes a folder, so `Personal/GitHub` for a login called
+//! GitHub in the Personal vault. Logins and passwords become online
+//! accounts; the categories with a built-in counterpart (passports, Wi-Fi
+//! networks, licences, ...) are mapped field by field, and everything else,
+//! including any field a form rejects, is kept as a custom field. Items of
+//! unknown categories, documents among them, become secure notes with their
+//! files attached. Archived items are imported with an `archived` tag.
+
+use std::{collections::BTreeMap, io::{Read, Seek}, path::Path};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{Imported, Login, build_leniently, entry_name};
+use crate::{Error, Item, Result, forms::build_item, models::CustomField};
+
+/// The tag given to items archived in 1Password.
+pub const ARCHIVED_TAG: &str = "archived";
+
+#[derive(Deserialize)]
+struct Export {
+	accounts: Vec<Account>,
+}
+
+#[derive(Deserialize)]
+struct Account {
+	#[serde(default)]
+	vaults: Vec<Vault>,
+}
+
+#[derive(Deserialize)]
+struct Vault {
+	attrs: VaultAttrs,
+	#[serde(default)]
+	items: Vec<RawItem>,
+}
+
+#[derive(Deserialize)]
+struct VaultAttrs {
+	name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawItem {
+	category_uuid: String,
+	#[serde(default)]
+	state:         String,
+	overview:      Overview,
+	#[serde(default)]
+	details:       Details,
+}
+
+#[derive(Deserialize)]
+struct Overview {
+	#[serde(default)]
+	title: String,
+	url:   Option<String>,
+	#[serde(default)]
+	tags:  Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Details {
+	login_fields:        Vec<LoginField>,
+	notes_plain:         Option<String>,
+	password:            Option<String>,
+	sections:            Vec<Section>,
+	document_attributes: Option<FileAttributes>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoginField {
+	#[serde(default)]
+	value:       String,
+	#[serde(default)]
+	name:        String,
+	designation: Option<String>,
+	#[serde(default)]
+	field_type:  String,
+}
+
+#[derive(Deserialize)]
+struct Section {
+	#[serde(default)]
+	fields: Vec<SectionField>,
+}
+
+#[derive(Deserialize)]
+struct SectionField {
+	#[serde(default)]
+	title: String,
+	#[serde(default)]
+	id:    String,
+	value: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileAttributes {
+	file_name:   String,
+	document_id: String,
+}
+
+/// A section field with its value flattened to text.
+struct Field {
+	id:     String,
+	label:  String,
+	value:  String,
+	secret: bool,
+}
+
+/// How one 1Password category maps onto a built-in kind.
+struct Mapping {
+	category: &'static str,
+	kind:     &'static str,
+	/// The form field the item's title fills in, if any.
+	title:    Option<&'static str>,
+	/// Form values every item of the category gets.
+	fixed:    &'static [(&'static str, &'static str)],
+	/// `(1Password field id, form field)` pairs.
+	fields:   &'static [(&'static str, &'static str)],
+}
+
+const LOGIN: &str = "001";
+const PASSWORD: &str = "005";
+
+const MAPPINGS: &[Mapping] = &[
+	Mapping {
+		category: "100",
+		kind:     "software_license",
+		title:    Some("product"),
+		fixed:    &[],
+		fields:   &[
+			("reg_code", "license_key"),
+			("reg_name", "licensee"),
+			("order_date", "purchase_date"),
+			("order_number", "order_number"),
+			("download_link", "download_url"),
+		],
+	},
+	Mapping {
+		category: "102",
+		kind:     "server_credential",
+		title:    None,
+		fixed:    &[],
+		fields:   &[
+			("hostname", "host"),
+			("port", "port"),
+			("database_type", "protocol"),
+			("username", "username"),
+			("password", "password"),
+		],
+	},
+	Mapping {
+		category: "103",
+		kind:     "drivers_license",
+		title:    None,
+		fixed:    &[],
+		fields:   &[
+			("number", "number"),
+			("fullname", "legal_name"),
+			("class", "license_class"),
+			("state", "issuing_region"),
+			("country", "issuing_country"),
+			("expiry_date", "expiry_date"),
+		],
+	},
+	Mapping {
+		category: "105",
+		kind:     "membership",
+		title:    Some("program"),
+		fixed:    &[],
+		fields:   &[
+			("org_name", "program"),
+			("membership_no", "member_id"),
+			("member_name", "member_name"),
+			("expiry_date", "expiry_date"),
+			("pin", "pin"),
+		],
+	},
+	Mapping {
+		category: "106",
+		kind:     "identity_document",
+		title:    None,
+		fixed:    &[("document_type", "Passport")],
+		fields:   &[
+			("number", "number"),
+			("fullname", "legal_name"),
+			("issuing_country", "issuing_country"),
+			("issue_date", "issue_date"),
+			("expiry_date", "expiry_date"),
+		],
+	},
+	Mapping {
+		category: "107",
+		kind:     "membership",
+		title:    Some("program"),
+		fixed:    &[],
+		fields:   &[
+			("company_name", "program"),
+			("membership_no", "member_id"),
+			("member_name", "member_name"),
+			("pin", "pin"),
+		],
+	},
+	Mapping {
+		category: "108",
+		kind:     "social_security",
+		title:    None,
+		fixed:    &[],
+		fields:   &[("number", "account_number"), ("name", "legal_name")],
+	},
+	Mapping {
+		category: "109",
+		kind:     "wifi_network",
+		title:    Some("ssid"),
+		fixed:    &[],
+		fields:   &[("network_name", "ssid"), ("wireless_password", "passphrase")],
+	},
+	Mapping {
+		category: "110",
+		kind:     "server_credential",
+		title:    None,
+		fixed:    &[],
+		fields:   &[("url", "host"), ("username", "username"), ("password", "password")],
+	},
+	Mapping {
+		category: "111",
+		kind:     "email_account",
+		title:    None,
+		fixed:    &[],
+		fields:   &[
+			("pop_username", "address"),
+			("pop_username", "username"),
+			("pop_password", "password"),
+		],
+	},
+	Mapping {
+		category: "112",
+		kind:     "api_credential",
+		title:    None,
+		fixed:    &[],
+		fields:   &[("credential", "token"), ("hostname", "service_url"), ("expires", "expiry_date")],
+	},
+	Mapping {
+		category: "115",
+		kind:     "crypto_wallet",
+		title:    None,
+		fixed:    &[],
+		fields:   &[("recoveryPhrase", "seed_phrase"), ("password", "passphrase")],
+	},
+];
+
+/// Read the `.1pux` export at `path`.
+pub fn read_export(path: &Path) -> Result<Vec<Imported>> {
+	read_archive(std::fs::File::open(path)?)
+}
+
+/// Read a `.1pux` export from `reader`, attaching the files its items refer
+/// to.
+pub fn read_archive(reader: impl Read + Seek) -> Result<Vec<Imported>> {
+	let zip_err = |e: zip::result::ZipError| Error::Import(format!("not a 1PUX export: {e}"));
+	let mut archive = zip::ZipArchive::new(reader).map_err(zip_err)?;
+	let mut data = String::new();
+	archive.by_name("export.data").map_err(zip_err)?.read_to_string(&mut data)?;
+	let (entries, files) = parse_export(&data)?;
+	entries
+		.into_iter()
+		.zip(files)
+		.map(|(mut entry, files)| {
+			for FileAttributes { file_name, document_id } in files {
+				let path = format!("files/{document_id}__{file_name}");
+				let mut contents = vec![];
+				archive
+					.by_name(&path)
+					.map_err(|e| Error::Import(format!("{path}: {e}")))?
+					.read_to_end(&mut contents)?;
+				entry.attachments.push((file_name, contents));
+			}
+			Ok(entry)
+		})
+		.collect()
+}
+
+/// The entries of `export.data`, each with the files it should get.
+fn parse_export(data: &str) -> Result<(Vec<Imported>, Vec<Vec<FileAttributes>>)> {
+	let export: Export = serde_json::from_str(data)
+		.map_err(|e| Error::Import(format!("unreadable export.data: {e}")))?;
+	let mut entries = vec![];
+	let mut files = vec![];
+	for vault in export.accounts.into_iter().flat_map(|account| account.vaults) {
+		for raw in vault.items {
+			let title = match raw.overview.title.trim() {
+				"" => "Untitled",
+				title => title,
+			};
+			let name = entry_name(&format!("{}/{title}", vault.attrs.name))?;
+			let (item, item_files) = convert(raw);
+			entries.push(Imported::new(name, item));
+			files.push(item_files);
+		}
+	}
+	Ok((entries, files))
+}
+
+/// The item for one 1Password item, and the files to attach to it.
+fn convert(raw: RawItem) -> (Item, Vec<FileAttributes>) {
+	let RawItem { category_uuid, state, overview, details } = raw;
+	let mut files: Vec<FileAttributes> = details.document_attributes.into_iter().collect();
+	let mut fields = vec![];
+	for field in details.sections.into_iter().flat_map(|s| s.fields) {
+		match field.value.get("file") {
+			Some(file) => files.extend(serde_json::from_value(file.clone()).ok()),
+			None => fields.extend(flatten(field)),
+		}
+	}
+	let notes = details.notes_plain.filter(|n| !n.trim().is_empty());
+
+	let mut item = match category_uuid.as_str() {
+		LOGIN | PASSWORD => {
+			let designated = |designation: &str| {
+				details
+					.login_fields
+					.iter()
+					.find(|f| f.designation.as_deref() == Some(designation))
+					.map(|f| f.value.clone())
+			};
+			let login = Login {
+				username: designated("username"),
+				password: designated("password").or(details.password),
+				url: overview.url,
+				totp: take(&mut fields, |f| f.value.starts_with("otpauth://")),
+				notes,
+				..Default::default()
+			};
+			let mut item = login.into_item();
+			item.custom_fields_mut().extend(
+				details
+					.login_fields
+					.into_iter()
+					.filter(|f| f.designation.is_none() && !f.value.is_empty())
+					.map(|f| CustomField { label: f.name, value: f.value, secret: f.field_type == "P" }),
+			);
+			item
+		}
+		category => MAPPINGS
+			.iter()
+			.find(|m| m.category == category)
+			.and_then(|mapping| mapped(mapping, &overview.title, notes.as_deref(), &mut fields))
+			.unwrap_or_else(|| note(&overview.title, notes.as_deref())),
+	};
+
+	item.custom_fields_mut().extend(fields.into_iter().map(|f| CustomField {
+		label:  if f.label.is_empty() { f.id } else { f.label },
+		value:  f.value,
+		secret: f.secret,
+	}));
+	item.tags_mut().extend(overview.tags);
+	if state == "archived" {
+		item.tags_mut().push(ARCHIVED_TAG.into());
+	}
+	(item, files)
+}
+
+/// Build the item for `mapping`, removing the fields it used from `fields`.
+/// A value the form rejects, such as a country it doesn't know, stays in
+/// `fields` rather than failing the whole item.
+fn mapped(
+	mapping: &Mapping,
+	title: &str,
+	notes: Option<&str>,
+	fields: &mut Vec<Field>,
+) -> Option<Item> {
+	let mut values: BTreeMap<String, String> =
+		mapping.fixed.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+	values.extend(notes.map(|n| ("notes".to_string(), n.to_string())));
+	if let Some(field) = mapping.title.filter(|_| !title.trim().is_empty()) {
+		values.insert(field.to_string(), title.to_string());
+	}
+	for (from, to) in mapping.fields {
+		if let Some(field) = fields.iter().find(|f| f.id == *from) {
+			values.insert(to.to_string(), field.value.clone());
+		}
+	}
+
+	let (item, rejected) = build_leniently(mapping.kind, values.clone()).ok()?;
+	fields.retain(|f| {
+		!mapping.fields.iter().any(|(from, to)| {
+			f.id == *from && values.get(*to) == Some(&f.value) && !rejected.contains_key(*to)
+		})
+	});
+	Some(item)
+}
+
+/// A secure note titled `title`, for categories without a better match.
+fn note(title: &str, notes: Option<&str>) -> Item {
+	let title = if title.trim().is_empty() { "Untitled" } else { title };
+	let values = BTreeMap::from([
+		("title".to_string(), title.to_string()),
+		("body".to_string(), notes.unwrap_or(title).to_string()),
+	]);
+	build_item("secure_note", &values).expect("a titled note with a body is valid")
+}
+
+/// Remove and return the value of the first field matching `pred`.
+fn take(fields: &mut Vec<Field>, pred: impl Fn(&Field) -> bool) -> Option<String> {
+	let index = fields.iter().position(pred)?;
+	Some(fields.remove(index).value)
+}
+
+/// A section field as text; `None` when it is empty or of a kind with no
+/// text form.
+fn flatten(field: SectionField) -> Option<Field> {
+	let (kind, value) = field.value.as_object()?.iter().next()?;
+	let text = |v: &Value| v.as_str().map(str::to_string);
+	let (value, secret) = match kind.as_str() {
+		"concealed" | "creditCardNumber" => (text(value)?, true),
+		"totp" => (text(value)?, true),
+		"email" => (text(value).or_else(|| text(&value["email_address"]))?, false),
+		"date" => {
+			let date = jiff::Timestamp::from_second(value.as_i64()?).ok()?;
+			(date.to_zoned(jiff::tz::TimeZone::UTC).date().to_string(), false)
+		}
+		"monthYear" => {
+			let month_year = value.as_i64()?;
+			(format!("{:04}-{:02}", month_year / 100, month_year % 100), false)
+		}
+		"address" => {
+			let parts = ["street", "city", "state", "zip", "country"];
+			let address: Vec<_> =
+				parts.iter().filter_map(|p| value[*p].as_str()).filter(|p| !p.is_empty()).collect();
+			(address.join(", "), false)
+		}
+		"sshKey" => (text(&value["privateKey"])?, true),
+		_ => (text(value).or_else(|| value.as_i64().map(|n| n.to_string()))?, false),
+	};
+	if value.trim().is_empty() {
+		return None;
+	}
+	Some(Field { id: field.id, label: field.title, value, secret })
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{Cursor, Write};
+
+	use super::*;
+
+	fn export(items: Value) -> String {
+		serde_json::json!({
+			"accounts": [{ "vaults": [{ "attrs": { "name": "Personal" }, "items": items }] }]
+		})
+		.to_string()
+	}
+
+	#[test]
+	fn logins_keep_their_sections() {
+		let data = export(serde_json::json!([{
+			"categoryUuid": "001",
+			"state": "archived",
+			"overview": { "title": "GitHub", "url": "https://github.com", "tags": ["dev"] },
+			"details": {
+				"loginFields": [
+					{ "value": "alice", "name": "login", "designation": "username", "fieldType": "T" },
+					{ "value": "hunter2", "name": "pass", "designation": "password", "fieldType": "P" }
+				],
+				"sections": [{ "fields": [
+					{ "title": "one-time password", "id": "totp", "value": { "totp": "otpauth://totp/GitHub?secret=JBSWY3DPEHPK3PXP" } },
+					{ "title": "recovery PIN", "id": "pin", "value": { "concealed": "1234" } }
+				] }]
+			}
+		}]));
+		let (entries, _) = parse_export(&data).unwrap();
+		assert_eq!(entries[0].name.as_str(), "Personal/GitHub");
+		let Item::OnlineAccount(account) = &entries[0].item else { panic!("not a login") };
+		assert_eq!(account.username.as_deref(), Some("alice"));
+		assert_eq!(account.password.as_deref(), Some("hunter2"));
+		assert!(account.totp.is_some());
+		assert_eq!(account.custom_fields[0].label, "recovery PIN");
+		assert!(account.custom_fields[0].secret);
+		assert_eq!(account.tags, ["dev", ARCHIVED_TAG]);
+	}
+
+	#[test]
+	fn categories_map_onto_built_in_kinds() {
+		let data = export(serde_json::json!([
+			{
+				"categoryUuid": "109",
+				"overview": { "title": "Home" },
+				"details": { "sections": [{ "fields": [
+					{ "title": "network name", "id": "network_name", "value": { "string": "home-5g" } },
+					{ "title": "password", "id": "wireless_password", "value": { "concealed": "s3cret" } },
+					{ "title": "security", "id": "wireless_security", "value": { "menu": "wpa2p" } }
+				] }] }
+			},
+			{ "categoryUuid": "999", "overview": { "title": "Odd" }, "details": {} }
+		]));
+		let (entries, _) = parse_export(&data).unwrap();
+		assert_eq!(entries[0].item.kind_str(), "wifi_network");
+		let fields: BTreeMap<_, _> = crate::forms::editable_fields(&entries[0].item)
+			.into_iter()
+			.map(|(f, v)| (f.name, v))
+			.collect();
+		assert_eq!(fields["ssid"], "home-5g");
+		assert_eq!(fields["passphrase"], "s3cret");
+		assert_eq!(entries[0].item.custom_fields()[0].label, "security");
+		assert_eq!(entries[1].item.kind_str(), "secure_note");
+	}
+
+	#[test]
+	fn documents_are_attached() {
+		let data = export(serde_json::json!([{
+			"categoryUuid": "006",
+			"overview": { "title": "Lease" },
+			"details": { "documentAttributes": { "fileName": "lease.pdf", "documentId": "abc" } }
+		}]));
+		let mut buffer = Cursor::new(vec![]);
+		let mut zip = zip::ZipWriter::new(&mut buffer);
+		let options = zip::write::SimpleFileOptions::default();
+		zip.start_file("export.data", options).unwrap();
+		zip.write_all(data.as_bytes()).unwrap();
+		zip.start_file("files/abc__lease.pdf", options).unwrap();
+		zip.write_all(b"%PDF").unwrap();
+		zip.finish().unwrap();
+
+		let entries = read_archive(Cursor::new(buffer.into_inner())).unwrap();
+		assert_eq!(entries[0].name.as_str(), "Personal/Lease");
+		assert_eq!(entries[0].attachments, [("lease.pdf".to_string(), b"%PDF".to_vec())]);
+	}
+}
@@ -0,0 +1,139 @@
+//! Entries from [pass](https://www.passwordstore.org), the standard unix
+//! password manager: one GPG-encrypted file per entry, decrypted here with
+//! the `gpg` binary so the user's agent and pinentry handle the key.
+//!
+//! The first line of an entry is the password. Later `key: value` lines
+//! become fields, with the usual `login`, `url` and `otpauth://` lines
+//! recognised; anything else is kept as notes.
+
+use std::path::{Path, PathBuf};
+
+use super::{Imported, Login, entry_name};
+use crate::{Error, Result, models::CustomField};
+
+/// Read every entry of the pass store at `dir`, decrypting each `.gpg` file
+/// with `decrypt`, normally [`gpg_decrypt`]. An entry at `web/github.com.gpg`
+/// is named `web/github-com`.
+pub fn read_store(dir: &Path, decrypt: impl Fn(&Path) -> Result<String>) -> Result<Vec<Imported>> {
+	let mut files = vec![];
+	collect_entries(dir, &mut files)?;
+	files.sort();
+	files
+		.into_iter()
+		.map(|path| {
+			let relative = path.strip_prefix(dir).unwrap_or(&path).with_extension("");
+			let name = entry_name(&relative.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/"))?;
+			Ok(Imported::new(name, parse_entry(&decrypt(&path)?).into_item()))
+		})
+		.collect()
+}
+
+/// `.gpg` files under `dir`, skipping hidden directories such as `.git`.
+fn collect_entries(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+	for entry in std::fs::read_dir(dir)? {
+		let path = entry?.path();
+		if path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')) {
+			continue;
+		}
+		if path.is_dir() {
+			collect_entries(&path, out)?;
+		} else if path.extension().is_some_and(|ext| ext == "gpg") {
+			out.push(path);
+		}
+	}
+	Ok(())
+}
+
+/// Decrypt `path` with `gpg --decrypt`.
+pub fn gpg_decrypt(path: &Path) -> Result<String> {
+	let output = std::process::Command::new("gpg")
+		.args(["--quiet", "--batch", "--decrypt"])
+		.arg(path)
+		.stderr(std::process::Stdio::inherit())
+		.output()
+		.map_err(|e| Error::Import(format!("could not run gpg: {e}")))?;
+	if !output.status.success() {
+		return Err(Error::Import(format!("gpg could not decrypt {}", path.display())));
+	}
+	String::from_utf8(output.stdout)
+		.map_err(|_| Error::Import(format!("{} is not UTF-8 text", path.display())))
+}
+
+/// The login in one decrypted entry.
+pub fn parse_entry(text: &str) -> Login {
+	let mut lines = text.lines();
+	let mut login = Login {
+		password: lines.next().map(str::to_owned).filter(|p| !p.is_empty()),
+		..Default::default()
+	};
+	let mut notes = vec![];
+	for line in lines {
+		if line.trim_start().starts_with("otpauth://") {
+			login.totp = Some(line.trim().to_owned());
+			continue;
+		}
+		let Some((key, value)) =
+			line.split_once(':').filter(|(key, value)| is_key(key) && !value.starts_with("//"))
+		else {
+			notes.push(line);
+			continue;
+		};
+		let value = value.trim().to_owned();
+		match key.trim().to_lowercase().as_str() {
+			"login" | "user" | "username" => login.username = Some(value),
+			"email" | "e-mail" | "mail" => login.email = Some(value),
+			"url" | "website" | "site" => login.url = Some(value),
+			"otp" | "totp" => login.totp = Some(value),
+			label => {
+				let secret = ["pin", "password", "secret", "key"].iter().any(|s| label.contains(s));
+				login.custom_fields.push(CustomField { label: key.trim().to_owned(), value, secret });
+			}
+		}
+	}
+	let notes = notes.join("\n");
+	login.notes = Some(notes.trim().to_owned()).filter(|n| !n.is_empty());
+	login
+}
+
+/// Whether `key` reads as a field name rather than the start of a sentence
+/// or a URL: short, and made of words.
+fn is_key(key: &str) -> bool {
+	let key = key.trim();
+	!key.is_empty()
+		&& key.len() <= 32
+		&& key.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Item;
+
+	#[test]
+	fn entries_map_the_first_line_and_fields() {
+		let text = "hunter2\nlogin: alice\nurl: https://github.com\nPIN: 1234\n\
+		            otpauth://totp/GitHub:alice?secret=JBSWY3DPEHPK3PXP\nRecovery: see drawer\n";
+		let Item::OnlineAccount(account) = parse_entry(text).into_item() else { unreachable!() };
+		assert_eq!(account.password.as_deref(), Some("hunter2"));
+		assert_eq!(account.username.as_deref(), Some("alice"));
+		assert_eq!(account.host_website.unwrap().host_str(), Some("github.com"));
+		assert!(account.totp.is_some());
+		assert_eq!(account.custom_fields.len(), 2);
+		assert!(account.custom_fields[0].secret && !account.custom_fields[1].secret);
+		assert_eq!(account.notes, None);
+	}
+
+	#[test]
+	fn folders_become_part_of_the_name() {
+		let dir = tempfile::tempdir().unwrap();
+		std::fs::create_dir_all(dir.path().join("web")).unwrap();
+		std::fs::create_dir_all(dir.path().join(".git")).unwrap();
+		std::fs::write(dir.path().join("web/github.com.gpg"), "hunter2\n").unwrap();
+		std::fs::write(dir.path().join(".git/config.gpg"), "").unwrap();
+		std::fs::write(dir.path().join(".gpg-id"), "alice@example.com\n").unwrap();
+
+		let entries = read_store(dir.path(), |path| Ok(std::fs::read_to_string(path)?)).unwrap();
+		assert_eq!(entries.len(), 1);
+		assert_eq!(entries[0].name.as_str(), "web/github-com");
+	}
+}
@@ -0,0 +1,97 @@
+//! Entries from the password CSV Safari and iCloud Keychain export, with the
+//! columns `Title,URL,Username,Password,Notes,OTPAuth`.
+//!
+//! Safari titles its entries after the site and the username, as in
+//! `example.com (alice)`; the username is dropped from the entry name since
+//! it has a field of its own. OTPAuth holds an `otpauth://` URI, which
+//! becomes the account's TOTP.
+
+use std::{io::Read, path::Path};
+
+use super::{Imported, Login, entry_name};
+use crate::{Error, Result};
+
+/// Read the Safari export at `path`.
+pub fn read_file(path: &Path) -> Result<Vec<Imported>> { read_csv(std::fs::File::open(path)?) }
+
+/// Read a Safari or iCloud Keychain password export from `reader`.
+pub fn read_csv(reader: impl Read) -> Result<Vec<Imported>> {
+	let csv_err = |e: ::csv::Error| Error::Import(e.to_string());
+	let mut reader = ::csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+	let headers = reader.headers().map_err(csv_err)?.clone();
+	let column = |name: &str| headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+	let (Some(title), Some(url), Some(username), Some(password)) =
+		(column("Title"), column("URL"), column("Username"), column("Password"))
+	else {
+		return Err(Error::Import(
+			"not a Safari password export: no Title, URL, Username and Password columns".into(),
+		));
+	};
+	let (notes, otpauth) = (column("Notes"), column("OTPAuth"));
+
+	let mut entries = vec![];
+	for record in reader.records() {
+		let record = record.map_err(csv_err)?;
+		let get =
+			|index: Option<usize>| index.and_then(|i| record.get(i)).unwrap_or_default().to_string();
+		let login = Login {
+			username: Some(get(Some(username))),
+			password: Some(get(Some(password))),
+			url: Some(get(Some(url))),
+			totp: Some(get(otpauth)),
+			notes: Some(get(notes)),
+			..Default::default()
+		};
+		if [&login.username, &login.password, &login.totp].iter().all(|v| v.as_deref() == Some("")) {
+			continue;
+		}
+		let title = get(Some(title));
+		let name = name(&title, login.username.as_deref().unwrap_or_default(), &get(Some(url)));
+		entries.push(Imported::new(entry_name(&name)?, login.into_item()));
+	}
+	Ok(entries)
+}
+
+/// The entry name for a row: its title without the ` (username)` Safari
+/// adds, else the URL's host, else "Untitled".
+fn name(title: &str, username: &str, url: &str) -> String {
+	let title = title.trim();
+	let title = title
+		.strip_suffix(&format!(" ({username})"))
+		.filter(|_| !username.is_empty())
+		.unwrap_or(title)
+		.trim();
+	if !title.is_empty() {
+		return title.to_string();
+	}
+	url::Url::parse(url.trim())
+		.ok()
+		.and_then(|url| url.host_str().map(|h| h.trim_start_matches("www.").to_string()))
+		.filter(|host| !host.is_empty())
+		.unwrap_or_else(|| "Untitled".into())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Item;
+
+	#[test]
+	fn otpauth_becomes_the_totp() {
+		let csv = "Title,URL,Username,Password,Notes,OTPAuth\n\
+			github.com (alice),https://github.com/,alice,hunter2,work,\"otpauth://totp/GitHub:alice?secret=JBSWY3DPEHPK3PXP&issuer=GitHub\"\n\
+			,https://www.example.com/,bob,letmein,,\n";
+		let entries = read_csv(csv.as_bytes()).unwrap();
+		let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+		assert_eq!(names, ["github-com", "example-com"]);
+		let Item::OnlineAccount(account) = &entries[0].item else { panic!("not a login") };
+		assert_eq!(account.password.as_deref(), Some("hunter2"));
+		assert!(account.totp.is_some());
+		assert!(entries[0].item.custom_fields().is_empty());
+	}
+
+	#[test]
+	fn other_csv_files_are_refused() {
+		assert!(read_csv("url,username,password\na,b,c\n".as_bytes()).is_err());
+	}
+}
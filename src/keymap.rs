@@ -0,0 +1,522 @@
+//! Keyboard bindings for the interactive front ends.
+//!
+//! Front ends translate a key press into a key name ("a", "?", "enter",
+//! "ctrl-s", ...) and ask the [`Keymap`] which [`Action`] it triggers in the
+//! current [`KeyContext`]. A binding may be a chord of several keys separated
+//! by spaces ("g g"); while a chord is incomplete the keymap reports
+//! [`KeyResolution::Pending`]. The help overlay is rendered from the same
+//! bindings, so it always matches what the keys actually do.
+//!
+//! The defaults can be overridden per context from the `[keys]` table of the
+//! config file; see [`Keymap::with_overrides`].
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Where a binding applies. [`KeyContext::Global`] bindings apply everywhere
+/// unless the active context binds the same key.
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, uniffi::Enum,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyContext {
+	Global,
+	List,
+	Detail,
+	Edit,
+	Sync,
+}
+
+impl KeyContext {
+	pub const ALL: [KeyContext; 5] =
+		[KeyContext::Global, KeyContext::List, KeyContext::Detail, KeyContext::Edit, KeyContext::Sync];
+
+	/// The context's name in the config file's `[keys]` table.
+	pub fn config_name(self) -> &'static str {
+		match self {
+			KeyContext::Global => "global",
+			KeyContext::List => "list",
+			KeyContext::Detail => "detail",
+			KeyContext::Edit => "edit",
+			KeyContext::Sync => "sync",
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			KeyContext::Global => "Everywhere",
+			KeyContext::List => "Entry list",
+			KeyContext::Detail => "Entry details",
+			KeyContext::Edit => "Editing",
+			KeyContext::Sync => "Share / receive",
+		}
+	}
+}
+
+/// Something a key can do.
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, uniffi::Enum,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+	Help,
+	Close,
+	NextEntry,
+	PreviousEntry,
+	NewEntry,
+	DeleteEntry,
+	ShowTrash,
+	OpenSync,
+	CopyTicket,
+	EditEntry,
+	CopyField,
+	ToggleHistory,
+	Save,
+	Cancel,
+	NextCategory,
+	PreviousCategory,
+	ShowAll,
+	ShowLogins,
+	ShowIdentity,
+	ShowCards,
+	ShowNotes,
+	ExpandFolder,
+	CollapseFolder,
+	ToggleFavorite,
+	CycleSort,
+	ToggleRawNotes,
+	OpenUrl,
+	RevealSecret,
+	NextField,
+	PreviousField,
+	FirstEntry,
+	LastEntry,
+	PageDown,
+	PageUp,
+	JumpToName,
+	ShowRecent,
+	Quit,
+	ShowLargeField,
+	GoBack,
+	RenameEntry,
+	DuplicateEntry,
+	MergeEntry,
+	ShowStats,
+	ToggleDeactivated,
+	ShowGraveyard,
+	OpenGenerator,
+}
+
+impl Action {
+	/// A word or two for the status bar.
+	pub fn short_label(self) -> &'static str {
+		match self {
+			Action::Help => "help",
+			Action::Close => "close",
+			Action::NextEntry => "next",
+			Action::PreviousEntry => "previous",
+			Action::NewEntry => "new",
+			Action::DeleteEntry => "delete",
+			Action::ShowTrash => "trash",
+			Action::OpenSync => "share",
+			Action::CopyTicket => "copy ticket",
+			Action::EditEntry => "edit",
+			Action::CopyField => "copy",
+			Action::ToggleHistory => "history",
+			Action::Save => "save",
+			Action::Cancel => "cancel",
+			Action::NextCategory => "next tab",
+			Action::PreviousCategory => "previous tab",
+			Action::ShowAll => "all",
+			Action::ShowLogins => "logins",
+			Action::ShowIdentity => "identity",
+			Action::ShowCards => "cards",
+			Action::ShowNotes => "notes",
+			Action::ExpandFolder => "expand",
+			Action::CollapseFolder => "collapse",
+			Action::ToggleFavorite => "star",
+			Action::CycleSort => "sort",
+			Action::ToggleRawNotes => "raw notes",
+			Action::OpenUrl => "open",
+			Action::RevealSecret => "reveal",
+			Action::NextField => "next field",
+			Action::PreviousField => "previous field",
+			Action::FirstEntry => "first",
+			Action::LastEntry => "last",
+			Action::PageDown => "page down",
+			Action::PageUp => "page up",
+			Action::JumpToName => "jump",
+			Action::ShowRecent => "recent",
+			Action::Quit => "quit",
+			Action::ShowLargeField => "enlarge",
+			Action::GoBack => "back",
+			Action::RenameEntry => "rename",
+			Action::DuplicateEntry => "duplicate",
+			Action::MergeEntry => "merge",
+			Action::ShowStats => "stats",
+			Action::ToggleDeactivated => "deactivated",
+			Action::ShowGraveyard => "graveyard",
+			Action::OpenGenerator => "generate",
+		}
+	}
+
+	/// One-line description shown in the help overlay.
+	pub fn description(self) -> &'static str {
+		match self {
+			Action::Help => "Show this help",
+			Action::Close => "Close the open panel",
+			Action::NextEntry => "Select the next entry",
+			Action::PreviousEntry => "Select the previous entry",
+			Action::NewEntry => "Create a new entry",
+			Action::DeleteEntry => "Move the selected entry to the trash",
+			Action::ShowTrash => "Show the trash",
+			Action::OpenSync => "Share or receive entries",
+			Action::CopyTicket => "Copy the share ticket",
+			Action::EditEntry => "Edit the selected entry",
+			Action::CopyField => "Copy the focused field, or the main secret",
+			Action::ToggleHistory => "Show or hide history",
+			Action::Save => "Save the field being edited",
+			Action::Cancel => "Stop editing without saving",
+			Action::NextCategory => "Switch to the next category tab",
+			Action::PreviousCategory => "Switch to the previous category tab",
+			Action::ShowAll => "Show entries of every category",
+			Action::ShowLogins => "Show only logins",
+			Action::ShowIdentity => "Show only identity documents",
+			Action::ShowCards => "Show only cards and memberships",
+			Action::ShowNotes => "Show only notes",
+			Action::ExpandFolder => "Expand the selected folder",
+			Action::CollapseFolder => "Collapse the selected folder",
+			Action::ToggleFavorite => "Star or unstar the selected entry",
+			Action::CycleSort => "Switch between name, modified, viewed and type order",
+			Action::ToggleRawNotes => "Show notes as typed or formatted",
+			Action::OpenUrl => "Open the entry's website in the browser",
+			Action::RevealSecret => "Briefly show the focused secret, or follow the focused link",
+			Action::NextField => "Focus the next field of the entry",
+			Action::PreviousField => "Focus the previous field of the entry",
+			Action::FirstEntry => "Select the first entry",
+			Action::LastEntry => "Select the last entry",
+			Action::PageDown => "Move the selection a page down",
+			Action::PageUp => "Move the selection a page up",
+			Action::JumpToName => "Jump to an entry by typing the start of its name",
+			Action::ShowRecent => "Jump to a recently opened entry",
+			Action::Quit => "Quit, offering to save an unsaved edit first",
+			Action::ShowLargeField => "Show the focused field full screen in large letters",
+			Action::GoBack => "Return to the entry a followed link came from",
+			Action::RenameEntry => "Rename or move the selected entry",
+			Action::DuplicateEntry => "Copy the selected entry under a new name",
+			Action::MergeEntry => "Mark the entry to merge, or merge the marked entry with this one",
+			Action::ShowStats => "Show the vault statistics dashboard",
+			Action::ToggleDeactivated => "Hide or show deactivated accounts in the list",
+			Action::ShowGraveyard => "List deactivated accounts for cleanup",
+			Action::OpenGenerator => "Open the password generator",
+		}
+	}
+}
+
+/// Per-context replacements for the default bindings: every action listed
+/// gets exactly the given keys in that context (an empty list unbinds it).
+pub type KeyOverrides = BTreeMap<KeyContext, BTreeMap<Action, Vec<String>>>;
+
+/// The outcome of feeding a key press to a [`Keymap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyResolution {
+	/// The keys pressed so far complete a binding.
+	Action(Action),
+	/// The keys pressed so far start at least one chord.
+	Pending,
+	/// Nothing is bound to the keys pressed so far.
+	Unbound,
+}
+
+/// One key (or space-separated chord) bound to an action in a context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Record)]
+pub struct KeyBinding {
+	pub context: KeyContext,
+	pub key:     String,
+	pub action:  Action,
+}
+
+/// Every key binding, looked up by context and key name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+	bindings: Vec<KeyBinding>,
+}
+
+impl Default for Keymap {
+	fn default() -> Self {
+		use Action::*;
+		use KeyContext::*;
+		let bindings = [
+			(Global, "?", Help),
+			(Global, "esc", Close),
+			(Global, "cmd-q", Quit),
+			(List, "j", NextEntry),
+			(List, "down", NextEntry),
+			(List, "k", PreviousEntry),
+			(List, "up", PreviousEntry),
+			(List, "a", NewEntry),
+			(List, "n", NewEntry),
+			(List, "d", DeleteEntry),
+			(List, "x", DeleteEntry),
+			(List, "t", ShowTrash),
+			(List, "s", CycleSort),
+			(List, "p", OpenSync),
+			(List, "tab", NextCategory),
+			(List, "shift-tab", PreviousCategory),
+			(List, "1", ShowAll),
+			(List, "2", ShowLogins),
+			(List, "3", ShowIdentity),
+			(List, "4", ShowCards),
+			(List, "5", ShowNotes),
+			(List, "right", ExpandFolder),
+			(List, "l", ExpandFolder),
+			(List, "left", CollapseFolder),
+			(List, "f", ToggleFavorite),
+			(List, "g g", FirstEntry),
+			(List, "home", FirstEntry),
+			(List, "shift-g", LastEntry),
+			(List, "end", LastEntry),
+			(List, "pagedown", PageDown),
+			(List, "ctrl-d", PageDown),
+			(List, "pageup", PageUp),
+			(List, "ctrl-u", PageUp),
+			(List, "'", JumpToName),
+			(List, "ctrl-o", ShowRecent),
+			(List, "shift-r", RenameEntry),
+			(List, "shift-d", DuplicateEntry),
+			(List, "shift-m", MergeEntry),
+			(List, "shift-s", ShowStats),
+			(List, "shift-h", ToggleDeactivated),
+			(List, "shift-t", ShowGraveyard),
+			(List, "ctrl-g", OpenGenerator),
+			(Detail, "e", EditEntry),
+			(Detail, "h", ToggleHistory),
+			(Detail, "y", CopyField),
+			(Detail, "c", CopyField),
+			(Detail, "m", ToggleRawNotes),
+			(Detail, "o", OpenUrl),
+			(Detail, "enter", RevealSecret),
+			(Detail, "r", RevealSecret),
+			(Detail, "ctrl-j", NextField),
+			(Detail, "ctrl-down", NextField),
+			(Detail, "ctrl-k", PreviousField),
+			(Detail, "ctrl-up", PreviousField),
+			(Detail, "z", ShowLargeField),
+			(Detail, "backspace", GoBack),
+			(Edit, "enter", Save),
+			(Edit, "esc", Cancel),
+			(Sync, "c", CopyTicket),
+		];
+		Self {
+			bindings: bindings
+				.into_iter()
+				.map(|(context, key, action)| KeyBinding { context, key: key.into(), action })
+				.collect(),
+		}
+	}
+}
+
+impl Keymap {
+	pub fn bindings(&self) -> &[KeyBinding] { &self.bindings }
+
+	/// The defaults with `overrides` applied.
+	///
+	/// Fails if a key is bound twice in one context, or if one binding is a
+	/// prefix of another chord in the same context and so could never fire.
+	pub fn with_overrides(overrides: &KeyOverrides) -> Result<Self> {
+		let mut bindings = Self::default().bindings;
+		for (context, actions) in overrides {
+			bindings.retain(|b| b.context != *context || !actions.contains_key(&b.action));
+			for (action, keys) in actions {
+				for key in keys {
+					bindings.push(KeyBinding {
+						context: *context,
+						key:     normalize_chord(key)?,
+						action:  *action,
+					});
+				}
+			}
+		}
+		let keymap = Self { bindings };
+		keymap.check()?;
+		Ok(keymap)
+	}
+
+	fn check(&self) -> Result<()> {
+		for (i, a) in self.bindings.iter().enumerate() {
+			for b in self.bindings[i + 1..].iter().filter(|b| b.context == a.context) {
+				let (short, long) = if a.key.len() <= b.key.len() { (a, b) } else { (b, a) };
+				let conflict = long.key == short.key || long.key.starts_with(&format!("{} ", short.key));
+				if conflict && a.action != b.action {
+					return Err(Error::Validation {
+						field:  format!("keys.{}", a.context.config_name()),
+						reason: format!(
+							"'{}' ({:?}) conflicts with '{}' ({:?})",
+							short.key, short.action, long.key, long.action
+						),
+					});
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Resolve the keys pressed so far (one entry per key) given the active
+	/// contexts, most specific first, falling back to [`KeyContext::Global`].
+	///
+	/// A complete binding in a more specific context wins; otherwise, if any
+	/// active context has a chord starting with these keys, the press is
+	/// pending.
+	pub fn resolve(&self, contexts: &[KeyContext], pressed: &[String]) -> KeyResolution {
+		let chord = pressed.join(" ");
+		let prefix = format!("{chord} ");
+		let mut pending = false;
+		for context in contexts.iter().chain([&KeyContext::Global]) {
+			let in_context = || self.bindings.iter().filter(|b| b.context == *context);
+			if let Some(binding) = in_context().find(|b| b.key == chord) {
+				return KeyResolution::Action(binding.action);
+			}
+			pending |= in_context().any(|b| b.key.starts_with(&prefix));
+		}
+		if pending { KeyResolution::Pending } else { KeyResolution::Unbound }
+	}
+
+	/// The action a single key triggers, ignoring chords.
+	pub fn action(&self, contexts: &[KeyContext], key: &str) -> Option<Action> {
+		match self.resolve(contexts, &[key.to_owned()]) {
+			KeyResolution::Action(action) => Some(action),
+			_ => None,
+		}
+	}
+
+	/// The keys bound to `action` in `context`, in binding order.
+	pub fn keys_for(&self, context: KeyContext, action: Action) -> Vec<String> {
+		self
+			.bindings
+			.iter()
+			.filter(|b| b.context == context && b.action == action)
+			.map(|b| b.key.clone())
+			.collect()
+	}
+
+	/// Up to `limit` bindings to advertise for the active contexts, most
+	/// specific context first, one row per action.
+	pub fn hints(&self, contexts: &[KeyContext], limit: usize) -> Vec<(Vec<String>, Action)> {
+		let help = self.help();
+		contexts
+			.iter()
+			.filter_map(|context| help.iter().find(|(c, _)| c == context))
+			.flat_map(|(_, rows)| rows.iter().cloned())
+			.take(limit)
+			.collect()
+	}
+
+	/// Bindings grouped by context in [`KeyContext::ALL`] order, with the keys
+	/// for each action collected together, for the help overlay.
+	pub fn help(&self) -> Vec<(KeyContext, Vec<(Vec<String>, Action)>)> {
+		KeyContext::ALL
+			.into_iter()
+			.filter_map(|context| {
+				let mut rows: Vec<(Vec<String>, Action)> = Vec::new();
+				for binding in self.bindings.iter().filter(|b| b.context == context) {
+					match rows.iter_mut().find(|(_, action)| *action == binding.action) {
+						Some((keys, _)) => keys.push(binding.key.clone()),
+						None => rows.push((vec![binding.key.clone()], binding.action)),
+					}
+				}
+				(!rows.is_empty()).then_some((context, rows))
+			})
+			.collect()
+	}
+}
+
+/// Lower-case a chord and collapse its separators to single spaces.
+fn normalize_chord(raw: &str) -> Result<String> {
+	let keys: Vec<String> = raw.split_whitespace().map(str::to_lowercase).collect();
+	if keys.is_empty() {
+		return Err(Error::Validation { field: "keys".into(), reason: "empty key binding".into() });
+	}
+	Ok(keys.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn context_bindings_override_global_ones() {
+		use KeyContext::*;
+		let keymap = Keymap::default();
+		assert_eq!(keymap.action(&[List], "n"), Some(Action::NewEntry));
+		assert_eq!(keymap.action(&[Detail], "?"), Some(Action::Help));
+		assert_eq!(keymap.action(&[Edit, Detail, List], "esc"), Some(Action::Cancel));
+		assert_eq!(keymap.action(&[Detail, List], "esc"), Some(Action::Close));
+		assert_eq!(keymap.action(&[Detail, List], "n"), Some(Action::NewEntry));
+		assert_eq!(keymap.action(&[Detail], "n"), None);
+	}
+
+	#[test]
+	fn overrides_replace_bindings_and_support_chords() {
+		use KeyContext::*;
+		let overrides: KeyOverrides = toml::from_str(
+			r#"
+			list.new_entry = ["Ctrl-N"]
+			list.next_entry = ["g  j"]
+			detail.toggle_history = []
+			"#,
+		)
+		.unwrap();
+		let keymap = Keymap::with_overrides(&overrides).unwrap();
+		assert_eq!(keymap.action(&[List], "a"), None);
+		assert_eq!(keymap.action(&[List], "ctrl-n"), Some(Action::NewEntry));
+		assert_eq!(keymap.action(&[Detail, List], "h"), None);
+
+		let press = |keys: &[&str]| {
+			keymap.resolve(&[List], &keys.iter().map(|k| k.to_string()).collect::<Vec<_>>())
+		};
+		assert_eq!(press(&["g"]), KeyResolution::Pending);
+		assert_eq!(press(&["g", "j"]), KeyResolution::Action(Action::NextEntry));
+		assert_eq!(press(&["g", "x"]), KeyResolution::Unbound);
+	}
+
+	#[test]
+	fn list_has_jump_chords() {
+		use KeyContext::*;
+		let keymap = Keymap::default();
+		let press = |keys: &[&str]| {
+			keymap.resolve(&[Detail, List], &keys.iter().map(|k| k.to_string()).collect::<Vec<_>>())
+		};
+		assert_eq!(press(&["g"]), KeyResolution::Pending);
+		assert_eq!(press(&["g", "g"]), KeyResolution::Action(Action::FirstEntry));
+		assert_eq!(press(&["shift-g"]), KeyResolution::Action(Action::LastEntry));
+	}
+
+	#[test]
+	fn conflicting_overrides_are_rejected() {
+		let overrides: KeyOverrides = toml::from_str(r#"list.show_trash = ["a"]"#).unwrap();
+		assert!(Keymap::with_overrides(&overrides).is_err());
+		let overrides: KeyOverrides = toml::from_str(r#"list.show_trash = ["d d"]"#).unwrap();
+		assert!(Keymap::with_overrides(&overrides).is_err());
+	}
+
+	#[test]
+	fn hints_prefer_the_most_specific_context() {
+		let hints = Keymap::default().hints(&[KeyContext::Detail, KeyContext::List], 3);
+		let actions: Vec<Action> = hints.into_iter().map(|(_, action)| action).collect();
+		assert_eq!(actions, [Action::EditEntry, Action::ToggleHistory, Action::CopyField]);
+	}
+
+	#[test]
+	fn help_groups_keys_per_action() {
+		let help = Keymap::default().help();
+		assert_eq!(help[0].0, KeyContext::Global);
+		let (_, list) = help.iter().find(|(c, _)| *c == KeyContext::List).unwrap();
+		assert!(list.contains(&(vec!["a".to_string(), "n".to_string()], Action::NewEntry)));
+		assert_eq!(Keymap::default().keys_for(KeyContext::Detail, Action::RevealSecret), [
+			"enter", "r"
+		]);
+	}
+}
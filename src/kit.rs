@@ -0,0 +1,143 @@
+//! The emergency kit: a printable page telling whoever comes after you how
+//! to open the store, where it lives and what is in it.
+//!
+//! The store passphrase is left as a blank to fill in by hand unless it is
+//! asked for, in which case it is printed along with a QR code of it. Entry
+//! names and kinds are listed, never their secrets.
+
+use std::{fmt::Write as _, path::PathBuf};
+
+use crate::{Result, models::AccountName, qr};
+
+const WIDTH: usize = 72;
+
+/// Everything that goes on the kit.
+#[derive(Debug, Clone)]
+pub struct EmergencyKit {
+	pub store_dir:  PathBuf,
+	pub branch:     String,
+	/// The name the store has in the config's `[stores]`, if any.
+	pub store_name: Option<String>,
+	/// Entry names with their kinds.
+	pub entries:    Vec<(AccountName, &'static str)>,
+	/// The store passphrase, or `None` to leave it blank.
+	pub passphrase: Option<String>,
+	pub created:    jiff::civil::Date,
+}
+
+fn heading(out: &mut String, title: &str) {
+	let _ = writeln!(out, "\n{title}\n{}", "-".repeat(title.chars().count()));
+}
+
+impl EmergencyKit {
+	/// The kit as plain text, laid out for printing.
+	pub fn render(&self) -> Result<String> {
+		let mut out = String::new();
+		let title = "PWD EMERGENCY KIT";
+		let _ = writeln!(out, "{}\n{title:^WIDTH$}\n{}", "=".repeat(WIDTH), "=".repeat(WIDTH));
+		let _ = writeln!(out, "Created {}. Keep this somewhere safe, like a", self.created);
+		let _ = writeln!(out, "fireproof box or with your will, and never online.");
+
+		heading(&mut out, "Where the store is");
+		let _ = writeln!(out, "Folder:  {}", self.store_dir.display());
+		if let Some(name) = &self.store_name {
+			let _ = writeln!(out, "Name:    {name} (pwd --store {name})");
+		}
+		let _ = writeln!(out, "Branch:  {}", self.branch);
+		let _ = writeln!(out, "Entries: {}", self.entries.len());
+
+		heading(&mut out, "Store passphrase");
+		match &self.passphrase {
+			Some(passphrase) => {
+				let _ = writeln!(out, "{passphrase}\n");
+				out.push_str(&qr::render_printable(passphrase)?);
+				out.push('\n');
+			}
+			None => {
+				let _ = writeln!(out, "Write it here by hand:\n");
+				let _ = writeln!(out, "{}\n", "_".repeat(WIDTH));
+				let _ = writeln!(out, "{}", "_".repeat(WIDTH));
+			}
+		}
+
+		heading(&mut out, "For the person opening it");
+		let steps = [
+			"Install pwd on a computer you trust.".to_string(),
+			"Copy the store folder above to that computer, or ask someone who syncs with it \
+			 for a copy. The files in it are encrypted and useless without the passphrase."
+				.to_string(),
+			format!(
+				"Run `pwd --store <folder> --branch {} list` and enter the passphrase to see \
+				 every entry.",
+				self.branch
+			),
+			"Run `pwd --store <folder> show <name> --reveal` to read one, or \
+			 `pwd --store <folder> cp <name>` to copy its password."
+				.to_string(),
+			"To move everything to another password manager, run \
+			 `pwd --store <folder> export kdbx <file>` for KeePass-compatible ones."
+				.to_string(),
+		];
+		for (i, step) in steps.iter().enumerate() {
+			let _ = writeln!(out, "{}. {}", i + 1, wrap(step, WIDTH - 3, "   "));
+		}
+
+		if !self.entries.is_empty() {
+			heading(&mut out, "What is in it");
+			let widest = self.entries.iter().map(|(name, _)| name.as_str().len()).max().unwrap_or(0);
+			for (name, kind) in &self.entries {
+				let _ = writeln!(out, "  {:widest$}  {kind}", name.as_str());
+			}
+		}
+		Ok(out)
+	}
+}
+
+/// `text` wrapped at `width`, with `indent` before every line but the first.
+fn wrap(text: &str, width: usize, indent: &str) -> String {
+	let mut lines: Vec<String> = vec![];
+	let mut line = String::new();
+	for word in text.split_whitespace() {
+		if !line.is_empty() && line.len() + 1 + word.len() > width {
+			lines.push(std::mem::take(&mut line));
+		}
+		if !line.is_empty() {
+			line.push(' ');
+		}
+		line.push_str(word);
+	}
+	lines.push(line);
+	lines.join(&format!("\n{indent}"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn kit(passphrase: Option<&str>) -> EmergencyKit {
+		EmergencyKit {
+			store_dir:  "/home/alice/.pwd".into(),
+			branch:     "main".into(),
+			store_name: None,
+			entries:    vec![(AccountName::new("bank").unwrap(), "online_account")],
+			passphrase: passphrase.map(str::to_string),
+			created:    jiff::civil::date(2026, 1, 2),
+		}
+	}
+
+	#[test]
+	fn the_passphrase_is_only_printed_when_asked_for() {
+		let redacted = kit(None).render().unwrap();
+		assert!(redacted.contains("/home/alice/.pwd") && redacted.contains("bank"));
+		assert!(redacted.contains("Write it here by hand"));
+
+		let revealed = kit(Some("correct horse")).render().unwrap();
+		assert!(revealed.contains("correct horse") && revealed.contains('#'));
+		assert!(!revealed.contains("Write it here by hand"));
+	}
+
+	#[test]
+	fn steps_are_wrapped() {
+		assert_eq!(wrap("aaa bbb ccc", 7, "  "), "aaa bbb\n  ccc");
+	}
+}
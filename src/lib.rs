@@ -1,11 +1,30 @@
 pub mod access_control;
+pub mod audit;
+pub mod bigtext;
+pub mod browser;
+pub mod clipboard;
+pub mod config;
 pub mod encryption;
 pub mod error;
+pub mod export;
 pub mod ffi;
+pub mod forms;
+pub mod generator;
+pub mod import;
+pub mod keymap;
+pub mod kit;
+pub mod markdown;
+pub mod merge;
 pub mod models;
+pub mod otp;
 pub mod p2p;
+pub mod qr;
 pub mod rekey;
+pub mod rpc;
+pub mod search;
+pub mod stats;
 pub mod store;
+pub mod templates;
 pub mod versioning;
 
 pub use access_control::{AccessControl, AccessLevel, AdminAccess, Authorized, BranchPath, BranchSegment, BranchTarget, EditAccess, GrantsAdmin, GrantsEdit, GrantsRead, GroupBranch, InMemoryAccessControl, ItemTarget, PersonalBranch, PrincipalId, ReadAccess, RelayAccess, branch_storage_component};
@@ -13,7 +32,9 @@ pub use encryption::{AgeScrypt, EncryptionMethod, Locked, Unlocked};
 pub use error::{Error, Result};
 pub use models::{AccountName, Item, PasswordStore};
 pub use rekey::{MasterIdentity, MasterKeySet, OfflineSession, OfflineSessionPolicy};
+pub use search::{FieldMatch, SearchFilter, SearchHit, SearchIndex, SortMode};
 pub use store::{ShareTicket, StoreBackend, StoreChange, StorePayload, VersionedEntry};
+pub use templates::{FieldKind, ItemTemplate, TemplateField, TemplateItem, TemplateRegistry};
 pub use versioning::{ChangeEntry, EntryHandle, PijulStore};
 
 uniffi::setup_scaffolding!();
@@ -1,15 +1,16 @@
-use std::{path::PathBuf, str::FromStr as _};
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr as _};
 
 use clap::{Parser, Subcommand};
-use password::{AccountName, AgeScrypt, BranchPath, BranchSegment, Item, PersonalBranch, PijulStore, ShareTicket, StoreBackend, StoreChange, VersionedEntry, models::{AccountStatus, OnlineAccount, SocialSecurity}, p2p::{IrohSyncHandle, decode_store, encode_store}};
+use password::{AccountName, AgeScrypt, BranchPath, BranchSegment, FieldKind, Item, ItemTemplate, PersonalBranch, PijulStore, ShareTicket, StoreBackend, StoreChange, TemplateRegistry, VersionedEntry, config::{Config, MaskPolicy}, generator::GeneratorOptions, models::{AccountStatus, CustomField, OnlineAccount, SocialSecurity, Totp}, p2p::{IrohSyncHandle, ShareCode, decode_store, encode_store}};
 
 /// A type-safe, Pijul-versioned, Iroh P2P credential store.
 #[derive(Parser)]
 #[command(name = "pwd", about, version)]
 struct Cli {
-	/// Path to the store directory (defaults to $HOME/.pwd).
-	#[arg(long, short = 'd', global = true, env = "PWD_STORE_DIR")]
-	store_dir: Option<PathBuf>,
+	/// The store: a name from the config's `[stores]` table or a directory.
+	/// Defaults to $PWD_STORE_DIR, then $HOME/.pwd.
+	#[arg(long, short = 'd', visible_alias = "store-dir", global = true, env = "PWD_STORE")]
+	store: Option<String>,
 
 	/// The branch (party) to operate on.
 	#[arg(long, short = 'b', global = true, default_value = "main")]
@@ -20,6 +21,12 @@ struct Cli {
 	#[arg(long, global = true, env = "PWD_STORE_PASSPHRASE")]
 	passphrase: Option<String>,
 
+	/// Read the store passphrase from the first line of this file
+	/// descriptor instead; 0 is stdin. Without either, the program named by
+	/// $PWD_ASKPASS is asked, then the terminal.
+	#[arg(long, global = true, value_name = "FD")]
+	passphrase_fd: Option<u32>,
+
 	#[command(subcommand)]
 	command: Cmd,
 }
@@ -35,11 +42,12 @@ enum Cmd {
 		/// Unique name for this entry.
 		name: String,
 
-		/// Entry type: "online" (default) or "ssn".
-		#[arg(long, default_value = "online")]
+		/// Entry type: "online-account" (default; also "online") or "ssn".
+		#[arg(long, default_value = "online-account")]
 		r#type: String,
 
-		/// Password (online accounts only).
+		/// Password (online accounts only); "-" reads it from the first line
+		/// of stdin, keeping it out of shell history.
 		#[arg(long)]
 		password: Option<String>,
 
@@ -55,11 +63,59 @@ enum Cmd {
 		#[arg(long)]
 		website: Option<String>,
 
+		/// TOTP secret: an otpauth:// URI, the path of a QR code image, or a
+		/// base32 secret.
+		#[arg(long)]
+		totp: Option<String>,
+
+		/// Name of a YubiKey OATH credential computing the account's codes,
+		/// instead of a stored TOTP secret.
+		#[arg(long, conflicts_with = "totp")]
+		totp_yubikey: Option<String>,
+
+		/// Extra field as LABEL=VALUE (repeatable).
+		#[arg(long = "custom", value_name = "LABEL=VALUE")]
+		custom: Vec<String>,
+
+		/// Extra secret (masked) field as LABEL=VALUE (repeatable).
+		#[arg(long = "custom-secret", value_name = "LABEL=VALUE")]
+		custom_secret: Vec<String>,
+
+		/// Tag to attach (repeatable).
+		#[arg(long = "tag", short = 't')]
+		tags: Vec<String>,
+
+		/// Record message for history.
+		#[arg(long, short = 'm', default_value = "add entry")]
+		message: String,
+	},
+
+	/// Interactively create an entry of any built-in type, prompting for each
+	/// field in turn.
+	New {
+		/// Unique name for this entry.
+		name: String,
+
+		/// Item kind (e.g. "wifi_network"); prompted for when omitted.
+		#[arg(long)]
+		kind: Option<String>,
+
 		/// Record message for history.
 		#[arg(long, short = 'm', default_value = "add entry")]
 		message: String,
 	},
 
+	/// Edit an entry's TOML in $VISUAL or $EDITOR. The entry is checked
+	/// before it is saved, and reopened in the editor when it is invalid.
+	Edit {
+		/// Entry name.
+		name: String,
+
+		/// Record message for history.
+		#[arg(long, short = 'm', default_value = "edit entry")]
+		message: String,
+	},
+
 	/// Print a credential entry to stdout.
 	Get {
 		/// Entry name.
@@ -70,18 +126,191 @@ enum Cmd {
 		field: Option<String>,
 	},
 
-	/// Remove a credential entry.
+	/// Copy a field of an entry to the clipboard without printing it, and
+	/// clear it again after `[clipboard] clear_after_secs`.
+	Cp {
+		/// Entry name.
+		name: String,
+
+		/// Field to copy (defaults to the entry's password, number or key).
+		field: Option<String>,
+	},
+
+	/// Copy stdin to the clipboard and clear it after the timeout; the
+	/// background process behind `pwd cp`.
+	#[command(hide = true)]
+	ClipboardHelper,
+
+	/// Pick an entry in dmenu, rofi, fuzzel or wofi and copy its secret, or
+	/// type it into the focused window with `--type`.
+	Menu {
+		/// Launcher command reading names on stdin and printing the choice,
+		/// e.g. "rofi -dmenu". Defaults to the first of fuzzel, wofi, rofi or
+		/// dmenu found, preferring the Wayland ones under Wayland.
+		#[arg(long, env = "PWD_MENU")]
+		launcher: Option<String>,
+
+		/// Field to use (defaults to the entry's password, number or key).
+		#[arg(long, short = 'f')]
+		field: Option<String>,
+
+		/// Type the value with wtype (Wayland) or xdotool (X11) instead of
+		/// copying it.
+		#[arg(long = "type")]
+		type_it: bool,
+	},
+
+	/// Run a command with entry fields in its environment, so secrets never
+	/// pass through shell history or dotfiles. Exits with the command's
+	/// status. pwd wipes its copies of the values once the command exits.
+	Run {
+		/// Variable to set as VAR=ENTRY/FIELD, or VAR=ENTRY for the entry's
+		/// password, number or key (repeatable).
+		#[arg(long = "env", short = 'e', value_name = "VAR=ENTRY[/FIELD]", required = true)]
+		env: Vec<String>,
+
+		/// The command and its arguments, after `--`.
+		#[arg(last = true, required = true, value_name = "COMMAND")]
+		command: Vec<String>,
+	},
+
+	/// Print an entry's current one-time password, waiting for the next one
+	/// when it is about to expire. For counter-based (HOTP) secrets this is
+	/// the next code, and the counter is saved. Only the code goes to stdout;
+	/// how long it stays valid is reported on stderr.
+	Otp {
+		/// Entry name.
+		name: String,
+
+		/// Print the otpauth:// provisioning QR code instead, to enrol the
+		/// secret in another authenticator.
+		#[arg(long, conflicts_with = "copy")]
+		qr: bool,
+
+		/// Copy the code to the clipboard instead of printing it.
+		#[arg(long, short = 'c')]
+		copy: bool,
+	},
+
+	/// Move a credential entry to the trash, after asking on a terminal.
+	#[command(visible_alias = "rm")]
 	Remove {
 		/// Entry name.
 		name: String,
 
+		/// Delete the entry and its attachments permanently instead.
+		#[arg(long)]
+		purge: bool,
+
+		/// Don't ask for confirmation.
+		#[arg(long, short = 'f')]
+		force: bool,
+
 		/// Record message for history.
 		#[arg(long, short = 'm', default_value = "remove entry")]
 		message: String,
 	},
 
+	/// Rename an entry, with its attachments and history, and point other
+	/// entries' `associated_items` links at the new name.
+	#[command(visible_alias = "mv")]
+	Rename {
+		/// Current entry name.
+		from: String,
+
+		/// New entry name.
+		to: String,
+	},
+
 	/// List all credential entries.
-	List,
+	List {
+		/// Only list entries carrying this tag.
+		#[arg(long, short = 't')]
+		tag: Option<String>,
+
+		/// Print a JSON array of `{"name", "kind", "subtitle", "tags"}`
+		/// objects, sorted by name; `subtitle` may be null. No secrets are
+		/// included.
+		#[arg(long)]
+		json: bool,
+	},
+
+	/// Search entry names and field values for a case-insensitive string,
+	/// printing each matching entry with the fields that matched. Secret
+	/// fields are skipped unless `--include-secrets` is given.
+	Grep {
+		/// Text to look for.
+		pattern: String,
+
+		/// Search passwords, keys and other secret fields too, printing them
+		/// where they match.
+		#[arg(long)]
+		include_secrets: bool,
+	},
+
+	/// Report weak, reused and expired secrets, and breached passwords with
+	/// `--breached`. Fails when a check finds more than the config's
+	/// `[audit]` limits allow, so it can run from cron or CI.
+	Audit {
+		/// Also look passwords up in Have I Been Pwned. Only the first five
+		/// hex digits of each password's SHA-1 are sent.
+		#[arg(long)]
+		breached: bool,
+
+		/// Print `{"findings": [{"name", "check", "detail"}], "exceeded":
+		/// [...]}`, where `check` and the `exceeded` entries are "weak",
+		/// "reused", "breached" or "expired".
+		#[arg(long)]
+		json: bool,
+	},
+
+	/// List every tag used on this branch.
+	Tags,
+
+	/// List the user-defined item templates in the store's `templates/`
+	/// directory.
+	Templates,
+
+	/// Bring entries over from another password manager.
+	Import {
+		/// List what would be created, overwritten and skipped, without
+		/// changing the store.
+		#[arg(long, global = true)]
+		dry_run: bool,
+
+		/// What to do with an entry whose name is taken: skip, overwrite or
+		/// keep-both (adds it as "NAME 2"). Without it, each conflict is
+		/// reviewed on a terminal and skipped otherwise.
+		#[arg(long, global = true, value_name = "STRATEGY")]
+		merge: Option<password::import::MergeStrategy>,
+
+		#[command(subcommand)]
+		source: ImportCmd,
+	},
+
+	/// Write entries out for another password manager or store.
+	Export {
+		/// Only export the entries matching this: tag:TAG, folder:FOLDER,
+		/// kind:KIND or name:NAME (repeatable). Repeating a sort of filter
+		/// adds to the selection; combining sorts narrows it.
+		#[arg(long, short, global = true, value_name = "FILTER")]
+		filter: Vec<password::export::Filter>,
+
+		#[command(subcommand)]
+		format: ExportCmd,
+	},
+
+	/// Manage encrypted file attachments on an entry.
+	Attachment {
+		#[command(subcommand)]
+		action: AttachmentCmd,
+	},
+
+	/// Inspect, restore from, or empty the trash.
+	Trash {
+		#[command(subcommand)]
+		action: TrashCmd,
+	},
 
 	/// Show the change history.
 	Log {
@@ -90,14 +319,29 @@ enum Cmd {
 		entry: Option<String>,
 	},
 
-	/// Show the content of an entry at a specific patch hash.
+	/// Print an entry's fields, one per line. Secrets are masked on a
+	/// terminal unless `--reveal` is given; piped output shows them.
 	Show {
 		/// Entry name.
 		name: String,
 
-		/// Patch hash (base32) to restore to.
+		/// Print only this field's value (e.g. "password", "username").
+		#[arg(long, short = 'f')]
+		field: Option<String>,
+
+		/// Show secret fields in clear on a terminal too.
+		#[arg(long)]
+		reveal: bool,
+
+		/// Show the entry as it was at this patch hash (base32).
+		#[arg(long)]
+		at: Option<String>,
+
+		/// Print `{"name": ..., "item": ...}`, where `item` is the entry as
+		/// stored, tagged by its `kind`. Masked secrets read "••••••••". With
+		/// `--field`, print the value as a JSON string.
 		#[arg(long)]
-		at: String,
+		json: bool,
 	},
 
 	/// Revert an entry to the state it had after a specific patch.
@@ -124,13 +368,56 @@ enum Cmd {
 		to: Option<String>,
 	},
 
-	/// Share the store branch via Iroh; prints a ticket for the receiver.
-	Share,
+	/// Share the store branch via Iroh; prints a ticket and the code the
+	/// branch is encrypted with, both of which the receiver needs.
+	Share {
+		/// Also print the ticket as a QR code.
+		#[arg(long)]
+		qr: bool,
+	},
 
 	/// Receive a shared store branch via an Iroh ticket.
 	Receive {
 		/// Ticket string printed by `pwd share`.
 		ticket: String,
+
+		/// The share code printed alongside the ticket; asked for when
+		/// left out.
+		#[arg(long)]
+		code: Option<String>,
+	},
+
+	/// Answer JSON-RPC 2.0 requests, one per line, for editor plugins and
+	/// other front ends. The store starts locked unless a passphrase is
+	/// given up front; clients can send `unlock`.
+	Serve {
+		/// Talk over stdin and stdout, the only transport so far.
+		#[arg(long, required = true)]
+		stdio: bool,
+	},
+
+	/// Print a newly generated password, or save it to an entry.
+	Generate {
+		/// Use the named `[generator.presets]` entry from the config.
+		#[arg(long)]
+		preset: Option<String>,
+
+		/// Generate a password of this many characters.
+		#[arg(long, short = 'l')]
+		length: Option<u32>,
+
+		/// Generate a passphrase of this many words.
+		#[arg(long, short = 'w', conflicts_with = "length")]
+		words: Option<u32>,
+
+		/// Set it as this entry's password (or passphrase) instead of
+		/// printing it, within the entry's password rules.
+		#[arg(long, value_name = "ENTRY")]
+		save: Option<String>,
+
+		/// Record message for history.
+		#[arg(long, short = 'm', default_value = "generate password")]
+		message: String,
 	},
 
 	/// Re-encrypt this branch with a new passphrase.
@@ -146,18 +433,231 @@ enum Cmd {
 	},
 }
 
+#[derive(Subcommand)]
+enum ImportCmd {
+	/// A pass (password-store) directory, decrypting each entry with gpg.
+	Pass {
+		/// The store directory (defaults to $PASSWORD_STORE_DIR, then
+		/// ~/.password-store).
+		#[arg(env = "PASSWORD_STORE_DIR")]
+		dir: Option<PathBuf>,
+	},
+	/// A 1Password export (.1pux), with every vault, section and document.
+	Onepux {
+		/// The .1pux file.
+		file: PathBuf,
+	},
+	/// A password CSV exported from Chrome, Edge or Firefox.
+	#[command(visible_aliases = ["chrome", "edge", "firefox"])]
+	Browser {
+		/// The .csv file.
+		file: PathBuf,
+	},
+	/// A password CSV exported from Safari or iCloud Keychain, with its
+	/// one-time codes.
+	#[command(visible_aliases = ["keychain", "icloud"])]
+	Safari {
+		/// The .csv file.
+		file: PathBuf,
+	},
+	/// Any CSV file with a header row. The columns are matched to fields
+	/// by their headers; on a terminal the mapping is shown with the first
+	/// rows for correcting before anything is imported.
+	Csv {
+		/// The .csv file.
+		file: PathBuf,
+		/// The kind of item every row becomes, e.g. "wifi_network".
+		#[arg(long, default_value = "online_account")]
+		kind: String,
+		/// Map the column with this header: HEADER=TARGET, where TARGET is
+		/// name, folder, tags, custom, skip or a field of the kind
+		/// (repeatable).
+		#[arg(long = "map", value_name = "HEADER=TARGET")]
+		map:  Vec<String>,
+		/// Import with the mapping as guessed and given, without asking.
+		#[arg(long, short)]
+		yes:  bool,
+	},
+	/// An entry written by `pwd export item`, sealed or in plaintext.
+	Item {
+		/// The item file.
+		file: PathBuf,
+		/// Import it under this name rather than the one it was exported as.
+		#[arg(long = "as", value_name = "NAME")]
+		name: Option<String>,
+	},
+	/// A KeePass database (.kdbx), as written by KeePassXC and friends.
+	Kdbx {
+		/// The .kdbx file.
+		file:    PathBuf,
+		/// Key file the database also needs.
+		#[arg(long)]
+		keyfile: Option<PathBuf>,
+	},
+	/// A FIDO Credential Exchange Format (CXF) file, with passkeys and
+	/// passwords from Apple Passwords, 1Password, Bitwarden and others.
+	Cxf {
+		/// The .json file.
+		file: PathBuf,
+	},
+}
+
+#[derive(Subcommand)]
+enum ExportCmd {
+	/// A KeePass KDBX 4 database, for KeePassXC and friends.
+	Kdbx {
+		/// The .kdbx file to write; an existing file is replaced.
+		file:    PathBuf,
+		/// Key file to lock the database with as well as a password.
+		#[arg(long)]
+		keyfile: Option<PathBuf>,
+	},
+	/// Passkeys and logins in the FIDO Credential Exchange Format (CXF),
+	/// unencrypted, for managers that import it.
+	Cxf {
+		/// Write to this file, readable only by you, instead of stdout.
+		#[arg(long, short)]
+		output: Option<PathBuf>,
+	},
+	/// One entry and its attachments as a file of its own, sealed with a
+	/// passphrase, for `pwd import item` in another store.
+	Item {
+		/// Entry name.
+		name:      String,
+		/// Write plaintext TOML instead of sealing it.
+		#[arg(long)]
+		plaintext: bool,
+		/// The file to write (defaults to NAME.toml.age, or NAME.toml).
+		#[arg(long, short)]
+		output:    Option<PathBuf>,
+	},
+	/// Unencrypted CSV or JSON, one row per entry. Anyone who can read the
+	/// output can read every exported secret.
+	Plaintext {
+		/// "csv" or "json".
+		#[arg(long)]
+		format:             password::export::plain::Format,
+		/// Confirm that you want secrets written out unencrypted.
+		#[arg(long)]
+		insecure_plaintext: bool,
+		/// Only these columns, in this order: name, kind, tags, any form
+		/// field, totp, or custom:LABEL (comma-separated).
+		#[arg(long, value_delimiter = ',')]
+		fields:             Vec<String>,
+		/// Leave out passwords, keys and every other secret value.
+		#[arg(long)]
+		no_secrets:         bool,
+		/// Write to this file, readable only by you, instead of stdout.
+		#[arg(long, short)]
+		output:             Option<PathBuf>,
+	},
+	/// A printable emergency kit: where the store is, how to open it and
+	/// what is in it, for whoever may need it after you.
+	#[command(visible_alias = "emergency-kit")]
+	Kit {
+		/// Print the store passphrase and a QR code of it instead of a blank
+		/// to fill in by hand.
+		#[arg(long)]
+		reveal: bool,
+		/// Write to this file, readable only by you, instead of stdout.
+		#[arg(long, short)]
+		output: Option<PathBuf>,
+	},
+}
+
+#[derive(Subcommand)]
+enum TrashCmd {
+	/// List trashed entries.
+	List,
+
+	/// Put a trashed entry back on the branch.
+	Restore {
+		/// Entry name.
+		name: String,
+	},
+
+	/// Permanently delete everything in the trash.
+	Empty,
+}
+
+#[derive(Subcommand)]
+enum AttachmentCmd {
+	/// Encrypt a file and attach it to an entry.
+	Add {
+		/// Entry name.
+		name: String,
+
+		/// File to attach; stored under its file name.
+		path: PathBuf,
+	},
+
+	/// List an entry's attachments.
+	List {
+		/// Entry name.
+		name: String,
+	},
+
+	/// Decrypt an attachment to a temporary file and open it.
+	Open {
+		/// Entry name.
+		name: String,
+
+		/// Attachment file name.
+		file: String,
+	},
+
+	/// Decrypt an attachment to a file.
+	Export {
+		/// Entry name.
+		name: String,
+
+		/// Attachment file name.
+		file: String,
+
+		/// Destination path (defaults to the attachment's name in the current
+		/// directory).
+		#[arg(long, short = 'o')]
+		out: Option<PathBuf>,
+	},
+
+	/// Delete an attachment.
+	Remove {
+		/// Entry name.
+		name: String,
+
+		/// Attachment file name.
+		file: String,
+	},
+}
+
 // ── entry point
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-	let cli = Cli::parse();
+	let terminal = TerminalState::capture();
+	terminal.restore_on_panic();
+	let result = run(Cli::parse()).await;
+	terminal.restore();
+	result
+}
 
-	let store_dir = cli
-		.store_dir
-		.unwrap_or_else(|| dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".pwd"));
+async fn run(mut cli: Cli) -> anyhow::Result<()> {
+	let store_dir = match &cli.store {
+		Some(store) => load_config()?.store_dir(store),
+		None => std::env::var_os("PWD_STORE_DIR")
+			.map(PathBuf::from)
+			.unwrap_or_else(|| dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".pwd")),
+	};
+
+	if let Cmd::ClipboardHelper = cli.command {
+		return clipboard_helper();
+	}
 
 	let locked_store = PijulStore::open(&store_dir)?;
 	let branch = personal_branch(&cli.branch)?;
+	if let Some(fd) = cli.passphrase_fd {
+		cli.passphrase = Some(read_fd_line(fd)?);
+	}
 
 	match cli.command {
 		Cmd::Init => {
@@ -165,18 +665,40 @@ async fn main() -> anyhow::Result<()> {
 			println!("Initialized branch '{branch}' in {}", store_dir.display());
 		}
 
-		Cmd::Add { name, r#type, password, username, email, website, message } => {
+		Cmd::Add {
+			name,
+			r#type,
+			password,
+			username,
+			email,
+			website,
+			totp,
+			totp_yubikey,
+			custom,
+			custom_secret,
+			tags,
+			message,
+		} => {
 			let store = unlock_store(locked_store, cli.passphrase)?;
 			let account_name = AccountName::new(&name)?;
-			let item = match r#type.as_str() {
-				"ssn" => Item::SocialSecurity(SocialSecurity {
-					account_number:   name.clone().parse().unwrap_or_else(|_| unimplemented!()),
+			let password = match password.as_deref() {
+				Some("-") => Some(read_stdin_line()?),
+				_ => password,
+			};
+			let mut item = match r#type.as_str() {
+				"ssn" | "social-security" => Item::SocialSecurity(SocialSecurity {
+					account_number:   name
+						.parse()
+						.map_err(|e| anyhow::anyhow!("'{name}' is not a valid account number: {e}"))?,
 					legal_name:       None,
 					issuance_date:    None,
 					country_of_issue: None,
 					notes:            None,
+					tags:             vec![],
+					custom_fields:    vec![],
+					appearance:       None,
 				}),
-				_ => {
+				"online" | "online-account" => {
 					let host_website = website.as_deref().map(|u| u.parse::<url::Url>()).transpose()?;
 					let email_addr =
 						email.as_deref().map(|e| e.parse::<email_address::EmailAddress>()).transpose()?;
@@ -191,24 +713,83 @@ async fn main() -> anyhow::Result<()> {
 						host_website,
 						login_pages: None,
 						security_questions: None,
+						recovery_codes: vec![],
+						password_policy: None,
+						totp: match totp_yubikey {
+							Some(credential) => Some(Totp::on_yubikey(credential)),
+							None => totp.as_deref().map(Totp::import).transpose()?,
+						},
 						date_created: Some(jiff::Zoned::now().date()),
 						two_factor_enabled: None,
 						associated_items: None,
 						notes: None,
+						tags: vec![],
+						custom_fields: vec![],
+						appearance: None,
 					})
 				}
+				other => anyhow::bail!("unknown entry type '{other}'; use `pwd new` for other kinds"),
 			};
 
+			for (raw, secret) in
+				custom.iter().map(|c| (c, false)).chain(custom_secret.iter().map(|c| (c, true)))
+			{
+				item.custom_fields_mut().push(parse_custom_field(raw, secret)?);
+			}
+			*item.tags_mut() = tags;
+			item.validate()?;
+
 			store.insert(&branch, account_name.clone(), item, StoreChange::Custom(message))?;
 			println!("Added '{name}' to branch '{branch}'");
 		}
 
+		Cmd::New { name, kind, message } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let account_name = AccountName::new(&name)?;
+			let form = match kind {
+				Some(kind) => password::forms::builtin_form(&kind)
+					.ok_or_else(|| anyhow::anyhow!("unknown kind '{kind}'"))?,
+				None => choose_form()?,
+			};
+			let item = loop {
+				let values = prompt_form(&form)?;
+				match password::forms::build_item(&form.name, &values) {
+					Ok(item) => break item,
+					Err(e) => eprintln!("{e}; please try again"),
+				}
+			};
+			store.insert(&branch, account_name, item, StoreChange::Custom(message))?;
+			println!("Added '{name}' to branch '{branch}'");
+		}
+
+		Cmd::Edit { name, message } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let account_name = AccountName::new(&name)?;
+			let item = store
+				.get(&branch, &account_name)?
+				.ok_or_else(|| anyhow::anyhow!("no entry '{name}' on branch '{branch}'"))?;
+			let original = toml::to_string_pretty(&item)?;
+			match edit_item(&name, &original)? {
+				None => println!("No changes to '{name}'"),
+				Some(item) => {
+					store.update(&branch, &account_name, item, StoreChange::Custom(message))?;
+					println!("Updated '{name}' on branch '{branch}'");
+				}
+			}
+		}
+
 		Cmd::Get { name, field } => {
 			let store = unlock_store(locked_store, cli.passphrase)?;
 			let account_name = AccountName::new(&name)?;
 			match store.get(&branch, &account_name)? {
 				None => eprintln!("No entry '{name}' on branch '{branch}'"),
 				Some(item) => {
+					if let Item::ApiCredential(c) = &item
+						&& let Some(expiry) = c.expiry_date
+						&& c.is_expired()
+					{
+						eprintln!("warning: '{name}' expired on {expiry}");
+					}
 					if let Some(f) = field {
 						println!("{}", extract_field(&item, &f).unwrap_or_default());
 					} else {
@@ -218,70 +799,678 @@ async fn main() -> anyhow::Result<()> {
 			}
 		}
 
-		Cmd::Remove { name, message } => {
+		Cmd::Cp { name, field } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let account_name = AccountName::new(&name)?;
+			let item = store
+				.get(&branch, &account_name)?
+				.ok_or_else(|| anyhow::anyhow!("no entry '{name}' on branch '{branch}'"))?;
+			let (field, value) = field_or_secret(&name, &item, field)?;
+			copy_in_background(&value)?;
+			match load_config()?.clipboard.clear_after_secs {
+				0 => println!("Copied {field} of '{name}' to the clipboard"),
+				secs => println!("Copied {field} of '{name}' to the clipboard; clearing in {secs}s"),
+			}
+		}
+
+		Cmd::Menu { launcher, field, type_it } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let names = store.list(&branch)?;
+			let launcher = launcher.or_else(default_launcher).ok_or_else(|| {
+				anyhow::anyhow!(
+					"no launcher found; install fuzzel, wofi, rofi or dmenu, or pass --launcher"
+				)
+			})?;
+			let Some(choice) = pick(&launcher, names.iter().map(AccountName::as_str))? else {
+				return Ok(());
+			};
+			let account_name = AccountName::new(&choice)?;
+			let item = store
+				.get(&branch, &account_name)?
+				.ok_or_else(|| anyhow::anyhow!("no entry '{choice}' on branch '{branch}'"))?;
+			let (_, value) = field_or_secret(&choice, &item, field)?;
+			if type_it { type_text(&value)? } else { copy_in_background(&value)? }
+		}
+
+		Cmd::Run { env, command } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let mut vars = Vec::with_capacity(env.len());
+			for raw in &env {
+				let (var, reference) = raw
+					.split_once('=')
+					.ok_or_else(|| anyhow::anyhow!("expected VAR=ENTRY[/FIELD], got '{raw}'"))?;
+				let value = zeroize::Zeroizing::new(resolve_reference(&store, &branch, reference)?);
+				vars.push((var.to_owned(), value));
+			}
+			let (program, args) = command.split_first().expect("clap requires a command");
+			let mut child = std::process::Command::new(program);
+			child
+				.args(args)
+				.envs(vars.iter().map(|(var, value)| (var, value.as_str())))
+				.env_remove("PWD_STORE_PASSPHRASE");
+			let status = child.status().map_err(|e| anyhow::anyhow!("could not run {program}: {e}"))?;
+			// `exit` skips destructors, so free the command's copies and wipe
+			// ours first.
+			drop(child);
+			drop(vars);
+			std::process::exit(status.code().unwrap_or(1));
+		}
+
+		Cmd::ClipboardHelper => unreachable!("handled before the store is opened"),
+
+		Cmd::Otp { name, qr, copy } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let account_name = AccountName::new(&name)?;
+			let mut item = store
+				.get(&branch, &account_name)?
+				.ok_or_else(|| anyhow::anyhow!("no entry '{name}' on branch '{branch}'"))?;
+			let account = item.subtitle().unwrap_or_else(|| name.clone());
+			let totp = item.totp_mut().ok_or_else(|| anyhow::anyhow!("'{name}' has no TOTP secret"))?;
+			let emit = |code: &str| -> anyhow::Result<()> {
+				if copy {
+					copy_in_background(code)?;
+					eprintln!("Copied the one-time code for '{name}'");
+				} else {
+					println!("{code}");
+				}
+				Ok(())
+			};
+			if qr {
+				let uri = totp.to_uri(&account)?;
+				println!("{}", render_qr(&uri)?);
+			} else if totp.is_counter_based() {
+				emit(&totp.advance()?)?;
+				let message = format!("advance HOTP counter for {name}");
+				store.update(&branch, &account_name, item, StoreChange::Custom(message))?;
+			} else {
+				let mut code = totp.code()?;
+				if code.expires_soon() {
+					eprintln!("waiting {}s for the next code", code.remaining);
+					std::thread::sleep(std::time::Duration::from_secs(code.remaining.into()));
+					code = totp.code()?;
+				}
+				emit(&code.code)?;
+				eprintln!("valid {} ({}s left)", code.window(), code.remaining);
+			}
+		}
+
+		Cmd::Remove { name, purge, force, message } => {
+			use std::io::IsTerminal as _;
 			let store = unlock_store(locked_store, cli.passphrase)?;
 			let account_name = AccountName::new(&name)?;
-			let removed = store.remove(&branch, &account_name, StoreChange::Custom(message))?;
-			if removed {
+			if store.get(&branch, &account_name)?.is_none() {
+				anyhow::bail!("no entry '{name}' on branch '{branch}'");
+			}
+			if !force && std::io::stdin().is_terminal() {
+				let question = if purge {
+					format!("Permanently delete '{name}' and its attachments? [y/N]")
+				} else {
+					format!("Move '{name}' to the trash? [y/N]")
+				};
+				if !prompt_line(&question)?.trim().eq_ignore_ascii_case("y") {
+					return Ok(());
+				}
+			}
+			let change = StoreChange::Custom(message);
+			let removed = if purge {
+				store.remove(&branch, &account_name, change)?
+			} else {
+				store.trash(&branch, &account_name, change)?
+			};
+			if removed && purge {
 				println!("Removed '{name}' from branch '{branch}'");
+			} else if removed {
+				println!("Moved '{name}' to the trash on branch '{branch}'");
 			} else {
 				eprintln!("No entry '{name}' found on branch '{branch}'");
 			}
 		}
 
-		Cmd::List => {
+		Cmd::Rename { from, to } => {
 			let store = unlock_store(locked_store, cli.passphrase)?;
-			let names = store.list(&branch)?;
-			if names.is_empty() {
+			let (from_name, to_name) = (AccountName::new(&from)?, AccountName::new(&to)?);
+			let change = StoreChange::Custom(format!("rename {from} to {to}"));
+			store.rename(&branch, &from_name, &to_name, change)?;
+			println!("Renamed '{from}' to '{to}' on branch '{branch}'");
+		}
+
+		Cmd::List { tag, json } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let mut items: Vec<(AccountName, Item)> = store
+				.load(&branch)?
+				.items
+				.into_iter()
+				.filter(|(_, item)| tag.as_ref().is_none_or(|tag| item.has_tag(tag)))
+				.collect();
+			items.sort_by(|(a, _), (b, _)| a.cmp(b));
+			if json {
+				let entries: Vec<_> = items
+					.iter()
+					.map(|(name, item)| ListEntry {
+						name:     name.as_ref(),
+						kind:     item.kind_str(),
+						subtitle: item.subtitle(),
+						tags:     item.tags(),
+					})
+					.collect();
+				println!("{}", serde_json::to_string_pretty(&entries)?);
+			} else if let Some(tag) = &tag
+				&& items.is_empty()
+			{
+				println!("(no entries tagged '{tag}' on branch '{branch}')");
+			} else if items.is_empty() {
 				println!("(empty store on branch '{branch}')");
 			} else {
-				for n in names {
+				for (n, _) in items {
 					println!("{n}");
 				}
 			}
 		}
 
-		Cmd::Log { entry } => {
+		Cmd::Grep { pattern, include_secrets } => {
+			use std::io::IsTerminal as _;
+			if pattern.is_empty() {
+				anyhow::bail!("the search pattern is empty");
+			}
 			let store = unlock_store(locked_store, cli.passphrase)?;
-			let filter = match entry {
-				Some(ref n) => Some(AccountName::new(n)?),
-				None => None,
-			};
-			let entries = store.log_impl(&branch, filter.as_ref())?;
-			if entries.is_empty() {
-				println!("(no history on branch '{branch}')");
+			let registry = TemplateRegistry::for_store(&store_dir)?;
+			let no_color = load_config().unwrap_or_default().display.with_env().no_color;
+			let color = std::io::stdout().is_terminal() && !no_color;
+			let mut items: Vec<(AccountName, Item)> = store.load(&branch)?.items.into_iter().collect();
+			items.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+			let mut any = false;
+			for (name, item) in &items {
+				let mut fields = item.text_fields();
+				if include_secrets {
+					fields.extend(
+						shown_fields(item, &registry)
+							.into_iter()
+							.filter(|(_, _, secret)| *secret)
+							.map(|(label, value, _)| (label, value)),
+					);
+				}
+				let hits: Vec<_> =
+					fields.iter().filter(|(_, value)| !find_matches(value, &pattern).is_empty()).collect();
+				if hits.is_empty() && find_matches(name.as_str(), &pattern).is_empty() {
+					continue;
+				}
+				any = true;
+				println!("{}", highlight(name.as_str(), &pattern, color));
+				for (label, value) in hits {
+					for line in value.lines().filter(|line| !find_matches(line, &pattern).is_empty()) {
+						println!("  {label}: {}", highlight(line, &pattern, color));
+					}
+				}
 			}
-			for e in entries {
-				let scope = e.entry_name.as_ref().map(|n| format!(" [{}]", n)).unwrap_or_default();
-				println!("{}  {}  {}{scope}", e.hash, e.timestamp, e.message);
+			if !any {
+				anyhow::bail!("no entries matching '{pattern}' on branch '{branch}'");
 			}
 		}
 
-		Cmd::Show { name, at } => {
+		Cmd::Audit { breached, json } => {
+			use password::audit::{Check, audit, pwned_count};
 			let store = unlock_store(locked_store, cli.passphrase)?;
-			let account_name = AccountName::new(&name)?;
-			use pijul_at_core::Base32;
-			let hash = pijul_at_core::Hash::from_base32(at.as_bytes())
-				.ok_or_else(|| anyhow::anyhow!("invalid hash: {at}"))?;
+			let limits = load_config()?.audit;
+			let lookup: &dyn Fn(&str) -> password::Result<u64> = &pwned_count;
+			let today = jiff::Zoned::now().date();
+			let report = audit(&store.load(&branch)?, today, breached.then_some(lookup))?;
+			let exceeded = report.exceeded(&limits);
 
-			match store.entry(&branch, account_name).snapshot_at(&hash)? {
-				Some(item) => println!("{}", toml::to_string_pretty(&item)?),
-				None => eprintln!("Entry '{name}' not found at patch {at}"),
+			if json {
+				let output = serde_json::json!({ "findings": report.findings, "exceeded": exceeded });
+				println!("{}", serde_json::to_string_pretty(&output)?);
+			} else {
+				for check in Check::ALL.into_iter().filter(|&c| breached || c != Check::Breached) {
+					println!("{} {}", report.count(check), check.label());
+					for finding in report.findings.iter().filter(|f| f.check == check) {
+						println!("  {}: {}", finding.name, finding.detail);
+					}
+				}
+			}
+			if !exceeded.is_empty() {
+				let reasons: Vec<String> = exceeded
+					.iter()
+					.map(|&check| {
+						let (count, limit) = (report.count(check), limits.limit(check));
+						format!("{count} {} (at most {limit} allowed)", check.label())
+					})
+					.collect();
+				anyhow::bail!("audit failed: {}", reasons.join("; "));
 			}
 		}
 
-		Cmd::Revert { name, to } => {
+		Cmd::Tags => {
 			let store = unlock_store(locked_store, cli.passphrase)?;
-			let account_name = AccountName::new(&name)?;
-			use pijul_at_core::Base32;
-			let hash = pijul_at_core::Hash::from_base32(to.as_bytes())
-				.ok_or_else(|| anyhow::anyhow!("invalid hash: {to}"))?;
-
-			store.entry(&branch, account_name).revert_to(&hash)?;
-			println!("Reverted '{name}' to {to} on branch '{branch}'");
+			for tag in store.load(&branch)?.tags() {
+				println!("{tag}");
+			}
 		}
 
-		Cmd::Diff { name, from, to } => {
+		Cmd::Templates => {
+			let registry = TemplateRegistry::for_store(&store_dir)?;
+			let mut any = false;
+			for template in registry.iter() {
+				any = true;
+				println!("{} ({})", template.name, template.label());
+				for field in &template.fields {
+					let required = if field.required { " *" } else { "" };
+					println!("  {}: {:?}{required}", field.name, field.kind);
+				}
+			}
+			if !any {
+				println!(
+					"(no templates in {})",
+					store_dir.join(password::templates::TEMPLATES_DIR).display()
+				);
+			}
+		}
+
+		Cmd::Attachment { action } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			match action {
+				AttachmentCmd::Add { name, path } => {
+					let account_name = AccountName::new(&name)?;
+					let file = path
+						.file_name()
+						.and_then(|f| f.to_str())
+						.ok_or_else(|| anyhow::anyhow!("not a file: {}", path.display()))?;
+					store.attach(&branch, &account_name, file, &std::fs::read(&path)?)?;
+					println!("Attached '{file}' to '{name}'");
+				}
+				AttachmentCmd::List { name } => {
+					let files = store.attachments(&branch, &AccountName::new(&name)?)?;
+					if files.is_empty() {
+						println!("(no attachments on '{name}')");
+					}
+					for file in files {
+						println!("{file}");
+					}
+				}
+				AttachmentCmd::Open { name, file } => {
+					use std::io::IsTerminal as _;
+					let path = store.export_attachment_to_temp(&branch, &AccountName::new(&name)?, &file)?;
+					println!("Decrypted to {}", path.display());
+					let opened = open::that(&path);
+					// Give the viewer time to read the file before deleting the
+					// plaintext copy.
+					if opened.is_ok() {
+						if std::io::stdin().is_terminal() {
+							let _ =
+								prompt_line("Press Enter when you are done with it to delete the decrypted copy");
+						} else {
+							std::thread::sleep(std::time::Duration::from_secs(30));
+						}
+					}
+					store.remove_temp_attachment(&path)?;
+					opened?;
+				}
+				AttachmentCmd::Export { name, file, out } => {
+					let dest = out.unwrap_or_else(|| PathBuf::from(&file));
+					store.export_attachment(&branch, &AccountName::new(&name)?, &file, &dest)?;
+					println!("Wrote {}", dest.display());
+				}
+				AttachmentCmd::Remove { name, file } => {
+					if store.detach(&branch, &AccountName::new(&name)?, &file)? {
+						println!("Removed '{file}' from '{name}'");
+					} else {
+						eprintln!("No attachment '{file}' on '{name}'");
+					}
+				}
+			}
+		}
+
+		Cmd::Import { dry_run, merge, source } => {
+			let entries = match source {
+				ImportCmd::Pass { dir } => {
+					let dir = dir.unwrap_or_else(|| {
+						dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".password-store")
+					});
+					password::import::pass::read_store(&dir, password::import::pass::gpg_decrypt)?
+				}
+				ImportCmd::Onepux { file } => password::import::onepux::read_export(&file)?,
+				ImportCmd::Browser { file } => password::import::browser::read_file(&file)?,
+				ImportCmd::Safari { file } => password::import::safari::read_file(&file)?,
+				ImportCmd::Csv { file, kind, map, yes } => {
+					use std::io::IsTerminal as _;
+
+					use password::import::csv;
+					let kind = kind.replace('-', "_");
+					let table = csv::read_file(&file)?;
+					let mut mapping: Vec<csv::Column> =
+						table.headers.iter().map(|header| csv::guess(&kind, header)).collect();
+					for spec in map {
+						let Some((header, target)) = spec.split_once('=') else {
+							anyhow::bail!("--map takes HEADER=TARGET, not '{spec}'");
+						};
+						let Some(index) = table.headers.iter().position(|h| h == header) else {
+							anyhow::bail!("{} has no column '{header}'", file.display());
+						};
+						mapping[index] = target.parse()?;
+					}
+					if !yes && std::io::stdin().is_terminal() {
+						map_columns(&mut std::io::stdin().lock(), &table, &kind, &mut mapping)?;
+					}
+					csv::entries(&table, &kind, &mapping)?
+				}
+				ImportCmd::Item { file, name } => {
+					use password::import::item;
+					let bytes = std::fs::read(&file)?;
+					let mut entry = if item::is_sealed(&bytes) {
+						let passphrase = read_passphrase(None, "Item file passphrase")?;
+						item::read_sealed(&bytes, &AgeScrypt::new(passphrase)?)?
+					} else {
+						item::read_toml(std::str::from_utf8(&bytes)?)?
+					};
+					if let Some(name) = name {
+						entry.name = AccountName::new(name)?;
+					}
+					vec![entry]
+				}
+				ImportCmd::Kdbx { file, keyfile } => {
+					let password = read_passphrase(None, "KeePass password")?;
+					let password = Some(password.as_str()).filter(|p| !p.is_empty() || keyfile.is_none());
+					let key = password::import::kdbx::database_key(password, keyfile.as_deref())?;
+					password::import::kdbx::read_database(&file, key)?
+				}
+				ImportCmd::Cxf { file } => password::import::cxf::read_file(&file)?,
+			};
+			use std::io::IsTerminal as _;
+
+			use password::import::{Action, MergeStrategy};
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let review = merge.is_none() && !dry_run && std::io::stdin().is_terminal();
+			let mut for_all = merge;
+			let mut failed = None;
+			let planned = password::import::plan(&store, &branch, entries, |entry| {
+				if let Some(strategy) = for_all {
+					return strategy;
+				}
+				if !review {
+					return MergeStrategy::Skip;
+				}
+				review_conflict(&store, &branch, entry, &mut for_all).unwrap_or_else(|e| {
+					failed = Some(e);
+					MergeStrategy::Skip
+				})
+			})?;
+			if let Some(e) = failed {
+				return Err(e);
+			}
+
+			if dry_run {
+				let count =
+					|action: fn(&Action) -> bool| planned.iter().filter(|p| action(&p.action)).count();
+				for p in &planned {
+					let name = &p.entry.name;
+					match &p.action {
+						Action::Create => println!("create     {name}"),
+						Action::Overwrite => println!("overwrite  {name}"),
+						Action::Skip => println!("conflict   {name} (exists; skipped)"),
+						Action::Rename(renamed) if p.conflict => {
+							println!("create     {renamed} (conflict: '{name}' exists)")
+						}
+						Action::Rename(renamed) => println!("create     {renamed} (twice in the import)"),
+					}
+				}
+				println!(
+					"Dry run: {} to create, {} to overwrite, {} skipped; nothing was changed",
+					count(|a| matches!(a, Action::Create | Action::Rename(_))),
+					count(|a| *a == Action::Overwrite),
+					count(|a| *a == Action::Skip),
+				);
+				return Ok(());
+			}
+
+			let summary = password::import::apply(&store, &branch, planned)?;
+			for name in &summary.skipped {
+				eprintln!("skipped '{name}': an entry with that name exists");
+			}
+			println!(
+				"Imported {} entries into branch '{branch}', overwrote {}",
+				summary.added.len(),
+				summary.updated.len()
+			);
+		}
+
+		Cmd::Export { filter, format } => {
+			if !filter.is_empty() && matches!(format, ExportCmd::Item { .. } | ExportCmd::Kit { .. }) {
+				anyhow::bail!("--filter only applies to the kdbx, plaintext and cxf exports");
+			}
+			if let ExportCmd::Plaintext { insecure_plaintext: false, .. } = format {
+				anyhow::bail!(
+					"a plaintext export holds every secret unencrypted; pass --insecure-plaintext if \
+					 that is what you want, or --no-secrets with it to leave them out"
+				);
+			}
+			let passphrase = read_passphrase(cli.passphrase, "Store passphrase")?;
+			let store = unlock_store(locked_store, Some(passphrase.clone()))?;
+			match format {
+				ExportCmd::Item { name, plaintext, output } => {
+					use password::export::item;
+					let name = AccountName::new(name)?;
+					let item = store
+						.get(&branch, &name)?
+						.ok_or_else(|| password::Error::EntryNotFound { name: name.clone() })?;
+					let attachments = store
+						.attachments(&branch, &name)?
+						.into_iter()
+						.map(|file| {
+							let contents = store.read_attachment(&branch, &name, &file)?;
+							Ok((file, contents))
+						})
+						.collect::<password::Result<Vec<_>>>()?;
+					let extension = if plaintext { "toml" } else { "toml.age" };
+					let output =
+						output.unwrap_or_else(|| PathBuf::from(format!("{}.{extension}", name.leaf())));
+					let contents = if plaintext {
+						eprintln!("WARNING: {} will NOT be encrypted.", output.display());
+						item::to_toml(&name, &item, &attachments)?.into_bytes()
+					} else {
+						let passphrase = read_passphrase(None, "Passphrase for the item file")?;
+						if passphrase != read_passphrase(None, "Repeat the passphrase")? {
+							anyhow::bail!("the passphrases don't match");
+						}
+						item::seal(&name, &item, &attachments, &AgeScrypt::new(passphrase)?)?
+					};
+					std::io::Write::write_all(&mut create_private(&output)?, &contents)?;
+					println!("Exported '{name}' to {}", output.display());
+				}
+				ExportCmd::Kdbx { file, keyfile } => {
+					let entries =
+						password::export::select(password::export::entries(&store, &branch)?, &filter)?;
+					let password = read_passphrase(None, "New KeePass password")?;
+					if password != read_passphrase(None, "Repeat the password")? {
+						anyhow::bail!("the passwords don't match");
+					}
+					let password = Some(password.as_str()).filter(|p| !p.is_empty() || keyfile.is_none());
+					let key = password::import::kdbx::database_key(password, keyfile.as_deref())?;
+					password::export::kdbx::write_database(&file, &entries, key)?;
+					println!("Exported {} entries to {}", entries.len(), file.display());
+				}
+				ExportCmd::Cxf { output } => {
+					let entries =
+						password::export::select(password::export::entries(&store, &branch)?, &filter)?;
+					eprintln!(
+						"WARNING: this export is NOT encrypted. Anyone who can read it can use every \
+						 password and passkey in it; delete it once it is imported."
+					);
+					let written = match &output {
+						Some(path) => password::export::cxf::write(create_private(path)?, &entries)?,
+						None => password::export::cxf::write(std::io::stdout().lock(), &entries)?,
+					};
+					let to = output.as_ref().map(|p| format!(" to {}", p.display())).unwrap_or_default();
+					eprintln!(
+						"Exported {written} of {} entries{to}; only passkeys and online accounts go into CXF",
+						entries.len()
+					);
+				}
+				ExportCmd::Plaintext { format, fields, no_secrets, output, .. } => {
+					use password::export::plain;
+					let entries =
+						password::export::select(password::export::entries(&store, &branch)?, &filter)?;
+					let fields = Some(fields.as_slice()).filter(|f| !f.is_empty());
+					let table = plain::table(&entries, fields, !no_secrets)?;
+					if !no_secrets {
+						eprintln!(
+							"WARNING: this export is NOT encrypted. Anyone who can read it can read \
+							 every secret in it; delete it as soon as you are done."
+						);
+					}
+					match &output {
+						Some(path) => {
+							plain::write(&table, format, create_private(path)?)?;
+							eprintln!("Exported {} entries to {}", entries.len(), path.display());
+						}
+						None => plain::write(&table, format, std::io::stdout().lock())?,
+					}
+				}
+				ExportCmd::Kit { reveal, output } => {
+					let entries = password::export::entries(&store, &branch)?;
+					let store_name = cli
+						.store
+						.filter(|store| load_config().is_ok_and(|config| config.stores.contains_key(store)));
+					let kit = password::kit::EmergencyKit {
+						store_dir: std::fs::canonicalize(&store_dir).unwrap_or(store_dir),
+						branch: cli.branch,
+						store_name,
+						entries: entries.iter().map(|(name, item)| (name.clone(), item.kind_str())).collect(),
+						passphrase: reveal.then_some(passphrase),
+						created: jiff::Zoned::now().date(),
+					};
+					let text = kit.render()?;
+					match &output {
+						Some(path) => {
+							std::io::Write::write_all(&mut create_private(path)?, text.as_bytes())?;
+							eprintln!("Wrote the emergency kit to {}", path.display());
+						}
+						None => print!("{text}"),
+					}
+				}
+			}
+		}
+
+		Cmd::Trash { action } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			match action {
+				TrashCmd::List => {
+					let names = store.trashed(&branch)?;
+					if names.is_empty() {
+						println!("(trash is empty)");
+					}
+					for name in names {
+						println!("{name}");
+					}
+				}
+				TrashCmd::Restore { name } => {
+					let account_name = AccountName::new(&name)?;
+					store.restore(&branch, &account_name, StoreChange::Custom(format!("restore {name}")))?;
+					println!("Restored '{name}' to branch '{branch}'");
+				}
+				TrashCmd::Empty => {
+					let count = store.empty_trash(&branch)?;
+					println!("Permanently deleted {count} trashed entries");
+				}
+			}
+		}
+
+		Cmd::Log { entry } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let filter = match entry {
+				Some(ref n) => Some(AccountName::new(n)?),
+				None => None,
+			};
+			let entries = store.log_impl(&branch, filter.as_ref())?;
+			if entries.is_empty() {
+				println!("(no history on branch '{branch}')");
+			}
+			for e in entries {
+				let scope = e.entry_name.as_ref().map(|n| format!(" [{}]", n)).unwrap_or_default();
+				println!("{}  {}  {}{scope}", e.hash, e.timestamp, e.message);
+			}
+		}
+
+		Cmd::Show { name, field, reveal, at, json } => {
+			use std::io::IsTerminal as _;
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let account_name = AccountName::new(&name)?;
+			let item = match &at {
+				None => store
+					.get(&branch, &account_name)?
+					.ok_or_else(|| anyhow::anyhow!("no entry '{name}' on branch '{branch}'"))?,
+				Some(at) => {
+					use pijul_at_core::Base32;
+					let hash = pijul_at_core::Hash::from_base32(at.as_bytes())
+						.ok_or_else(|| anyhow::anyhow!("invalid hash: {at}"))?;
+					store
+						.entry(&branch, account_name)
+						.snapshot_at(&hash)?
+						.ok_or_else(|| anyhow::anyhow!("entry '{name}' not found at patch {at}"))?
+				}
+			};
+
+			let secrets = load_config()?.secrets;
+			let reveal = reveal || !std::io::stdout().is_terminal();
+			let shown = |label: &str, secret: bool| {
+				!secret || reveal || secrets.mask_policy(label) == MaskPolicy::Never
+			};
+			let fields = shown_fields(&item, &TemplateRegistry::for_store(&store_dir)?);
+
+			if let Some(f) = field {
+				let (value, secret) = match fields.iter().find(|(label, ..)| *label == f) {
+					Some((_, value, secret)) => (value.clone(), *secret),
+					None => {
+						let value = extract_field(&item, &f)
+							.ok_or_else(|| anyhow::anyhow!("'{name}' has no field '{f}'"))?;
+						// Aliases ("number" for "account_number") resolve to a
+						// listed field's value; keep that field's masking.
+						let secret = fields.iter().any(|(_, v, secret)| *secret && *v == value);
+						(value, secret)
+					}
+				};
+				let secret = secret || derived_secret(&item, &f);
+				let value = if shown(&f, secret) {
+					value
+				} else {
+					eprintln!("'{f}' is secret; pass --reveal or pipe the output to see it");
+					MASK.to_owned()
+				};
+				if json {
+					println!("{}", serde_json::to_string(&value)?);
+				} else {
+					println!("{value}");
+				}
+			} else if json {
+				let mut object = serde_json::to_value(&item)?;
+				if !reveal {
+					mask_json(&mut object, &hidden_labels(&fields, shown));
+				}
+				let output = serde_json::json!({ "name": name, "item": object });
+				println!("{}", serde_json::to_string_pretty(&output)?);
+			} else {
+				println!("{name} ({})", item.kind_str());
+				for (label, value, secret) in &fields {
+					let value = if shown(label, *secret) { value.as_str() } else { MASK };
+					println!("  {label}: {value}");
+				}
+				if !item.tags().is_empty() {
+					println!("  tags: {}", item.tags().join(", "));
+				}
+			}
+		}
+
+		Cmd::Revert { name, to } => {
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let account_name = AccountName::new(&name)?;
+			use pijul_at_core::Base32;
+			let hash = pijul_at_core::Hash::from_base32(to.as_bytes())
+				.ok_or_else(|| anyhow::anyhow!("invalid hash: {to}"))?;
+
+			store.entry(&branch, account_name).revert_to(&hash)?;
+			println!("Reverted '{name}' to {to} on branch '{branch}'");
+		}
+
+		Cmd::Diff { name, from, to } => {
 			let store = unlock_store(locked_store, cli.passphrase)?;
 			let account_name = AccountName::new(&name)?;
 			use pijul_at_core::Base32;
@@ -308,25 +1497,32 @@ async fn main() -> anyhow::Result<()> {
 			}
 		}
 
-		Cmd::Share => {
+		Cmd::Share { qr } => {
 			let store = unlock_store(locked_store, cli.passphrase)?;
 			let loaded = store.load(&branch)?;
 			let payload = encode_store(&loaded)?;
 
+			let code = ShareCode::generate();
 			let handle = IrohSyncHandle::new();
-			let ticket = handle.share(payload).await?;
+			let ticket = handle.share(payload, &code).await?;
 			println!("{ticket}");
+			if qr {
+				println!("{}", render_qr(&ticket.to_string())?);
+			}
+			eprintln!("Share code: {code}");
+			eprintln!("Send the code by a different channel than the ticket; the receiver needs both.");
 
 			// Wait for termination.
 			tokio::signal::ctrl_c().await?;
 			handle.shutdown().await?;
 		}
 
-		Cmd::Receive { ticket } => {
+		Cmd::Receive { ticket, code } => {
 			let store = unlock_store(locked_store, cli.passphrase)?;
 			let share_ticket = ShareTicket::from_str(&ticket)?;
+			let code = ShareCode::from_str(&read_passphrase(code, "Share code")?)?;
 			let handle = IrohSyncHandle::new();
-			let payload = handle.receive(&share_ticket).await?;
+			let payload = handle.receive(&share_ticket, &code).await?;
 			handle.shutdown().await?;
 
 			let received = decode_store(payload)?;
@@ -338,6 +1534,50 @@ async fn main() -> anyhow::Result<()> {
 			println!("Store updated on branch '{branch}' — {} entries now.", current.items.len());
 		}
 
+		Cmd::Serve { stdio: _ } => {
+			let mut server = password::rpc::Server::new(locked_store, branch, load_config()?);
+			if let Some(passphrase) = cli.passphrase {
+				server.unlock(passphrase)?;
+			}
+			server.serve(std::io::stdin().lock(), std::io::stdout().lock())?;
+		}
+
+		Cmd::Generate { preset, length, words, save, message } => {
+			let mut options = match preset {
+				Some(name) => load_config()?
+					.generator
+					.presets
+					.remove(&name)
+					.ok_or_else(|| anyhow::anyhow!("no generator preset named '{name}' in the config"))?,
+				None => GeneratorOptions::Password { options: Default::default() },
+			};
+			if let Some(length) = length {
+				options = options.with_length(length);
+			}
+			if let Some(words) = words {
+				options = options.with_words(words);
+			}
+
+			let Some(name) = save else {
+				println!("{}", options.generate()?);
+				return Ok(());
+			};
+			let store = unlock_store(locked_store, cli.passphrase)?;
+			let account_name = AccountName::new(&name)?;
+			let mut item = store
+				.get(&branch, &account_name)?
+				.ok_or_else(|| anyhow::anyhow!("no entry '{name}' on branch '{branch}'"))?;
+			if let Some(policy) = item.password_policy() {
+				options = options.within(policy);
+			}
+			let field = item
+				.set_password(options.generate()?)
+				.ok_or_else(|| anyhow::anyhow!("'{name}' has no password to set"))?;
+			item.validate()?;
+			store.update(&branch, &account_name, item, StoreChange::Custom(message))?;
+			println!("Saved a new {field} to '{name}' on branch '{branch}'");
+		}
+
 		Cmd::Rekey { new_passphrase, message } => {
 			let store = unlock_store(locked_store, cli.passphrase)?;
 			let new_passphrase = read_passphrase(new_passphrase, "New store passphrase")?;
@@ -350,6 +1590,237 @@ async fn main() -> anyhow::Result<()> {
 	Ok(())
 }
 
+/// The terminal settings found at startup. Hidden prompts switch echo off;
+/// if a panic or error cuts one short, these are put back before the report
+/// is printed so the shell is left usable.
+#[derive(Clone)]
+struct TerminalState {
+	saved: Option<String>,
+}
+
+impl TerminalState {
+	fn capture() -> Self {
+		use std::io::IsTerminal as _;
+		let saved = if std::io::stdin().is_terminal() { stty(&["-g"]) } else { None };
+		Self { saved: saved.map(|s| s.trim().to_owned()) }
+	}
+
+	fn restore(&self) {
+		if let Some(saved) = &self.saved {
+			stty(&[saved]);
+		}
+	}
+
+	fn restore_on_panic(&self) {
+		let terminal = self.clone();
+		let report = std::panic::take_hook();
+		std::panic::set_hook(Box::new(move |info| {
+			terminal.restore();
+			report(info);
+		}));
+	}
+}
+
+/// Run `stty` against the controlling terminal, returning its output.
+fn stty(args: &[&str]) -> Option<String> {
+	if !cfg!(unix) {
+		return None;
+	}
+	let out = std::process::Command::new("stty")
+		.args(args)
+		.stdin(std::process::Stdio::inherit())
+		.stderr(std::process::Stdio::null())
+		.output()
+		.ok()?;
+	out.status.success().then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// The named `field` of `item`, or its primary secret when none is named,
+/// as `(field, value)`. Fails when the value is missing or empty.
+fn field_or_secret(
+	name: &str,
+	item: &Item,
+	field: Option<String>,
+) -> anyhow::Result<(String, String)> {
+	let (field, value) = match field {
+		Some(field) => {
+			let value = extract_field(item, &field)
+				.ok_or_else(|| anyhow::anyhow!("'{name}' has no field '{field}'"))?;
+			(field, value)
+		}
+		None => password::forms::primary_secret(item)
+			.ok_or_else(|| anyhow::anyhow!("'{name}' has no secret to copy"))?,
+	};
+	if value.is_empty() {
+		anyhow::bail!("'{field}' of '{name}' is empty");
+	}
+	Ok((field, value))
+}
+
+/// The value an `ENTRY/FIELD` or `ENTRY` reference names. Entry names may
+/// contain folders, so the part after the last slash is a field only when
+/// the rest names an entry that has it.
+fn resolve_reference(
+	store: &password::versioning::PijulStore<password::Unlocked<AgeScrypt>>,
+	branch: &BranchPath<PersonalBranch>,
+	reference: &str,
+) -> anyhow::Result<String> {
+	if let Some((entry, field)) = reference.rsplit_once('/')
+		&& let Ok(entry) = AccountName::new(entry)
+		&& let Some(item) = store.get(branch, &entry)?
+		&& let Some(value) = extract_field(&item, field)
+	{
+		return Ok(value);
+	}
+	let item = store
+		.get(branch, &AccountName::new(reference)?)?
+		.ok_or_else(|| anyhow::anyhow!("no entry or field '{reference}' on branch '{branch}'"))?;
+	Ok(field_or_secret(reference, &item, None)?.1)
+}
+
+/// Whether `program` is an executable file on `$PATH`.
+fn on_path(program: &str) -> bool {
+	std::env::var_os("PATH")
+		.is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+fn wayland() -> bool { std::env::var_os("WAYLAND_DISPLAY").is_some() }
+
+/// The first dmenu-style launcher installed, Wayland-native ones first
+/// under Wayland.
+fn default_launcher() -> Option<String> {
+	let wayland_launchers = ["fuzzel --dmenu", "wofi --dmenu"];
+	let x11_launchers = ["rofi -dmenu", "dmenu"];
+	let launchers: Vec<&str> = if wayland() {
+		wayland_launchers.into_iter().chain(x11_launchers).collect()
+	} else {
+		x11_launchers.into_iter().chain(wayland_launchers).collect()
+	};
+	launchers
+		.into_iter()
+		.find(|launcher| launcher.split_whitespace().next().is_some_and(on_path))
+		.map(str::to_owned)
+}
+
+/// Offer `names` in `launcher`, one per line, and return the one picked;
+/// `None` when the launcher is dismissed.
+fn pick<'a>(
+	launcher: &str,
+	names: impl Iterator<Item = &'a str>,
+) -> anyhow::Result<Option<String>> {
+	use std::{io::Write as _, process::{Command, Stdio}};
+
+	let mut words = launcher.split_whitespace();
+	let program = words.next().ok_or_else(|| anyhow::anyhow!("the launcher command is empty"))?;
+	let mut child =
+		Command::new(program).args(words).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+	let mut stdin = child.stdin.take().expect("piped stdin");
+	for name in names {
+		writeln!(stdin, "{name}")?;
+	}
+	drop(stdin);
+	let output = child.wait_with_output()?;
+	let choice = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+	Ok((output.status.success() && !choice.is_empty()).then_some(choice))
+}
+
+/// Type `text` into the focused window, with wtype under Wayland and xdotool
+/// otherwise. The text is passed on stdin, not the command line.
+fn type_text(text: &str) -> anyhow::Result<()> {
+	use std::{io::Write as _, process::{Command, Stdio}};
+
+	let (program, args): (&str, &[&str]) = if wayland() {
+		("wtype", &["-"])
+	} else {
+		("xdotool", &["type", "--clearmodifiers", "--file", "-"])
+	};
+	let mut child = Command::new(program)
+		.args(args)
+		.stdin(Stdio::piped())
+		.spawn()
+		.map_err(|e| anyhow::anyhow!("could not run {program}: {e}"))?;
+	child.stdin.take().expect("piped stdin").write_all(text.as_bytes())?;
+	let status = child.wait()?;
+	if !status.success() {
+		anyhow::bail!("{program} exited with {status}");
+	}
+	Ok(())
+}
+
+/// Hand `text` to a detached `pwd clipboard-helper`, which copies it, owns
+/// the clipboard until the timeout and then clears it, so this process can
+/// exit straight away. The secret goes over a pipe, never the command line.
+fn copy_in_background(text: &str) -> anyhow::Result<()> {
+	use std::{io::{BufRead as _, Write as _}, process::{Command, Stdio}};
+
+	let mut command = Command::new(std::env::current_exe()?);
+	command
+		.arg("clipboard-helper")
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::null());
+	// A process group of its own, so ^C in the shell doesn't cut the clear
+	// short.
+	#[cfg(unix)]
+	std::os::unix::process::CommandExt::process_group(&mut command, 0);
+	let mut child = command.spawn()?;
+	child.stdin.take().expect("piped stdin").write_all(text.as_bytes())?;
+
+	let mut line = String::new();
+	std::io::BufReader::new(child.stdout.take().expect("piped stdout")).read_line(&mut line)?;
+	match line.trim_end() {
+		"copied" => Ok(()),
+		"" => anyhow::bail!("the clipboard helper exited without copying"),
+		reason => anyhow::bail!("{reason}"),
+	}
+}
+
+/// The `pwd clipboard-helper` side of [`copy_in_background`]: report
+/// "copied" or the error on stdout, then wait out the timeout.
+fn clipboard_helper() -> anyhow::Result<()> {
+	use std::io::{Read as _, Write as _};
+
+	let mut text = String::new();
+	std::io::stdin().read_to_string(&mut text)?;
+	// The parent stops reading after the first line; later writes may fail.
+	let report = |line: &str| {
+		let mut stdout = std::io::stdout();
+		let _ = writeln!(stdout, "{line}").and_then(|()| stdout.flush());
+	};
+	let config = match load_config() {
+		Ok(config) => config.clipboard,
+		Err(e) => {
+			report(&e.to_string());
+			return Ok(());
+		}
+	};
+	let mut copied = false;
+	let result = password::clipboard::copy_and_clear(&text, &config, || {
+		copied = true;
+		report("copied");
+	});
+	if let Err(e) = result
+		&& !copied
+	{
+		report(&e.to_string());
+	}
+	Ok(())
+}
+
+/// The config file at the default location, or the defaults without one.
+fn load_config() -> password::Result<Config> {
+	Config::default_path().map(|path| Config::load(&path)).transpose().map(Option::unwrap_or_default)
+}
+
+/// A QR code for the terminal, in plain ASCII when the config's
+/// `[display] ascii` is set.
+fn render_qr(data: &str) -> password::Result<String> {
+	let ascii = Config::default_path()
+		.and_then(|path| Config::load(&path).ok())
+		.is_some_and(|config| config.display.ascii);
+	if ascii { password::qr::render_ascii(data) } else { password::qr::render_half_blocks(data) }
+}
+
 fn unlock_store(
 	store: PijulStore,
 	passphrase: Option<String>,
@@ -357,18 +1828,480 @@ fn unlock_store(
 	Ok(store.unlock_with(AgeScrypt::new(read_passphrase(passphrase, "Store passphrase")?)?))
 }
 
+/// `passphrase` when given, else the answer of the $PWD_ASKPASS program,
+/// else a prompt on the terminal.
 fn read_passphrase(passphrase: Option<String>, prompt: &str) -> anyhow::Result<String> {
-	match passphrase {
-		Some(passphrase) => Ok(passphrase),
+	if let Some(passphrase) = passphrase {
+		return Ok(passphrase);
+	}
+	match std::env::var_os("PWD_ASKPASS").filter(|program| !program.is_empty()) {
+		Some(program) => askpass(&program, prompt),
 		None => Ok(rpassword::prompt_password(format!("{prompt}: "))?),
 	}
 }
 
+/// Run an askpass program the way ssh and sudo do: the prompt is its only
+/// argument and the first line it prints is the answer.
+fn askpass(program: &std::ffi::OsStr, prompt: &str) -> anyhow::Result<String> {
+	let output = std::process::Command::new(program)
+		.arg(format!("{prompt}: "))
+		.stderr(std::process::Stdio::inherit())
+		.output()
+		.map_err(|e| anyhow::anyhow!("could not run {}: {e}", program.to_string_lossy()))?;
+	if !output.status.success() {
+		anyhow::bail!("{} exited with {}", program.to_string_lossy(), output.status);
+	}
+	let answer = String::from_utf8(output.stdout)?;
+	Ok(answer.lines().next().unwrap_or_default().to_owned())
+}
+
+/// The first line readable from file descriptor `fd`.
+fn read_fd_line(fd: u32) -> anyhow::Result<String> {
+	if fd == 0 {
+		return read_stdin_line();
+	}
+	use std::io::BufRead as _;
+	let file = std::fs::File::open(format!("/dev/fd/{fd}"))
+		.map_err(|e| anyhow::anyhow!("cannot read file descriptor {fd}: {e}"))?;
+	let mut line = String::new();
+	std::io::BufReader::new(file).read_line(&mut line)?;
+	Ok(line.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// The first line of stdin, for secrets passed as "-".
+fn read_stdin_line() -> anyhow::Result<String> {
+	let mut line = String::new();
+	if std::io::stdin().read_line(&mut line)? == 0 {
+		anyhow::bail!("aborted");
+	}
+	Ok(line.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// Let the user edit `original`, an entry's TOML, until it parses and
+/// validates. The file lives in a private directory, on /dev/shm when there
+/// is one so the plaintext never reaches a disk, and is removed afterwards.
+/// Returns `None` when nothing changed.
+fn edit_item(name: &str, original: &str) -> anyhow::Result<Option<Item>> {
+	let editor = std::env::var("VISUAL")
+		.or_else(|_| std::env::var("EDITOR"))
+		.unwrap_or_else(|_| "vi".to_owned());
+	let shm = std::path::Path::new("/dev/shm");
+	let base = if shm.is_dir() { shm.to_path_buf() } else { std::env::temp_dir() };
+	let dir = tempfile::Builder::new().prefix("pwd-edit-").tempdir_in(base)?;
+	let path = dir.path().join(format!("{}.toml", name.replace(['/', '\\'], "-")));
+	std::fs::write(&path, original)?;
+
+	loop {
+		// The editor command may carry arguments, as in `code --wait`.
+		let mut words = editor.split_whitespace();
+		let program = words.next().unwrap_or("vi");
+		let status = std::process::Command::new(program).args(words).arg(&path).status()?;
+		if !status.success() {
+			anyhow::bail!("{editor} exited with {status}; '{name}' was not changed");
+		}
+		let edited = std::fs::read_to_string(&path)?;
+		if edited == original {
+			return Ok(None);
+		}
+		let checked = toml::from_str::<Item>(&edited)
+			.map_err(anyhow::Error::from)
+			.and_then(|item| item.validate().map(|()| item).map_err(anyhow::Error::from));
+		match checked {
+			Ok(item) => return Ok(Some(item)),
+			Err(e) => {
+				eprintln!("{e}");
+				let answer = prompt_line("Edit again? [Y/n]")?;
+				if answer.trim().eq_ignore_ascii_case("n") {
+					anyhow::bail!("'{name}' was not changed");
+				}
+			}
+		}
+	}
+}
+
+fn prompt_line(prompt: &str) -> anyhow::Result<String> {
+	read_prompt(&mut std::io::stdin().lock(), prompt)
+}
+
+/// Print `prompt` and read a line from `input`, failing once it ends.
+fn read_prompt(input: &mut impl std::io::BufRead, prompt: &str) -> anyhow::Result<String> {
+	use std::io::Write as _;
+	print!("{prompt}: ");
+	std::io::stdout().flush()?;
+	let mut line = String::new();
+	if input.read_line(&mut line)? == 0 {
+		anyhow::bail!("aborted");
+	}
+	Ok(line.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// Create (or truncate) `path` readable and writable only by the user.
+fn create_private(path: &std::path::Path) -> std::io::Result<std::fs::File> {
+	let mut options = std::fs::OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+	options.open(path)
+}
+
+/// Show an imported entry next to the existing one of the same name and ask
+/// what to do with it. A capital letter answers for every later conflict
+/// too, through `for_all`. Running out of input fails, and the import with
+/// it.
+fn review_conflict(
+	store: &PijulStore<password::Unlocked<AgeScrypt>>,
+	branch: &BranchPath<PersonalBranch>,
+	entry: &password::import::Imported,
+	for_all: &mut Option<password::import::MergeStrategy>,
+) -> anyhow::Result<password::import::MergeStrategy> {
+	/// A field value for the comparison, secrets masked.
+	fn show(value: Option<&(String, bool)>) -> &str {
+		match value {
+			None => "(none)",
+			Some((_, true)) => MASK,
+			Some((value, false)) => value,
+		}
+	}
+
+	let existing = store.get(branch, &entry.name)?;
+	println!("'{}' already exists", entry.name);
+	let fields = |item: &Item| -> BTreeMap<String, (String, bool)> {
+		password::forms::editable_fields(item)
+			.into_iter()
+			.filter(|(_, value)| !value.is_empty())
+			.map(|(field, value)| (field.name, (value, field.kind == FieldKind::Secret)))
+			.collect()
+	};
+	let old = existing.as_ref().map(fields).unwrap_or_default();
+	let new = fields(&entry.item);
+	if let Some(item) = &existing {
+		println!("  existing: {}", item.kind_str());
+	}
+	println!("  imported: {}", entry.item.kind_str());
+	for name in old.keys().chain(new.keys().filter(|k| !old.contains_key(*k))) {
+		let (a, b) = (old.get(name), new.get(name));
+		if a == b {
+			println!("    {name}: {}", show(a));
+		} else {
+			println!("  ~ {name}: {} -> {}", show(a), show(b));
+		}
+	}
+	ask_strategy(&mut std::io::stdin().lock(), for_all)
+}
+
+/// Read answers from `input` until one names a strategy. Input ending
+/// skips this and every later conflict, through `for_all`, and fails.
+fn ask_strategy(
+	input: &mut impl std::io::BufRead,
+	for_all: &mut Option<password::import::MergeStrategy>,
+) -> anyhow::Result<password::import::MergeStrategy> {
+	use password::import::MergeStrategy;
+
+	loop {
+		let answer = match read_prompt(input, "[s]kip, [o]verwrite, [k]eep both (capital: for all)") {
+			Ok(answer) => answer,
+			Err(e) => {
+				*for_all = Some(MergeStrategy::Skip);
+				return Err(e);
+			}
+		};
+		let strategy = match answer.trim().to_lowercase().as_str() {
+			"s" | "skip" => MergeStrategy::Skip,
+			"o" | "overwrite" => MergeStrategy::Overwrite,
+			"k" | "keep both" | "keep-both" => MergeStrategy::KeepBoth,
+			_ => {
+				eprintln!("answer s, o or k");
+				continue;
+			}
+		};
+		if answer.trim().chars().next().is_some_and(char::is_uppercase) {
+			*for_all = Some(strategy);
+		}
+		return Ok(strategy);
+	}
+}
+
+/// Show each column of `table` with what it maps to and a few of its
+/// values, and take `NUMBER=TARGET` changes from `input` until the user
+/// accepts the mapping with an empty line. Input ending fails instead.
+fn map_columns(
+	input: &mut impl std::io::BufRead,
+	table: &password::import::csv::Table,
+	kind: &str,
+	mapping: &mut [password::import::csv::Column],
+) -> anyhow::Result<()> {
+	use password::import::csv::{self, Column};
+	const PREVIEW_ROWS: usize = 3;
+	const PREVIEW_WIDTH: usize = 24;
+
+	let form = password::forms::builtin_form(kind);
+	let secret = |column: &Column| match column {
+		Column::Field(field) => {
+			field == "totp"
+				|| form.as_ref().and_then(|f| f.field(field)).is_some_and(|f| f.kind == FieldKind::Secret)
+		}
+		_ => false,
+	};
+	let targets = format!("name, folder, tags, custom, skip, {}", csv::fields(kind)?.join(", "));
+	println!("{} rows; first {PREVIEW_ROWS} shown", table.rows.len());
+	loop {
+		for (i, (header, column)) in table.headers.iter().zip(mapping.iter()).enumerate() {
+			let samples: Vec<String> = table
+				.rows
+				.iter()
+				.take(PREVIEW_ROWS)
+				.filter_map(|row| row.get(i))
+				.map(|value| match value {
+					_ if secret(column) && !value.is_empty() => MASK.to_string(),
+					_ if value.chars().count() > PREVIEW_WIDTH => {
+						format!("{}…", value.chars().take(PREVIEW_WIDTH).collect::<String>())
+					}
+					_ => value.clone(),
+				})
+				.collect();
+			println!("{:>2}) {header} -> {column}   [{}]", i + 1, samples.join(" | "));
+		}
+		println!("Targets: {targets}");
+		// Input ending leaves the table unaccepted, so nothing is imported.
+		let answer = read_prompt(input, "Change a column (NUMBER=TARGET), or press Enter to import")
+			.map_err(|e| anyhow::anyhow!("{e}: nothing imported"))?;
+		let answer = answer.trim();
+		if answer.is_empty() {
+			match csv::check(table, kind, mapping) {
+				Ok(()) => return Ok(()),
+				Err(e) => {
+					eprintln!("{e}");
+					continue;
+				}
+			}
+		}
+		let change = answer.split_once('=').and_then(|(number, target)| {
+			let index = number.trim().parse::<usize>().ok()?.checked_sub(1)?;
+			Some((index, target.parse::<Column>().ok()?))
+		});
+		match change {
+			Some((index, column)) if index < mapping.len() => mapping[index] = column,
+			_ => eprintln!("type a column number and a target, e.g. 2=username"),
+		}
+	}
+}
+
+fn choose_form() -> anyhow::Result<ItemTemplate> {
+	let forms = password::forms::builtin_forms();
+	for (i, form) in forms.iter().enumerate() {
+		println!("{:>2}) {}", i + 1, form.label.as_deref().unwrap_or(&form.name));
+	}
+	loop {
+		let choice = prompt_line("Item type")?;
+		if let Some(form) =
+			choice.parse::<usize>().ok().and_then(|n| n.checked_sub(1)).and_then(|i| forms.get(i))
+		{
+			return Ok(form.clone());
+		}
+		if let Some(form) = forms.iter().find(|f| f.name == choice) {
+			return Ok(form.clone());
+		}
+		eprintln!("pick a number from the list");
+	}
+}
+
+/// Prompt for every field of `form`. Secrets are read without echo; an empty
+/// password or passphrase is replaced with a generated one.
+fn prompt_form(form: &ItemTemplate) -> anyhow::Result<BTreeMap<String, String>> {
+	let mut values = BTreeMap::new();
+	for field in &form.fields {
+		let mut label = field.name.clone();
+		if field.kind == FieldKind::Enum {
+			label.push_str(&format!(" [{}]", field.options.join("/")));
+		}
+		if field.required {
+			label.push('*');
+		}
+		let generatable = field.name.contains("password") || field.name.contains("passphrase");
+		let value = if field.kind == FieldKind::Secret {
+			let hint = if generatable { " (empty to generate)" } else { "" };
+			let value = rpassword::prompt_password(format!("{label}{hint}: "))?;
+			if value.is_empty() && generatable {
+				let generated = password::generator::generate_password(20, true)?;
+				println!("Generated a 20-character {}", field.name);
+				generated
+			} else {
+				value
+			}
+		} else {
+			prompt_line(&label)?
+		};
+		values.insert(field.name.clone(), value);
+	}
+	Ok(values)
+}
+
 fn personal_branch(raw: &str) -> anyhow::Result<BranchPath<PersonalBranch>> {
 	Ok(BranchPath::personal(BranchSegment::new(raw)?))
 }
 
+fn parse_custom_field(raw: &str, secret: bool) -> anyhow::Result<CustomField> {
+	let (label, value) =
+		raw.split_once('=').ok_or_else(|| anyhow::anyhow!("expected LABEL=VALUE, got '{raw}'"))?;
+	Ok(CustomField { label: label.trim().to_owned(), value: value.to_owned(), secret })
+}
+
+/// What `pwd show` prints in place of a masked secret.
+const MASK: &str = "••••••••";
+
+/// Byte ranges of the non-overlapping, case-insensitive matches of
+/// `pattern` in `text`.
+fn find_matches(text: &str, pattern: &str) -> Vec<std::ops::Range<usize>> {
+	fn match_len(text: &str, pattern: &str) -> Option<usize> {
+		let mut chars = text.char_indices();
+		for p in pattern.chars() {
+			let (_, c) = chars.next()?;
+			if !c.to_lowercase().eq(p.to_lowercase()) {
+				return None;
+			}
+		}
+		Some(chars.next().map_or(text.len(), |(i, _)| i))
+	}
+
+	let mut matches = vec![];
+	let mut start = 0;
+	while let Some(c) = text[start..].chars().next() {
+		match match_len(&text[start..], pattern) {
+			Some(len) if len > 0 => {
+				matches.push(start..start + len);
+				start += len;
+			}
+			_ => start += c.len_utf8(),
+		}
+	}
+	matches
+}
+
+/// `text` with the matches of `pattern` in bold red when `color` is set.
+fn highlight(text: &str, pattern: &str, color: bool) -> String {
+	if !color {
+		return text.to_owned();
+	}
+	let mut out = String::new();
+	let mut last = 0;
+	for range in find_matches(text, pattern) {
+		out.push_str(&text[last..range.start]);
+		out.push_str(&format!("\x1b[1;31m{}\x1b[0m", &text[range.clone()]));
+		last = range.end;
+	}
+	out.push_str(&text[last..]);
+	out
+}
+
+/// One entry of `pwd list --json`.
+#[derive(serde::Serialize)]
+struct ListEntry<'a> {
+	name:     &'a str,
+	kind:     &'static str,
+	subtitle: Option<String>,
+	tags:     &'a [String],
+}
+
+/// Secrets kept in lists inside an item, as `(list, key in each element)`.
+/// They are not among [`shown_fields`], so `show --json` masks them by the
+/// list's name.
+const NESTED_SECRETS: &[(&str, &str)] =
+	&[("recovery_codes", "code"), ("security_questions", "answer")];
+
+/// The labels of `fields` that `shown` hides, then the lists of
+/// [`NESTED_SECRETS`] it hides.
+fn hidden_labels<'a>(
+	fields: &'a [(String, String, bool)],
+	shown: impl Fn(&str, bool) -> bool,
+) -> Vec<&'a str> {
+	let listed = fields
+		.iter()
+		.filter(|(label, _, secret)| !shown(label, *secret))
+		.map(|(label, ..)| label.as_str());
+	let nested = NESTED_SECRETS.iter().map(|(list, _)| *list).filter(|list| !shown(list, true));
+	listed.chain(nested).collect()
+}
+
+/// Replace the secrets labelled `hidden` in a serialized item with [`MASK`]:
+/// top-level fields, template fields, custom fields, the secrets in
+/// [`NESTED_SECRETS`], and a TOTP secret.
+fn mask_json(item: &mut serde_json::Value, hidden: &[&str]) {
+	let mask = || serde_json::Value::from(MASK);
+	let Some(object) = item.as_object_mut() else { return };
+	for label in hidden {
+		if let Some((list, key)) = NESTED_SECRETS.iter().find(|(list, _)| list == label) {
+			let elements = object.get_mut(*list).and_then(|l| l.as_array_mut());
+			for element in elements.into_iter().flatten() {
+				if let Some(value) = element.get_mut(*key) {
+					*value = mask();
+				}
+			}
+			continue;
+		}
+		if let Some(value) = object.get_mut(*label) {
+			*value = mask();
+		}
+		if let Some(value) = object.get_mut("fields").and_then(|f| f.get_mut(*label)) {
+			*value = mask();
+		}
+	}
+	if let Some(secret) = object.get_mut("totp").and_then(|t| t.get_mut("secret")) {
+		*secret = mask();
+	}
+	for field in object.get_mut("custom_fields").and_then(|f| f.as_array_mut()).into_iter().flatten()
+	{
+		if field.get("label").and_then(|l| l.as_str()).is_some_and(|l| hidden.contains(&l)) {
+			field["value"] = mask();
+		}
+	}
+}
+
+/// The non-empty fields of `item` in form order, then its custom fields, as
+/// `(label, value, secret)`. Template items take their field kinds from
+/// `registry`; fields of an unknown template count as plain text.
+fn shown_fields(item: &Item, registry: &TemplateRegistry) -> Vec<(String, String, bool)> {
+	let mut fields: Vec<_> = match item {
+		Item::Template(t) => {
+			let template = registry.get(&t.template);
+			t.fields
+				.iter()
+				.map(|(name, value)| {
+					let secret = template
+						.and_then(|template| template.field(name))
+						.is_some_and(|field| field.kind == FieldKind::Secret);
+					(name.clone(), value.clone(), secret)
+				})
+				.collect()
+		}
+		_ => password::forms::editable_fields(item)
+			.into_iter()
+			.map(|(field, value)| (field.name, value, field.kind == FieldKind::Secret))
+			.collect(),
+	};
+	fields.retain(|(_, value, _)| !value.is_empty());
+	fields.extend(item.custom_fields().iter().map(|f| (f.label.clone(), f.value.clone(), f.secret)));
+	fields
+}
+
 fn extract_field(item: &Item, field: &str) -> Option<String> {
+	builtin_field(item, field)
+		.or_else(|| (field == "tags").then(|| item.tags().join(", ")))
+		.or_else(|| item.custom_field(field).map(|f| f.value.clone()))
+}
+
+/// Whether `field` is an alias that builds a new value out of secrets, such
+/// as a Wi-Fi join string holding the passphrase. Comparing against the
+/// listed secret fields can't catch these, so they are masked outright.
+fn derived_secret(item: &Item, field: &str) -> bool {
+	matches!(
+		(item, field),
+		(Item::OnlineAccount(_), "recovery_codes")
+			| (Item::WifiNetwork(_), "join" | "qr")
+			| (Item::ServerCredential(_), "connection" | "connection_string")
+	)
+}
+
+fn builtin_field(item: &Item, field: &str) -> Option<String> {
 	match item {
 		Item::OnlineAccount(a) => match field {
 			"username" => a.username.clone(),
@@ -377,6 +2310,14 @@ fn extract_field(item: &Item, field: &str) -> Option<String> {
 			"phone" => a.phone.as_ref().map(|p| p.to_string()),
 			"website" => a.host_website.as_ref().map(|u| u.to_string()),
 			"2fa" => a.two_factor_enabled.map(|b| b.to_string()),
+			"recovery_codes" => Some(
+				a.recovery_codes
+					.iter()
+					.filter(|c| !c.used)
+					.map(|c| c.code.as_str())
+					.collect::<Vec<_>>()
+					.join("\n"),
+			),
 			"status" => a.status.as_ref().map(|s| format!("{s:?}")),
 			"notes" => a.notes.clone(),
 			_ => None,
@@ -389,5 +2330,197 @@ fn extract_field(item: &Item, field: &str) -> Option<String> {
 			"notes" => s.notes.clone(),
 			_ => None,
 		},
+		Item::ApiCredential(c) => match field {
+			"token" => Some(c.token.clone()),
+			"secret" => c.secret.clone(),
+			"scopes" => c.scopes.as_ref().map(|s| s.join(",")),
+			"expires" | "expiry_date" => c.expiry_date.as_ref().map(|d| d.to_string()),
+			"url" | "service_url" => c.service_url.as_ref().map(|u| u.to_string()),
+			"env" | "environment" => c.environment.clone(),
+			"notes" => c.notes.clone(),
+			_ => None,
+		},
+		Item::IdentityDocument(d) => match field {
+			"type" | "document_type" => Some(d.document_type.to_string()),
+			"number" => Some(d.number.to_string()),
+			"name" | "legal_name" => d.legal_name.clone(),
+			"country" | "issuing_country" => d.issuing_country.as_ref().map(|c| c.to_string()),
+			"issued" | "issue_date" => d.issue_date.as_ref().map(|d| d.to_string()),
+			"expires" | "expiry_date" => d.expiry_date.as_ref().map(|d| d.to_string()),
+			"mrz" => d.mrz.as_ref().map(|l| l.join("\n")),
+			"notes" => d.notes.clone(),
+			_ => None,
+		},
+		Item::WifiNetwork(w) => match field {
+			"ssid" => Some(w.ssid.to_string()),
+			"security" => w.security.as_ref().map(|s| s.to_string()),
+			"password" | "passphrase" => w.passphrase.clone(),
+			"hidden" => w.hidden.map(|b| b.to_string()),
+			"join" => Some(w.join_string()),
+			"qr" => render_qr(&w.join_string()).ok(),
+			"notes" => w.notes.clone(),
+			_ => None,
+		},
+		Item::CryptoWallet(w) => match field {
+			"seed" | "seed_phrase" => w.seed_phrase.clone(),
+			"passphrase" => w.passphrase.clone(),
+			"chain" => w.chain.clone(),
+			"paths" | "derivation_paths" => w.derivation_paths.as_ref().map(|p| p.join("\n")),
+			"addresses" => w.addresses.as_ref().map(|a| a.join("\n")),
+			"notes" => w.notes.clone(),
+			_ => None,
+		},
+		Item::SoftwareLicense(l) => match field {
+			"product" => Some(l.product.to_string()),
+			"key" | "license_key" => l.license_key.clone(),
+			"licensee" => l.licensee.clone(),
+			"purchased" | "purchase_date" => l.purchase_date.as_ref().map(|d| d.to_string()),
+			"order" | "order_number" => l.order_number.clone(),
+			"url" | "download_url" => l.download_url.as_ref().map(|u| u.to_string()),
+			"seats" => l.seats.map(|n| n.to_string()),
+			"notes" => l.notes.clone(),
+			_ => None,
+		},
+		Item::SecureNote(n) => match field {
+			"title" => Some(n.title.clone()),
+			"body" | "notes" => Some(n.body.clone()),
+			_ => None,
+		},
+		Item::ServerCredential(s) => match field {
+			"host" => Some(s.host.to_string()),
+			"port" => s.port.map(|p| p.to_string()),
+			"protocol" => s.protocol.clone(),
+			"username" => s.username.clone(),
+			"password" => s.password.clone(),
+			"connection" | "connection_string" => s.connection_string(),
+			"notes" => s.notes.clone(),
+			_ => None,
+		},
+		Item::EmailAccount(e) => match field {
+			"email" | "address" => Some(e.address.to_string()),
+			"username" => e.username.clone(),
+			"password" => e.password.clone(),
+			"app_password" => e.app_password.clone(),
+			"imap" => e.imap.as_ref().map(|s| mail_server(&s.host, s.port)),
+			"smtp" => e.smtp.as_ref().map(|s| mail_server(&s.host, s.port)),
+			"notes" => e.notes.clone(),
+			_ => None,
+		},
+		Item::Membership(m) => match field {
+			"program" => Some(m.program.to_string()),
+			"id" | "member_id" => m.member_id.clone(),
+			"tier" => m.tier.clone(),
+			"name" | "member_name" => m.member_name.clone(),
+			"expires" | "expiry_date" => m.expiry_date.as_ref().map(|d| d.to_string()),
+			"barcode" => m.barcode.clone().or_else(|| m.member_id.clone()),
+			"pin" => m.pin.clone(),
+			"notes" => m.notes.clone(),
+			_ => None,
+		},
+		Item::InsurancePolicy(p) => match field {
+			"provider" => Some(p.provider.to_string()),
+			"policy" | "policy_number" => p.policy_number.clone(),
+			"group" | "group_number" => p.group_number.clone(),
+			"id" | "member_id" => p.member_id.clone(),
+			"start" | "coverage_start" => p.coverage_start.as_ref().map(|d| d.to_string()),
+			"end" | "coverage_end" => p.coverage_end.as_ref().map(|d| d.to_string()),
+			"phone" | "emergency_phone" => p.emergency_phone.as_ref().map(|n| n.to_string()),
+			"notes" => p.notes.clone(),
+			_ => None,
+		},
+		Item::Passkey(p) => match field {
+			"rp" | "rp_id" => Some(p.rp_id.to_string()),
+			"rp_name" => p.rp_name.clone(),
+			"credential_id" => Some(p.credential_id.clone()),
+			"private_key" => Some(p.private_key.clone()),
+			"user_handle" => p.user_handle.clone(),
+			"user" | "user_name" => p.user_name.clone(),
+			"user_display_name" => p.user_display_name.clone(),
+			"created" | "creation_date" => p.creation_date.as_ref().map(|d| d.to_string()),
+			"notes" => p.notes.clone(),
+			_ => None,
+		},
+		Item::PgpKey(k) => match field {
+			"private_key" => Some(k.private_key.clone()),
+			"public_key" => k.public_key.clone(),
+			"passphrase" | "password" => k.passphrase.clone(),
+			"fingerprint" => k.formatted_fingerprint(),
+			"key_id" => k.key_id.clone(),
+			"uids" => k.uids.as_ref().map(|u| u.join("\n")),
+			"expires" | "expiry_date" => k.expiry_date.as_ref().map(|d| d.to_string()),
+			"notes" => k.notes.clone(),
+			_ => None,
+		},
+		Item::DriversLicense(l) => match field {
+			"number" => Some(l.number.to_string()),
+			"name" | "legal_name" => l.legal_name.clone(),
+			"class" | "license_class" => l.license_class.clone(),
+			"region" | "issuing_region" => l.issuing_region.clone(),
+			"country" | "issuing_country" => l.issuing_country.as_ref().map(|c| c.to_string()),
+			"issued" | "issue_date" => l.issue_date.as_ref().map(|d| d.to_string()),
+			"expires" | "expiry_date" => l.expiry_date.as_ref().map(|d| d.to_string()),
+			"restrictions" => l.restrictions.as_ref().map(|r| r.join(", ")),
+			"notes" => l.notes.clone(),
+			_ => None,
+		},
+		Item::Template(t) => t.fields.get(field).cloned(),
+	}
+}
+
+fn mail_server(host: &str, port: Option<u16>) -> String {
+	match port {
+		Some(port) => format!("{host}:{port}"),
+		None => host.to_owned(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use password::models::RecoveryCode;
+
+	use super::*;
+
+	#[test]
+	fn conflict_review_stops_at_end_of_input() {
+		use password::import::MergeStrategy;
+
+		let mut for_all = None;
+		assert!(ask_strategy(&mut "".as_bytes(), &mut for_all).is_err());
+		assert_eq!(for_all, Some(MergeStrategy::Skip));
+
+		let mut for_all = None;
+		let answer = ask_strategy(&mut "x\nO\n".as_bytes(), &mut for_all).unwrap();
+		assert_eq!((answer, for_all), (MergeStrategy::Overwrite, Some(MergeStrategy::Overwrite)));
+		assert!(ask_strategy(&mut "x\n".as_bytes(), &mut None).is_err());
+	}
+
+	#[test]
+	fn column_mapping_is_not_accepted_at_end_of_input() {
+		use password::import::csv;
+
+		let table = csv::read_table("name,url,username,password\nx,a,b,c\n".as_bytes()).unwrap();
+		let mut mapping: Vec<csv::Column> =
+			table.headers.iter().map(|header| csv::guess("online_account", header)).collect();
+		assert!(map_columns(&mut "".as_bytes(), &table, "online_account", &mut mapping).is_err());
+		assert!(map_columns(&mut "\n".as_bytes(), &table, "online_account", &mut mapping).is_ok());
+	}
+
+	#[test]
+	fn show_json_masks_recovery_codes() {
+		let mut item = password::forms::build_item(
+			"online_account",
+			&BTreeMap::from([("password".to_string(), "hunter2".to_string())]),
+		)
+		.unwrap();
+		let Item::OnlineAccount(account) = &mut item else { unreachable!() };
+		account.recovery_codes = RecoveryCode::parse_list("1234 5678\n2345 6789\n");
+
+		let fields = shown_fields(&item, &TemplateRegistry::default());
+		let mut object = serde_json::to_value(&item).unwrap();
+		mask_json(&mut object, &hidden_labels(&fields, |_, secret| !secret));
+		let json = object.to_string();
+		assert!(!json.contains("hunter2") && !json.contains("1234") && !json.contains("2345"));
+		assert_eq!(object["recovery_codes"][0]["code"], MASK);
+		assert_eq!(object["recovery_codes"][0]["used"], false);
 	}
 }
@@ -0,0 +1,208 @@
+//! Markdown in notes, flattened into styled blocks for the front ends.
+//!
+//! Notes are stored as plain text; this only decides how they are shown.
+//! Each block is one visual line group (a paragraph, heading, list item, …)
+//! holding runs of text with their inline styles, so a front end needs no
+//! markdown knowledge of its own.
+
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+
+/// A run of text sharing one inline style.
+#[derive(Debug, Clone, Default, PartialEq, Eq, uniffi::Record)]
+pub struct MarkdownSpan {
+	pub text:   String,
+	pub bold:   bool,
+	pub italic: bool,
+	pub code:   bool,
+	/// Target of the link this text belongs to.
+	pub link:   Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum MarkdownBlockKind {
+	Paragraph,
+	Heading {
+		level: u8,
+	},
+	/// `marker` is "•" or the item's number, e.g. "2."; `depth` counts from
+	/// zero for top-level lists.
+	ListItem {
+		depth:  u32,
+		marker: String,
+	},
+	Quote,
+	CodeBlock,
+	Rule,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct MarkdownBlock {
+	pub kind:  MarkdownBlockKind,
+	pub spans: Vec<MarkdownSpan>,
+}
+
+/// Split `text` into blocks. Anything not understood is kept as plain
+/// text, so no part of a note is ever hidden. Line breaks inside a
+/// paragraph are kept as `\n`.
+pub fn render(text: &str) -> Vec<MarkdownBlock> {
+	let mut blocks = Vec::new();
+	let mut current: Option<MarkdownBlock> = None;
+	let (mut bold, mut italic, mut quote) = (0u32, 0u32, 0u32);
+	let mut link: Option<String> = None;
+	// One entry per open list: the next item number, or `None` for bullets.
+	let mut lists: Vec<Option<u64>> = Vec::new();
+
+	let flush = |current: &mut Option<MarkdownBlock>, blocks: &mut Vec<MarkdownBlock>| {
+		if let Some(mut block) = current.take() {
+			if block.kind == MarkdownBlockKind::CodeBlock
+				&& let Some(last) = block.spans.last_mut()
+			{
+				last.text.truncate(last.text.trim_end_matches('\n').len());
+			}
+			blocks.push(block);
+		}
+	};
+
+	for event in Parser::new(text) {
+		let kind = match &event {
+			Event::Start(Tag::Paragraph) if current.is_some() => None,
+			Event::Start(Tag::Paragraph) => {
+				Some(if quote > 0 { MarkdownBlockKind::Quote } else { MarkdownBlockKind::Paragraph })
+			}
+			Event::Start(Tag::Heading { level, .. }) => {
+				Some(MarkdownBlockKind::Heading { level: heading_level(*level) })
+			}
+			Event::Start(Tag::CodeBlock(_)) => Some(MarkdownBlockKind::CodeBlock),
+			Event::Start(Tag::Item) => {
+				let marker = match lists.last_mut() {
+					Some(Some(next)) => {
+						*next += 1;
+						format!("{}.", *next - 1)
+					}
+					_ => "•".into(),
+				};
+				Some(MarkdownBlockKind::ListItem { depth: lists.len().saturating_sub(1) as u32, marker })
+			}
+			_ => None,
+		};
+		if let Some(kind) = kind {
+			flush(&mut current, &mut blocks);
+			current = Some(MarkdownBlock { kind, spans: vec![] });
+			continue;
+		}
+
+		match event {
+			Event::Start(Tag::List(first)) => {
+				flush(&mut current, &mut blocks);
+				lists.push(first);
+			}
+			Event::End(TagEnd::List(_)) => {
+				lists.pop();
+			}
+			Event::Start(Tag::BlockQuote(_)) => quote += 1,
+			Event::End(TagEnd::BlockQuote(_)) => quote = quote.saturating_sub(1),
+			Event::Start(Tag::Strong) => bold += 1,
+			Event::End(TagEnd::Strong) => bold = bold.saturating_sub(1),
+			Event::Start(Tag::Emphasis) => italic += 1,
+			Event::End(TagEnd::Emphasis) => italic = italic.saturating_sub(1),
+			Event::Start(Tag::Link { dest_url, .. }) => link = Some(dest_url.into_string()),
+			Event::End(TagEnd::Link) => link = None,
+			Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::CodeBlock) => {
+				flush(&mut current, &mut blocks)
+			}
+			Event::Rule => {
+				flush(&mut current, &mut blocks);
+				blocks.push(MarkdownBlock { kind: MarkdownBlockKind::Rule, spans: vec![] });
+			}
+			Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+				let span = MarkdownSpan {
+					text:   text.into_string(),
+					bold:   bold > 0,
+					italic: italic > 0,
+					code:   false,
+					link:   link.clone(),
+				};
+				push_span(&mut current, quote, span);
+			}
+			Event::Code(text) => {
+				let span = MarkdownSpan {
+					text: text.into_string(),
+					code: true,
+					link: link.clone(),
+					..Default::default()
+				};
+				push_span(&mut current, quote, span);
+			}
+			// Notes are written line by line, so single newlines are kept.
+			Event::SoftBreak | Event::HardBreak => {
+				push_span(&mut current, quote, MarkdownSpan { text: "\n".into(), ..Default::default() })
+			}
+			_ => {}
+		}
+	}
+	flush(&mut current, &mut blocks);
+	blocks
+}
+
+/// Append `span` to the open block, opening a paragraph if text appears
+/// outside one, and merging it into the previous span when styles match.
+fn push_span(current: &mut Option<MarkdownBlock>, quote: u32, span: MarkdownSpan) {
+	let block = current.get_or_insert_with(|| MarkdownBlock {
+		kind:  if quote > 0 { MarkdownBlockKind::Quote } else { MarkdownBlockKind::Paragraph },
+		spans: vec![],
+	});
+	match block.spans.last_mut() {
+		Some(last)
+			if (last.bold, last.italic, last.code, &last.link)
+				== (span.bold, span.italic, span.code, &span.link) =>
+		{
+			last.text.push_str(&span.text)
+		}
+		_ => block.spans.push(span),
+	}
+}
+
+fn heading_level(level: HeadingLevel) -> u8 {
+	match level {
+		HeadingLevel::H1 => 1,
+		HeadingLevel::H2 => 2,
+		HeadingLevel::H3 => 3,
+		HeadingLevel::H4 => 4,
+		HeadingLevel::H5 => 5,
+		HeadingLevel::H6 => 6,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn text(block: &MarkdownBlock) -> String { block.spans.iter().map(|s| s.text.as_str()).collect() }
+
+	#[test]
+	fn blocks_carry_structure_and_inline_styles() {
+		let blocks = render(
+			"# Recovery\n\nCall **support** at `555-0100`.\n\n1. first\n2. second\n   - nested\n\n---\n",
+		);
+		let kinds: Vec<&MarkdownBlockKind> = blocks.iter().map(|b| &b.kind).collect();
+		assert_eq!(kinds, [
+			&MarkdownBlockKind::Heading { level: 1 },
+			&MarkdownBlockKind::Paragraph,
+			&MarkdownBlockKind::ListItem { depth: 0, marker: "1.".into() },
+			&MarkdownBlockKind::ListItem { depth: 0, marker: "2.".into() },
+			&MarkdownBlockKind::ListItem { depth: 1, marker: "•".into() },
+			&MarkdownBlockKind::Rule,
+		]);
+		assert_eq!(text(&blocks[1]), "Call support at 555-0100.");
+		assert!(blocks[1].spans.iter().any(|s| s.bold && s.text == "support"));
+		assert!(blocks[1].spans.iter().any(|s| s.code && s.text == "555-0100"));
+		assert_eq!(text(&blocks[4]), "nested");
+	}
+
+	#[test]
+	fn plain_text_survives_unchanged() {
+		let blocks = render("PIN is 1234\nback door code 99");
+		assert_eq!(blocks.len(), 1);
+		assert_eq!(text(&blocks[0]), "PIN is 1234\nback door code 99");
+	}
+}
@@ -0,0 +1,109 @@
+//! Combining two near-duplicate entries into one.
+//!
+//! The merge works on the same form fields as inline editing: every field on
+//! which the two items disagree is listed side by side, and the caller picks
+//! the side to keep for each. Everything the form doesn't cover (custom
+//! fields, lists, nested settings) comes from the left item, and the tags of
+//! both are combined.
+
+use crate::{Error, Item, Result, forms, templates::TemplateField};
+
+/// A form field on which two items disagree. An empty value is unset.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct FieldDifference {
+	pub field: TemplateField,
+	pub left:  String,
+	pub right: String,
+}
+
+fn same_kind(left: &Item, right: &Item) -> Result<()> {
+	if left.kind_str() != right.kind_str() {
+		return Err(Error::Validation {
+			field:  "kind".into(),
+			reason: format!("cannot merge a {} with a {}", left.kind_str(), right.kind_str()),
+		});
+	}
+	Ok(())
+}
+
+/// The form fields whose values differ between `left` and `right`, in form
+/// order. Fails if the items are of different kinds.
+pub fn differences(left: &Item, right: &Item) -> Result<Vec<FieldDifference>> {
+	same_kind(left, right)?;
+	Ok(
+		forms::editable_fields(left)
+			.into_iter()
+			.zip(forms::editable_fields(right))
+			.filter(|((_, l), (_, r))| l != r)
+			.map(|((field, left), (_, right))| FieldDifference { field, left, right })
+			.collect(),
+	)
+}
+
+/// `left` with the fields named in `take_right` replaced by `right`'s
+/// values and the tags of both combined.
+pub fn merge(left: &Item, right: &Item, take_right: &[String]) -> Result<Item> {
+	same_kind(left, right)?;
+	let theirs = forms::editable_fields(right);
+	let mut merged = left.clone();
+	for name in take_right {
+		let (_, value) =
+			theirs.iter().find(|(field, _)| field.name == *name).ok_or_else(|| Error::Validation {
+				field:  name.clone(),
+				reason: format!("not a field of '{}'", right.kind_str()),
+			})?;
+		merged = forms::set_field(&merged, name, value)?;
+	}
+	for tag in right.tags() {
+		if !merged.has_tag(tag) {
+			merged.tags_mut().push(tag.clone());
+		}
+	}
+	Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use super::*;
+
+	fn account(pairs: &[(&str, &str)]) -> Item {
+		let values: BTreeMap<String, String> =
+			pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+		forms::build_item("online_account", &values).unwrap()
+	}
+
+	#[test]
+	fn only_differing_fields_are_listed() {
+		let left = account(&[("username", "ana"), ("password", "old")]);
+		let right = account(&[("username", "ana"), ("password", "new"), ("email", "ana@example.com")]);
+		let diffs = differences(&left, &right).unwrap();
+		let fields: Vec<_> = diffs.iter().map(|d| d.field.name.as_str()).collect();
+		assert_eq!(fields, ["password", "email"]);
+		assert_eq!((diffs[1].left.as_str(), diffs[1].right.as_str()), ("", "ana@example.com"));
+	}
+
+	#[test]
+	fn merge_takes_the_chosen_side_per_field() {
+		let mut left = account(&[("username", "ana"), ("password", "old")]);
+		left.tags_mut().push("work".into());
+		let mut right = account(&[("username", "ana2"), ("password", "new")]);
+		right.tags_mut().push("shared".into());
+
+		let merged = merge(&left, &right, &["password".into()]).unwrap();
+		let values: BTreeMap<_, _> =
+			forms::editable_fields(&merged).into_iter().map(|(f, v)| (f.name, v)).collect();
+		assert_eq!(values["username"], "ana");
+		assert_eq!(values["password"], "new");
+		assert_eq!(merged.tags(), ["work", "shared"]);
+	}
+
+	#[test]
+	fn different_kinds_cannot_be_merged() {
+		let values = BTreeMap::from([("ssid".to_string(), "home".to_string())]);
+		let wifi = forms::build_item("wifi_network", &values).unwrap();
+		assert!(differences(&account(&[]), &wifi).is_err());
+		assert!(merge(&account(&[]), &wifi, &[]).is_err());
+	}
+}
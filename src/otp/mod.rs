@@ -0,0 +1,545 @@
+//! One-time passwords: HOTP (RFC 4226), TOTP (RFC 6238) and Steam Guard.
+//!
+//! A [`Totp`] is time-based unless its `kind` says otherwise. Counter-based
+//! HOTP codes come from [`Totp::advance`], which moves the stored counter on
+//! so no code is handed out twice; Steam Guard codes are TOTP codes spelled
+//! in five characters from Steam's own alphabet.
+//!
+//! A [`Totp`] keeps its secret either as bare base32 or as the
+//! `otpauth://` URI an enrolment QR code carries. [`Totp::params`]
+//! resolves both forms; digits, period and algorithm set on the item win
+//! over the ones in the URI. [`Totp::import`] reads one the way people hand
+//! it over: a URI, a screenshot of the QR code, or the bare secret.
+//!
+//! A [`Totp`] naming a `yubikey` credential keeps no secret at all: its codes
+//! come from the key; see [`yubikey`].
+
+pub mod yubikey;
+
+use std::path::Path;
+
+use base64::Engine as _;
+use hmac::{Hmac, Mac, digest::KeyInit};
+
+use crate::{Error, Result, models::{Totp, TotpAlgorithm, TotpKind}};
+
+/// Code length when neither the item nor its URI says.
+pub const DEFAULT_DIGITS: u8 = 6;
+
+/// Seconds per code when neither the item nor its URI says.
+pub const DEFAULT_PERIOD: u32 = 30;
+
+/// The longest code the 31-bit HOTP truncation can fill.
+const MAX_DIGITS: u8 = 10;
+
+/// Codes with fewer seconds than this left are not worth copying: they may
+/// expire before they are pasted.
+pub const MIN_COPY_SECS: u32 = 5;
+
+/// The characters Steam Guard codes are spelled with.
+const STEAM_ALPHABET: &[u8] = b"23456789BCDFGHJKMNPQRTVWXY";
+
+/// The length of a Steam Guard code.
+const STEAM_DIGITS: u8 = 5;
+
+/// Everything needed to compute a code, with defaults filled in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtpParams {
+	pub secret:    Vec<u8>,
+	pub kind:      TotpKind,
+	/// The counter the next HOTP code is made from.
+	pub counter:   u64,
+	pub digits:    u8,
+	pub period:    u32,
+	pub algorithm: TotpAlgorithm,
+}
+
+/// A TOTP code and how long it stays valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TotpCode {
+	pub code:       String,
+	/// Seconds until the next code.
+	pub remaining:  u32,
+	pub period:     u32,
+	/// When the code became valid, in seconds since the epoch.
+	pub valid_from: u64,
+}
+
+impl TotpCode {
+	/// Whether the code runs out too soon to be copied; the next one comes
+	/// in `remaining` seconds.
+	pub fn expires_soon(&self) -> bool { self.remaining < MIN_COPY_SECS }
+
+	/// The span the code is valid for, in local time: `14:02:30–14:03:00`.
+	pub fn window(&self) -> String {
+		let time = |unix: u64| {
+			jiff::Timestamp::from_second(unix as i64)
+				.map(|t| t.to_zoned(jiff::tz::TimeZone::system()).strftime("%H:%M:%S").to_string())
+				.unwrap_or_default()
+		};
+		format!("{}–{}", time(self.valid_from), time(self.valid_from + u64::from(self.period)))
+	}
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+	Error::Validation { field: "totp".into(), reason: reason.into() }
+}
+
+/// Decode a base32 secret as apps print it: case, spaces and padding are
+/// ignored.
+pub fn decode_secret(raw: &str) -> Result<Vec<u8>> {
+	let cleaned: String = raw
+		.chars()
+		.filter(|c| !c.is_whitespace() && *c != '=')
+		.map(|c| c.to_ascii_uppercase())
+		.collect();
+	match base32::decode(base32::Alphabet::Rfc4648 { padding: false }, &cleaned) {
+		Some(secret) if !secret.is_empty() => Ok(secret),
+		Some(_) => Err(invalid("the secret is empty")),
+		None => Err(invalid("the secret is not valid base32")),
+	}
+}
+
+/// Decode a Steam Guard secret, which Steam hands out in base64 rather than
+/// base32; base32 is accepted too.
+fn decode_steam_secret(raw: &str) -> Result<Vec<u8>> {
+	decode_secret(raw).or_else(|_| {
+		base64::engine::general_purpose::STANDARD
+			.decode(raw.trim())
+			.ok()
+			.filter(|secret| !secret.is_empty())
+			.ok_or_else(|| invalid("the secret is neither base32 nor base64"))
+	})
+}
+
+/// The HOTP code for `counter`.
+pub fn hotp(secret: &[u8], counter: u64, digits: u8, algorithm: TotpAlgorithm) -> String {
+	let binary = truncate(secret, counter, algorithm);
+	let width = usize::from(digits);
+	format!("{:0width$}", u64::from(binary) % 10u64.pow(u32::from(digits)))
+}
+
+/// The Steam Guard code for `counter`: the HOTP value written out in base 26,
+/// least significant character first.
+pub fn steam(secret: &[u8], counter: u64) -> String {
+	let mut binary = truncate(secret, counter, TotpAlgorithm::Sha1) as usize;
+	(0..STEAM_DIGITS)
+		.map(|_| {
+			let c = STEAM_ALPHABET[binary % STEAM_ALPHABET.len()];
+			binary /= STEAM_ALPHABET.len();
+			char::from(c)
+		})
+		.collect()
+}
+
+/// RFC 4226 dynamic truncation of the HMAC of `counter`.
+fn truncate(secret: &[u8], counter: u64, algorithm: TotpAlgorithm) -> u32 {
+	let mac = match algorithm {
+		TotpAlgorithm::Sha1 => mac::<Hmac<sha1::Sha1>>(secret, counter),
+		TotpAlgorithm::Sha256 => mac::<Hmac<sha2::Sha256>>(secret, counter),
+		TotpAlgorithm::Sha512 => mac::<Hmac<sha2::Sha512>>(secret, counter),
+	};
+	let offset = usize::from(mac[mac.len() - 1] & 0x0f);
+	u32::from_be_bytes([mac[offset], mac[offset + 1], mac[offset + 2], mac[offset + 3]]) & 0x7fff_ffff
+}
+
+fn mac<M: Mac + KeyInit>(key: &[u8], counter: u64) -> Vec<u8> {
+	let mut mac = <M as KeyInit>::new_from_slice(key).expect("HMAC takes keys of any length");
+	mac.update(&counter.to_be_bytes());
+	mac.finalize().into_bytes().to_vec()
+}
+
+impl Totp {
+	/// A TOTP holding `secret`, with every other setting left to its default.
+	pub fn new(secret: impl Into<String>) -> Self {
+		Self {
+			secret:    secret.into(),
+			kind:      None,
+			counter:   None,
+			yubikey:   None,
+			issuer:    None,
+			digits:    None,
+			period:    None,
+			algorithm: None,
+		}
+	}
+
+	/// Read a TOTP from an `otpauth://` URI, the path of an image holding its
+	/// QR code, or a base32 secret. URIs are taken apart so the item records
+	/// the secret, issuer and settings on their own.
+	pub fn import(input: &str) -> Result<Self> {
+		let input = input.trim();
+		if input.starts_with("otpauth://") {
+			return Self::from_uri(input);
+		}
+		let path = Path::new(input);
+		if path.is_file() {
+			return Self::from_uri(&crate::qr::decode_image(path)?);
+		}
+		decode_secret(input)?;
+		Ok(Self::new(input))
+	}
+
+	/// Read an `otpauth://totp/...`, `otpauth://hotp/...` or
+	/// `otpauth://steam/...` URI into a generator with a bare secret. Steam
+	/// secrets may also come as TOTP URIs with `encoder=steam`.
+	pub fn from_uri(uri: &str) -> Result<Self> {
+		let url = url::Url::parse(uri.trim()).map_err(|e| invalid(format!("bad otpauth URI: {e}")))?;
+		if url.scheme() != "otpauth" {
+			return Err(invalid("not an otpauth:// URI"));
+		}
+		let mut totp = Self::new(String::new());
+		totp.kind = match url.host_str() {
+			Some("totp") => None,
+			Some("hotp") => Some(TotpKind::Hotp),
+			Some("steam") => Some(TotpKind::Steam),
+			other => return Err(invalid(format!("unsupported OTP type '{}'", other.unwrap_or("")))),
+		};
+		// The label is "Issuer:account" or just "account"; an `issuer`
+		// parameter takes precedence.
+		let label = percent_encoding::percent_decode_str(url.path().trim_start_matches('/'))
+			.decode_utf8_lossy()
+			.into_owned();
+		if let Some((issuer, _)) = label.split_once(':') {
+			totp.issuer = Some(issuer.trim().to_owned()).filter(|i| !i.is_empty());
+		}
+		for (key, value) in url.query_pairs() {
+			match key.as_ref() {
+				"secret" => totp.secret = value.into_owned(),
+				"issuer" if !value.is_empty() => totp.issuer = Some(value.into_owned()),
+				"digits" => {
+					totp.digits = Some(value.parse().map_err(|_| invalid(format!("bad digits '{value}'")))?)
+				}
+				"period" => {
+					totp.period = Some(value.parse().map_err(|_| invalid(format!("bad period '{value}'")))?)
+				}
+				"counter" => {
+					totp.counter = Some(value.parse().map_err(|_| invalid(format!("bad counter '{value}'")))?)
+				}
+				"encoder" if value.eq_ignore_ascii_case("steam") => totp.kind = Some(TotpKind::Steam),
+				"algorithm" => {
+					totp.algorithm = Some(
+						value
+							.to_ascii_uppercase()
+							.parse()
+							.map_err(|_| invalid(format!("unsupported algorithm '{value}'")))?,
+					)
+				}
+				_ => {}
+			}
+		}
+		if totp.secret.is_empty() {
+			return Err(invalid("the URI has no secret"));
+		}
+		if totp.kind == Some(TotpKind::Hotp) && totp.counter.is_none() {
+			return Err(invalid("HOTP URIs must give a counter"));
+		}
+		Ok(totp)
+	}
+
+	/// A TOTP whose codes come from the YubiKey OATH credential `name`. Keys
+	/// prefix the names of credentials with other periods, as in
+	/// `60/Issuer:account`, and the period is taken from there.
+	pub fn on_yubikey(name: impl Into<String>) -> Self {
+		let name = name.into();
+		let period = name.split_once('/').and_then(|(period, _)| period.parse().ok());
+		Self { yubikey: Some(name), period, ..Self::new(String::new()) }
+	}
+
+	/// Check the secret and settings, without asking a YubiKey for anything.
+	pub fn validate(&self) -> Result<()> {
+		match &self.yubikey {
+			Some(name) if name.trim().is_empty() => Err(invalid("the YubiKey credential has no name")),
+			Some(_) if self.period == Some(0) => Err(invalid("the period must be at least one second")),
+			Some(_) => Ok(()),
+			None => self.params().map(drop),
+		}
+	}
+
+	/// The generator as stored: taken apart when its secret is a URI.
+	fn unpacked(&self) -> Result<Self> {
+		if self.secret.trim_start().starts_with("otpauth://") {
+			Self::from_uri(&self.secret)
+		} else {
+			Ok(self.clone())
+		}
+	}
+
+	/// The secret and settings to compute codes with. Generators on a
+	/// YubiKey have no secret to give.
+	pub fn params(&self) -> Result<OtpParams> {
+		if self.yubikey.is_some() {
+			return Err(invalid("the secret is kept on the YubiKey"));
+		}
+		let stored = self.unpacked()?;
+		let kind = self.kind.or(stored.kind).unwrap_or(TotpKind::Totp);
+		let (secret, digits) = match kind {
+			TotpKind::Steam => (decode_steam_secret(&stored.secret)?, STEAM_DIGITS),
+			_ => {
+				(decode_secret(&stored.secret)?, self.digits.or(stored.digits).unwrap_or(DEFAULT_DIGITS))
+			}
+		};
+		let params = OtpParams {
+			secret,
+			kind,
+			counter: self.counter.or(stored.counter).unwrap_or(0),
+			digits,
+			period: self.period.or(stored.period).unwrap_or(DEFAULT_PERIOD),
+			algorithm: self.algorithm.or(stored.algorithm).unwrap_or(TotpAlgorithm::Sha1),
+		};
+		if !(1..=MAX_DIGITS).contains(&params.digits) {
+			return Err(invalid(format!("codes must have 1 to {MAX_DIGITS} digits")));
+		}
+		if params.period == 0 {
+			return Err(invalid("the period must be at least one second"));
+		}
+		Ok(params)
+	}
+
+	/// The `otpauth://` URI enrolling this generator in an authenticator app
+	/// under `account`, for a provisioning QR code. Steam Guard secrets are
+	/// written as TOTP URIs with `encoder=steam`.
+	pub fn to_uri(&self, account: &str) -> Result<String> {
+		use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+		let encode = |s: &str| utf8_percent_encode(s, NON_ALPHANUMERIC).to_string();
+
+		let params = self.params()?;
+		let issuer = self.issuer.clone().or(self.unpacked()?.issuer);
+		let label = match &issuer {
+			Some(issuer) => format!("{}:{}", encode(issuer), encode(account)),
+			None => encode(account),
+		};
+		let secret = base32::encode(base32::Alphabet::Rfc4648 { padding: false }, &params.secret);
+		let kind = if params.kind == TotpKind::Hotp { "hotp" } else { "totp" };
+		let mut uri = format!("otpauth://{kind}/{label}?secret={secret}");
+		if let Some(issuer) = &issuer {
+			uri += &format!("&issuer={}", encode(issuer));
+		}
+		match params.kind {
+			TotpKind::Totp => {
+				uri += &format!(
+					"&algorithm={}&digits={}&period={}",
+					params.algorithm, params.digits, params.period
+				)
+			}
+			TotpKind::Hotp => {
+				uri += &format!(
+					"&algorithm={}&digits={}&counter={}",
+					params.algorithm, params.digits, params.counter
+				)
+			}
+			TotpKind::Steam => uri += &format!("&period={}&encoder=steam", params.period),
+		}
+		Ok(uri)
+	}
+
+	/// The code valid at `unix` seconds since the epoch. Counter-based
+	/// generators have none; see [`Totp::advance`].
+	pub fn code_at(&self, unix: u64) -> Result<TotpCode> {
+		let (code, period) = match &self.yubikey {
+			Some(name) => {
+				self.validate()?;
+				let period = self.period.unwrap_or(DEFAULT_PERIOD);
+				(yubikey::calculate(name, unix / u64::from(period))?, period)
+			}
+			None => {
+				let params = self.params()?;
+				(params.code(unix / u64::from(params.period))?, params.period)
+			}
+		};
+		let period_secs = u64::from(period);
+		Ok(TotpCode {
+			code,
+			remaining: (period_secs - unix % period_secs) as u32,
+			period,
+			valid_from: unix - unix % period_secs,
+		})
+	}
+
+	/// The code valid now.
+	pub fn code(&self) -> Result<TotpCode> {
+		self.code_at(jiff::Timestamp::now().as_second().max(0) as u64)
+	}
+
+	/// The next HOTP code. The counter moves on, so the item has to be saved
+	/// afterwards for the code not to be handed out again.
+	pub fn advance(&mut self) -> Result<String> {
+		let params = self.params()?;
+		if params.kind != TotpKind::Hotp {
+			return Err(invalid("only HOTP codes come from a counter"));
+		}
+		let code = hotp(&params.secret, params.counter, params.digits, params.algorithm);
+		self.counter = Some(params.counter + 1);
+		Ok(code)
+	}
+
+	/// Whether codes come from [`Totp::advance`] rather than the clock.
+	pub fn is_counter_based(&self) -> bool {
+		self.params().is_ok_and(|params| params.kind == TotpKind::Hotp)
+	}
+}
+
+impl OtpParams {
+	/// The time-based code for time step `step`.
+	fn code(&self, step: u64) -> Result<String> {
+		match self.kind {
+			TotpKind::Totp => Ok(hotp(&self.secret, step, self.digits, self.algorithm)),
+			TotpKind::Steam => Ok(steam(&self.secret, step)),
+			TotpKind::Hotp => Err(invalid("HOTP codes come from a counter, not the clock")),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The RFC 6238 test keys, in base32.
+	const SHA1_KEY: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+	fn rfc_totp(key: &[u8], algorithm: TotpAlgorithm) -> Totp {
+		let secret = base32::encode(base32::Alphabet::Rfc4648 { padding: false }, key);
+		Totp { digits: Some(8), algorithm: Some(algorithm), ..Totp::new(secret) }
+	}
+
+	#[test]
+	fn hotp_matches_rfc_4226() {
+		let expected = ["755224", "287082", "359152", "969429", "338314"];
+		for (counter, code) in expected.iter().enumerate() {
+			assert_eq!(hotp(b"12345678901234567890", counter as u64, 6, TotpAlgorithm::Sha1), *code);
+		}
+	}
+
+	#[test]
+	fn totp_matches_rfc_6238() {
+		let sha1 = rfc_totp(b"12345678901234567890", TotpAlgorithm::Sha1);
+		let sha256 = rfc_totp(b"12345678901234567890123456789012", TotpAlgorithm::Sha256);
+		let sha512 = rfc_totp(
+			b"1234567890123456789012345678901234567890123456789012345678901234",
+			TotpAlgorithm::Sha512,
+		);
+		assert_eq!(sha1.code_at(59).unwrap().code, "94287082");
+		assert_eq!(sha256.code_at(59).unwrap().code, "46119246");
+		assert_eq!(sha512.code_at(59).unwrap().code, "90693936");
+		assert_eq!(sha1.code_at(1_111_111_109).unwrap().code, "07081804");
+		assert_eq!(sha1.code_at(20_000_000_000).unwrap().code, "65353130");
+
+		let code = Totp::new(SHA1_KEY).code_at(59).unwrap();
+		assert_eq!((code.code.as_str(), code.remaining, code.period), ("287082", 1, 30));
+		assert_eq!(code.valid_from, 30);
+		assert!(code.expires_soon());
+		assert!(!Totp::new(SHA1_KEY).code_at(60).unwrap().expires_soon());
+	}
+
+	#[test]
+	fn secrets_may_be_otpauth_uris() {
+		let uri = format!(
+			"otpauth://totp/Example:alice@example.com?secret={}&issuer=Example&digits=8",
+			SHA1_KEY.to_lowercase()
+		);
+		let from_uri = Totp::new(uri.clone());
+		assert_eq!(from_uri.code_at(59).unwrap().code, "94287082");
+
+		// Settings on the item override the URI's.
+		let overridden = Totp { digits: Some(6), ..Totp::new(uri) };
+		assert_eq!(overridden.code_at(59).unwrap().code, "287082");
+
+		assert!(Totp::from_uri("otpauth://hotp/x?secret=GEZDGNBV").is_err());
+		assert!(Totp::from_uri("otpauth://totp/x?issuer=nobody").is_err());
+	}
+
+	#[test]
+	fn imported_uris_are_taken_apart() {
+		let totp = Totp::import(
+			" otpauth://totp/ACME%20Co:alice@example.com?secret=GEZDGNBV&algorithm=sha256&period=60 ",
+		)
+		.unwrap();
+		assert_eq!(totp.secret, "GEZDGNBV");
+		assert_eq!(totp.issuer.as_deref(), Some("ACME Co"));
+		assert_eq!(
+			(totp.algorithm, totp.period, totp.digits),
+			(Some(TotpAlgorithm::Sha256), Some(60), None)
+		);
+
+		let named = Totp::import("otpauth://totp/alice?secret=GEZDGNBV&issuer=Example").unwrap();
+		assert_eq!(named.issuer.as_deref(), Some("Example"));
+
+		assert_eq!(Totp::import("gezd gnbv").unwrap().secret, "gezd gnbv");
+		assert!(Totp::import("/no/such/screenshot.png").is_err());
+	}
+
+	#[test]
+	fn provisioning_uris_round_trip() {
+		let totp = Totp {
+			issuer: Some("ACME Co".into()),
+			algorithm: Some(TotpAlgorithm::Sha256),
+			..Totp::new("gezd gnbv gy3t qojq")
+		};
+		let uri = totp.to_uri("alice@example.com").unwrap();
+		assert_eq!(
+			uri,
+			"otpauth://totp/ACME%20Co:alice%40example%2Ecom?secret=GEZDGNBVGY3TQOJQ&issuer=ACME%20Co\
+			 &algorithm=SHA256&digits=6&period=30"
+		);
+		let back = Totp::import(&uri).unwrap();
+		assert_eq!(back.issuer.as_deref(), Some("ACME Co"));
+		assert_eq!(back.code_at(59).unwrap(), totp.code_at(59).unwrap());
+
+		let hotp = Totp { kind: Some(TotpKind::Hotp), counter: Some(7), ..Totp::new(SHA1_KEY) };
+		assert!(hotp.to_uri("alice").unwrap().contains("otpauth://hotp/alice?"));
+		assert_eq!(Totp::import(&hotp.to_uri("alice").unwrap()).unwrap().counter, Some(7));
+	}
+
+	#[test]
+	fn hotp_advances_its_counter() {
+		let mut hotp = Totp { kind: Some(TotpKind::Hotp), ..Totp::new(SHA1_KEY) };
+		assert!(hotp.code_at(59).is_err());
+		assert_eq!(hotp.advance().unwrap(), "755224");
+		assert_eq!(hotp.advance().unwrap(), "287082");
+		assert_eq!(hotp.counter, Some(2));
+		assert!(hotp.is_counter_based());
+
+		let mut from_uri =
+			Totp::import(&format!("otpauth://hotp/Example:alice?secret={SHA1_KEY}&counter=3")).unwrap();
+		assert_eq!(from_uri.advance().unwrap(), "969429");
+		assert!(Totp::import(&format!("otpauth://hotp/alice?secret={SHA1_KEY}")).is_err());
+		assert!(Totp::new(SHA1_KEY).advance().is_err());
+	}
+
+	#[test]
+	fn steam_codes_use_five_letters() {
+		let steam = Totp { kind: Some(TotpKind::Steam), ..Totp::new(SHA1_KEY) };
+		assert_eq!(steam.code_at(29).unwrap().code, "GG5F5");
+		assert_eq!(steam.code_at(59).unwrap().code, "PV9M4");
+
+		// Steam hands out base64 secrets; encoder=steam marks them in TOTP URIs.
+		let base64 = Totp {
+			kind: Some(TotpKind::Steam),
+			..Totp::new(base64::engine::general_purpose::STANDARD.encode(b"12345678901234567890"))
+		};
+		assert_eq!(base64.code_at(59).unwrap().code, "PV9M4");
+		let uri = Totp::import(&format!("otpauth://totp/Steam:alice?secret={SHA1_KEY}&encoder=steam"));
+		assert_eq!(uri.unwrap().code_at(59).unwrap().code, "PV9M4");
+	}
+
+	#[test]
+	fn yubikey_generators_keep_no_secret() {
+		let on_key = Totp::on_yubikey("GitHub:alice");
+		assert!(on_key.validate().is_ok());
+		assert!(on_key.params().is_err());
+		assert!(on_key.to_uri("alice").is_err());
+		assert!(!on_key.is_counter_based());
+		assert!(Totp::on_yubikey(" ").validate().is_err());
+		assert_eq!(on_key.period, None);
+		assert_eq!(Totp::on_yubikey("60/Example:bob").period, Some(60));
+	}
+
+	#[test]
+	fn malformed_secrets_are_rejected() {
+		assert_eq!(decode_secret("gezd gnbv").unwrap(), decode_secret("GEZDGNBV").unwrap());
+		assert!(decode_secret("not base32!").is_err());
+		assert!(Totp::new("").params().is_err());
+		assert!(Totp { digits: Some(12), ..Totp::new(SHA1_KEY) }.params().is_err());
+		assert!(Totp { period: Some(0), ..Totp::new(SHA1_KEY) }.params().is_err());
+	}
+}
@@ -0,0 +1,173 @@
+//! TOTP codes computed by a YubiKey's OATH applet, over the same CCID
+//! protocol `ykman oath` speaks, so the shared secret never leaves the key.
+//!
+//! The first connected YubiKey is used. Applets protected by a password are
+//! not unlocked; remove the password with `ykman oath access change` first.
+//! Talking to the key needs the `yubikey` feature; without it every call
+//! fails with [`Error::YubiKey`].
+
+use crate::{Error, Result};
+
+const TAG_NAME: u8 = 0x71;
+const TAG_NAME_LIST: u8 = 0x72;
+const TAG_CHALLENGE: u8 = 0x74;
+const TAG_TRUNCATED: u8 = 0x76;
+
+const INS_LIST: u8 = 0xa1;
+const INS_CALCULATE: u8 = 0xa2;
+
+fn err(reason: impl Into<String>) -> Error { Error::YubiKey(reason.into()) }
+
+/// The code for time step `step` from the credential called `name`.
+pub fn calculate(name: &str, step: u64) -> Result<String> {
+	let mut data = tlv(TAG_NAME, name.as_bytes());
+	data.extend(tlv(TAG_CHALLENGE, &step.to_be_bytes()));
+	// P2 = 1 asks for the truncated code rather than the full HMAC.
+	code_from_response(&session()?.send(INS_CALCULATE, 0x00, 0x01, &data)?)
+}
+
+/// The names of the OATH credentials on the key.
+pub fn credentials() -> Result<Vec<String>> {
+	names_from_response(&session()?.send(INS_LIST, 0x00, 0x00, &[])?)
+}
+
+fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+	let mut out = vec![tag, value.len() as u8];
+	out.extend_from_slice(value);
+	out
+}
+
+/// Split a response into its tag-length-value records.
+fn records(mut data: &[u8]) -> Result<Vec<(u8, &[u8])>> {
+	let mut out = vec![];
+	while let [tag, rest @ ..] = data {
+		let (len, rest) = match rest {
+			[0x81, len, rest @ ..] => (usize::from(*len), rest),
+			[0x82, hi, lo, rest @ ..] => (usize::from(u16::from_be_bytes([*hi, *lo])), rest),
+			[len, rest @ ..] if *len < 0x80 => (usize::from(*len), rest),
+			_ => return Err(err("malformed response")),
+		};
+		if rest.len() < len {
+			return Err(err("truncated response"));
+		}
+		out.push((*tag, &rest[..len]));
+		data = &rest[len..];
+	}
+	Ok(out)
+}
+
+fn code_from_response(response: &[u8]) -> Result<String> {
+	let value = records(response)?
+		.into_iter()
+		.find_map(|(tag, value)| (tag == TAG_TRUNCATED).then_some(value))
+		.ok_or_else(|| err("the key sent no code; does the credential need a touch?"))?;
+	let [digits, a, b, c, d] = *value else { return Err(err("malformed code")) };
+	let binary = u32::from_be_bytes([a, b, c, d]) & 0x7fff_ffff;
+	let width = usize::from(digits);
+	Ok(format!("{:0width$}", u64::from(binary) % 10u64.pow(u32::from(digits))))
+}
+
+fn names_from_response(response: &[u8]) -> Result<Vec<String>> {
+	records(response)?
+		.into_iter()
+		.filter(|(tag, _)| *tag == TAG_NAME_LIST)
+		// The first byte packs the credential's type and hash algorithm.
+		.map(|(_, value)| match value {
+			[_, name @ ..] => Ok(String::from_utf8_lossy(name).into_owned()),
+			[] => Err(err("malformed credential list")),
+		})
+		.collect()
+}
+
+#[cfg(feature = "yubikey")]
+struct Session {
+	card: pcsc::Card,
+}
+
+#[cfg(feature = "yubikey")]
+fn session() -> Result<Session> {
+	const OATH_AID: [u8; 7] = [0xa0, 0x00, 0x00, 0x05, 0x27, 0x21, 0x01];
+	const INS_SELECT: u8 = 0xa4;
+
+	let pcsc_err = |e: pcsc::Error| err(e.to_string());
+	let context = pcsc::Context::establish(pcsc::Scope::User).map_err(pcsc_err)?;
+	let readers = context.list_readers_owned().map_err(pcsc_err)?;
+	let reader = readers
+		.iter()
+		.find(|r| r.to_string_lossy().contains("YubiKey"))
+		.ok_or_else(|| err("no YubiKey is connected"))?;
+	let card =
+		context.connect(reader, pcsc::ShareMode::Shared, pcsc::Protocols::ANY).map_err(pcsc_err)?;
+	let session = Session { card };
+	let selected = session.send(INS_SELECT, 0x04, 0x00, &OATH_AID)?;
+	if records(&selected)?.iter().any(|(tag, _)| *tag == TAG_CHALLENGE) {
+		return Err(err("the OATH applet is password protected"));
+	}
+	Ok(session)
+}
+
+#[cfg(feature = "yubikey")]
+impl Session {
+	/// Send one APDU and collect the whole answer, following "more data"
+	/// status words.
+	fn send(&self, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<Vec<u8>> {
+		const INS_SEND_REMAINING: u8 = 0xa5;
+
+		let mut apdu = vec![0x00, ins, p1, p2, data.len() as u8];
+		apdu.extend_from_slice(data);
+		let mut buffer = [0; pcsc::MAX_BUFFER_SIZE_EXTENDED];
+		let mut out = vec![];
+		loop {
+			let response = self.card.transmit(&apdu, &mut buffer).map_err(|e| err(e.to_string()))?;
+			let Some((status, body)) = response.split_last_chunk::<2>() else {
+				return Err(err("empty response"));
+			};
+			out.extend_from_slice(body);
+			match *status {
+				[0x90, 0x00] => return Ok(out),
+				[0x61, _] => apdu = vec![0x00, INS_SEND_REMAINING, 0x00, 0x00],
+				[0x6a, 0x82] => return Err(err("no such credential on the key")),
+				[0x69, 0x82] => return Err(err("the OATH applet is locked")),
+				[sw1, sw2] => return Err(err(format!("the key answered {sw1:02x}{sw2:02x}"))),
+			}
+		}
+	}
+}
+
+#[cfg(not(feature = "yubikey"))]
+enum Session {}
+
+#[cfg(not(feature = "yubikey"))]
+fn session() -> Result<Session> {
+	Err(err("this build has no YubiKey support; rebuild with the `yubikey` feature"))
+}
+
+#[cfg(not(feature = "yubikey"))]
+impl Session {
+	fn send(&self, _ins: u8, _p1: u8, _p2: u8, _data: &[u8]) -> Result<Vec<u8>> { match *self {} }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn truncated_codes_are_read_from_the_response() {
+		// 0x3ebb3a2f masks to 1052457519; its last six digits are the code.
+		let response = [TAG_TRUNCATED, 5, 6, 0x3e, 0xbb, 0x3a, 0x2f];
+		assert_eq!(code_from_response(&response).unwrap(), "457519");
+
+		let padded = [TAG_TRUNCATED, 5, 8, 0x00, 0x00, 0x00, 0x2a];
+		assert_eq!(code_from_response(&padded).unwrap(), "00000042");
+
+		assert!(code_from_response(&[0x77, 1, 6]).is_err());
+		assert!(code_from_response(&[TAG_TRUNCATED, 5, 6]).is_err());
+	}
+
+	#[test]
+	fn credential_names_are_listed() {
+		let mut response = tlv(TAG_NAME_LIST, b"\x21GitHub:alice");
+		response.extend(tlv(TAG_NAME_LIST, b"\x2260/Example:bob"));
+		assert_eq!(names_from_response(&response).unwrap(), ["GitHub:alice", "60/Example:bob"]);
+	}
+}
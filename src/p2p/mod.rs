@@ -8,11 +8,68 @@ use iroh_docs::{ALPN as DOCS_ALPN, DocTicket, api::{Doc, protocol::{AddrInfoOpti
 use iroh_gossip::{ALPN as GOSSIP_ALPN, net::Gossip};
 use tokio::sync::Mutex;
 
-use crate::{Error as PwdError, Result as PwdResult, store::{ShareTicket, StorePayload}};
+use crate::{Error as PwdError, Result as PwdResult, encryption::{AgeScrypt, EncryptionMethod}, store::{ShareTicket, StorePayload}};
 
 /// The document key under which the full store payload is stored.
 const PAYLOAD_KEY: &[u8] = b"store_payload";
 
+/// The one-time code a shared payload is sealed with, passed to the
+/// receiver over some other channel than the ticket: six words from the
+/// BIP-39 list, 66 bits, stretched with scrypt so that whoever gets hold of
+/// the ticket alone can't read the store.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ShareCode(String);
+
+impl ShareCode {
+	const WORDS: usize = 6;
+
+	/// A fresh random code.
+	pub fn generate() -> Self {
+		use rand::{rand_core::UnwrapErr, rngs::SysRng, seq::IndexedRandom};
+		let mut rng = UnwrapErr(SysRng);
+		let words = bip39::Language::English.word_list();
+		let code: Vec<&str> =
+			(0..Self::WORDS).map(|_| *words.choose(&mut rng).expect("wordlist is not empty")).collect();
+		Self(code.join("-"))
+	}
+
+	pub fn as_str(&self) -> &str { &self.0 }
+}
+
+impl std::fmt::Display for ShareCode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { f.write_str(&self.0) }
+}
+
+impl std::str::FromStr for ShareCode {
+	type Err = PwdError;
+
+	/// Words may be separated by dashes or spaces, in any case.
+	fn from_str(s: &str) -> PwdResult<Self> {
+		let words: Vec<String> = s
+			.split(|c: char| c == '-' || c.is_whitespace())
+			.filter(|w| !w.is_empty())
+			.map(str::to_lowercase)
+			.collect();
+		if words.is_empty() {
+			return Err(PwdError::WrongShareCode);
+		}
+		Ok(Self(words.join("-")))
+	}
+}
+
+/// Encrypt `payload` with `code` for sharing.
+pub fn seal(payload: StorePayload, code: &ShareCode) -> PwdResult<StorePayload> {
+	Ok(StorePayload(AgeScrypt::new(code.as_str())?.encrypt(&payload.into_inner())?))
+}
+
+/// Decrypt a payload sealed with `code`.
+pub fn open(payload: StorePayload, code: &ShareCode) -> PwdResult<StorePayload> {
+	let plaintext = AgeScrypt::new(code.as_str())?
+		.decrypt(&payload.into_inner())
+		.map_err(|_| PwdError::WrongShareCode)?;
+	Ok(StorePayload(plaintext))
+}
+
 struct NodeHandles {
 	router: Router,
 	blobs:  MemStore,
@@ -47,15 +104,20 @@ impl IrohSync {
 
 	fn docs(&self) -> AResult<&Docs> { Ok(&self.handles()?.docs) }
 
-	/// Publish `payload` into a new iroh-docs document and return a
-	/// [`ShareTicket`] that the receiver can use to import it.
-	pub async fn share_payload(&self, payload: StorePayload) -> PwdResult<ShareTicket> {
+	/// Seal `payload` with `code`, publish it into a new iroh-docs document
+	/// and return a [`ShareTicket`] that the receiver can use to import it.
+	pub async fn share_payload(
+		&self,
+		payload: StorePayload,
+		code: &ShareCode,
+	) -> PwdResult<ShareTicket> {
+		let sealed = seal(payload, code)?;
 		let api = self.docs().map_err(PwdError::Iroh)?;
 		let doc: Doc = api.create().await.map_err(PwdError::Iroh)?;
 
 		let author = api.author_default().await.map_err(PwdError::Iroh)?;
 		doc
-			.set_bytes(author, PAYLOAD_KEY.to_vec(), payload.into_inner())
+			.set_bytes(author, PAYLOAD_KEY.to_vec(), sealed.into_inner())
 			.await
 			.map_err(PwdError::Iroh)?;
 
@@ -68,8 +130,12 @@ impl IrohSync {
 	}
 
 	/// Import `ticket`, wait for the payload entry to arrive, and return the
-	/// raw [`StorePayload`] bytes.
-	pub async fn receive_payload(&self, ticket: &ShareTicket) -> PwdResult<StorePayload> {
+	/// [`StorePayload`] bytes opened with `code`.
+	pub async fn receive_payload(
+		&self,
+		ticket: &ShareTicket,
+		code: &ShareCode,
+	) -> PwdResult<StorePayload> {
 		let raw: DocTicket =
 			ticket.as_str().parse::<DocTicket>().map_err(|e| PwdError::InvalidTicket(e.to_string()))?;
 
@@ -110,7 +176,7 @@ impl IrohSync {
 		let bytes =
 			handles.blobs.get_bytes(entry.content_hash()).await.map_err(|e| PwdError::Iroh(e.into()))?;
 
-		Ok(StorePayload(bytes.to_vec()))
+		open(StorePayload(bytes.to_vec()), code)
 	}
 
 	/// Gracefully shut down the router and release the endpoint.
@@ -139,18 +205,18 @@ impl IrohSyncHandle {
 		Ok(())
 	}
 
-	/// Publish `payload`; returns a [`ShareTicket`].
-	pub async fn share(&self, payload: StorePayload) -> PwdResult<ShareTicket> {
+	/// Publish `payload` sealed with `code`; returns a [`ShareTicket`].
+	pub async fn share(&self, payload: StorePayload, code: &ShareCode) -> PwdResult<ShareTicket> {
 		self.init().await?;
 		let mut guard = self.inner.lock().await;
-		guard.as_mut().unwrap().share_payload(payload).await
+		guard.as_mut().unwrap().share_payload(payload, code).await
 	}
 
-	/// Import from `ticket`; returns the raw [`StorePayload`].
-	pub async fn receive(&self, ticket: &ShareTicket) -> PwdResult<StorePayload> {
+	/// Import from `ticket`; returns the [`StorePayload`] opened with `code`.
+	pub async fn receive(&self, ticket: &ShareTicket, code: &ShareCode) -> PwdResult<StorePayload> {
 		self.init().await?;
 		let mut guard = self.inner.lock().await;
-		guard.as_mut().unwrap().receive_payload(ticket).await
+		guard.as_mut().unwrap().receive_payload(ticket, code).await
 	}
 
 	/// Shut down and clean up.
@@ -177,3 +243,21 @@ pub fn decode_store(payload: StorePayload) -> PwdResult<crate::models::PasswordS
 	let text = std::str::from_utf8(&payload.0)?;
 	Ok(toml::from_str(text)?)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn payloads_open_only_with_their_code() {
+		let code = ShareCode::generate();
+		assert_eq!(code.as_str().split('-').count(), ShareCode::WORDS);
+		let sealed = seal(StorePayload(b"items = {}".to_vec()), &code).unwrap();
+		assert!(!sealed.0.windows(5).any(|w| w == b"items"));
+
+		let typed: ShareCode = code.as_str().replace('-', " ").to_uppercase().parse().unwrap();
+		assert_eq!(open(sealed.clone(), &typed).unwrap().0, b"items = {}");
+		let wrong = ShareCode::generate();
+		assert!(matches!(open(sealed, &wrong), Err(PwdError::WrongShareCode)));
+	}
+}
@@ -0,0 +1,101 @@
+//! Terminal-friendly QR code rendering, and reading QR codes back out of
+//! images.
+
+use std::path::Path;
+
+use qrcode::{QrCode, render::unicode::Dense1x2};
+
+use crate::{Error, Result};
+
+/// Render `data` as a QR code using Unicode half-block characters, two
+/// modules per character cell.
+///
+/// Colours are inverted so the code scans correctly on dark terminal
+/// backgrounds.
+pub fn render_half_blocks(data: &str) -> Result<String> {
+	let code = QrCode::new(data.as_bytes()).map_err(|e| Error::Qr(e.to_string()))?;
+	Ok(
+		code
+			.render::<Dense1x2>()
+			.dark_color(Dense1x2::Light)
+			.light_color(Dense1x2::Dark)
+			.quiet_zone(true)
+			.build(),
+	)
+}
+
+/// Render `data` as a QR code in plain ASCII, each module two characters
+/// wide, for terminals and fonts without block characters. Inverted like
+/// [`render_half_blocks`].
+pub fn render_ascii(data: &str) -> Result<String> {
+	let code = QrCode::new(data.as_bytes()).map_err(|e| Error::Qr(e.to_string()))?;
+	Ok(
+		code
+			.render::<char>()
+			.dark_color(' ')
+			.light_color('#')
+			.module_dimensions(2, 1)
+			.quiet_zone(true)
+			.build(),
+	)
+}
+
+/// Render `data` as a QR code in plain ASCII for printing on paper: dark
+/// modules are `##`, so unlike [`render_ascii`] it is not inverted.
+pub fn render_printable(data: &str) -> Result<String> {
+	let code = QrCode::new(data.as_bytes()).map_err(|e| Error::Qr(e.to_string()))?;
+	Ok(
+		code
+			.render::<char>()
+			.dark_color('#')
+			.light_color(' ')
+			.module_dimensions(2, 1)
+			.quiet_zone(true)
+			.build(),
+	)
+}
+
+/// The text of the first readable QR code in the image at `path`, such as a
+/// screenshot of an authenticator enrolment code.
+pub fn decode_image(path: &Path) -> Result<String> {
+	let image = image::open(path).map_err(|e| Error::Qr(format!("{}: {e}", path.display())))?;
+	let image = image.to_luma8();
+	decode_luma(image.width() as usize, image.height() as usize, |x, y| {
+		image.get_pixel(x as u32, y as u32).0[0]
+	})
+	.ok_or_else(|| Error::Qr(format!("no QR code found in {}", path.display())))
+}
+
+fn decode_luma(
+	width: usize,
+	height: usize,
+	pixel: impl FnMut(usize, usize) -> u8,
+) -> Option<String> {
+	let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(width, height, pixel);
+	prepared.detect_grids().into_iter().find_map(|grid| grid.decode().ok().map(|(_, text)| text))
+}
+
+#[cfg(test)]
+mod tests {
+	use qrcode::Color;
+
+	use super::*;
+
+	#[test]
+	fn rendered_codes_decode_back_to_their_text() {
+		let text = "otpauth://totp/Example:alice?secret=GEZDGNBVGY3TQOJQ&issuer=Example";
+		let code = QrCode::new(text).unwrap();
+		let (modules, colors) = (code.width(), code.to_colors());
+		// Four pixels per module, inside a four-module quiet zone.
+		let (scale, margin) = (4, 4);
+		let side = (modules + 2 * margin) * scale;
+		let decoded = decode_luma(side, side, |x, y| {
+			let (column, row) = (x / scale, y / scale);
+			let inside =
+				(margin..margin + modules).contains(&column) && (margin..margin + modules).contains(&row);
+			let dark = inside && colors[(row - margin) * modules + column - margin] == Color::Dark;
+			if dark { 0 } else { 255 }
+		});
+		assert_eq!(decoded.as_deref(), Some(text));
+	}
+}
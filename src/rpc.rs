@@ -0,0 +1,317 @@
+//! A small JSON-RPC 2.0 API, one request per line, through which editor
+//! plugins and other front ends drive a store: `pwd serve --stdio`.
+//!
+//! | method     | params                          | result                            |
+//! |------------|---------------------------------|-----------------------------------|
+//! | `unlock`   | `{passphrase}`                  | `null`                            |
+//! | `lock`     |                                 | `null`                            |
+//! | `list`     | `{tag?}`                        | `[{name, kind, subtitle, tags}]`  |
+//! | `get`      | `{name, field?}`                | the item as stored, or the field  |
+//! | `otp`      | `{name}`                        | `{code, remaining?, window?}`     |
+//! | `generate` | `{preset?, length?, words?}`    | the generated string              |
+//!
+//! Everything but `unlock` and `generate` needs an unlocked store. Getting
+//! an HOTP code advances and saves its counter. Failures of the store itself
+//! are reported with code -32000.
+
+use std::io::{BufRead, Write};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::{AgeScrypt, BranchPath, Error, PersonalBranch, Result, config::Config, encryption::{Locked, Unlocked}, generator::GeneratorOptions, models::AccountName, store::{StoreBackend, StoreChange}, versioning::PijulStore};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const STORE_ERROR: i64 = -32000;
+
+struct RpcError {
+	code:    i64,
+	message: String,
+}
+
+impl RpcError {
+	fn new(code: i64, message: impl Into<String>) -> Self { Self { code, message: message.into() } }
+}
+
+impl From<Error> for RpcError {
+	fn from(e: Error) -> Self { Self::new(STORE_ERROR, e.to_string()) }
+}
+
+enum Vault {
+	Locked(PijulStore<Locked>),
+	Unlocked(PijulStore<Unlocked<AgeScrypt>>),
+}
+
+/// Answers requests against one branch of a store.
+pub struct Server {
+	/// `None` only while switching between locked and unlocked.
+	vault:  Option<Vault>,
+	branch: BranchPath<PersonalBranch>,
+	config: Config,
+}
+
+#[derive(Deserialize)]
+struct Request {
+	#[serde(default)]
+	id:     Option<Value>,
+	method: String,
+	#[serde(default)]
+	params: Value,
+}
+
+#[derive(Deserialize)]
+struct UnlockParams {
+	passphrase: String,
+}
+
+#[derive(Deserialize, Default)]
+struct ListParams {
+	tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GetParams {
+	name:  String,
+	field: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OtpParams {
+	name: String,
+}
+
+#[derive(Deserialize, Default)]
+struct GenerateParams {
+	preset: Option<String>,
+	length: Option<u32>,
+	words:  Option<u32>,
+}
+
+impl Server {
+	/// A server for `branch` of `store`, which starts out locked.
+	pub fn new(
+		store: PijulStore<Locked>,
+		branch: BranchPath<PersonalBranch>,
+		config: Config,
+	) -> Self {
+		Self { vault: Some(Vault::Locked(store)), branch, config }
+	}
+
+	/// Unlock the store, checking `passphrase` against the branch's entries.
+	pub fn unlock(&mut self, passphrase: String) -> Result<()> {
+		let method = AgeScrypt::new(passphrase)?;
+		let store = match self.vault.take() {
+			Some(Vault::Locked(store)) => store.unlock_with(method),
+			Some(Vault::Unlocked(store)) => store.lock().unlock_with(method),
+			None => unreachable!("the vault is always put back"),
+		};
+		match store.load(&self.branch) {
+			Ok(_) => {
+				self.vault = Some(Vault::Unlocked(store));
+				Ok(())
+			}
+			Err(e) => {
+				self.vault = Some(Vault::Locked(store.lock()));
+				Err(e)
+			}
+		}
+	}
+
+	/// Forget the key.
+	pub fn lock(&mut self) {
+		self.vault = self.vault.take().map(|vault| match vault {
+			Vault::Unlocked(store) => Vault::Locked(store.lock()),
+			locked => locked,
+		});
+	}
+
+	/// Read requests from `input`, one per line, until it ends, writing each
+	/// response to `output` on a line of its own.
+	pub fn serve(&mut self, input: impl BufRead, mut output: impl Write) -> Result<()> {
+		for line in input.lines() {
+			if let Some(response) = self.handle_line(&line?) {
+				writeln!(output, "{response}")?;
+				output.flush()?;
+			}
+		}
+		Ok(())
+	}
+
+	/// The response to one request line; `None` for a notification (a
+	/// request without an `id`) or a blank line.
+	pub fn handle_line(&mut self, line: &str) -> Option<String> {
+		if line.trim().is_empty() {
+			return None;
+		}
+		let (id, result) = match serde_json::from_str::<Value>(line) {
+			Err(e) => (Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string()))),
+			Ok(value) => match serde_json::from_value::<Request>(value) {
+				Err(e) => (Value::Null, Err(RpcError::new(INVALID_REQUEST, e.to_string()))),
+				Ok(request) => {
+					let result = self.call(&request.method, request.params);
+					(request.id?, result)
+				}
+			},
+		};
+		let response = match result {
+			Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+			Err(e) => json!({
+				"jsonrpc": "2.0",
+				"id": id,
+				"error": { "code": e.code, "message": e.message },
+			}),
+		};
+		Some(response.to_string())
+	}
+
+	fn call(&mut self, method: &str, params: Value) -> std::result::Result<Value, RpcError> {
+		match method {
+			"unlock" => {
+				let params: UnlockParams = parse(params)?;
+				self.unlock(params.passphrase)?;
+				Ok(Value::Null)
+			}
+			"lock" => {
+				self.lock();
+				Ok(Value::Null)
+			}
+			"list" => self.list(parse_or_default(params)?),
+			"get" => self.get(parse(params)?),
+			"otp" => self.otp(parse(params)?),
+			"generate" => self.generate(parse_or_default(params)?),
+			other => Err(RpcError::new(METHOD_NOT_FOUND, format!("unknown method '{other}'"))),
+		}
+	}
+
+	fn store(&self) -> std::result::Result<&PijulStore<Unlocked<AgeScrypt>>, RpcError> {
+		match &self.vault {
+			Some(Vault::Unlocked(store)) => Ok(store),
+			_ => Err(RpcError::new(STORE_ERROR, "the store is locked; call unlock first")),
+		}
+	}
+
+	fn list(&self, params: ListParams) -> std::result::Result<Value, RpcError> {
+		let mut items: Vec<_> = self
+			.store()?
+			.load(&self.branch)?
+			.items
+			.into_iter()
+			.filter(|(_, item)| params.tag.as_ref().is_none_or(|tag| item.has_tag(tag)))
+			.collect();
+		items.sort_by(|(a, _), (b, _)| a.cmp(b));
+		Ok(
+			items
+				.into_iter()
+				.map(|(name, item)| {
+					json!({
+						"name": name.as_str(),
+						"kind": item.kind_str(),
+						"subtitle": item.subtitle(),
+						"tags": item.tags(),
+					})
+				})
+				.collect(),
+		)
+	}
+
+	fn get(&self, params: GetParams) -> std::result::Result<Value, RpcError> {
+		let name = AccountName::new(&params.name)?;
+		let item = self.store()?.get(&self.branch, &name)?.ok_or(Error::EntryNotFound { name })?;
+		let Some(field) = params.field else {
+			return serde_json::to_value(&item).map_err(|e| RpcError::new(STORE_ERROR, e.to_string()));
+		};
+		let value = crate::forms::editable_fields(&item)
+			.into_iter()
+			.find(|(f, _)| f.name == field)
+			.map(|(_, value)| value)
+			.or_else(|| item.custom_field(&field).map(|f| f.value.clone()))
+			.ok_or_else(|| {
+				RpcError::new(INVALID_PARAMS, format!("'{}' has no field '{field}'", params.name))
+			})?;
+		Ok(value.into())
+	}
+
+	fn otp(&self, params: OtpParams) -> std::result::Result<Value, RpcError> {
+		let store = self.store()?;
+		let name = AccountName::new(&params.name)?;
+		let mut item =
+			store.get(&self.branch, &name)?.ok_or(Error::EntryNotFound { name: name.clone() })?;
+		let totp = item.totp_mut().ok_or_else(|| {
+			RpcError::new(INVALID_PARAMS, format!("'{}' has no TOTP secret", params.name))
+		})?;
+		if totp.is_counter_based() {
+			let code = totp.advance()?;
+			let change = StoreChange::Custom(format!("advance HOTP counter for {name}"));
+			store.update(&self.branch, &name, item, change)?;
+			return Ok(json!({ "code": code }));
+		}
+		let code = totp.code()?;
+		Ok(json!({ "code": code.code, "remaining": code.remaining, "window": code.window() }))
+	}
+
+	fn generate(&self, params: GenerateParams) -> std::result::Result<Value, RpcError> {
+		let mut options = match &params.preset {
+			Some(preset) => self.config.generator.presets.get(preset).cloned().ok_or_else(|| {
+				RpcError::new(INVALID_PARAMS, format!("no generator preset named '{preset}'"))
+			})?,
+			None => GeneratorOptions::Password { options: Default::default() },
+		};
+		if let Some(length) = params.length {
+			options = options.with_length(length);
+		}
+		if let Some(words) = params.words {
+			options = options.with_words(words);
+		}
+		Ok(options.generate()?.into())
+	}
+}
+
+fn parse<T: serde::de::DeserializeOwned>(params: Value) -> std::result::Result<T, RpcError> {
+	serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// Like [`parse`], with the defaults when `params` is left out.
+fn parse_or_default<T: serde::de::DeserializeOwned + Default>(
+	params: Value,
+) -> std::result::Result<T, RpcError> {
+	if params.is_null() { Ok(T::default()) } else { parse(params) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::BranchSegment;
+
+	fn call(server: &mut Server, request: Value) -> Value {
+		serde_json::from_str(&server.handle_line(&request.to_string()).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn requests_need_an_unlocked_store() {
+		let branch = BranchPath::personal(BranchSegment::new("main").unwrap());
+		let mut server = Server::new(PijulStore::ephemeral().unwrap(), branch, Config::default());
+
+		let locked = call(&mut server, json!({ "jsonrpc": "2.0", "id": 1, "method": "list" }));
+		assert_eq!(locked["error"]["code"], STORE_ERROR);
+
+		let unlock = json!({ "id": 2, "method": "unlock", "params": { "passphrase": "hunter22" } });
+		assert_eq!(call(&mut server, unlock)["result"], Value::Null);
+		assert_eq!(call(&mut server, json!({ "id": 3, "method": "list" }))["result"], json!([]));
+
+		let generate = json!({ "id": 4, "method": "generate", "params": { "length": 12 } });
+		assert_eq!(call(&mut server, generate)["result"].as_str().unwrap().len(), 12);
+
+		let unknown = call(&mut server, json!({ "id": 5, "method": "frobnicate" }));
+		assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+		assert_eq!(unknown["id"], 5);
+
+		assert!(server.handle_line(r#"{"method": "lock"}"#).is_none());
+		let relocked =
+			call(&mut server, json!({ "id": 6, "method": "get", "params": { "name": "x" } }));
+		assert_eq!(relocked["error"]["code"], STORE_ERROR);
+		assert_eq!(call(&mut server, json!("nonsense"))["error"]["code"], INVALID_REQUEST);
+	}
+}
@@ -0,0 +1,456 @@
+//! Substring search over entry names and, optionally, item contents.
+//!
+//! A [`SearchIndex`] is built once from a loaded [`PasswordStore`] and queried
+//! on every keystroke without touching the disk or decrypting anything.
+//! Matching is ASCII case-insensitive so byte offsets in the lowered text
+//! line up with the original, which is what highlighting needs.
+
+use std::{cmp::Reverse, collections::BTreeMap, ops::Range};
+
+use jiff::Timestamp;
+
+use crate::{models::{AccountName, ItemCategory, PasswordStore}, stats};
+
+/// A match inside one field of an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMatch {
+	pub field: String,
+	pub value: String,
+	/// Byte range of the match within `value`.
+	pub range: Range<usize>,
+}
+
+/// One entry matching a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+	pub name:           AccountName,
+	/// Byte range of the match within the entry name, if the name matched.
+	pub name_match:     Option<Range<usize>>,
+	/// Matching item fields; always empty for shallow searches.
+	pub fields:         Vec<FieldMatch>,
+	/// The entry's tags, for display alongside the hit.
+	pub tags:           Vec<String>,
+	pub category:       ItemCategory,
+	pub favorite:       bool,
+	/// The item's [`Item::kind_str`], for picking an icon.
+	///
+	/// [`Item::kind_str`]: crate::models::Item::kind_str
+	pub kind:           &'static str,
+	/// See [`Item::subtitle`](crate::models::Item::subtitle).
+	pub subtitle:       Option<String>,
+	/// See [`Item::appearance`](crate::models::Item::appearance).
+	pub accent:         Option<String>,
+	pub icon:           Option<String>,
+	/// The zxcvbn score of the entry's password, if it has one; see
+	/// [`stats::password_score`].
+	pub password_score: Option<u8>,
+	/// When the entry last changed, if the index was built
+	/// [`with_modified`](SearchIndex::with_modified).
+	pub modified:       Option<Timestamp>,
+}
+
+/// Restrictions applied before matching; the default lets every entry
+/// through.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchFilter {
+	/// Only entries carrying this tag (case-insensitive).
+	pub tag:         Option<String>,
+	/// Only entries in this category.
+	pub category:    Option<ItemCategory>,
+	/// Only entries inside this folder or its subfolders.
+	pub folder:      Option<String>,
+	/// Only deactivated accounts (`Some(true)`), or only entries that are
+	/// not (`Some(false)`).
+	pub deactivated: Option<bool>,
+}
+
+impl SearchFilter {
+	fn admits(&self, entry: &IndexedEntry) -> bool {
+		self.tag.as_deref().is_none_or(|tag| entry.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+			&& self.category.is_none_or(|category| entry.category == category)
+			&& self.folder.as_deref().is_none_or(|folder| {
+				entry.name.as_str().strip_prefix(folder).is_some_and(|rest| rest.starts_with('/'))
+			}) && self.deactivated.is_none_or(|deactivated| entry.deactivated == deactivated)
+	}
+}
+
+/// How an unfiltered entry list is ordered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, uniffi::Enum)]
+pub enum SortMode {
+	#[default]
+	Name,
+	/// Most recently changed first.
+	Modified,
+	/// Most recently opened first.
+	Viewed,
+	/// Grouped by category, then item kind.
+	Kind,
+}
+
+#[derive(Debug, Clone)]
+struct IndexedField {
+	field:  String,
+	value:  String,
+	folded: String,
+}
+
+#[derive(Debug, Clone)]
+struct IndexedEntry {
+	name:        AccountName,
+	folded:      String,
+	fields:      Vec<IndexedField>,
+	tags:        Vec<String>,
+	category:    ItemCategory,
+	kind:        &'static str,
+	subtitle:    Option<String>,
+	accent:      Option<String>,
+	icon:        Option<String>,
+	score:       Option<u8>,
+	favorite:    bool,
+	deactivated: bool,
+	modified:    Option<Timestamp>,
+}
+
+impl IndexedEntry {
+	fn hit(&self, name_match: Option<Range<usize>>, fields: Vec<FieldMatch>) -> SearchHit {
+		SearchHit {
+			name: self.name.clone(),
+			name_match,
+			fields,
+			tags: self.tags.clone(),
+			category: self.category,
+			favorite: self.favorite,
+			kind: self.kind,
+			subtitle: self.subtitle.clone(),
+			accent: self.accent.clone(),
+			icon: self.icon.clone(),
+			password_score: self.score,
+			modified: self.modified,
+		}
+	}
+}
+
+/// Pre-lowered search text for every entry in a store.
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+	entries: Vec<IndexedEntry>,
+}
+
+impl SearchIndex {
+	/// Index every entry's name and its [`Item::text_fields`], and score its
+	/// password.
+	///
+	/// [`Item::text_fields`]: crate::models::Item::text_fields
+	pub fn build(store: &PasswordStore) -> Self {
+		let mut entries: Vec<IndexedEntry> = store
+			.items
+			.iter()
+			.map(|(name, item)| IndexedEntry {
+				name:        name.clone(),
+				folded:      name.as_str().to_ascii_lowercase(),
+				fields:      item
+					.text_fields()
+					.into_iter()
+					.map(|(field, value)| IndexedField { folded: value.to_ascii_lowercase(), field, value })
+					.collect(),
+				tags:        item.tags().to_vec(),
+				category:    item.category(),
+				kind:        item.kind_str(),
+				subtitle:    item.subtitle(),
+				accent:      item.appearance().and_then(|a| a.accent.clone()),
+				icon:        item.appearance().and_then(|a| a.icon.clone()),
+				score:       stats::password(item).map(|p| stats::password_score(&p)),
+				favorite:    item.is_favorite(),
+				deactivated: item.is_deactivated(),
+				modified:    None,
+			})
+			.collect();
+		entries.sort_by(|a, b| a.name.cmp(&b.name));
+		Self { entries }
+	}
+
+	/// Record when each entry last changed, for [`SortMode::Modified`].
+	pub fn with_modified(mut self, modified: &BTreeMap<AccountName, Timestamp>) -> Self {
+		for entry in &mut self.entries {
+			entry.modified = modified.get(&entry.name).copied();
+		}
+		self
+	}
+
+	/// The password score of every entry that has a password.
+	pub fn password_scores(&self) -> BTreeMap<AccountName, u8> {
+		self.entries.iter().filter_map(|e| Some((e.name.clone(), e.score?))).collect()
+	}
+
+	fn entry(&self, name: &AccountName) -> Option<&IndexedEntry> {
+		self.entries.binary_search_by(|e| e.name.cmp(name)).ok().map(|i| &self.entries[i])
+	}
+
+	pub fn len(&self) -> usize { self.entries.len() }
+
+	pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+	/// Every tag used in the index, sorted and de-duplicated
+	/// case-insensitively.
+	pub fn tags(&self) -> Vec<String> {
+		let mut seen = std::collections::BTreeMap::new();
+		for tag in self.entries.iter().flat_map(|e| &e.tags) {
+			seen.entry(tag.to_lowercase()).or_insert_with(|| tag.clone());
+		}
+		seen.into_values().collect()
+	}
+
+	/// Every folder holding an entry, including the folders above it,
+	/// sorted so each folder comes right before its contents.
+	pub fn folders(&self) -> Vec<String> {
+		let mut folders = std::collections::BTreeSet::new();
+		for entry in &self.entries {
+			let mut folder = entry.name.folder();
+			while let Some(f) = folder {
+				folders.insert(f.to_owned());
+				folder = f.rsplit_once('/').map(|(parent, _)| parent);
+			}
+		}
+		folders.into_iter().collect()
+	}
+
+	/// Entries matching `query`: name matches first, favorites first within
+	/// those, then by name.
+	///
+	/// A shallow search only looks at entry names; a `deep` one also matches
+	/// item contents. An empty query returns every entry without matches.
+	/// Only entries admitted by `filter` are considered.
+	pub fn search(&self, query: &str, deep: bool, filter: &SearchFilter) -> Vec<SearchHit> {
+		let needle = query.trim().to_ascii_lowercase();
+		let locate = |haystack: &str| haystack.find(&needle).map(|at| at..at + needle.len());
+
+		let mut hits: Vec<SearchHit> = self
+			.entries
+			.iter()
+			.filter(|entry| filter.admits(entry))
+			.filter_map(|entry| {
+				if needle.is_empty() {
+					return Some(entry.hit(None, vec![]));
+				}
+				let name_match = locate(&entry.folded);
+				let fields: Vec<FieldMatch> = if deep {
+					entry
+						.fields
+						.iter()
+						.filter_map(|f| {
+							locate(&f.folded).map(|range| FieldMatch {
+								field: f.field.clone(),
+								value: f.value.clone(),
+								range,
+							})
+						})
+						.collect()
+				} else {
+					vec![]
+				};
+				(name_match.is_some() || !fields.is_empty()).then(|| entry.hit(name_match, fields))
+			})
+			.collect();
+		hits.sort_by_key(|hit| (hit.name_match.is_none(), !hit.favorite));
+		hits
+	}
+
+	/// Reorder `hits` by `mode`, favorites first and ties broken by name.
+	/// `viewed` lists names most recently opened first; entries never viewed
+	/// or never recorded sort last.
+	pub fn sort(&self, hits: &mut [SearchHit], mode: SortMode, viewed: &[AccountName]) {
+		hits.sort_by(|a, b| a.name.cmp(&b.name));
+		match mode {
+			SortMode::Name => hits.sort_by_key(|hit| !hit.favorite),
+			SortMode::Modified => hits.sort_by_key(|hit| {
+				(!hit.favorite, Reverse(self.entry(&hit.name).and_then(|e| e.modified)))
+			}),
+			SortMode::Viewed => hits.sort_by_key(|hit| {
+				(!hit.favorite, viewed.iter().position(|v| *v == hit.name).unwrap_or(usize::MAX))
+			}),
+			SortMode::Kind => {
+				hits.sort_by_key(|hit| (!hit.favorite, hit.category, self.entry(&hit.name).map(|e| e.kind)))
+			}
+		}
+	}
+}
+
+/// How long ago `then` was, coarsely: "just now", "5 min ago", "yesterday",
+/// "3 weeks ago", ...
+pub fn relative_age(then: Timestamp, now: Timestamp) -> String {
+	let secs = now.as_second().saturating_sub(then.as_second()).max(0);
+	let (minutes, hours, days) = (secs / 60, secs / 3_600, secs / 86_400);
+	match () {
+		_ if minutes < 1 => "just now".into(),
+		_ if hours < 1 => format!("{minutes} min ago"),
+		_ if days < 1 => format!("{hours} h ago"),
+		_ if days < 2 => "yesterday".into(),
+		_ if days < 14 => format!("{days} days ago"),
+		_ if days < 60 => format!("{} weeks ago", days / 7),
+		_ if days < 730 => format!("{} months ago", days / 30),
+		_ => format!("{} years ago", days / 365),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::{Item, SecureNote};
+
+	fn note(title: &str, body: &str, tags: &[&str]) -> Item {
+		Item::SecureNote(SecureNote {
+			title:         title.into(),
+			body:          body.into(),
+			tags:          tags.iter().map(|t| t.to_string()).collect(),
+			custom_fields: vec![],
+			appearance:    None,
+		})
+	}
+
+	fn index() -> SearchIndex {
+		let mut store = PasswordStore::new();
+		store
+			.items
+			.insert(AccountName::new("wifi-codes").unwrap(), note("Router", "admin panel", &["Home"]));
+		store.items.insert(
+			AccountName::new("bank").unwrap(),
+			note("Bank PINs", "see WiFi card", &["finance", "home"]),
+		);
+		SearchIndex::build(&store)
+	}
+
+	#[test]
+	fn shallow_search_matches_names_only() {
+		let hits = index().search("WIFI", false, &SearchFilter::default());
+		assert_eq!(hits.len(), 1);
+		assert_eq!(hits[0].name.as_str(), "wifi-codes");
+		assert_eq!(hits[0].name_match, Some(0..4));
+	}
+
+	#[test]
+	fn deep_search_reports_field_ranges_after_name_matches() {
+		let hits = index().search("wifi", true, &SearchFilter::default());
+		assert_eq!(hits.iter().map(|h| h.name.as_str()).collect::<Vec<_>>(), ["wifi-codes", "bank"]);
+		let body = &hits[1].fields[0];
+		assert_eq!(body.field, "body");
+		assert_eq!(&body.value[body.range.clone()], "WiFi");
+	}
+
+	#[test]
+	fn favorites_come_first_among_equal_matches() {
+		let mut store = PasswordStore::new();
+		let mut starred = note("zeta", "", &[]);
+		starred.set_favorite(true);
+		store.items.insert(AccountName::new("zeta").unwrap(), starred);
+		store.items.insert(AccountName::new("alpha").unwrap(), note("alpha", "", &[]));
+		let hits = SearchIndex::build(&store).search("a", false, &SearchFilter::default());
+		assert_eq!(hits.iter().map(|h| h.name.as_str()).collect::<Vec<_>>(), ["zeta", "alpha"]);
+		assert!(hits[0].favorite && !hits[1].favorite);
+	}
+
+	#[test]
+	fn sort_modes_order_unfiltered_lists() {
+		let index = index();
+		let names = |mode, viewed: &[AccountName]| {
+			let mut hits = index.search("", false, &SearchFilter::default());
+			index.sort(&mut hits, mode, viewed);
+			hits.into_iter().map(|h| h.name.to_string()).collect::<Vec<_>>()
+		};
+		assert_eq!(names(SortMode::Name, &[]), ["bank", "wifi-codes"]);
+		assert_eq!(names(SortMode::Viewed, &[AccountName::new("wifi-codes").unwrap()]), [
+			"wifi-codes",
+			"bank"
+		]);
+
+		let modified =
+			BTreeMap::from([(AccountName::new("wifi-codes").unwrap(), Timestamp::UNIX_EPOCH)]);
+		let index = index.with_modified(&modified);
+		let mut hits = index.search("", false, &SearchFilter::default());
+		index.sort(&mut hits, SortMode::Modified, &[]);
+		assert_eq!(hits[0].name.as_str(), "wifi-codes");
+
+		let mut store = PasswordStore::new();
+		let mut starred = note("zeta", "", &[]);
+		starred.set_favorite(true);
+		store.items.insert(AccountName::new("zeta").unwrap(), starred);
+		store.items.insert(AccountName::new("alpha").unwrap(), note("alpha", "", &[]));
+		let alpha = AccountName::new("alpha").unwrap();
+		let modified = BTreeMap::from([(alpha.clone(), Timestamp::UNIX_EPOCH)]);
+		let index = SearchIndex::build(&store).with_modified(&modified);
+		for mode in [SortMode::Name, SortMode::Modified, SortMode::Viewed, SortMode::Kind] {
+			let mut hits = index.search("", false, &SearchFilter::default());
+			index.sort(&mut hits, mode, &[alpha.clone()]);
+			assert_eq!(hits[0].name.as_str(), "zeta", "{mode:?}");
+		}
+	}
+
+	#[test]
+	fn ages_are_coarse() {
+		let now = Timestamp::from_second(1_000_000_000).unwrap();
+		let ago = |secs: i64| relative_age(Timestamp::from_second(1_000_000_000 - secs).unwrap(), now);
+		assert_eq!(ago(20), "just now");
+		assert_eq!(ago(5 * 60), "5 min ago");
+		assert_eq!(ago(30 * 3_600), "yesterday");
+		assert_eq!(ago(21 * 86_400), "3 weeks ago");
+		assert_eq!(ago(3 * 365 * 86_400), "3 years ago");
+	}
+
+	#[test]
+	fn tag_filter_narrows_results() {
+		let index = index();
+		assert_eq!(index.tags(), ["finance", "home"]);
+		let tag = |t: &str| SearchFilter { tag: Some(t.into()), ..Default::default() };
+		let hits = index.search("", false, &tag("FINANCE"));
+		assert_eq!(hits.iter().map(|h| h.name.as_str()).collect::<Vec<_>>(), ["bank"]);
+		assert_eq!(index.search("", false, &tag("home")).len(), 2);
+	}
+
+	#[test]
+	fn folder_filter_includes_subfolders() {
+		let mut store = PasswordStore::new();
+		for name in ["work/github", "work/ops/aws", "workshop", "home"] {
+			store.items.insert(AccountName::new(name).unwrap(), note(name, "", &[]));
+		}
+		let index = SearchIndex::build(&store);
+		assert_eq!(index.folders(), ["work", "work/ops"]);
+		let work = SearchFilter { folder: Some("work".into()), ..Default::default() };
+		let hits = index.search("", false, &work);
+		assert_eq!(hits.iter().map(|h| h.name.as_str()).collect::<Vec<_>>(), [
+			"work/github",
+			"work/ops/aws"
+		]);
+	}
+
+	#[test]
+	fn category_filter_narrows_results() {
+		let index = index();
+		let only = |category| SearchFilter { category: Some(category), ..Default::default() };
+		assert_eq!(index.search("", false, &only(ItemCategory::Notes)).len(), 2);
+		assert!(index.search("", false, &only(ItemCategory::Logins)).is_empty());
+		assert_eq!(
+			index.search("bank", false, &only(ItemCategory::Notes))[0].category,
+			ItemCategory::Notes
+		);
+	}
+
+	#[test]
+	fn deactivated_filter_hides_or_isolates_deactivated_accounts() {
+		let mut store = PasswordStore::new();
+		let values = BTreeMap::from([("password".to_string(), "hunter2".to_string())]);
+		let Item::OnlineAccount(mut old) = crate::forms::build_item("online_account", &values).unwrap()
+		else {
+			unreachable!()
+		};
+		old.status = Some(crate::models::AccountStatus::Deactivated);
+		store.items.insert(AccountName::new("old-mail").unwrap(), Item::OnlineAccount(old));
+		store.items.insert(AccountName::new("bank").unwrap(), note("Bank", "", &[]));
+		let index = SearchIndex::build(&store);
+		let names = |deactivated| {
+			let filter = SearchFilter { deactivated, ..Default::default() };
+			index.search("", false, &filter).into_iter().map(|h| h.name.to_string()).collect::<Vec<_>>()
+		};
+		assert_eq!(names(None).len(), 2);
+		assert_eq!(names(Some(false)), ["bank"]);
+		assert_eq!(names(Some(true)), ["old-mail"]);
+	}
+}
@@ -0,0 +1,201 @@
+//! Vault-wide figures for the statistics dashboard.
+//!
+//! A password is weak when zxcvbn scores it below [`STRONG_SCORE`]; the
+//! scores are worked out once per load and kept in the
+//! [`SearchIndex`](crate::search::SearchIndex). It is reused when another
+//! entry has the same one, and old when its entry has not changed for a
+//! year.
+
+use std::collections::{BTreeMap, HashMap};
+
+use jiff::Timestamp;
+
+use crate::{forms, models::{AccountName, Item, PasswordStore}, versioning::ChangeEntry};
+
+/// Entries unchanged for longer than this count as old.
+const OLD_AFTER_DAYS: i64 = 365;
+
+/// The lowest zxcvbn score, out of 4, that does not count as weak.
+pub const STRONG_SCORE: u8 = 3;
+
+/// Weeks of history shown in the activity sparkline.
+pub const ACTIVITY_WEEKS: usize = 12;
+
+/// How many entries there are of one kind.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct KindCount {
+	pub kind:  String,
+	pub label: String,
+	pub count: u32,
+}
+
+/// The dashboard's figures for one branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultStats {
+	pub total:              u32,
+	/// Most common kind first.
+	pub by_kind:            Vec<KindCount>,
+	/// Entries with a password at all; the base for the password figures.
+	pub with_password:      u32,
+	pub weak:               u32,
+	pub reused:             u32,
+	pub old:                u32,
+	/// Online accounts without two-factor authentication turned on.
+	pub accounts:           u32,
+	pub missing_two_factor: u32,
+	pub last_sync:          Option<Timestamp>,
+	/// Changes recorded per week, oldest first, ending with this week.
+	pub weekly_changes:     Vec<u32>,
+}
+
+/// How hard `password` is to guess, from 0 (trivially) to 4 (very hard),
+/// by zxcvbn's estimate of the guesses needed.
+pub fn password_score(password: &str) -> u8 { u8::from(zxcvbn::zxcvbn(password, &[]).score()) }
+
+/// Whether `password` scores below [`STRONG_SCORE`].
+pub fn is_weak(password: &str) -> bool { password_score(password) < STRONG_SCORE }
+
+/// The password-like secret of `item`: its main secret when that is a
+/// password or passphrase rather than a token, key or number.
+pub fn password(item: &Item) -> Option<String> {
+	forms::primary_secret(item)
+		.filter(|(field, _)| matches!(field.as_str(), "password" | "passphrase"))
+		.map(|(_, value)| value)
+}
+
+fn kind_label(kind: &str) -> String {
+	forms::builtin_form(kind).and_then(|form| form.label).unwrap_or_else(|| "Custom".into())
+}
+
+impl VaultStats {
+	/// Gather the figures for `store`, given each password's
+	/// [`password_score`], when each entry last changed, the branch history
+	/// and the last sync.
+	pub fn compute(
+		store: &PasswordStore,
+		scores: &BTreeMap<AccountName, u8>,
+		modified: &BTreeMap<AccountName, Timestamp>,
+		log: &[ChangeEntry],
+		last_sync: Option<Timestamp>,
+		now: Timestamp,
+	) -> Self {
+		let mut kinds: HashMap<&'static str, u32> = HashMap::new();
+		let mut passwords: HashMap<String, u32> = HashMap::new();
+		let mut stats = VaultStats {
+			total: store.items.len() as u32,
+			by_kind: vec![],
+			with_password: 0,
+			weak: 0,
+			reused: 0,
+			old: 0,
+			accounts: 0,
+			missing_two_factor: 0,
+			last_sync,
+			weekly_changes: vec![0; ACTIVITY_WEEKS],
+		};
+
+		for (name, item) in &store.items {
+			*kinds.entry(item.kind_str()).or_default() += 1;
+			if let Item::OnlineAccount(account) = item {
+				stats.accounts += 1;
+				// A stored TOTP secret means a second factor is set up, whatever
+				// the flag says.
+				if account.two_factor_enabled != Some(true) && account.totp.is_none() {
+					stats.missing_two_factor += 1;
+				}
+			}
+			let Some(password) = password(item) else { continue };
+			stats.with_password += 1;
+			if scores.get(name).is_some_and(|&score| score < STRONG_SCORE) {
+				stats.weak += 1;
+			}
+			if modified.get(name).is_some_and(|t| days_between(*t, now) > OLD_AFTER_DAYS) {
+				stats.old += 1;
+			}
+			*passwords.entry(password).or_default() += 1;
+		}
+		stats.reused = passwords.values().filter(|&&n| n > 1).sum();
+
+		stats.by_kind = kinds
+			.into_iter()
+			.map(|(kind, count)| KindCount { kind: kind.into(), label: kind_label(kind), count })
+			.collect();
+		stats.by_kind.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+
+		for change in log {
+			let week = (days_between(change.timestamp, now) / 7) as usize;
+			if week < ACTIVITY_WEEKS {
+				stats.weekly_changes[ACTIVITY_WEEKS - 1 - week] += 1;
+			}
+		}
+		stats
+	}
+}
+
+fn days_between(then: Timestamp, now: Timestamp) -> i64 {
+	(now.as_second() - then.as_second()).max(0) / 86_400
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::Totp;
+
+	fn account(password: &str, two_factor: bool) -> Item {
+		let values = BTreeMap::from([("password".to_string(), password.to_string())]);
+		let Item::OnlineAccount(mut account) = forms::build_item("online_account", &values).unwrap()
+		else {
+			unreachable!()
+		};
+		account.two_factor_enabled = Some(two_factor);
+		Item::OnlineAccount(account)
+	}
+
+	#[test]
+	fn weak_passwords_are_easy_to_guess() {
+		assert!(is_weak("hunter2"));
+		assert!(is_weak("abcdefghijklmn"));
+		assert!(is_weak("password123"));
+		assert!(!is_weak("correct horse battery staple"));
+		assert_eq!(password_score("q7#Vx!pL2m@Rz9wK"), 4);
+	}
+
+	#[test]
+	fn figures_cover_weak_reused_old_and_two_factor() {
+		let now = Timestamp::from_second(400 * 86_400).unwrap();
+		let name = |s: &str| AccountName::new(s).unwrap();
+		let mut store = PasswordStore::new();
+		store.items.insert(name("a"), account("hunter2", true));
+		store.items.insert(name("b"), account("hunter2", false));
+		store.items.insert(name("c"), account("correct horse battery staple", false));
+		let modified = BTreeMap::from([(name("c"), Timestamp::from_second(0).unwrap())]);
+		let log = [ChangeEntry {
+			hash:       String::new(),
+			message:    String::new(),
+			timestamp:  now,
+			author:     String::new(),
+			entry_name: None,
+		}];
+
+		let scores = crate::search::SearchIndex::build(&store).password_scores();
+		let stats = VaultStats::compute(&store, &scores, &modified, &log, None, now);
+		assert_eq!(stats.total, 3);
+		assert_eq!(stats.by_kind[0].count, 3);
+		assert_eq!((stats.with_password, stats.weak, stats.reused, stats.old), (3, 2, 2, 1));
+		assert_eq!((stats.accounts, stats.missing_two_factor), (3, 2));
+		assert_eq!(stats.weekly_changes.last(), Some(&1));
+	}
+
+	#[test]
+	fn a_stored_totp_counts_as_two_factor() {
+		let Item::OnlineAccount(mut account) = account("hunter2", false) else { unreachable!() };
+		account.two_factor_enabled = None;
+		account.totp = Some(Totp::import("otpauth://totp/GitHub?secret=JBSWY3DPEHPK3PXP").unwrap());
+		let mut store = PasswordStore::new();
+		store.items.insert(AccountName::new("a").unwrap(), Item::OnlineAccount(account));
+
+		let now = Timestamp::from_second(0).unwrap();
+		let stats = VaultStats::compute(&store, &BTreeMap::new(), &BTreeMap::new(), &[], None, now);
+		assert_eq!((stats.accounts, stats.missing_two_factor), (1, 0));
+	}
+}
@@ -0,0 +1,263 @@
+//! User-defined item types, loaded from TOML files at startup.
+//!
+//! Each `*.toml` file under `<store>/templates/` describes one item type:
+//!
+//! ```toml
+//! name = "gym_locker"
+//! label = "Gym Locker"
+//!
+//! [[fields]]
+//! name = "locker"
+//! kind = "text"
+//! required = true
+//!
+//! [[fields]]
+//! name = "combination"
+//! kind = "secret"
+//!
+//! [[fields]]
+//! name = "size"
+//! kind = "enum"
+//! options = ["small", "large"]
+//! ```
+//!
+//! Items created from a template are stored as [`TemplateItem`]s: the
+//! template name plus string values keyed by field name. Templates only
+//! describe and validate; an item whose template has since been deleted
+//! still loads and displays as plain text fields.
+
+use std::{collections::BTreeMap, path::{Path, PathBuf}};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result, models::{Appearance, CustomField}};
+
+/// Name of the directory, relative to the store root, holding templates.
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// The value kind of a template field; drives validation and rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, uniffi::Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+	Text,
+	Secret,
+	Date,
+	Url,
+	Enum,
+	Number,
+}
+
+/// One field of an [`ItemTemplate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Record)]
+pub struct TemplateField {
+	pub name:     String,
+	pub kind:     FieldKind,
+	#[serde(default)]
+	pub required: bool,
+	/// Allowed values for [`FieldKind::Enum`] fields.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub options:  Vec<String>,
+}
+
+/// A user-defined item type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Record)]
+pub struct ItemTemplate {
+	/// Stable identifier stored on every item built from this template.
+	pub name:   String,
+	/// Human-readable name; falls back to `name`.
+	#[serde(default)]
+	pub label:  Option<String>,
+	pub fields: Vec<TemplateField>,
+}
+
+/// A stored item whose shape is described by an [`ItemTemplate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateItem {
+	pub template:      String,
+	#[serde(default)]
+	pub fields:        BTreeMap<String, String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags:          Vec<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub custom_fields: Vec<CustomField>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub appearance:    Option<Appearance>,
+}
+
+impl ItemTemplate {
+	pub fn label(&self) -> &str { self.label.as_deref().unwrap_or(&self.name) }
+
+	pub fn field(&self, name: &str) -> Option<&TemplateField> {
+		self.fields.iter().find(|f| f.name == name)
+	}
+
+	fn check(&self) -> std::result::Result<(), String> {
+		if self.name.is_empty() {
+			return Err("template name must not be empty".into());
+		}
+		let mut seen = std::collections::HashSet::new();
+		for field in &self.fields {
+			if !seen.insert(field.name.as_str()) {
+				return Err(format!("duplicate field '{}'", field.name));
+			}
+			if field.kind == FieldKind::Enum && field.options.is_empty() {
+				return Err(format!("enum field '{}' has no options", field.name));
+			}
+		}
+		Ok(())
+	}
+
+	/// Check `item` against this template: required fields are present,
+	/// unknown fields are rejected, and typed values parse.
+	pub fn validate(&self, item: &TemplateItem) -> Result<()> { self.validate_fields(&item.fields) }
+
+	/// [`validate`](Self::validate) for a bare field-name → value map.
+	pub fn validate_fields(&self, fields: &BTreeMap<String, String>) -> Result<()> {
+		for field in &self.fields {
+			match fields.get(&field.name) {
+				None if field.required => {
+					return Err(Error::MissingField { field: field.name.clone() });
+				}
+				None => {}
+				Some(value) => field.validate(value)?,
+			}
+		}
+		if let Some(unknown) = fields.keys().find(|k| self.field(k).is_none()) {
+			return Err(Error::Validation {
+				field:  unknown.clone(),
+				reason: format!("not a field of template '{}'", self.name),
+			});
+		}
+		Ok(())
+	}
+}
+
+impl TemplateField {
+	pub(crate) fn validate(&self, value: &str) -> Result<()> {
+		let reason = match self.kind {
+			FieldKind::Text | FieldKind::Secret => None,
+			FieldKind::Date => value.parse::<jiff::civil::Date>().err().map(|e| e.to_string()),
+			FieldKind::Url => value.parse::<url::Url>().err().map(|e| e.to_string()),
+			FieldKind::Enum => (!self.options.iter().any(|o| o == value))
+				.then(|| format!("expected one of: {}", self.options.join(", "))),
+			FieldKind::Number => value.parse::<i64>().err().map(|e| e.to_string()),
+		};
+		match reason {
+			Some(reason) => Err(Error::Validation { field: self.name.clone(), reason }),
+			None => Ok(()),
+		}
+	}
+}
+
+/// All templates known to a store, keyed by template name.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRegistry {
+	templates: BTreeMap<String, ItemTemplate>,
+}
+
+impl TemplateRegistry {
+	/// Load every `*.toml` file in `dir`. A missing directory yields an empty
+	/// registry.
+	pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+		let dir = dir.as_ref();
+		let mut registry = Self::default();
+		if !dir.exists() {
+			return Ok(registry);
+		}
+		let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+			.filter_map(|e| e.ok().map(|e| e.path()))
+			.filter(|p| p.extension().and_then(|e| e.to_str()) == Some("toml"))
+			.collect();
+		paths.sort();
+		for path in paths {
+			let invalid = |reason: String| Error::InvalidTemplate { path: path.clone(), reason };
+			let template: ItemTemplate =
+				toml::from_str(&std::fs::read_to_string(&path)?).map_err(|e| invalid(e.to_string()))?;
+			template.check().map_err(invalid)?;
+			if registry.templates.contains_key(&template.name) {
+				return Err(invalid(format!("template '{}' is defined twice", template.name)));
+			}
+			registry.templates.insert(template.name.clone(), template);
+		}
+		Ok(registry)
+	}
+
+	/// Load the templates belonging to the store rooted at `store_dir`.
+	pub fn for_store(store_dir: impl AsRef<Path>) -> Result<Self> {
+		Self::load(store_dir.as_ref().join(TEMPLATES_DIR))
+	}
+
+	pub fn get(&self, name: &str) -> Option<&ItemTemplate> { self.templates.get(name) }
+
+	pub fn iter(&self) -> impl Iterator<Item = &ItemTemplate> { self.templates.values() }
+
+	/// Validate `item` against its template.
+	pub fn validate(&self, item: &TemplateItem) -> Result<()> {
+		self
+			.get(&item.template)
+			.ok_or_else(|| Error::Validation {
+				field:  "template".into(),
+				reason: format!("unknown template '{}'", item.template),
+			})?
+			.validate(item)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn locker() -> ItemTemplate {
+		toml::from_str(
+			r#"
+			name = "gym_locker"
+
+			[[fields]]
+			name = "locker"
+			kind = "text"
+			required = true
+
+			[[fields]]
+			name = "renewal"
+			kind = "date"
+
+			[[fields]]
+			name = "size"
+			kind = "enum"
+			options = ["small", "large"]
+			"#,
+		)
+		.unwrap()
+	}
+
+	fn item(fields: &[(&str, &str)]) -> TemplateItem {
+		TemplateItem {
+			template:      "gym_locker".into(),
+			fields:        fields.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+			tags:          vec![],
+			custom_fields: vec![],
+			appearance:    None,
+		}
+	}
+
+	#[test]
+	fn template_validation_checks_required_and_typed_fields() {
+		let t = locker();
+		assert!(t.validate(&item(&[("locker", "42"), ("size", "small")])).is_ok());
+		assert!(t.validate(&item(&[("size", "small")])).is_err());
+		assert!(t.validate(&item(&[("locker", "42"), ("size", "huge")])).is_err());
+		assert!(t.validate(&item(&[("locker", "42"), ("renewal", "soon")])).is_err());
+		assert!(t.validate(&item(&[("locker", "42"), ("colour", "red")])).is_err());
+	}
+
+	#[test]
+	fn registry_loads_templates_from_directory() {
+		let dir = tempfile::tempdir().unwrap();
+		std::fs::write(dir.path().join("locker.toml"), toml::to_string(&locker()).unwrap()).unwrap();
+		std::fs::write(dir.path().join("README.md"), "ignored").unwrap();
+
+		let registry = TemplateRegistry::load(dir.path()).unwrap();
+		assert_eq!(registry.iter().count(), 1);
+		assert!(registry.validate(&item(&[("locker", "7")])).is_ok());
+	}
+}
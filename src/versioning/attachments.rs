@@ -0,0 +1,243 @@
+//! Encrypted file attachments stored next to an entry.
+//!
+//! Attachments for `<name>` live in `branches/<branch>/<name>.attachments/`,
+//! one encrypted file per attachment under its original file name. Entry
+//! names cannot contain `.`, so the directory never collides with an entry.
+//! Attachments are not recorded in the entry's history.
+
+use std::{path::{Path, PathBuf}, time::Duration};
+
+use super::PijulStore;
+use crate::{Error, Result, access_control::{BranchKind, BranchPath}, encryption::{EncryptionMethod, Unlocked}, models::AccountName, store::StoreBackend};
+
+pub(super) const ATTACHMENTS_SUFFIX: &str = "attachments";
+
+/// The prefix of the directories attachments are decrypted into for viewing.
+const TEMP_PREFIX: &str = "pwd-attachment-";
+
+/// How long a decrypted attachment may be left behind before the next
+/// decryption sweeps it away.
+const TEMP_LIFETIME: Duration = Duration::from_secs(15 * 60);
+
+/// Where attachments are decrypted for viewing: /dev/shm when there is one,
+/// so the plaintext never reaches a disk.
+fn temp_base() -> PathBuf {
+	let shm = Path::new("/dev/shm");
+	if shm.is_dir() { shm.to_path_buf() } else { std::env::temp_dir() }
+}
+
+/// Delete decrypted attachments older than [`TEMP_LIFETIME`], left behind
+/// by a viewer nobody cleaned up after. Failures are ignored; the next
+/// sweep tries again.
+fn sweep_temp_attachments() {
+	let mut bases = vec![temp_base(), std::env::temp_dir()];
+	bases.dedup();
+	for base in bases {
+		let Ok(entries) = std::fs::read_dir(&base) else { continue };
+		for entry in entries.flatten() {
+			let stale = entry.file_name().to_string_lossy().starts_with(TEMP_PREFIX)
+				&& entry
+					.metadata()
+					.and_then(|m| m.modified())
+					.is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > TEMP_LIFETIME));
+			if stale {
+				let _ = std::fs::remove_dir_all(entry.path());
+			}
+		}
+	}
+}
+
+/// Validate an attachment file name: a single, non-hidden path component.
+fn check_file_name(file: &str) -> Result<()> {
+	let invalid = |reason: &str| Err(Error::InvalidAttachmentName(format!("{file:?}: {reason}")));
+	if file.is_empty() {
+		return invalid("name must not be empty");
+	}
+	if file.len() > 255 {
+		return invalid("name exceeds 255 bytes");
+	}
+	if file.starts_with('.') {
+		return invalid("name must not start with '.'");
+	}
+	if file.contains(['/', '\\', '\0']) {
+		return invalid("name must be a single path component");
+	}
+	Ok(())
+}
+
+impl<State> PijulStore<State> {
+	pub(super) fn attachments_dir(&self, branch: &str, name: &AccountName) -> PathBuf {
+		self.branch_dir(branch).join(format!("{}.{ATTACHMENTS_SUFFIX}", name.as_str()))
+	}
+}
+
+impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
+	fn attachment_path(&self, branch: &str, name: &AccountName, file: &str) -> Result<PathBuf> {
+		check_file_name(file)?;
+		Ok(self.attachments_dir(branch, name).join(file))
+	}
+
+	/// Encrypt `contents` and attach it to `name` as `file`, replacing any
+	/// attachment of the same name.
+	pub fn attach<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+		file: &str,
+		contents: &[u8],
+	) -> Result<()> {
+		if self.get(branch, name)?.is_none() {
+			return Err(Error::EntryNotFound { name: name.clone() });
+		}
+		let path = self.attachment_path(branch.as_str(), name, file)?;
+		let dir = self.attachments_dir(branch.as_str(), name);
+		std::fs::create_dir_all(&dir)?;
+		let encrypted = self.state.method.encrypt(contents)?;
+		let mut tmp = tempfile::NamedTempFile::new_in(&dir)?;
+		std::io::Write::write_all(&mut tmp, &encrypted)?;
+		tmp.persist(&path).map_err(|e| e.error)?;
+		Ok(())
+	}
+
+	/// File names of every attachment on `name`, sorted.
+	pub fn attachments<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+	) -> Result<Vec<String>> {
+		let dir = self.attachments_dir(branch.as_str(), name);
+		if !dir.exists() {
+			return Ok(vec![]);
+		}
+		let mut files: Vec<String> = std::fs::read_dir(dir)?
+			.filter_map(|e| e.ok()?.file_name().into_string().ok())
+			.filter(|f| check_file_name(f).is_ok())
+			.collect();
+		files.sort();
+		Ok(files)
+	}
+
+	/// Decrypt and return the contents of an attachment.
+	pub fn read_attachment<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+		file: &str,
+	) -> Result<Vec<u8>> {
+		let path = self.attachment_path(branch.as_str(), name, file)?;
+		if !path.exists() {
+			return Err(Error::AttachmentNotFound { name: name.clone(), file: file.to_owned() });
+		}
+		self.state.method.decrypt(&std::fs::read(path)?)
+	}
+
+	/// Decrypt an attachment to `dest`, readable only by the current user.
+	pub fn export_attachment<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+		file: &str,
+		dest: &Path,
+	) -> Result<()> {
+		let contents = self.read_attachment(branch, name, file)?;
+		let mut options = std::fs::OpenOptions::new();
+		options.write(true).create(true).truncate(true);
+		#[cfg(unix)]
+		std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+		std::io::Write::write_all(&mut options.open(dest)?, &contents)?;
+		Ok(())
+	}
+
+	/// Decrypt an attachment into a fresh private temporary directory, on
+	/// /dev/shm when there is one, and return the path of the plaintext
+	/// file, keeping its original name so the system opener picks the right
+	/// application.
+	///
+	/// The caller should delete the file with [`Self::remove_temp_attachment`]
+	/// once the viewer has opened it; copies left behind are swept away by a
+	/// later call after [`TEMP_LIFETIME`].
+	pub fn export_attachment_to_temp<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+		file: &str,
+	) -> Result<PathBuf> {
+		sweep_temp_attachments();
+		let dir = tempfile::Builder::new().prefix(TEMP_PREFIX).tempdir_in(temp_base())?.keep();
+		let dest = dir.join(file);
+		self.export_attachment(branch, name, file, &dest)?;
+		Ok(dest)
+	}
+
+	/// Delete a file from [`Self::export_attachment_to_temp`] along with its
+	/// directory. Paths that didn't come from there are refused.
+	pub fn remove_temp_attachment(&self, path: &Path) -> Result<()> {
+		let dir = path
+			.parent()
+			.filter(|dir| {
+				dir.file_name().is_some_and(|name| name.to_string_lossy().starts_with(TEMP_PREFIX))
+			})
+			.ok_or_else(|| {
+				Error::InvalidAttachmentName(format!("{}: not a decrypted attachment", path.display()))
+			})?;
+		Ok(std::fs::remove_dir_all(dir)?)
+	}
+
+	/// Delete an attachment. Returns whether it existed.
+	pub fn detach<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+		file: &str,
+	) -> Result<bool> {
+		let path = self.attachment_path(branch.as_str(), name, file)?;
+		if !path.exists() {
+			return Ok(false);
+		}
+		std::fs::remove_file(path)?;
+		let dir = self.attachments_dir(branch.as_str(), name);
+		if std::fs::read_dir(&dir)?.next().is_none() {
+			std::fs::remove_dir(dir)?;
+		}
+		Ok(true)
+	}
+
+	/// Delete every attachment on `name`.
+	pub(super) fn remove_attachments_dir(&self, branch: &str, name: &AccountName) -> Result<()> {
+		let dir = self.attachments_dir(branch, name);
+		if dir.exists() {
+			std::fs::remove_dir_all(dir)?;
+		}
+		Ok(())
+	}
+
+	/// Decrypt every attachment on every entry in `branch`, for re-encryption.
+	pub(super) fn read_all_attachments<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		names: impl IntoIterator<Item = AccountName>,
+	) -> Result<Vec<(AccountName, String, Vec<u8>)>> {
+		let mut out = Vec::new();
+		for name in names {
+			for file in self.attachments(branch, &name)? {
+				let contents = self.read_attachment(branch, &name, &file)?;
+				out.push((name.clone(), file, contents));
+			}
+		}
+		Ok(out)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::check_file_name;
+
+	#[test]
+	fn attachment_names_must_be_single_components() {
+		assert!(check_file_name("recovery-codes.pdf").is_ok());
+		assert!(check_file_name("").is_err());
+		assert!(check_file_name(".hidden").is_err());
+		assert!(check_file_name("../escape").is_err());
+		assert!(check_file_name("a\\b").is_err());
+	}
+}
@@ -1,6 +1,12 @@
 //! Per-entry Pijul-backed versioning with multi-branch support.
 
-use std::{collections::BTreeMap, path::PathBuf};
+mod attachments;
+mod recent;
+mod rename;
+mod synced;
+mod trash;
+
+use std::{collections::BTreeMap, path::{Path, PathBuf}};
 
 use jiff::Timestamp;
 use pijul_at_core::{ArcTxn, Base32, ChannelRef, Hash, MutTxnT, MutTxnTExt, TxnT, TxnTExt, change::{Author, ChangeHeader}, changestore::ChangeStore, working_copy::filesystem::FileSystem};
@@ -27,6 +33,11 @@ pub struct PijulStore<State = Locked> {
 }
 
 impl PijulStore<Locked> {
+	/// Whether a store has already been created at `path`.
+	pub fn exists(path: impl AsRef<Path>) -> bool {
+		path.as_ref().join(pijul_at_core::DOT_DIR).is_dir()
+	}
+
 	pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
 		let store_dir: PathBuf = path.into();
 		std::fs::create_dir_all(&store_dir)?;
@@ -97,6 +108,16 @@ impl<State> PijulStore<State> {
 }
 
 impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
+	/// Forget the key; the store has to be unlocked again to be read.
+	pub fn lock(self) -> PijulStore<Locked> {
+		PijulStore {
+			store_dir: self.store_dir,
+			repo:      self.repo,
+			_temp:     self._temp,
+			state:     Locked,
+		}
+	}
+
 	fn entry_path(&self, branch: &str, name: &AccountName) -> PathBuf {
 		self.branch_dir(branch).join(format!(
 			"{}.{}",
@@ -150,9 +171,9 @@ impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
 		let toml = toml::to_string_pretty(item)?;
 		let encrypted = self.state.method.encrypt(toml.as_bytes())?;
 		let path = self.entry_path(branch, name);
-		let dir = self.branch_dir(branch);
-		std::fs::create_dir_all(&dir)?;
-		let mut tmp = tempfile::NamedTempFile::new_in(&dir)?;
+		let dir = path.parent().expect("entry paths have a parent");
+		std::fs::create_dir_all(dir)?;
+		let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
 		std::io::Write::write_all(&mut tmp, &encrypted)?;
 		tmp.persist(&path).map_err(|e| e.error)?;
 		Ok(())
@@ -169,6 +190,7 @@ impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
 		let path = self.entry_path(branch, name);
 		if path.exists() {
 			std::fs::remove_file(&path)?;
+			remove_empty_folders(&self.branch_dir(branch), &path);
 			Ok(true)
 		} else {
 			Ok(false)
@@ -183,6 +205,9 @@ impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
 	) -> Result<PijulStore<Unlocked<N>>> {
 		let branch_name = branch.as_str();
 		let current = self.load(branch)?;
+		let attachments = self.read_all_attachments(branch, current.items.keys().cloned())?;
+		let trash = self.read_trash(branch)?;
+		let recent = self.read_recent(branch_name)?;
 		let store = PijulStore {
 			store_dir: self.store_dir,
 			repo:      self.repo,
@@ -190,6 +215,13 @@ impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
 			state:     Unlocked::new(new_method),
 		};
 		store.save(branch, &current)?;
+		for (name, file, contents) in attachments {
+			store.attach(branch, &name, &file, &contents)?;
+		}
+		store.write_trash(branch, trash)?;
+		if !recent.is_empty() {
+			store.write_recent(branch_name, &recent)?;
+		}
 		for name in current.items.keys() {
 			let msg = change.message();
 			let _ = store.pijul_record(branch_name, name, &msg, true);
@@ -356,24 +388,8 @@ impl<M: EncryptionMethod> StoreBackend for PijulStore<Unlocked<M>> {
 		if !dir.exists() {
 			return Ok(store);
 		}
-		for entry in std::fs::read_dir(dir)? {
-			let entry = entry?;
-			let path = entry.path();
-			let filename = match path.file_name().and_then(|e| e.to_str()) {
-				Some(filename) => filename,
-				None => continue,
-			};
-			if !filename.ends_with(self.state.method.file_extension()) {
-				continue;
-			}
-			let stem =
-				filename.trim_end_matches(self.state.method.file_extension()).trim_end_matches('.');
-			let name = match AccountName::new(stem) {
-				Ok(n) => n,
-				Err(_) => continue,
-			};
-			let item = self.read_entry(&path)?;
-			store.items.insert(name, item);
+		for (name, path) in entry_files(&dir, self.state.method.file_extension())? {
+			store.items.insert(name, self.read_entry(&path)?);
 		}
 		Ok(store)
 	}
@@ -438,6 +454,7 @@ impl<M: EncryptionMethod> StoreBackend for PijulStore<Unlocked<M>> {
 	) -> Result<bool> {
 		validate_change_target(&change, name)?;
 		let existed = self.get(branch, name)?.is_some();
+		self.remove_attachments_dir(branch.as_str(), name)?;
 		let removed_file = self.remove_entry_file(branch.as_str(), name)?;
 		if existed || removed_file {
 			let msg = change.message();
@@ -447,6 +464,51 @@ impl<M: EncryptionMethod> StoreBackend for PijulStore<Unlocked<M>> {
 	}
 }
 
+/// Every `<name>.<extension>` file under `dir`, descending into folders so
+/// `work/github.<extension>` is the entry `work/github`. Directories with a
+/// `.` in their name, such as attachments, are not folders.
+fn entry_files(dir: &Path, extension: &str) -> Result<Vec<(AccountName, PathBuf)>> {
+	fn walk(
+		dir: &Path,
+		prefix: &str,
+		suffix: &str,
+		out: &mut Vec<(AccountName, PathBuf)>,
+	) -> Result<()> {
+		for entry in std::fs::read_dir(dir)? {
+			let path = entry?.path();
+			let Some(file) = path.file_name().and_then(|f| f.to_str()) else {
+				continue;
+			};
+			if path.is_dir() {
+				if !file.contains('.') {
+					walk(&path, &format!("{prefix}{file}/"), suffix, out)?;
+				}
+			} else if let Some(stem) = file.strip_suffix(suffix)
+				&& let Ok(name) = AccountName::new(format!("{prefix}{stem}"))
+			{
+				out.push((name, path));
+			}
+		}
+		Ok(())
+	}
+
+	let mut out = Vec::new();
+	if dir.exists() {
+		walk(dir, "", &format!(".{extension}"), &mut out)?;
+	}
+	Ok(out)
+}
+
+/// Remove the folders between `root` and the deleted file `path` that are
+/// now empty.
+fn remove_empty_folders(root: &Path, path: &Path) {
+	for dir in path.ancestors().skip(1).take_while(|dir| *dir != root && dir.starts_with(root)) {
+		if std::fs::remove_dir(dir).is_err() {
+			break;
+		}
+	}
+}
+
 fn validate_change_target(change: &StoreChange, name: &AccountName) -> Result<()> {
 	if let Some(change_name) = change.entry_name() {
 		if change_name != name {
@@ -582,6 +644,23 @@ impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
 		self.log_impl_raw(branch.as_str(), filter)
 	}
 
+	/// When each entry on the branch last changed, from its newest patch.
+	pub fn last_modified<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+	) -> Result<BTreeMap<AccountName, Timestamp>> {
+		let mut modified = BTreeMap::new();
+		for change in self.log_impl(branch, None)? {
+			if let Some(name) = change.entry_name {
+				modified
+					.entry(name)
+					.and_modify(|t: &mut Timestamp| *t = (*t).max(change.timestamp))
+					.or_insert(change.timestamp);
+			}
+		}
+		Ok(modified)
+	}
+
 	fn log_impl_raw(&self, branch: &str, filter: Option<&AccountName>) -> Result<Vec<ChangeEntry>> {
 		let txn = self.repo.pristine.txn_begin().map_err(|e| Error::Pijul(e.to_string()))?;
 		let channel_ref = match txn.load_channel(branch).map_err(|e| Error::Pijul(e.to_string()))? {
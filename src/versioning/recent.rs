@@ -0,0 +1,69 @@
+//! Entries opened recently, most recent first, for the quick-jump list.
+//!
+//! Kept per branch in `recent/<branch>`, encrypted like the entries since it
+//! shows which accounts are in daily use. The list belongs to this device: it
+//! is not versioned or shared, and rekeying re-encrypts it with the rest.
+
+use std::path::PathBuf;
+
+use super::PijulStore;
+use crate::{Result, access_control::{self, BranchKind, BranchPath}, encryption::{EncryptionMethod, Unlocked}, models::AccountName};
+
+/// How many names the recent list keeps.
+const RECENT_LIMIT: usize = 20;
+
+impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
+	fn recent_path(&self, branch: &str) -> PathBuf {
+		self.store_dir.join("recent").join(format!(
+			"{}.{}",
+			access_control::branch_storage_component_raw(branch),
+			self.state.method.file_extension()
+		))
+	}
+
+	/// Recently opened entries on the branch, most recent first. Entries
+	/// deleted or renamed since are left out.
+	pub fn recent<K: BranchKind>(&self, branch: &BranchPath<K>) -> Result<Vec<AccountName>> {
+		Ok(
+			self
+				.read_recent(branch.as_str())?
+				.into_iter()
+				.filter(|name| self.entry_path(branch.as_str(), name).exists())
+				.collect(),
+		)
+	}
+
+	/// Move `name` to the front of the branch's recent list, keeping at most
+	/// [`RECENT_LIMIT`] names, and return the new list.
+	pub fn mark_viewed<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+	) -> Result<Vec<AccountName>> {
+		let mut recent = self.recent(branch)?;
+		recent.retain(|n| n != name);
+		recent.insert(0, name.clone());
+		recent.truncate(RECENT_LIMIT);
+		self.write_recent(branch.as_str(), &recent)?;
+		Ok(recent)
+	}
+
+	pub(super) fn read_recent(&self, branch: &str) -> Result<Vec<AccountName>> {
+		let path = self.recent_path(branch);
+		if !path.exists() {
+			return Ok(vec![]);
+		}
+		let plain = self.state.method.decrypt(&std::fs::read(path)?)?;
+		Ok(
+			std::str::from_utf8(&plain)?.lines().filter_map(|line| AccountName::new(line).ok()).collect(),
+		)
+	}
+
+	pub(super) fn write_recent(&self, branch: &str, names: &[AccountName]) -> Result<()> {
+		let path = self.recent_path(branch);
+		std::fs::create_dir_all(path.parent().expect("recent paths have a parent"))?;
+		let plain: Vec<&str> = names.iter().map(AccountName::as_str).collect();
+		std::fs::write(path, self.state.method.encrypt(plain.join("\n").as_bytes())?)?;
+		Ok(())
+	}
+}
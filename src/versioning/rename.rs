@@ -0,0 +1,107 @@
+//! Renaming, duplicating and merging entries.
+//!
+//! A rename moves the encrypted file and its attachments to the new name and
+//! records it as the removal of the old entry plus the addition of the new
+//! one. Links to the entry from other accounts' `associated_items` and its
+//! place in the recent list follow it. A merge overwrites one entry with the
+//! combined item and trashes the other, pointing links at the survivor.
+
+use super::PijulStore;
+use crate::{Error, Result, access_control::{BranchKind, BranchPath}, encryption::{EncryptionMethod, Unlocked}, models::{AccountName, Item}, store::{StoreBackend, StoreChange}};
+
+impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
+	/// Move `from` to `to`, updating every link to it.
+	///
+	/// Fails if `from` does not exist or `to` already does.
+	pub fn rename<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		from: &AccountName,
+		to: &AccountName,
+		change: StoreChange,
+	) -> Result<()> {
+		super::validate_change_target(&change, from)?;
+		if from == to {
+			return Ok(());
+		}
+		let item =
+			self.get(branch, from)?.ok_or_else(|| Error::EntryNotFound { name: from.clone() })?;
+		if self.get(branch, to)?.is_some() {
+			return Err(Error::EntryAlreadyExists { name: to.clone() });
+		}
+
+		let raw = branch.as_str();
+		self.write_entry(raw, to, &item)?;
+		let attachments = self.attachments_dir(raw, from);
+		if attachments.exists() {
+			std::fs::rename(attachments, self.attachments_dir(raw, to))?;
+		}
+		self.remove_entry_file(raw, from)?;
+		let msg = change.message();
+		let _ = self.pijul_record(raw, from, &msg, false);
+		let _ = self.pijul_record(raw, to, &msg, true);
+
+		self.relink(branch, from, to)?;
+		let recent: Vec<AccountName> = self
+			.read_recent(raw)?
+			.into_iter()
+			.map(|name| if name == *from { to.clone() } else { name })
+			.collect();
+		self.write_recent(raw, &recent)
+	}
+
+	/// Replace `keep` with the merged `item` and move `discard` to the
+	/// trash, pointing links to `discard` at `keep`.
+	pub fn merge_into<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		keep: &AccountName,
+		discard: &AccountName,
+		item: Item,
+	) -> Result<()> {
+		if keep == discard {
+			return Err(Error::Validation {
+				field:  "merge".into(),
+				reason: format!("cannot merge {keep} with itself"),
+			});
+		}
+		if self.get(branch, discard)?.is_none() {
+			return Err(Error::EntryNotFound { name: discard.clone() });
+		}
+		let change = StoreChange::Custom(format!("merge {discard} into {keep}"));
+		self.update(branch, keep, item, change)?;
+		self.trash(branch, discard, StoreChange::remove_entry(discard.clone()))?;
+		self.relink(branch, discard, keep)
+	}
+
+	/// Point every `associated_items` link to `from` at `to`.
+	fn relink<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		from: &AccountName,
+		to: &AccountName,
+	) -> Result<()> {
+		for (name, mut item) in self.load(branch)?.items {
+			if item.replace_link(from.as_str(), to.as_str()) {
+				let change = StoreChange::update_entry(name.clone(), ["associated_items"]);
+				self.update(branch, &name, item, change)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Add a copy of `from` named `to`, unstarred and without attachments,
+	/// as the starting point for a similar account.
+	pub fn duplicate<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		from: &AccountName,
+		to: AccountName,
+	) -> Result<()> {
+		let mut item =
+			self.get(branch, from)?.ok_or_else(|| Error::EntryNotFound { name: from.clone() })?;
+		item.set_favorite(false);
+		let change = StoreChange::add_entry(to.clone(), &item);
+		self.insert(branch, to, item, change)
+	}
+}
@@ -0,0 +1,36 @@
+//! When this device last shared or received the branch, for the dashboard.
+//!
+//! Kept per branch in `synced/<branch>` as a plain timestamp: it says nothing
+//! about the entries, so unlike the recent list it is not encrypted. Like the
+//! recent list it belongs to this device and is not versioned.
+
+use std::path::PathBuf;
+
+use jiff::Timestamp;
+
+use super::PijulStore;
+use crate::{Result, access_control::{self, BranchKind, BranchPath}};
+
+impl<State> PijulStore<State> {
+	fn synced_path(&self, branch: &str) -> PathBuf {
+		self.store_dir.join("synced").join(access_control::branch_storage_component_raw(branch))
+	}
+
+	/// When the branch was last shared or received, if ever. A record that
+	/// does not parse counts as never.
+	pub fn last_sync<K: BranchKind>(&self, branch: &BranchPath<K>) -> Result<Option<Timestamp>> {
+		let path = self.synced_path(branch.as_str());
+		if !path.exists() {
+			return Ok(None);
+		}
+		Ok(std::fs::read_to_string(path)?.trim().parse().ok())
+	}
+
+	/// Record that the branch was shared or received at `at`.
+	pub fn mark_synced<K: BranchKind>(&self, branch: &BranchPath<K>, at: Timestamp) -> Result<()> {
+		let path = self.synced_path(branch.as_str());
+		std::fs::create_dir_all(path.parent().expect("synced paths have a parent"))?;
+		std::fs::write(path, at.to_string())?;
+		Ok(())
+	}
+}
@@ -0,0 +1,166 @@
+//! Deleted entries kept aside until the trash is emptied.
+//!
+//! Trashing an entry records its removal in history like any other delete,
+//! but instead of unlinking the encrypted file it moves it (and its
+//! attachments) to `trash/<branch>/`. Restoring puts it back as a new change.
+//! Entries in folders keep their folder inside the trash. The trash is not
+//! versioned, and trashing an entry with the same name again replaces the
+//! older copy.
+
+use std::path::PathBuf;
+
+use super::{PijulStore, attachments::ATTACHMENTS_SUFFIX};
+use crate::{Error, Result, access_control::{self, BranchKind, BranchPath}, encryption::{EncryptionMethod, Unlocked}, models::{AccountName, Item}, store::{StoreBackend, StoreChange}};
+
+/// A trashed entry with its decrypted attachments, for re-encryption.
+pub(super) type TrashedEntry = (AccountName, Item, Vec<(String, Vec<u8>)>);
+
+impl<State> PijulStore<State> {
+	fn trash_dir(&self, branch: &str) -> PathBuf {
+		self.store_dir.join("trash").join(access_control::branch_storage_component_raw(branch))
+	}
+}
+
+impl<M: EncryptionMethod> PijulStore<Unlocked<M>> {
+	fn trash_entry_path(&self, branch: &str, name: &AccountName) -> PathBuf {
+		self.trash_dir(branch).join(format!("{}.{}", name.as_str(), self.state.method.file_extension()))
+	}
+
+	fn trash_attachments_dir(&self, branch: &str, name: &AccountName) -> PathBuf {
+		self.trash_dir(branch).join(format!("{}.{ATTACHMENTS_SUFFIX}", name.as_str()))
+	}
+
+	/// Remove `name` from the branch, keeping it in the trash so it can be
+	/// restored. Returns whether the entry existed.
+	pub fn trash<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+		change: StoreChange,
+	) -> Result<bool> {
+		super::validate_change_target(&change, name)?;
+		let entry = self.entry_path(branch.as_str(), name);
+		if !entry.exists() {
+			return Ok(false);
+		}
+		let trashed = self.trash_entry_path(branch.as_str(), name);
+		std::fs::create_dir_all(trashed.parent().expect("trash paths have a parent"))?;
+		let attachments = self.trash_attachments_dir(branch.as_str(), name);
+		if attachments.exists() {
+			std::fs::remove_dir_all(&attachments)?;
+		}
+		let live_attachments = self.attachments_dir(branch.as_str(), name);
+		if live_attachments.exists() {
+			std::fs::rename(live_attachments, attachments)?;
+		}
+		std::fs::rename(&entry, trashed)?;
+		super::remove_empty_folders(&self.branch_dir(branch.as_str()), &entry);
+		let _ = self.pijul_record(branch.as_str(), name, &change.message(), false);
+		Ok(true)
+	}
+
+	/// Names of every trashed entry on the branch, sorted.
+	pub fn trashed<K: BranchKind>(&self, branch: &BranchPath<K>) -> Result<Vec<AccountName>> {
+		let dir = self.trash_dir(branch.as_str());
+		let mut names: Vec<AccountName> = super::entry_files(&dir, self.state.method.file_extension())?
+			.into_iter()
+			.map(|(name, _)| name)
+			.collect();
+		names.sort();
+		Ok(names)
+	}
+
+	/// Decrypt a trashed entry without restoring it.
+	pub fn get_trashed<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+	) -> Result<Option<Item>> {
+		let path = self.trash_entry_path(branch.as_str(), name);
+		if !path.exists() {
+			return Ok(None);
+		}
+		self.read_entry(&path).map(Some)
+	}
+
+	/// Move a trashed entry and its attachments back onto the branch.
+	///
+	/// Fails if an entry of the same name has been created since.
+	pub fn restore<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		name: &AccountName,
+		change: StoreChange,
+	) -> Result<()> {
+		let item =
+			self.get_trashed(branch, name)?.ok_or_else(|| Error::EntryNotFound { name: name.clone() })?;
+		self.insert(branch, name.clone(), item, change)?;
+		let attachments = self.trash_attachments_dir(branch.as_str(), name);
+		if attachments.exists() {
+			std::fs::rename(attachments, self.attachments_dir(branch.as_str(), name))?;
+		}
+		let trashed = self.trash_entry_path(branch.as_str(), name);
+		std::fs::remove_file(&trashed)?;
+		super::remove_empty_folders(&self.trash_dir(branch.as_str()), &trashed);
+		Ok(())
+	}
+
+	/// Permanently delete everything in the branch's trash. Returns how many
+	/// entries were deleted.
+	pub fn empty_trash<K: BranchKind>(&self, branch: &BranchPath<K>) -> Result<usize> {
+		let count = self.trashed(branch)?.len();
+		let dir = self.trash_dir(branch.as_str());
+		if dir.exists() {
+			std::fs::remove_dir_all(dir)?;
+		}
+		Ok(count)
+	}
+
+	/// Decrypt the whole trash, for re-encryption under a new key.
+	pub(super) fn read_trash<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+	) -> Result<Vec<TrashedEntry>> {
+		let mut out = Vec::new();
+		for name in self.trashed(branch)? {
+			let item = self.read_entry(&self.trash_entry_path(branch.as_str(), &name))?;
+			let dir = self.trash_attachments_dir(branch.as_str(), &name);
+			let mut files = Vec::new();
+			if dir.exists() {
+				for file in std::fs::read_dir(dir)? {
+					let path = file?.path();
+					let Some(file) = path.file_name().and_then(|f| f.to_str()).map(str::to_owned) else {
+						continue;
+					};
+					files.push((file, self.state.method.decrypt(&std::fs::read(&path)?)?));
+				}
+			}
+			out.push((name, item, files));
+		}
+		Ok(out)
+	}
+
+	/// Replace the branch's trash with `entries`, encrypted with this
+	/// store's method.
+	pub(super) fn write_trash<K: BranchKind>(
+		&self,
+		branch: &BranchPath<K>,
+		entries: Vec<TrashedEntry>,
+	) -> Result<()> {
+		self.empty_trash(branch)?;
+		for (name, item, files) in entries {
+			let path = self.trash_entry_path(branch.as_str(), &name);
+			std::fs::create_dir_all(path.parent().expect("trash paths have a parent"))?;
+			let toml = toml::to_string_pretty(&item)?;
+			std::fs::write(path, self.state.method.encrypt(toml.as_bytes())?)?;
+			if !files.is_empty() {
+				let attachments = self.trash_attachments_dir(branch.as_str(), &name);
+				std::fs::create_dir_all(&attachments)?;
+				for (file, contents) in files {
+					std::fs::write(attachments.join(file), self.state.method.encrypt(&contents)?)?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
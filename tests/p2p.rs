@@ -5,7 +5,7 @@
 
 use std::time::Duration;
 
-use password::{AccountName, Item, PasswordStore, models::{AccountStatus, OnlineAccount}, p2p::{IrohSyncHandle, decode_store, encode_store}};
+use password::{AccountName, Item, PasswordStore, models::{AccountStatus, OnlineAccount}, p2p::{IrohSyncHandle, ShareCode, decode_store, encode_store}};
 use tokio::time::timeout;
 
 fn generate_store(name: &str, num_items: usize) -> PasswordStore {
@@ -22,10 +22,16 @@ fn generate_store(name: &str, num_items: usize) -> PasswordStore {
 			host_website:       None,
 			login_pages:        None,
 			security_questions: None,
+			recovery_codes:     vec![],
+			password_policy:    None,
+			totp:               None,
 			date_created:       None,
 			two_factor_enabled: None,
 			associated_items:   None,
 			notes:              None,
+			tags:               vec![],
+			custom_fields:      vec![],
+			appearance:         None,
 		});
 		s.items.insert(n, item);
 	}
@@ -41,14 +47,15 @@ async fn sync_functions_between_concurrent_peers() {
 	let sender = IrohSyncHandle::new();
 
 	// Share payload asynchronously
-	let ticket = sender.share(payload).await.expect("share");
+	let code = ShareCode::generate();
+	let ticket = sender.share(payload, &code).await.expect("share");
 
 	// Receiver node runs concurrently
 	let receiver_task = tokio::spawn(async move {
 		let receiver = IrohSyncHandle::new();
 		let received_payload = timeout(
 			Duration::from_secs(10), // Wait reasonable time for discovery/gossip
-			receiver.receive(&ticket),
+			receiver.receive(&ticket, &code),
 		)
 		.await
 		.expect("receive timed out")
@@ -79,7 +86,7 @@ async fn invalid_tickets_are_rejected_gracefully() {
 	let receiver = IrohSyncHandle::new();
 	let bad_ticket = password::ShareTicket("docaaqaamaax3x3x3x3x3...garbage".into());
 
-	let res = receiver.receive(&bad_ticket).await;
+	let res = receiver.receive(&bad_ticket, &ShareCode::generate()).await;
 	assert!(res.is_err(), "Invalid tickets must be rejected before attempting sync");
 
 	receiver.shutdown().await.ok();
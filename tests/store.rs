@@ -14,10 +14,16 @@ fn sample_account(pass: &str) -> Item {
 		host_website:       None,
 		login_pages:        None,
 		security_questions: None,
+		recovery_codes:     vec![],
+		password_policy:    None,
+		totp:               None,
 		date_created:       None,
 		two_factor_enabled: Some(false),
 		associated_items:   None,
 		notes:              None,
+		tags:               vec![],
+		custom_fields:      vec![],
+		appearance:         None,
 	})
 }
 
@@ -140,6 +146,237 @@ fn rekey_rotates_the_store_passphrase() {
 	assert!(old.get(&main_branch(), &n1).is_err());
 }
 
+#[test]
+fn attachments_are_encrypted_and_survive_rekey() {
+	let store = store();
+	let n1 = name("github");
+	store.insert(&main_branch(), n1.clone(), sample_account("gh_pass_1"), add_change(&n1)).unwrap();
+	store.attach(&main_branch(), &n1, "codes.txt", b"recovery-1234").unwrap();
+	assert!(store.attach(&main_branch(), &n1, "../escape", b"x").is_err());
+	assert!(store.attach(&main_branch(), &name("missing"), "codes.txt", b"x").is_err());
+
+	let raw = store.store_dir.join("branches/personal%3Amain/github.attachments/codes.txt");
+	assert!(!String::from_utf8_lossy(&std::fs::read(raw).unwrap()).contains("recovery-1234"));
+	assert_eq!(store.list(&main_branch()).unwrap(), vec![n1.clone()]);
+	assert_eq!(store.attachments(&main_branch(), &n1).unwrap(), vec!["codes.txt".to_string()]);
+
+	let store = store
+		.rekey_with(
+			&main_branch(),
+			AgeScrypt::new("new-passphrase").unwrap(),
+			rekey_change(&[n1.clone()]),
+		)
+		.unwrap();
+	assert_eq!(store.read_attachment(&main_branch(), &n1, "codes.txt").unwrap(), b"recovery-1234");
+
+	let exported = store.export_attachment_to_temp(&main_branch(), &n1, "codes.txt").unwrap();
+	assert_eq!(exported.file_name().unwrap(), "codes.txt");
+	assert_eq!(std::fs::read(&exported).unwrap(), b"recovery-1234");
+	std::fs::remove_dir_all(exported.parent().unwrap()).unwrap();
+
+	assert!(store.detach(&main_branch(), &n1, "codes.txt").unwrap());
+	assert!(store.attachments(&main_branch(), &n1).unwrap().is_empty());
+	assert!(matches!(
+		store.read_attachment(&main_branch(), &n1, "codes.txt"),
+		Err(Error::AttachmentNotFound { .. })
+	));
+}
+
+#[test]
+fn trashed_entries_keep_attachments_and_can_be_restored() {
+	let store = store();
+	let n1 = name("github");
+	store.insert(&main_branch(), n1.clone(), sample_account("gh_pass_1"), add_change(&n1)).unwrap();
+	store.attach(&main_branch(), &n1, "codes.txt", b"recovery-1234").unwrap();
+
+	assert!(
+		store.trash(&main_branch(), &n1, password::StoreChange::remove_entry(n1.clone())).unwrap()
+	);
+	assert!(store.list(&main_branch()).unwrap().is_empty());
+	assert_eq!(store.trashed(&main_branch()).unwrap(), vec![n1.clone()]);
+	assert!(
+		!store.trash(&main_branch(), &n1, password::StoreChange::remove_entry(n1.clone())).unwrap()
+	);
+
+	let store = store
+		.rekey_with(&main_branch(), AgeScrypt::new("new-passphrase").unwrap(), rekey_change(&[]))
+		.unwrap();
+	assert!(matches!(
+		store.get_trashed(&main_branch(), &n1).unwrap(),
+		Some(Item::OnlineAccount(a)) if a.password.as_deref() == Some("gh_pass_1")
+	));
+
+	store.restore(&main_branch(), &n1, add_change(&n1)).unwrap();
+	assert!(store.trashed(&main_branch()).unwrap().is_empty());
+	assert!(store.get(&main_branch(), &n1).unwrap().is_some());
+	assert_eq!(store.read_attachment(&main_branch(), &n1, "codes.txt").unwrap(), b"recovery-1234");
+
+	store.trash(&main_branch(), &n1, password::StoreChange::remove_entry(n1.clone())).unwrap();
+	assert_eq!(store.empty_trash(&main_branch()).unwrap(), 1);
+	assert!(matches!(
+		store.restore(&main_branch(), &n1, add_change(&n1)),
+		Err(Error::EntryNotFound { .. })
+	));
+}
+
+#[test]
+fn recently_viewed_entries_are_remembered_most_recent_first() {
+	let store = store();
+	let (gh, mail) = (name("github"), name("mail"));
+	store.insert(&main_branch(), gh.clone(), sample_account("p1"), add_change(&gh)).unwrap();
+	store.insert(&main_branch(), mail.clone(), sample_account("p2"), add_change(&mail)).unwrap();
+	assert!(store.recent(&main_branch()).unwrap().is_empty());
+
+	store.mark_viewed(&main_branch(), &gh).unwrap();
+	store.mark_viewed(&main_branch(), &mail).unwrap();
+	assert_eq!(store.mark_viewed(&main_branch(), &gh).unwrap(), vec![gh.clone(), mail.clone()]);
+
+	let store = store
+		.rekey_with(&main_branch(), AgeScrypt::new("new-passphrase").unwrap(), rekey_change(&[]))
+		.unwrap();
+	store.trash(&main_branch(), &mail, password::StoreChange::remove_entry(mail.clone())).unwrap();
+	assert_eq!(store.recent(&main_branch()).unwrap(), vec![gh]);
+}
+
+#[test]
+fn last_sync_is_remembered_per_branch() {
+	let store = store();
+	assert_eq!(store.last_sync(&main_branch()).unwrap(), None);
+
+	let at = jiff::Timestamp::from_second(1_700_000_000).unwrap();
+	store.mark_synced(&main_branch(), at).unwrap();
+	assert_eq!(store.last_sync(&main_branch()).unwrap(), Some(at));
+	assert_eq!(store.last_sync(&personal_branch("work")).unwrap(), None);
+}
+
+#[test]
+fn renamed_entries_keep_attachments_and_links() {
+	let store = store();
+	let (gh, mail, work) = (name("github"), name("mail"), name("work/github"));
+	store.insert(&main_branch(), gh.clone(), sample_account("p1"), add_change(&gh)).unwrap();
+	store.attach(&main_branch(), &gh, "codes.txt", b"recovery-1234").unwrap();
+	let Item::OnlineAccount(mut linked) = sample_account("p2") else { unreachable!() };
+	linked.associated_items = Some(vec!["github".into()]);
+	let linked = Item::OnlineAccount(linked);
+	store.insert(&main_branch(), mail.clone(), linked, add_change(&mail)).unwrap();
+	store.mark_viewed(&main_branch(), &gh).unwrap();
+
+	assert!(matches!(
+		store.rename(&main_branch(), &gh, &mail, password::StoreChange::Custom("rename".into())),
+		Err(Error::EntryAlreadyExists { .. })
+	));
+	store.rename(&main_branch(), &gh, &work, password::StoreChange::Custom("rename".into())).unwrap();
+	assert_eq!(store.list(&main_branch()).unwrap(), vec![mail.clone(), work.clone()]);
+	assert_eq!(store.read_attachment(&main_branch(), &work, "codes.txt").unwrap(), b"recovery-1234");
+	assert!(matches!(
+		store.get(&main_branch(), &mail).unwrap(),
+		Some(Item::OnlineAccount(a)) if a.associated_items == Some(vec!["work/github".into()])
+	));
+	assert_eq!(store.recent(&main_branch()).unwrap(), vec![work.clone()]);
+
+	store.duplicate(&main_branch(), &work, gh.clone()).unwrap();
+	assert!(matches!(
+		store.get(&main_branch(), &gh).unwrap(),
+		Some(Item::OnlineAccount(a)) if a.password.as_deref() == Some("p1")
+	));
+	assert!(store.attachments(&main_branch(), &gh).unwrap().is_empty());
+}
+
+#[test]
+fn merging_entries_trashes_the_other_and_moves_links() {
+	let store = store();
+	let (gh, gh2, mail) = (name("github"), name("github-old"), name("mail"));
+	store.insert(&main_branch(), gh.clone(), sample_account("p1"), add_change(&gh)).unwrap();
+	store.insert(&main_branch(), gh2.clone(), sample_account("p2"), add_change(&gh2)).unwrap();
+	let Item::OnlineAccount(mut linked) = sample_account("p3") else { unreachable!() };
+	linked.associated_items = Some(vec!["github-old".into()]);
+	store
+		.insert(&main_branch(), mail.clone(), Item::OnlineAccount(linked), add_change(&mail))
+		.unwrap();
+
+	let (left, right) = (
+		store.get(&main_branch(), &gh).unwrap().unwrap(),
+		store.get(&main_branch(), &gh2).unwrap().unwrap(),
+	);
+	let merged = password::merge::merge(&left, &right, &["password".into()]).unwrap();
+	store.merge_into(&main_branch(), &gh, &gh2, merged).unwrap();
+
+	assert_eq!(store.list(&main_branch()).unwrap(), vec![gh.clone(), mail.clone()]);
+	assert_eq!(store.trashed(&main_branch()).unwrap(), vec![gh2]);
+	assert!(matches!(
+		store.get(&main_branch(), &gh).unwrap(),
+		Some(Item::OnlineAccount(a)) if a.password.as_deref() == Some("p2")
+	));
+	assert!(matches!(
+		store.get(&main_branch(), &mail).unwrap(),
+		Some(Item::OnlineAccount(a)) if a.associated_items == Some(vec!["github".into()])
+	));
+}
+
+#[test]
+fn entries_in_folders_are_stored_in_subdirectories() {
+	let store = store();
+	let nested = name("work/ops/aws");
+	let top = name("aws");
+	store
+		.insert(&main_branch(), nested.clone(), sample_account("nested"), add_change(&nested))
+		.unwrap();
+	store.insert(&main_branch(), top.clone(), sample_account("top"), add_change(&top)).unwrap();
+	assert_eq!(store.list(&main_branch()).unwrap(), vec![top.clone(), nested.clone()]);
+	let modified = store.last_modified(&main_branch()).unwrap();
+	assert!(modified.contains_key(&top) && modified.contains_key(&nested));
+
+	let folder =
+		store.store_dir.join("branches").join(branch_storage_component(&main_branch())).join("work");
+	assert!(folder.join("ops").is_dir());
+
+	store
+		.trash(&main_branch(), &nested, password::StoreChange::remove_entry(nested.clone()))
+		.unwrap();
+	assert!(!folder.exists());
+	assert_eq!(store.trashed(&main_branch()).unwrap(), vec![nested.clone()]);
+	store.restore(&main_branch(), &nested, add_change(&nested)).unwrap();
+	assert!(matches!(
+		store.get(&main_branch(), &nested).unwrap(),
+		Some(Item::OnlineAccount(a)) if a.password.as_deref() == Some("nested")
+	));
+}
+
+#[test]
+fn imports_settle_taken_names_with_the_merge_strategy() {
+	use password::import::{Action, Imported, MergeStrategy, apply, plan};
+
+	let store = store();
+	let github = name("github");
+	store.insert(&main_branch(), github.clone(), sample_account("old"), add_change(&github)).unwrap();
+	let incoming = || {
+		vec![
+			Imported::new(github.clone(), sample_account("new")),
+			Imported::new(github.clone(), sample_account("newer")),
+			Imported::new(name("gitlab"), sample_account("gl")),
+		]
+	};
+
+	let skipped = plan(&store, &main_branch(), incoming(), |_| MergeStrategy::Skip).unwrap();
+	let actions: Vec<_> = skipped.iter().map(|p| (p.action.clone(), p.conflict)).collect();
+	assert_eq!(actions, [(Action::Skip, true), (Action::Skip, true), (Action::Create, false)]);
+
+	let kept = plan(&store, &main_branch(), incoming(), |_| MergeStrategy::KeepBoth).unwrap();
+	assert_eq!(kept[0].action, Action::Rename(name("github 2")));
+	assert_eq!(kept[1].action, Action::Rename(name("github 3")));
+
+	let overwrite = plan(&store, &main_branch(), incoming(), |_| MergeStrategy::Overwrite).unwrap();
+	assert_eq!(overwrite[0].action, Action::Overwrite);
+	assert_eq!(overwrite[1].action, Action::Rename(name("github 2")));
+	let summary = apply(&store, &main_branch(), overwrite).unwrap();
+	assert_eq!(summary.updated, [github.clone()]);
+	assert_eq!(summary.added, [name("github 2"), name("gitlab")]);
+	assert!(matches!(
+		store.get(&main_branch(), &github).unwrap(),
+		Some(Item::OnlineAccount(a)) if a.password.as_deref() == Some("new")
+	));
+}
+
 #[test]
 fn offline_session_policy_ignores_wall_clock_and_tracks_policy_epoch() {
 	let policy = password::OfflineSessionPolicy { max_operations: Some(2) };